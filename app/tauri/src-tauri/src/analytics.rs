@@ -0,0 +1,157 @@
+use crate::calendar::parse_numeric;
+use chrono::NaiveDate;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Correlation is only reported with at least this many overlapping numeric actuals.
+pub const MIN_CORRELATION_POINTS: usize = 6;
+
+/// Parses the date formats seen in history points (`2026-01-31`, `31-01-2026`, `2026/01/31`).
+pub fn normalize_date(raw: &str) -> Option<NaiveDate> {
+    let raw = raw.trim();
+    ["%Y-%m-%d", "%d-%m-%Y", "%Y/%m/%d", "%d/%m/%Y"]
+        .iter()
+        .find_map(|fmt| NaiveDate::parse_from_str(raw, fmt).ok())
+}
+
+pub struct AlignedRow {
+    pub date: NaiveDate,
+    pub a: Option<Value>,
+    pub b: Option<Value>,
+}
+
+/// Outer-joins two point series on their normalized `date`. Points without a parseable date
+/// are dropped; when a series repeats a date the last point wins.
+pub fn align_by_date(a: &[Value], b: &[Value]) -> Vec<AlignedRow> {
+    let mut rows: BTreeMap<NaiveDate, (Option<Value>, Option<Value>)> = BTreeMap::new();
+    let date_of = |p: &Value| normalize_date(p.get("date").and_then(|v| v.as_str()).unwrap_or(""));
+    for point in a {
+        if let Some(date) = date_of(point) {
+            rows.entry(date).or_default().0 = Some(point.clone());
+        }
+    }
+    for point in b {
+        if let Some(date) = date_of(point) {
+            rows.entry(date).or_default().1 = Some(point.clone());
+        }
+    }
+    rows.into_iter()
+        .map(|(date, (a, b))| AlignedRow { date, a, b })
+        .collect()
+}
+
+/// Pearson correlation; `None` with fewer than two pairs or zero variance.
+pub fn pearson(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 2 {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some(cov / (var_x.sqrt() * var_y.sqrt()))
+}
+
+/// Dates where both sides have a numeric `actual`, and their correlation when there are enough.
+pub fn actual_correlation(rows: &[AlignedRow]) -> (usize, Option<f64>) {
+    let actual = |p: &Option<Value>| {
+        p.as_ref()
+            .and_then(|p| p.get("actual"))
+            .and_then(|v| v.as_str())
+            .and_then(parse_numeric)
+    };
+    let pairs: Vec<(f64, f64)> = rows
+        .iter()
+        .filter_map(|row| Some((actual(&row.a)?, actual(&row.b)?)))
+        .collect();
+    if pairs.len() < MIN_CORRELATION_POINTS {
+        return (pairs.len(), None);
+    }
+    (pairs.len(), pearson(&pairs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn point(date: &str, actual: &str) -> Value {
+        json!({"date": date, "actual": actual})
+    }
+
+    #[test]
+    fn outer_join_keeps_both_sides_and_normalizes_dates() {
+        let a = vec![point("2026-01-15", "3.1%"), point("2026-02-12", "3.0%")];
+        let b = vec![
+            point("15-01-2026", "2.9%"),
+            point("2026/03/12", "2.8%"),
+            point("soon", "1"),
+        ];
+        let rows = align_by_date(&a, &b);
+        let dates: Vec<String> = rows.iter().map(|r| r.date.to_string()).collect();
+        assert_eq!(dates, vec!["2026-01-15", "2026-02-12", "2026-03-12"]);
+        assert!(rows[0].a.is_some() && rows[0].b.is_some());
+        assert!(rows[1].a.is_some() && rows[1].b.is_none());
+        assert!(rows[2].a.is_none() && rows[2].b.is_some());
+    }
+
+    #[test]
+    fn monthly_against_weekly_overlaps_only_on_shared_dates() {
+        // Monthly CPI on the 7th; weekly claims every Thursday starting 2026-01-01.
+        let monthly: Vec<Value> = (1..=8)
+            .map(|m| {
+                point(
+                    &format!("2026-{m:02}-07"),
+                    &format!("{}", 2.0 + m as f64 / 10.0),
+                )
+            })
+            .collect();
+        let weekly: Vec<Value> = (0..35)
+            .map(|w| {
+                let date =
+                    NaiveDate::from_ymd_opt(2026, 1, 1).unwrap() + chrono::Duration::weeks(w);
+                point(&date.to_string(), &format!("{}K", 200 + w))
+            })
+            .collect();
+        let rows = align_by_date(&monthly, &weekly);
+        let both = rows
+            .iter()
+            .filter(|r| r.a.is_some() && r.b.is_some())
+            .count();
+        // 2026-05-07 is the only 7th falling on a Thursday between January and August.
+        assert_eq!(both, 1);
+        assert_eq!(rows.len(), 35 + 8 - both);
+        assert_eq!(actual_correlation(&rows), (1, None));
+    }
+
+    #[test]
+    fn correlation_needs_six_overlapping_numeric_points() {
+        let a: Vec<Value> = (1..=6)
+            .map(|d| point(&format!("2026-01-{d:02}"), &format!("{d}%")))
+            .collect();
+        let b: Vec<Value> = (1..=6)
+            .map(|d| point(&format!("2026-01-{d:02}"), &format!("{}", 10 - d)))
+            .collect();
+        let rows = align_by_date(&a, &b);
+        let (overlap, corr) = actual_correlation(&rows);
+        assert_eq!(overlap, 6);
+        assert!((corr.unwrap() + 1.0).abs() < 1e-9);
+
+        let rows = align_by_date(&a[..5], &b);
+        assert_eq!(actual_correlation(&rows), (5, None));
+    }
+
+    #[test]
+    fn pearson_rejects_constant_series() {
+        assert_eq!(pearson(&[(1.0, 2.0), (1.0, 3.0), (1.0, 4.0)]), None);
+        assert_eq!(pearson(&[(1.0, 2.0)]), None);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::calendar::{DEFAULT_MAX_CALENDAR_FILE_MB, DEFAULT_MAX_EVENTS_PER_FILE};
+use crate::log_file::DEFAULT_LOG_BUFFER_ENTRIES;
+use crate::snapshot::DEFAULT_RECENT_HIGHLIGHT_HOURS;
+use crate::sync_util::DeleteThreshold;
+use crate::time_util::DEFAULT_SOURCE_DATE_FORMAT;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// The user-facing config keys, typed. `AppSettings::default()` is the one place their defaults
+/// live: `config::default_config` is built from it. Bookkeeping keys (histories, timestamps,
+/// caches) and keys from newer builds stay raw `Value` entries in the config.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub auto_sync_after_pull: bool,
+    pub auto_update_enabled: bool,
+    pub auto_update_interval_minutes: i64,
+    pub check_interval_minutes: i64,
+    pub update_notify_mode: String,
+    pub update_verify_install: bool,
+    pub no_time_label: String,
+    /// Rendered event names are cut to this many characters; 0 leaves them whole.
+    pub event_name_max_len: i64,
+    pub recent_highlight_hours: i64,
+    pub min_forecast_change_pct: f64,
+    pub date_format: String,
+    pub source_date_format: String,
+    pub github_repo: String,
+    pub github_branch: String,
+    pub event_alerts_enabled: bool,
+    pub event_alert_minutes_before: i64,
+    pub event_alert_min_impact: String,
+    pub sync_timeout_secs: i64,
+    pub sync_interval_minutes: i64,
+    pub sync_atomic: bool,
+    /// "full" mirrors all the data; "current" only the calendar years around now.
+    pub sync_scope: String,
+    pub sync_delete_confirm_threshold: i64,
+    pub sync_delete_confirm_pct: f64,
+    pub sync_delete_confirm_timeout_secs: i64,
+    /// Pre-pull `data/` archives to keep; 0 turns backups off.
+    pub backup_keep_count: i64,
+    pub min_free_space_mb: i64,
+    pub tray_alert_minutes: i64,
+    pub respect_system_dnd: bool,
+    pub history_slim_mode: bool,
+    pub status_file_enabled: bool,
+    pub status_file_path: String,
+    pub instance_label: String,
+    pub run_on_startup: bool,
+    pub autostart_launch_mode: String,
+    pub close_behavior: String,
+    pub second_instance_action: String,
+    pub debug: bool,
+    pub log_min_level: String,
+    pub log_buffer_entries: i64,
+    pub max_calendar_file_mb: i64,
+    pub max_events_per_file: i64,
+    pub settings_auto_save: bool,
+    pub refresh_on_focus: bool,
+    pub split_ratio: f64,
+    pub enable_system_theme: bool,
+    pub theme_preference: String,
+    pub calendar_timezone_mode: String,
+    pub calendar_utc_offset_minutes: i64,
+    pub calendar_year_fallback: String,
+    pub enable_temporary_path: bool,
+    pub temporary_path: String,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        let delete_threshold = DeleteThreshold::default();
+        Self {
+            auto_sync_after_pull: true,
+            auto_update_enabled: true,
+            auto_update_interval_minutes: 60,
+            check_interval_minutes: 360,
+            update_notify_mode: "log".to_string(),
+            update_verify_install: true,
+            no_time_label: "All Day".to_string(),
+            event_name_max_len: 0,
+            recent_highlight_hours: DEFAULT_RECENT_HIGHLIGHT_HOURS,
+            min_forecast_change_pct: 0.0,
+            date_format: "dmy".to_string(),
+            source_date_format: DEFAULT_SOURCE_DATE_FORMAT.to_string(),
+            github_repo: "yiyousiow000814/XAUUSD-Calendar-Agent".to_string(),
+            github_branch: "main".to_string(),
+            event_alerts_enabled: false,
+            event_alert_minutes_before: 5,
+            event_alert_min_impact: "High".to_string(),
+            sync_timeout_secs: 120,
+            sync_interval_minutes: 0,
+            sync_atomic: false,
+            sync_scope: "full".to_string(),
+            sync_delete_confirm_threshold: delete_threshold.max_files as i64,
+            sync_delete_confirm_pct: delete_threshold.max_pct,
+            sync_delete_confirm_timeout_secs: 300,
+            backup_keep_count: 3,
+            min_free_space_mb: 200,
+            tray_alert_minutes: 30,
+            respect_system_dnd: true,
+            history_slim_mode: false,
+            status_file_enabled: false,
+            status_file_path: String::new(),
+            instance_label: String::new(),
+            run_on_startup: true,
+            autostart_launch_mode: "tray".to_string(),
+            close_behavior: "exit".to_string(),
+            second_instance_action: "focus".to_string(),
+            debug: false,
+            log_min_level: "INFO".to_string(),
+            log_buffer_entries: DEFAULT_LOG_BUFFER_ENTRIES as i64,
+            max_calendar_file_mb: DEFAULT_MAX_CALENDAR_FILE_MB,
+            max_events_per_file: DEFAULT_MAX_EVENTS_PER_FILE,
+            settings_auto_save: true,
+            refresh_on_focus: true,
+            split_ratio: 0.66,
+            enable_system_theme: false,
+            theme_preference: "system".to_string(),
+            calendar_timezone_mode: "system".to_string(),
+            calendar_utc_offset_minutes: 0,
+            calendar_year_fallback: "latest".to_string(),
+            enable_temporary_path: false,
+            temporary_path: String::new(),
+        }
+    }
+}
+
+/// Whether `value` can stand in for `default`: same JSON type, and integral where the default
+/// is an integer.
+fn same_kind(default: &Value, value: &Value) -> bool {
+    match (default, value) {
+        (Value::Bool(_), Value::Bool(_)) | (Value::String(_), Value::String(_)) => true,
+        (Value::Number(default), Value::Number(value)) => default.is_f64() || value.is_i64(),
+        _ => false,
+    }
+}
+
+impl AppSettings {
+    /// The defaults as config entries, keyed by config name.
+    pub fn default_entries() -> Map<String, Value> {
+        match serde_json::to_value(Self::default()) {
+            Ok(Value::Object(entries)) => entries,
+            _ => Map::new(),
+        }
+    }
+
+    /// Reads the settings from a merged config. Like `config::get_*`, a value of the wrong type
+    /// falls back to the default for that key only, and strings are trimmed.
+    pub fn from_config(cfg: &Value) -> Self {
+        let mut entries = Self::default_entries();
+        for (key, default) in entries.iter_mut() {
+            match cfg.get(key) {
+                Some(Value::String(text)) if default.is_string() => {
+                    *default = Value::String(text.trim().to_string());
+                }
+                Some(value) if same_kind(default, value) => *default = value.clone(),
+                _ => {}
+            }
+        }
+        serde_json::from_value(Value::Object(entries)).unwrap_or_default()
+    }
+
+    /// "utc" or "system"; anything else reads as "system".
+    pub fn timezone_mode(&self) -> &'static str {
+        if self.calendar_timezone_mode == "utc" {
+            "utc"
+        } else {
+            "system"
+        }
+    }
+
+    /// An autostart launch starts hidden in the tray unless the mode is "show".
+    pub fn launch_to_tray(&self) -> bool {
+        self.autostart_launch_mode != "show"
+    }
+
+    /// Closing the window hides it to the tray only when the behavior is "tray".
+    pub fn close_to_tray(&self) -> bool {
+        self.close_behavior == "tray"
+    }
+
+    /// "dark", "light" or "system".
+    pub fn theme(&self) -> &'static str {
+        match self.theme_preference.as_str() {
+            "dark" => "dark",
+            "light" => "light",
+            _ => "system",
+        }
+    }
+
+    /// "current" or "full"; anything else reads as "full".
+    pub fn sync_scope(&self) -> &'static str {
+        if self.sync_scope == "current" {
+            "current"
+        } else {
+            "full"
+        }
+    }
+
+    /// "modal", "silent" or "log"; anything else reads as "log", which only logs the update
+    /// like builds before the setting did.
+    pub fn notify_mode(&self) -> &'static str {
+        match self.update_notify_mode.as_str() {
+            "modal" => "modal",
+            "silent" => "silent",
+            _ => "log",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A valid value of the same type that differs from `default`.
+    fn changed(default: &Value) -> Value {
+        match default {
+            Value::Bool(b) => json!(!b),
+            Value::String(s) => json!(format!("{s}-changed")),
+            Value::Number(n) if n.is_f64() => json!(n.as_f64().unwrap() + 0.5),
+            Value::Number(n) => json!(n.as_i64().unwrap() + 1),
+            other => panic!("unexpected default {other}"),
+        }
+    }
+
+    #[test]
+    fn every_key_round_trips_through_the_config() {
+        let defaults = AppSettings::default_entries();
+        let cfg = crate::config::default_config();
+        for (key, default) in &defaults {
+            assert_eq!(cfg.get(key), Some(default), "{key} default");
+        }
+        assert_eq!(AppSettings::from_config(&cfg), AppSettings::default());
+
+        // Each field reads its own key: changing one key changes exactly that field.
+        for (key, default) in &defaults {
+            let mut one = defaults.clone();
+            one.insert(key.clone(), changed(default));
+            let settings = AppSettings::from_config(&Value::Object(one.clone()));
+            assert_eq!(
+                serde_json::to_value(&settings).unwrap(),
+                Value::Object(one),
+                "{key} round trip"
+            );
+        }
+
+        let edited: Map<String, Value> = defaults
+            .iter()
+            .map(|(key, default)| (key.clone(), changed(default)))
+            .collect();
+        let settings = AppSettings::from_config(&Value::Object(edited.clone()));
+        assert_ne!(settings, AppSettings::default());
+        assert_eq!(
+            serde_json::to_value(&settings).unwrap(),
+            Value::Object(edited)
+        );
+    }
+
+    #[test]
+    fn mistyped_values_fall_back_per_key() {
+        let settings = AppSettings::from_config(&json!({
+            "sync_timeout_secs": "fast",
+            "sync_atomic": 1,
+            "tray_alert_minutes": 12.5,
+            "split_ratio": 1,
+            "github_branch": "  dev  ",
+            "instance_label": null,
+            "debug": true,
+            "unknown_future_key": [1, 2],
+        }));
+        let defaults = AppSettings::default();
+        assert_eq!(settings.sync_timeout_secs, defaults.sync_timeout_secs);
+        assert_eq!(settings.sync_atomic, defaults.sync_atomic);
+        assert_eq!(settings.tray_alert_minutes, defaults.tray_alert_minutes);
+        assert_eq!(settings.split_ratio, 1.0);
+        assert_eq!(settings.github_branch, "dev");
+        assert_eq!(settings.instance_label, "");
+        assert!(settings.debug);
+    }
+
+    #[test]
+    fn enumerated_values_normalize_like_the_settings_payload() {
+        let settings = |cfg: Value| AppSettings::from_config(&cfg);
+        assert!(settings(json!({"autostart_launch_mode": ""})).launch_to_tray());
+        assert!(!settings(json!({"autostart_launch_mode": "show"})).launch_to_tray());
+        assert!(!settings(json!({"close_behavior": "hide"})).close_to_tray());
+        assert_eq!(
+            settings(json!({"theme_preference": "blue"})).theme(),
+            "system"
+        );
+        assert_eq!(
+            settings(json!({"update_notify_mode": "modal"})).notify_mode(),
+            "modal"
+        );
+        assert_eq!(
+            settings(json!({"update_notify_mode": "popup"})).notify_mode(),
+            "log"
+        );
+        assert_eq!(AppSettings::default().notify_mode(), "log");
+        assert_eq!(
+            settings(json!({"sync_scope": "recent"})).sync_scope(),
+            "full"
+        );
+        assert_eq!(
+            settings(json!({"calendar_timezone_mode": "utc"})).timezone_mode(),
+            "utc"
+        );
+    }
+}
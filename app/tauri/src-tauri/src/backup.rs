@@ -0,0 +1,242 @@
+use crate::sync_util::iter_files;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Archives live in `<appdata>/backups`, which `storage_usage` reports on its own.
+pub const BACKUPS_DIR: &str = "backups";
+
+const ARCHIVE_PREFIX: &str = "data-";
+const ARCHIVE_SUFFIX: &str = ".tar.gz";
+const STAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+/// Top-level folder inside each archive.
+const ARCHIVE_ROOT: &str = "data";
+
+pub fn backups_dir() -> PathBuf {
+    crate::config::appdata_dir().join(BACKUPS_DIR)
+}
+
+/// A pre-pull backup archive.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub name: String,
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(serialize_with = "rfc3339")]
+    pub created_at: DateTime<Utc>,
+    pub bytes: u64,
+}
+
+fn rfc3339<S: serde::Serializer>(at: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&at.to_rfc3339())
+}
+
+fn archive_name(at: DateTime<Utc>) -> String {
+    format!(
+        "{ARCHIVE_PREFIX}{}{ARCHIVE_SUFFIX}",
+        at.format(STAMP_FORMAT)
+    )
+}
+
+fn archive_time(name: &str) -> Option<DateTime<Utc>> {
+    let stamp = name
+        .strip_prefix(ARCHIVE_PREFIX)?
+        .strip_suffix(ARCHIVE_SUFFIX)?;
+    NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT)
+        .ok()
+        .map(|at| at.and_utc())
+}
+
+/// Archives in `dir`, newest first. Other files are ignored.
+pub fn list_backups(dir: &Path) -> Vec<BackupInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut backups: Vec<BackupInfo> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let created_at = archive_time(&name)?;
+            Some(BackupInfo {
+                bytes: entry.metadata().map(|meta| meta.len()).unwrap_or(0),
+                path: entry.path(),
+                name,
+                created_at,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    backups
+}
+
+/// Deletes all but the newest `keep` archives; returns how many were removed.
+pub fn prune_backups(dir: &Path, keep: usize) -> usize {
+    list_backups(dir)
+        .into_iter()
+        .skip(keep)
+        .filter(|backup| fs::remove_file(&backup.path).is_ok())
+        .count()
+}
+
+/// Compresses `data_dir` into `data-<timestamp>.tar.gz` in `dir`, then prunes to the newest
+/// `keep`. Lock sidecars are left out, as in every other walk. The archive is written under a
+/// temporary name so a failed backup never lists as one.
+pub fn create_backup(
+    data_dir: &Path,
+    dir: &Path,
+    keep: usize,
+    now: DateTime<Utc>,
+) -> Result<BackupInfo, String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let name = archive_name(now);
+    let path = dir.join(&name);
+    let tmp = dir.join(format!("{name}.tmp-{}", std::process::id()));
+    let written = (|| -> Result<(), String> {
+        let file = fs::File::create(&tmp).map_err(|e| e.to_string())?;
+        let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        let mut files: Vec<(String, PathBuf)> = iter_files(data_dir).into_iter().collect();
+        files.sort();
+        for (rel, file_path) in files {
+            let rel = rel.replace('\\', "/");
+            archive
+                .append_path_with_name(&file_path, format!("{ARCHIVE_ROOT}/{rel}"))
+                .map_err(|e| format!("{rel}: {e}"))?;
+        }
+        archive
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(|e| e.to_string())?;
+        fs::rename(&tmp, &path).map_err(|e| e.to_string())
+    })();
+    if let Err(err) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(err);
+    }
+    prune_backups(dir, keep.max(1));
+    Ok(BackupInfo {
+        bytes: fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0),
+        path,
+        name,
+        created_at: archive_time(&archive_name(now)).unwrap_or(now),
+    })
+}
+
+/// Makes `data_dir` match `archive`: every archived file is written back and files the archive
+/// does not hold are removed. The archive is unpacked next to `data_dir` first, so a corrupt
+/// archive leaves the current data untouched. Returns the number of files restored.
+pub fn restore_backup(archive: &Path, data_dir: &Path) -> Result<usize, String> {
+    let parent = data_dir
+        .parent()
+        .ok_or_else(|| format!("{} has no parent folder", data_dir.display()))?;
+    let staging = parent.join(format!(".restore-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&staging);
+    let restored = (|| -> Result<usize, String> {
+        let file = fs::File::open(archive).map_err(|e| e.to_string())?;
+        tar::Archive::new(GzDecoder::new(file))
+            .unpack(&staging)
+            .map_err(|e| format!("{}: {e}", archive.display()))?;
+        let src = staging.join(ARCHIVE_ROOT);
+        let wanted = iter_files(&src);
+        for (rel, current) in iter_files(data_dir) {
+            if !wanted.contains_key(&rel) {
+                fs::remove_file(&current).map_err(|e| format!("{rel}: {e}"))?;
+            }
+        }
+        for (rel, file_path) in &wanted {
+            let dst = data_dir.join(rel);
+            if let Some(dir) = dst.parent() {
+                fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            fs::copy(file_path, &dst).map_err(|e| format!("{rel}: {e}"))?;
+        }
+        Ok(wanted.len())
+    })();
+    let _ = fs::remove_dir_all(&staging);
+    restored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::collections::BTreeMap;
+
+    fn tree(root: &Path) -> BTreeMap<String, Vec<u8>> {
+        iter_files(root)
+            .into_iter()
+            .map(|(rel, path)| (rel.replace('\\', "/"), fs::read(path).unwrap()))
+            .collect()
+    }
+
+    fn at(second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 5, 8, 0, second).unwrap()
+    }
+
+    #[test]
+    fn backup_restores_a_corrupted_tree() {
+        let root = tempfile::tempdir().unwrap();
+        let data = root.path().join("data");
+        let history = data.join("event_history_index");
+        fs::create_dir_all(data.join("Economic_Calendar/2026")).unwrap();
+        fs::create_dir_all(&history).unwrap();
+        fs::write(
+            data.join("Economic_Calendar/2026/2026_calendar.json"),
+            r#"[{"Date": "2026-03-05", "Event": "CPI m/m"}]"#,
+        )
+        .unwrap();
+        fs::write(history.join("history.ndjson"), "{\"id\":1}\n".repeat(500)).unwrap();
+        fs::write(history.join("history.ndjson.lock"), "").unwrap();
+        let before = tree(&data);
+
+        let backups = root.path().join("backups");
+        let backup = create_backup(&data, &backups, 3, at(0)).unwrap();
+        assert_eq!(backup.name, "data-20260305T080000000Z.tar.gz");
+        // The repetitive index compresses well below its raw size.
+        assert!(backup.bytes < 1000, "{} bytes", backup.bytes);
+
+        fs::write(
+            data.join("Economic_Calendar/2026/2026_calendar.json"),
+            "[{\"Date\": \"bro",
+        )
+        .unwrap();
+        fs::remove_file(history.join("history.ndjson")).unwrap();
+        fs::write(data.join("stray.json"), "{}").unwrap();
+
+        assert_eq!(restore_backup(&backup.path, &data).unwrap(), 2);
+        assert_eq!(tree(&data), before);
+        // The live lock sidecar is neither archived nor removed.
+        assert!(history.join("history.ndjson.lock").exists());
+        assert!(!root
+            .path()
+            .join(format!(".restore-{}", std::process::id()))
+            .exists());
+    }
+
+    #[test]
+    fn only_the_newest_backups_are_kept() {
+        let root = tempfile::tempdir().unwrap();
+        let data = root.path().join("data");
+        fs::create_dir_all(&data).unwrap();
+        fs::write(data.join("a.json"), "[]").unwrap();
+        let backups = root.path().join("backups");
+        fs::create_dir_all(&backups).unwrap();
+        fs::write(backups.join("notes.txt"), "not a backup").unwrap();
+        for second in 0..4 {
+            create_backup(&data, &backups, 2, at(second)).unwrap();
+        }
+        let names: Vec<String> = list_backups(&backups).into_iter().map(|b| b.name).collect();
+        assert_eq!(
+            names,
+            vec![
+                "data-20260305T080003000Z.tar.gz",
+                "data-20260305T080002000Z.tar.gz"
+            ]
+        );
+        assert!(backups.join("notes.txt").exists());
+    }
+}
@@ -0,0 +1,116 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+static PROCESS_START: OnceLock<Instant> = OnceLock::new();
+
+/// How long after setup the deferred startup work runs when `frontend_boot_complete` has not
+/// arrived first.
+pub const DEFERRED_STARTUP_FALLBACK: Duration = Duration::from_millis(1500);
+
+/// Pins the process start instant; call first thing in `main`.
+pub fn mark_process_start() {
+    PROCESS_START.get_or_init(Instant::now);
+}
+
+pub fn since_start_ms() -> u64 {
+    PROCESS_START
+        .get_or_init(Instant::now)
+        .elapsed()
+        .as_millis() as u64
+}
+
+/// Milliseconds after process start at which each boot phase was first reached.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BootTimeline {
+    pub setup_done_ms: Option<u64>,
+    pub deferred_startup_ms: Option<u64>,
+    pub first_snapshot_ms: Option<u64>,
+    pub calendar_loaded_ms: Option<u64>,
+}
+
+/// Records the current offset in `phase` unless it was already reached.
+pub fn mark(phase: &mut Option<u64>) {
+    if phase.is_none() {
+        *phase = Some(since_start_ms());
+    }
+}
+
+/// Guards work that must run exactly once, whichever trigger gets there first.
+#[derive(Default)]
+pub struct RunOnce(AtomicBool);
+
+impl RunOnce {
+    /// Runs `work` if nothing has claimed this guard yet; returns whether it ran.
+    pub fn run(&self, work: impl FnOnce()) -> bool {
+        if self.0.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        work();
+        true
+    }
+}
+
+/// Runs `work` through `once` after `delay`, as the fallback for a trigger that may never fire.
+pub fn spawn_fallback(
+    once: Arc<RunOnce>,
+    delay: Duration,
+    work: impl FnOnce() + Send + 'static,
+) -> std::thread::JoinHandle<bool> {
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        once.run(work)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn deferred_work_runs_once_without_boot_complete() {
+        let once = Arc::new(RunOnce::default());
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let fallback = spawn_fallback(once.clone(), Duration::from_millis(20), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        assert!(fallback.join().unwrap());
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // A late boot-complete signal does not repeat the work.
+        assert!(!once.run(|| {
+            runs.fetch_add(1, Ordering::SeqCst);
+        }));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn boot_complete_preempts_the_fallback() {
+        let once = Arc::new(RunOnce::default());
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let fallback = spawn_fallback(once.clone(), Duration::from_millis(50), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        assert!(once.run(|| {
+            runs.fetch_add(1, Ordering::SeqCst);
+        }));
+        assert!(!fallback.join().unwrap());
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn phases_keep_their_first_mark() {
+        let mut timeline = BootTimeline::default();
+        mark(&mut timeline.setup_done_ms);
+        let first = timeline.setup_done_ms;
+        std::thread::sleep(Duration::from_millis(5));
+        mark(&mut timeline.setup_done_ms);
+        assert_eq!(timeline.setup_done_ms, first);
+        assert!(timeline.first_snapshot_ms.is_none());
+    }
+}
@@ -1,7 +1,16 @@
-use crate::time_util::parse_source_dt_to_utc;
+use crate::app_settings::AppSettings;
+use crate::categories::{CategoryRules, CATEGORY_OVERRIDE_FILE};
+use crate::config;
+use crate::custom_events::CUSTOM_EVENTS_FILE;
+use crate::time_util::{parse_source_dt_to_utc, source_date_format, DEFAULT_SOURCE_DATE_FORMAT};
 use chrono::{DateTime, Datelike, Utc};
-use serde::Deserialize;
+use serde::de::{DeserializeSeed, IgnoredAny, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha1::{Digest, Sha1};
+use std::collections::HashSet;
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
 pub const CALENDAR_SOURCE_UTC_OFFSET_MINUTES: i32 = 0;
@@ -16,6 +25,171 @@ pub struct CalendarEvent {
     pub actual: String,
     pub forecast: String,
     pub previous: String,
+    /// False when the source marks the time as tentative.
+    pub time_confirmed: bool,
+    /// Category names from the taxonomy rules, assigned at load.
+    pub categories: Vec<String>,
+    pub source: EventSource,
+}
+
+/// Where a row came from: the pulled calendar data or the user's `custom_events.json`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EventSource {
+    #[default]
+    Calendar,
+    Custom,
+    /// Stand-in rows from `generate_placeholder_events`; never real data.
+    Placeholder,
+}
+
+impl EventSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventSource::Calendar => "calendar",
+            EventSource::Custom => "custom",
+            EventSource::Placeholder => "placeholder",
+        }
+    }
+}
+
+/// Text of every placeholder row.
+pub const PLACEHOLDER_EVENT_TEXT: &str = "No data yet \u{2014} run Pull";
+
+/// One clearly-labelled row per day for the next `days` days, so a first run with no data and
+/// no network still renders the list. Currency and importance are `--`, and there is no time,
+/// so alerts never pick them up.
+pub fn generate_placeholder_events(days: i64) -> Vec<CalendarEvent> {
+    let today = crate::time_util::now_utc().date_naive();
+    (1..=days.max(0))
+        .filter_map(|offset| {
+            let day = today + chrono::Duration::days(offset);
+            Some(CalendarEvent {
+                dt_utc: day.and_hms_opt(12, 0, 0)?.and_utc(),
+                time_label: String::new(),
+                event: PLACEHOLDER_EVENT_TEXT.to_string(),
+                currency: "--".to_string(),
+                importance: "--".to_string(),
+                actual: String::new(),
+                forecast: String::new(),
+                previous: String::new(),
+                time_confirmed: true,
+                categories: vec![],
+                source: EventSource::Placeholder,
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum YearFallback {
+    /// Load the newest year directory when none covers the current window.
+    Latest,
+    /// Load nothing and report a warning instead.
+    None,
+}
+
+#[derive(Clone, Debug)]
+pub struct CalendarLoadOptions {
+    pub year_fallback: YearFallback,
+    /// User category rules merged over the bundled taxonomy.
+    pub category_override: Option<PathBuf>,
+    /// User reminders merged in after the calendar files.
+    pub custom_events: Option<PathBuf>,
+    /// strftime layout of the calendar files' `Date` column.
+    pub source_date_format: String,
+    pub limits: FileLimits,
+}
+
+impl Default for CalendarLoadOptions {
+    fn default() -> Self {
+        Self {
+            year_fallback: YearFallback::Latest,
+            category_override: None,
+            custom_events: None,
+            source_date_format: DEFAULT_SOURCE_DATE_FORMAT.to_string(),
+            limits: FileLimits::default(),
+        }
+    }
+}
+
+impl CalendarLoadOptions {
+    pub fn from_config(cfg: &Value) -> Self {
+        let year_fallback = if config::get_str(cfg, "calendar_year_fallback") == "none" {
+            YearFallback::None
+        } else {
+            YearFallback::Latest
+        };
+        Self {
+            year_fallback,
+            category_override: Some(config::working_data_dir(cfg).join(CATEGORY_OVERRIDE_FILE)),
+            custom_events: Some(config::appdata_dir().join(CUSTOM_EVENTS_FILE)),
+            source_date_format: source_date_format(cfg),
+            limits: FileLimits::from_config(cfg),
+        }
+    }
+}
+
+pub const DEFAULT_MAX_CALENDAR_FILE_MB: i64 = 50;
+pub const DEFAULT_MAX_EVENTS_PER_FILE: i64 = 100_000;
+
+/// Bounds on what one data file may cost to read, so a corrupt or hostile file cannot balloon
+/// memory. Applied to the year files, `custom_events.json` and the event history files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileLimits {
+    /// Files larger than this are refused without being read.
+    pub max_bytes: u64,
+    /// Rows past this many are counted but not kept.
+    pub max_events: usize,
+}
+
+impl Default for FileLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_CALENDAR_FILE_MB as u64 * 1024 * 1024,
+            max_events: DEFAULT_MAX_EVENTS_PER_FILE as usize,
+        }
+    }
+}
+
+impl FileLimits {
+    /// `max_calendar_file_mb` and `max_events_per_file`; values below 1 fall back to defaults.
+    pub fn from_config(cfg: &Value) -> Self {
+        let settings = AppSettings::from_config(cfg);
+        let (mb, events) = (settings.max_calendar_file_mb, settings.max_events_per_file);
+        let defaults = Self::default();
+        Self {
+            max_bytes: if mb >= 1 {
+                mb as u64 * 1024 * 1024
+            } else {
+                defaults.max_bytes
+            },
+            max_events: if events >= 1 {
+                events as usize
+            } else {
+                defaults.max_events
+            },
+        }
+    }
+
+    /// Size of `path`, or an error when it is over `max_bytes`.
+    pub fn check_size(&self, path: &Path) -> Result<u64, String> {
+        let size = fs::metadata(path).map_err(|e| e.to_string())?.len();
+        if size > self.max_bytes {
+            return Err(format!(
+                "{} is {size} bytes, over the {} byte limit (max_calendar_file_mb); not read",
+                path.display(),
+                self.max_bytes
+            ));
+        }
+        Ok(size)
+    }
+}
+
+#[derive(Default)]
+pub struct CalendarLoad {
+    pub events: Vec<CalendarEvent>,
+    pub warnings: Vec<String>,
+    pub files: Vec<FileLoadStats>,
 }
 
 #[derive(Deserialize)]
@@ -36,19 +210,172 @@ struct RawEvent {
     forecast: Option<String>,
     #[serde(rename = "Previous")]
     previous: Option<String>,
+    #[serde(rename = "TimeStatus")]
+    time_status: Option<String>,
+    #[serde(rename = "Confirmed")]
+    confirmed: Option<Value>,
 }
 
-fn read_year_file(path: &Path) -> Vec<RawEvent> {
-    let text = fs::read_to_string(path).unwrap_or_default();
-    serde_json::from_str::<Vec<RawEvent>>(&text).unwrap_or_default()
+/// `Confirmed` (bool or bool-like string) wins over `TimeStatus`; absent means confirmed.
+fn time_confirmed(confirmed: Option<&Value>, time_status: Option<&str>) -> bool {
+    match confirmed {
+        Some(Value::Bool(v)) => return *v,
+        Some(Value::String(v)) => match v.trim().to_lowercase().as_str() {
+            "true" | "yes" | "1" => return true,
+            "false" | "no" | "0" => return false,
+            _ => {}
+        },
+        _ => {}
+    }
+    let status = time_status.unwrap_or("").trim().to_lowercase();
+    !matches!(status.as_str(), "tentative" | "unconfirmed" | "tbd" | "tba")
 }
 
-fn pick_year_files(calendar_root: &Path) -> Vec<PathBuf> {
-    let now = chrono::Local::now();
-    let current_year = now.year();
-    let oldest_needed_year = (now - chrono::Duration::days(31)).year();
-    let wanted = [current_year, current_year + 1, oldest_needed_year];
+/// Per-file outcome of a calendar load, kept for diagnostics.
+#[derive(Clone, Debug, Default)]
+pub struct FileLoadStats {
+    pub parse_error: Option<String>,
+    pub rows: usize,
+    /// Rows with a date and event name whose date/time failed to parse.
+    pub dropped: usize,
+    /// Rows skipped because the file holds more than `FileLimits::max_events`.
+    pub truncated: usize,
+    /// The file was over `FileLimits::max_bytes` and was not read; `parse_error` says why.
+    pub oversized: bool,
+}
+
+/// Rows read from one file: the first `max_events` rows, plus how many the file holds in all.
+struct YearRows {
+    rows: Vec<RawEvent>,
+    total: usize,
+}
+
+impl YearRows {
+    fn skipped(&self) -> usize {
+        self.total - self.rows.len()
+    }
+}
+
+/// Parses a top-level array keeping at most `max` rows; the rest are skipped without being
+/// materialized, so an oversized file costs its row count, not its contents.
+struct CappedRows {
+    max: usize,
+}
+
+impl<'de> DeserializeSeed<'de> for CappedRows {
+    type Value = YearRows;
 
+    fn deserialize<D: serde::Deserializer<'de>>(self, de: D) -> Result<YearRows, D::Error> {
+        de.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for CappedRows {
+    type Value = YearRows;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("an array of calendar rows")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<YearRows, A::Error> {
+        let mut rows = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(self.max));
+        while rows.len() < self.max {
+            match seq.next_element::<RawEvent>()? {
+                Some(row) => rows.push(row),
+                None => {
+                    let total = rows.len();
+                    return Ok(YearRows { rows, total });
+                }
+            }
+        }
+        let mut total = rows.len();
+        while seq.next_element::<IgnoredAny>()?.is_some() {
+            total += 1;
+        }
+        Ok(YearRows { rows, total })
+    }
+}
+
+fn parse_capped<'de, R: serde_json::de::Read<'de>>(
+    mut de: serde_json::Deserializer<R>,
+    max_events: usize,
+) -> Result<YearRows, String> {
+    let rows = CappedRows { max: max_events }
+        .deserialize(&mut de)
+        .map_err(|e| e.to_string())?;
+    de.end().map_err(|e| e.to_string())?;
+    Ok(rows)
+}
+
+/// Year files larger than this are parsed straight from the file instead of from a `String`.
+const STREAMING_PARSE_THRESHOLD_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Suffix of gzipped year files (`2024_calendar.json.gz`).
+const GZIP_JSON_SUFFIX: &str = ".json.gz";
+
+fn is_gzip(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("gz")
+}
+
+fn read_year_file(path: &Path, limits: &FileLimits) -> Result<YearRows, String> {
+    if is_gzip(path) {
+        return read_gzip_year_file(path, limits);
+    }
+    let size = limits.check_size(path)?;
+    read_plain_year_file(path, size, limits)
+}
+
+/// Reads an uncompressed year file whose `size` already passed `FileLimits::check_size`.
+fn read_plain_year_file(path: &Path, size: u64, limits: &FileLimits) -> Result<YearRows, String> {
+    if size > STREAMING_PARSE_THRESHOLD_BYTES {
+        return read_year_file_streaming(path, limits);
+    }
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_capped(serde_json::Deserializer::from_str(&text), limits.max_events)
+}
+
+/// Same result as `read_year_file`, but rows are deserialized one at a time from a buffered
+/// reader, so a multi-decade archive is never held as text alongside its parsed rows. Slower
+/// than parsing a `String`, hence only used past `STREAMING_PARSE_THRESHOLD_BYTES`.
+fn read_year_file_streaming(path: &Path, limits: &FileLimits) -> Result<YearRows, String> {
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let reader = std::io::BufReader::new(file);
+    parse_capped(
+        serde_json::Deserializer::from_reader(reader),
+        limits.max_events,
+    )
+}
+
+/// Decompresses a gzipped year file and parses it like a plain one.
+fn read_gzip_year_file(path: &Path, limits: &FileLimits) -> Result<YearRows, String> {
+    let text = read_year_text(path, limits)?;
+    parse_capped(serde_json::Deserializer::from_str(&text), limits.max_events)
+}
+
+/// The JSON text of a year file, decompressed when gzipped. For a gzipped file the size limit
+/// applies to the decompressed text; decompression stops as soon as it is passed.
+fn read_year_text(path: &Path, limits: &FileLimits) -> Result<String, String> {
+    if !is_gzip(path) {
+        limits.check_size(path)?;
+        return fs::read_to_string(path).map_err(|e| e.to_string());
+    }
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut text = String::new();
+    flate2::read::GzDecoder::new(file)
+        .take(limits.max_bytes.saturating_add(1))
+        .read_to_string(&mut text)
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    if text.len() as u64 > limits.max_bytes {
+        return Err(format!(
+            "{} decompresses to over the {} byte limit (max_calendar_file_mb); not read",
+            path.display(),
+            limits.max_bytes
+        ));
+    }
+    Ok(text)
+}
+
+pub fn list_year_dirs(calendar_root: &Path) -> Vec<i32> {
     let mut year_dirs: Vec<i32> = vec![];
     if let Ok(entries) = fs::read_dir(calendar_root) {
         for entry in entries.flatten() {
@@ -63,6 +390,44 @@ fn pick_year_files(calendar_root: &Path) -> Vec<PathBuf> {
     }
     year_dirs.sort();
     year_dirs.dedup();
+    year_dirs
+}
+
+/// `{year}_calendar.json` when present, then `{year}_calendar.json.gz`, otherwise the first
+/// `.json` in the year directory and failing that the first `.json.gz`. An uncompressed file
+/// always wins over a gzipped one.
+pub fn year_file_path(calendar_root: &Path, year: i32) -> Option<PathBuf> {
+    let year_path = calendar_root.join(year.to_string());
+    let preferred = year_path.join(format!("{year}_calendar.json"));
+    let preferred_gz = year_path.join(format!("{year}_calendar{GZIP_JSON_SUFFIX}"));
+    if let Some(path) = [preferred, preferred_gz].into_iter().find(|p| p.exists()) {
+        return Some(path);
+    }
+    let entries = fs::read_dir(&year_path).ok()?;
+    let mut gzipped = None;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            return Some(path);
+        }
+        if gzipped.is_none() && path.to_string_lossy().ends_with(GZIP_JSON_SUFFIX) {
+            gzipped = Some(path);
+        }
+    }
+    gzipped
+}
+
+fn pick_year_files(
+    calendar_root: &Path,
+    options: &CalendarLoadOptions,
+    warnings: &mut Vec<String>,
+) -> Vec<PathBuf> {
+    let now = chrono::Local::now();
+    let current_year = now.year();
+    let oldest_needed_year = (now - chrono::Duration::days(31)).year();
+    let wanted = [current_year, current_year + 1, oldest_needed_year];
+
+    let year_dirs = list_year_dirs(calendar_root);
     if year_dirs.is_empty() {
         return vec![];
     }
@@ -73,41 +438,26 @@ fn pick_year_files(calendar_root: &Path) -> Vec<PathBuf> {
         .filter(|y| wanted.contains(y))
         .collect();
     if candidates.is_empty() {
-        candidates.push(*year_dirs.last().unwrap());
-    }
-
-    let mut files = vec![];
-    for year in candidates {
-        let year_path = calendar_root.join(year.to_string());
-        let preferred = year_path.join(format!("{year}_calendar.json"));
-        if preferred.exists() {
-            files.push(preferred);
-            continue;
-        }
-        if let Ok(entries) = fs::read_dir(&year_path) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().and_then(|e| e.to_str()) == Some("json") {
-                    files.push(path);
-                    break;
-                }
+        match options.year_fallback {
+            YearFallback::Latest => candidates.push(*year_dirs.last().unwrap()),
+            YearFallback::None => {
+                warnings.push("No year files for the current window; pull fresh data".to_string());
+                return vec![];
             }
         }
     }
-    files
-}
 
-pub fn load_calendar_events(repo_path: &Path) -> Vec<CalendarEvent> {
-    let calendar_root = repo_path.join("data").join("Economic_Calendar");
-    if !calendar_root.exists() {
-        return vec![];
-    }
-
-    let mut raw_items: Vec<RawEvent> = vec![];
-    for file in pick_year_files(&calendar_root) {
-        raw_items.extend(read_year_file(&file));
-    }
+    candidates
+        .into_iter()
+        .filter_map(|year| year_file_path(calendar_root, year))
+        .collect()
+}
 
+fn convert_raw_events(
+    raw_items: Vec<RawEvent>,
+    date_format: &str,
+    stats: &mut FileLoadStats,
+) -> Vec<CalendarEvent> {
     let mut events: Vec<CalendarEvent> = vec![];
     for item in raw_items {
         let date_raw = item.date.unwrap_or_default();
@@ -127,12 +477,17 @@ pub fn load_calendar_events(repo_path: &Path) -> Vec<CalendarEvent> {
         let dt_utc = match parse_source_dt_to_utc(
             &date_raw,
             &time_raw,
+            date_format,
             CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
         ) {
             Some(v) => v,
-            None => continue,
+            None => {
+                stats.dropped += 1;
+                continue;
+            }
         };
 
+        let time_confirmed = time_confirmed(item.confirmed.as_ref(), item.time_status.as_deref());
         events.push(CalendarEvent {
             dt_utc,
             time_label,
@@ -142,11 +497,611 @@ pub fn load_calendar_events(repo_path: &Path) -> Vec<CalendarEvent> {
             actual: item.actual.unwrap_or_default().trim().to_string(),
             forecast: item.forecast.unwrap_or_default().trim().to_string(),
             previous: item.previous.unwrap_or_default().trim().to_string(),
+            time_confirmed,
+            categories: vec![],
+            source: EventSource::Calendar,
         });
     }
+    events
+}
+
+/// A row skipped at load because its date/time did not parse.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DroppedRow {
+    pub file: PathBuf,
+    pub date: String,
+    pub time: String,
+    pub event: String,
+    pub currency: String,
+}
+
+/// Rows of the year files `load_calendar` would read that were dropped for an unparseable
+/// date/time and for which `matches(event, currency)` holds.
+pub fn find_dropped_rows(
+    repo_path: &Path,
+    options: &CalendarLoadOptions,
+    matches: &dyn Fn(&str, &str) -> bool,
+) -> Vec<DroppedRow> {
+    let calendar_root = repo_path.join("data").join("Economic_Calendar");
+    let mut dropped = vec![];
+    for file in pick_year_files(&calendar_root, options, &mut vec![]) {
+        let Ok(read) = read_year_file(&file, &options.limits) else {
+            continue;
+        };
+        for item in read.rows {
+            let date = item.date.unwrap_or_default().trim().to_string();
+            let time = item.time.unwrap_or_default().trim().to_string();
+            let event = item.event.unwrap_or_default().trim().to_string();
+            let currency = item.currency.unwrap_or_default().trim().to_uppercase();
+            if date.is_empty() || event.is_empty() || !matches(&event, &currency) {
+                continue;
+            }
+            let dt = parse_source_dt_to_utc(
+                &date,
+                &time,
+                &options.source_date_format,
+                CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
+            );
+            if dt.is_none() {
+                dropped.push(DroppedRow {
+                    file: file.clone(),
+                    date,
+                    time,
+                    event,
+                    currency,
+                });
+            }
+        }
+    }
+    dropped
+}
+
+/// Sample offenders kept per `IntegrityIssue`.
+const INTEGRITY_SAMPLES: usize = 5;
+
+/// A row quoted in an `IntegrityReport`; `row` is its 0-based position in the file.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct IntegritySample {
+    pub row: usize,
+    pub date: String,
+    pub time: String,
+    pub event: String,
+    pub currency: String,
+}
+
+/// How many rows broke one rule, with the first few of them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct IntegrityIssue {
+    pub count: usize,
+    pub samples: Vec<IntegritySample>,
+}
+
+impl IntegrityIssue {
+    fn record(&mut self, sample: impl FnOnce() -> IntegritySample) {
+        self.count += 1;
+        if self.samples.len() < INTEGRITY_SAMPLES {
+            self.samples.push(sample());
+        }
+    }
+}
+
+/// Consistency problems in one raw year file, for contributors to fix before committing.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub rows: usize,
+    /// Rows past `FileLimits::max_events`, which were not checked.
+    pub truncated: usize,
+    /// Rows whose date (source time) is not in the file's year, or does not parse.
+    pub out_of_year: IntegrityIssue,
+    /// Times with a colon that are not `HH:MM`; the loader reads them as midnight.
+    pub invalid_times: IntegrityIssue,
+    /// Currencies outside `currency_options`.
+    pub unknown_currencies: IntegrityIssue,
+    /// Rows identical in every column to an earlier row.
+    pub duplicates: IntegrityIssue,
+}
+
+/// Checks the raw rows of `path`, the file for `year`, with dates read in `date_format`.
+/// Times without a colon ("All Day", "Tentative") are the files' untimed markers and pass.
+pub fn check_year_integrity(
+    path: &Path,
+    year: i32,
+    date_format: &str,
+    limits: &FileLimits,
+) -> Result<IntegrityReport, String> {
+    let read = read_year_file(path, limits)?;
+    let known: HashSet<String> = currency_options()
+        .into_iter()
+        .filter(|c| c != "ALL")
+        .collect();
+    let mut report = IntegrityReport {
+        rows: read.rows.len(),
+        truncated: read.skipped(),
+        ..IntegrityReport::default()
+    };
+    let mut seen: HashSet<Vec<String>> = HashSet::new();
+    for (row, item) in read.rows.iter().enumerate() {
+        let text = |v: &Option<String>| v.as_deref().unwrap_or("").trim().to_string();
+        let (date, time, event) = (text(&item.date), text(&item.time), text(&item.event));
+        let currency = text(&item.currency).to_uppercase();
+        let sample = || IntegritySample {
+            row,
+            date: date.clone(),
+            time: time.clone(),
+            event: event.clone(),
+            currency: currency.clone(),
+        };
+
+        let source_year = parse_source_dt_to_utc(
+            &date,
+            &time,
+            date_format,
+            CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
+        )
+        .map(|dt| {
+            (dt + chrono::Duration::minutes(CALENDAR_SOURCE_UTC_OFFSET_MINUTES as i64)).year()
+        });
+        if source_year != Some(year) {
+            report.out_of_year.record(sample);
+        }
+        if time.contains(':') && crate::time_util::parse_hhmm(&time).is_none() {
+            report.invalid_times.record(sample);
+        }
+        if !known.contains(&currency) {
+            report.unknown_currencies.record(sample);
+        }
+        let key = vec![
+            date.clone(),
+            time.clone(),
+            event.clone(),
+            currency.clone(),
+            text(&item.importance),
+            text(&item.actual),
+            text(&item.forecast),
+            text(&item.previous),
+            text(&item.time_status),
+            item.confirmed
+                .as_ref()
+                .map(Value::to_string)
+                .unwrap_or_default(),
+        ];
+        if !seen.insert(key) {
+            report.duplicates.record(sample);
+        }
+    }
+    Ok(report)
+}
+
+/// Loads a single year file, returning its events in file order plus load statistics. Dates are
+/// read with `date_format` (see `CalendarLoadOptions::source_date_format`); rows past
+/// `limits.max_events` are left out and counted in `FileLoadStats::truncated`.
+pub fn load_year_file(
+    path: &Path,
+    date_format: &str,
+    limits: &FileLimits,
+) -> (Vec<CalendarEvent>, FileLoadStats) {
+    let mut stats = FileLoadStats::default();
+    let read = if is_gzip(path) {
+        read_year_file(path, limits)
+    } else {
+        match limits.check_size(path) {
+            Ok(size) => read_plain_year_file(path, size, limits),
+            Err(err) => {
+                stats.oversized = fs::metadata(path).is_ok();
+                stats.parse_error = Some(err);
+                return (vec![], stats);
+            }
+        }
+    };
+    let read = match read {
+        Ok(read) => read,
+        Err(err) => {
+            stats.parse_error = Some(err);
+            return (vec![], stats);
+        }
+    };
+    stats.truncated = read.skipped();
+    stats.rows = read.rows.len();
+    let events = convert_raw_events(read.rows, date_format, &mut stats);
+    (events, stats)
+}
+
+/// The WARN a capped file gets in `CalendarLoad::warnings`, if it was capped.
+fn truncation_warning(path: &Path, kept: usize, skipped: usize) -> Option<String> {
+    (skipped > 0).then(|| {
+        format!(
+            "{} holds {} rows; only the first {kept} were loaded (max_events_per_file)",
+            path.display(),
+            kept + skipped
+        )
+    })
+}
+
+pub fn load_calendar_events(repo_path: &Path, options: &CalendarLoadOptions) -> Vec<CalendarEvent> {
+    load_calendar(repo_path, options).events
+}
+
+/// Rows from the user's custom events file, tagged as custom. A missing file yields none.
+pub fn load_custom_events(
+    path: &Path,
+    limits: &FileLimits,
+    warnings: &mut Vec<String>,
+) -> Vec<CalendarEvent> {
+    if !path.exists() {
+        return vec![];
+    }
+    match read_year_file(path, limits) {
+        Ok(read) => {
+            warnings.extend(truncation_warning(path, read.rows.len(), read.skipped()));
+            // The app writes this file itself, always with the default layout.
+            let mut events = convert_raw_events(
+                read.rows,
+                DEFAULT_SOURCE_DATE_FORMAT,
+                &mut FileLoadStats::default(),
+            );
+            for e in events.iter_mut() {
+                e.source = EventSource::Custom;
+            }
+            events
+        }
+        Err(err) => {
+            warnings.push(format!("Custom events ignored ({}): {err}", path.display()));
+            vec![]
+        }
+    }
+}
+
+pub fn load_calendar(repo_path: &Path, options: &CalendarLoadOptions) -> CalendarLoad {
+    let mut load = CalendarLoad::default();
+    let calendar_root = repo_path.join("data").join("Economic_Calendar");
+
+    let mut events: Vec<CalendarEvent> = vec![];
+    if calendar_root.exists() {
+        for file in pick_year_files(&calendar_root, options, &mut load.warnings) {
+            let (file_events, stats) =
+                load_year_file(&file, &options.source_date_format, &options.limits);
+            if stats.oversized {
+                load.warnings.extend(stats.parse_error.clone());
+            }
+            load.warnings
+                .extend(truncation_warning(&file, stats.rows, stats.truncated));
+            events.extend(file_events);
+            load.files.push(stats);
+        }
+    }
+    // Appended after the calendar files so the stable sort keeps calendar rows first on ties.
+    if let Some(path) = options.custom_events.as_deref() {
+        events.extend(load_custom_events(
+            path,
+            &options.limits,
+            &mut load.warnings,
+        ));
+    }
+
+    let rules = CategoryRules::load(options.category_override.as_deref(), &mut load.warnings);
+    for e in events.iter_mut() {
+        e.categories = rules.categorize(&e.event);
+    }
 
     events.sort_by_key(|e| e.dt_utc);
-    events
+    load.events = events;
+    load
+}
+
+/// 1-based line on which the `index`-th object of a top-level JSON array starts.
+fn array_item_line(text: &str, index: usize) -> Option<usize> {
+    let (mut line, mut depth, mut seen) = (1, 0, 0);
+    let (mut in_string, mut escaped) = (false, false);
+    for ch in text.chars() {
+        if ch == '\n' {
+            line += 1;
+        }
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match ch {
+            '"' => in_string = true,
+            '{' | '[' => {
+                if ch == '{' && depth == 1 {
+                    if seen == index {
+                        return Some(line);
+                    }
+                    seen += 1;
+                }
+                depth += 1;
+            }
+            '}' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The year file a calendar row was loaded from, picked by the year of its source-time date,
+/// and that year.
+pub fn event_year_file(repo_path: &Path, event: &CalendarEvent) -> Option<(PathBuf, i32)> {
+    let source_dt =
+        event.dt_utc + chrono::Duration::minutes(CALENDAR_SOURCE_UTC_OFFSET_MINUTES as i64);
+    let year = source_dt.year();
+    let calendar_root = repo_path.join("data").join("Economic_Calendar");
+    Some((year_file_path(&calendar_root, year)?, year))
+}
+
+/// Rows of every year file on disk, loaded or not, for which `matches(event, currency)` holds,
+/// each with the file it is in.
+pub fn find_rows_on_disk(
+    repo_path: &Path,
+    options: &CalendarLoadOptions,
+    matches: &dyn Fn(&str, &str) -> bool,
+) -> Vec<(PathBuf, CalendarEvent)> {
+    let calendar_root = repo_path.join("data").join("Economic_Calendar");
+    let mut found = vec![];
+    for year in list_year_dirs(&calendar_root) {
+        let Some(path) = year_file_path(&calendar_root, year) else {
+            continue;
+        };
+        let (events, _) = load_year_file(&path, &options.source_date_format, &options.limits);
+        found.extend(
+            events
+                .into_iter()
+                .filter(|e| matches(&e.event, &e.currency))
+                .map(|e| (path.clone(), e)),
+        );
+    }
+    found
+}
+
+/// Finds the year file `event` was loaded from and, when the row can be matched, the line it
+/// starts on. The year comes from the event's source-time date; row dates are read with
+/// `date_format`.
+pub fn locate_event_source(
+    repo_path: &Path,
+    event: &CalendarEvent,
+    date_format: &str,
+    limits: &FileLimits,
+) -> Option<(PathBuf, i32, Option<usize>)> {
+    let (path, year) = event_year_file(repo_path, event)?;
+    let date = (event.dt_utc
+        + chrono::Duration::minutes(CALENDAR_SOURCE_UTC_OFFSET_MINUTES as i64))
+    .date_naive();
+    let index = read_year_file(&path, limits).ok().and_then(|read| {
+        read.rows.iter().position(|row| {
+            let text = |v: &Option<String>| v.as_deref().unwrap_or("").trim().to_string();
+            let time = text(&row.time);
+            let time = if time.is_empty() {
+                "All Day".to_string()
+            } else {
+                time
+            };
+            chrono::NaiveDate::parse_from_str(&text(&row.date), date_format).ok() == Some(date)
+                && text(&row.event) == event.event
+                && text(&row.currency).to_uppercase() == event.currency
+                && time == event.time_label
+        })
+    });
+    let line = index.and_then(|i| {
+        let text = read_year_text(&path, limits).ok()?;
+        array_item_line(&text, i)
+    });
+    Some((path, year, line))
+}
+
+/// Describes why `data_root` (the folder holding `Economic_Calendar/`) looks incomplete, if it
+/// does. Only the current-year file is checked; it is read like the loader reads it (gzipped or
+/// not), but no row past the first is kept.
+pub fn local_data_issue(data_root: &Path) -> Option<String> {
+    let calendar_root = data_root.join("Economic_Calendar");
+    if !calendar_root.exists() {
+        return Some("Economic_Calendar is missing".to_string());
+    }
+    let year = chrono::Local::now().year();
+    let Some(path) = year_file_path(&calendar_root, year) else {
+        return Some(format!("calendar file for {year} is missing"));
+    };
+    if fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0) == 0 {
+        return Some(format!("{} is empty", path.display()));
+    }
+    let limits = FileLimits {
+        max_events: 1,
+        ..FileLimits::default()
+    };
+    match read_year_file(&path, &limits) {
+        Ok(read) if read.total == 0 => Some(format!("{} has no rows", path.display())),
+        Ok(_) => None,
+        Err(err) => Some(format!("{} cannot be read: {err}", path.display())),
+    }
+}
+
+pub fn local_data_looks_complete(data_root: &Path) -> bool {
+    local_data_issue(data_root).is_none()
+}
+
+/// Outcome of checking a folder for the calendar repo layout.
+#[derive(Debug, Default, PartialEq)]
+pub struct RepoLayout {
+    /// Required paths (relative to the repo) that are absent.
+    pub missing: Vec<String>,
+    /// Year directories under `Economic_Calendar` that contain a `.json` or `.json.gz` file.
+    pub year_dirs: Vec<i32>,
+}
+
+impl RepoLayout {
+    pub fn is_valid(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Checks `repo_path` for the structure `resolve_calendar_repo_path` expects.
+pub fn check_repo_layout(repo_path: &Path) -> RepoLayout {
+    let data = repo_path.join("data");
+    let calendar_root = data.join("Economic_Calendar");
+    let mut layout = RepoLayout::default();
+    if calendar_root.is_dir() {
+        layout.year_dirs = list_year_dirs(&calendar_root)
+            .into_iter()
+            .filter(|year| year_file_path(&calendar_root, *year).is_some())
+            .collect();
+        if layout.year_dirs.is_empty() {
+            layout
+                .missing
+                .push("data/Economic_Calendar/<year>/*.json or *.json.gz".to_string());
+        }
+    } else {
+        layout.missing.push("data/Economic_Calendar".to_string());
+    }
+    if !data.join("event_history_index").is_dir() {
+        layout.missing.push("data/event_history_index".to_string());
+    }
+    layout
+}
+
+/// What a pulled tree holds under `data/`, used to explain a pull that loaded no events.
+#[derive(Debug, Default, PartialEq)]
+pub struct DataLayoutFindings {
+    pub data_present: bool,
+    /// Top-level directories under `data/`, sorted.
+    pub data_dirs: Vec<String>,
+    pub calendar_at_expected_path: bool,
+    /// Other directories under `data/` named `Economic_Calendar` in any casing, relative to
+    /// `root` with `/` separators.
+    pub calendar_elsewhere: Vec<String>,
+}
+
+impl DataLayoutFindings {
+    pub fn describe(&self) -> String {
+        if !self.data_present {
+            return "data/ is missing".to_string();
+        }
+        let mut parts = vec![if self.data_dirs.is_empty() {
+            "data/ is empty".to_string()
+        } else {
+            format!("data/ contains: {}", self.data_dirs.join(", "))
+        }];
+        if !self.calendar_at_expected_path {
+            parts.push("data/Economic_Calendar is missing".to_string());
+        }
+        if !self.calendar_elsewhere.is_empty() {
+            parts.push(format!(
+                "calendar folder found at: {}",
+                self.calendar_elsewhere.join(", ")
+            ));
+        }
+        parts.join("; ")
+    }
+}
+
+/// Inspects `root/data` for where the calendar files went.
+pub fn inspect_data_layout(root: &Path) -> DataLayoutFindings {
+    let data = root.join("data");
+    let mut findings = DataLayoutFindings {
+        data_present: data.is_dir(),
+        calendar_at_expected_path: data.join("Economic_Calendar").is_dir(),
+        ..DataLayoutFindings::default()
+    };
+    if !findings.data_present {
+        return findings;
+    }
+    if let Ok(entries) = fs::read_dir(&data) {
+        findings.data_dirs = entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        findings.data_dirs.sort();
+    }
+    findings.calendar_elsewhere = walkdir::WalkDir::new(&data)
+        .min_depth(1)
+        .max_depth(4)
+        .sort_by_file_name()
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_dir())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .eq_ignore_ascii_case("Economic_Calendar")
+        })
+        .filter_map(|entry| {
+            let rel = entry.path().strip_prefix(root).ok()?;
+            let rel = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            (rel != "data/Economic_Calendar").then_some(rel)
+        })
+        .collect();
+    findings
+}
+
+/// Deterministic digest of the loaded data, independent of file order.
+pub fn data_fingerprint(events: &[CalendarEvent], history_generated_at: &str) -> String {
+    let mut rows: Vec<String> = events
+        .iter()
+        .map(|e| {
+            // Only tentative rows add a field, so confirmed data keeps its fingerprint.
+            let tentative = if e.time_confirmed { "" } else { "|tentative" };
+            format!(
+                "{}|{}|{}|{}|{}|{}|{}|{}{tentative}",
+                e.dt_utc.to_rfc3339(),
+                e.time_label,
+                e.currency,
+                e.importance,
+                e.event,
+                e.actual,
+                e.forecast,
+                e.previous
+            )
+        })
+        .collect();
+    rows.sort();
+    let mut hasher = Sha1::new();
+    for row in rows {
+        hasher.update(row.as_bytes());
+        hasher.update(b"\n");
+    }
+    hasher.update(format!("history_generated_at={history_generated_at}").as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Parses a display value such as `"0.4%"`, `"-1,250K"` or `"3.1B"` into a number.
+/// Magnitude suffixes are expanded; percent signs are dropped.
+pub fn parse_numeric(raw: &str) -> Option<f64> {
+    let cleaned = raw.trim().replace(',', "");
+    let cleaned = cleaned.trim_end_matches('%').trim();
+    if cleaned.is_empty() || cleaned == "--" {
+        return None;
+    }
+    let (number, scale) = match cleaned.chars().last()? {
+        'K' | 'k' => (&cleaned[..cleaned.len() - 1], 1e3),
+        'M' | 'm' => (&cleaned[..cleaned.len() - 1], 1e6),
+        'B' | 'b' => (&cleaned[..cleaned.len() - 1], 1e9),
+        'T' | 't' => (&cleaned[..cleaned.len() - 1], 1e12),
+        _ => (cleaned, 1.0),
+    };
+    let value = number.trim().parse::<f64>().ok()?;
+    if !value.is_finite() {
+        return None;
+    }
+    Some(value * scale)
+}
+
+/// 3 for High, 2 for Medium, 1 for Low, 0 for anything else.
+pub fn impact_rank(impact: &str) -> u8 {
+    match impact.trim().to_lowercase().as_str() {
+        "high" => 3,
+        "medium" => 2,
+        "low" => 1,
+        _ => 0,
+    }
 }
 
 pub fn currency_options() -> Vec<String> {
@@ -157,3 +1112,487 @@ pub fn currency_options() -> Vec<String> {
     .map(|s| s.to_string())
     .collect()
 }
+
+/// The canonical form of a currency filter: `ALL` or an uppercase code from `currency_options`,
+/// matched case-insensitively after trimming. `None` for anything else.
+pub fn normalize_currency(value: &str) -> Option<String> {
+    let value = value.trim().to_uppercase();
+    if value == "ALL" {
+        return Some(value);
+    }
+    currency_options().contains(&value).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_root_with_current_year(contents: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let year = chrono::Local::now().year();
+        let year_dir = dir.path().join("Economic_Calendar").join(year.to_string());
+        fs::create_dir_all(&year_dir).unwrap();
+        fs::write(year_dir.join(format!("{year}_calendar.json")), contents).unwrap();
+        dir
+    }
+
+    #[test]
+    fn streaming_parse_matches_the_in_memory_parse() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("2026_calendar.json");
+        let rows: Vec<String> = (1..=28)
+            .map(|day| {
+                format!(
+                    r#"{{"Date": "2026-02-{day:02}", "Time": "13:30", "Event": "CPI {day}", "Cur.": "usd", "Imp.": "High", "Actual": "0.{day}%"}}"#
+                )
+            })
+            .collect();
+        fs::write(&path, format!("[{}]", rows.join(",\n"))).unwrap();
+
+        let convert = |rows: Vec<RawEvent>| {
+            let events =
+                convert_raw_events(rows, DEFAULT_SOURCE_DATE_FORMAT, &mut Default::default());
+            format!("{events:?}")
+        };
+        let text = fs::read_to_string(&path).unwrap();
+        let in_memory = serde_json::from_str::<Vec<RawEvent>>(&text).unwrap();
+        let streamed = read_year_file_streaming(&path, &FileLimits::default()).unwrap();
+        assert_eq!(streamed.rows.len(), 28);
+        assert_eq!(convert(streamed.rows), convert(in_memory));
+
+        fs::write(&path, "[{\"Date\": \"2026-02-01\"}] trailing").unwrap();
+        assert!(read_year_file_streaming(&path, &FileLimits::default()).is_err());
+        assert!(read_year_file(&path, &FileLimits::default()).is_err());
+    }
+
+    #[test]
+    fn gzipped_year_files_read_like_plain_ones() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let root = tempfile::tempdir().unwrap();
+        let year_dir = root.path().join("2026");
+        fs::create_dir_all(&year_dir).unwrap();
+        let rows = format!(
+            "[{}]",
+            vec![
+                r#"{"Date": "2026-02-01", "Time": "13:30", "Event": "CPI m/m", "Cur.": "USD"}"#;
+                40
+            ]
+            .join(",")
+        );
+        let gz_path = year_dir.join("2026_calendar.json.gz");
+        let mut encoder = GzEncoder::new(fs::File::create(&gz_path).unwrap(), Default::default());
+        encoder.write_all(rows.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        assert_eq!(year_file_path(root.path(), 2026), Some(gz_path.clone()));
+        let read = read_year_file(&gz_path, &FileLimits::default()).unwrap();
+        assert_eq!((read.rows.len(), read.total), (40, 40));
+
+        // The limit is on the decompressed size, which here is far above the compressed one.
+        let compressed = fs::metadata(&gz_path).unwrap().len();
+        let tight = FileLimits {
+            max_bytes: compressed * 2,
+            ..FileLimits::default()
+        };
+        assert!((rows.len() as u64) > tight.max_bytes);
+        let Err(err) = read_year_file(&gz_path, &tight) else {
+            panic!("over-limit gzip was read");
+        };
+        assert!(err.contains("decompresses to over"), "{err}");
+
+        fs::write(year_dir.join("2026_calendar.json"), "[]").unwrap();
+        assert_eq!(
+            year_file_path(root.path(), 2026),
+            Some(year_dir.join("2026_calendar.json"))
+        );
+    }
+
+    #[test]
+    fn integrity_check_counts_each_kind_of_bad_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("2026_calendar.json");
+        let row = |date: &str, time: &str, event: &str, cur: &str| serde_json::json!({"Date": date, "Time": time, "Event": event, "Cur.": cur});
+        let rows = serde_json::json!([
+            row("2026-01-05", "13:30", "CPI m/m", "USD"),
+            row("2026-01-05", "13:30", "CPI m/m", "USD"),
+            row("2025-12-31", "13:30", "GDP q/q", "EUR"),
+            row("05/01/2026", "13:30", "PPI m/m", "USD"),
+            row("2026-12-31", "23:30", "Holiday", "JPY"),
+            row("2026-01-06", "9:61", "Retail Sales", "AUD"),
+            row("2026-01-06", "25:00", "Retail Sales", "CAD"),
+            row("2026-01-06", "All Day", "Bank Holiday", "usd"),
+            row("2026-01-07", "08:00", "Trade Balance", "XYZ"),
+            // One-digit hours load fine, so they are not flagged.
+            row("2026-01-07", "9:30", "Building Approvals", "AUD"),
+        ]);
+        fs::write(&path, rows.to_string()).unwrap();
+
+        let report = check_year_integrity(
+            &path,
+            2026,
+            DEFAULT_SOURCE_DATE_FORMAT,
+            &FileLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(report.rows, 10);
+        assert_eq!(report.truncated, 0);
+        assert_eq!(report.duplicates.count, 1);
+        assert_eq!(report.duplicates.samples[0].row, 1);
+        let rows_of = |issue: &IntegrityIssue| -> Vec<usize> {
+            issue.samples.iter().map(|s| s.row).collect()
+        };
+        assert_eq!(rows_of(&report.out_of_year), vec![2, 3]);
+        assert_eq!(rows_of(&report.invalid_times), vec![5, 6]);
+        assert_eq!(rows_of(&report.unknown_currencies), vec![8]);
+        assert_eq!(report.unknown_currencies.samples[0].currency, "XYZ");
+
+        let many: Vec<Value> = (0..8)
+            .map(|_| row("2026-01-05", "24:00", "PMI", "GBP"))
+            .collect();
+        fs::write(&path, Value::Array(many).to_string()).unwrap();
+        let report = check_year_integrity(
+            &path,
+            2026,
+            DEFAULT_SOURCE_DATE_FORMAT,
+            &FileLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(report.invalid_times.count, 8);
+        assert_eq!(report.invalid_times.samples.len(), INTEGRITY_SAMPLES);
+        assert_eq!(report.duplicates.count, 7);
+    }
+
+    #[test]
+    fn file_limits_refuse_large_files_and_cap_rows() {
+        let repo = tempfile::tempdir().unwrap();
+        let year_dir = repo.path().join("data/Economic_Calendar/2026");
+        fs::create_dir_all(&year_dir).unwrap();
+        let path = year_dir.join("2026_calendar.json");
+        let rows: Vec<String> = (1..=20)
+            .map(|day| {
+                format!(
+                    r#"{{"Date": "2026-01-{day:02}", "Time": "13:30", "Event": "CPI {day}", "Cur.": "USD", "Extra": [[[1, 2], [3]]]}}"#
+                )
+            })
+            .collect();
+        fs::write(&path, format!("[{}]", rows.join(","))).unwrap();
+        let options = |max_bytes, max_events| CalendarLoadOptions {
+            limits: FileLimits {
+                max_bytes,
+                max_events,
+            },
+            ..CalendarLoadOptions::default()
+        };
+
+        let load = load_calendar(repo.path(), &options(1024 * 1024, 5));
+        assert_eq!(load.events.len(), 5);
+        assert_eq!(load.events[4].event, "CPI 5");
+        assert_eq!(load.files[0].truncated, 15);
+        assert_eq!(load.warnings.len(), 1);
+        assert!(load.warnings[0].contains("holds 20 rows; only the first 5"));
+
+        let load = load_calendar(repo.path(), &options(512, 100));
+        assert!(load.events.is_empty());
+        assert!(load.files[0].oversized);
+        assert!(
+            load.warnings[0].contains("over the 512 byte limit"),
+            "{:?}",
+            load.warnings
+        );
+
+        let mut warnings = vec![];
+        let custom = load_custom_events(
+            &path,
+            &FileLimits {
+                max_bytes: 1024 * 1024,
+                max_events: 3,
+            },
+            &mut warnings,
+        );
+        assert_eq!(custom.len(), 3);
+        assert_eq!(warnings.len(), 1);
+
+        let cfg = serde_json::json!({"max_calendar_file_mb": 0, "max_events_per_file": 10});
+        let limits = FileLimits::from_config(&cfg);
+        assert_eq!(limits.max_bytes, FileLimits::default().max_bytes);
+        assert_eq!(limits.max_events, 10);
+    }
+
+    #[test]
+    fn repo_layout_reports_missing_parts() {
+        let repo = tempfile::tempdir().unwrap();
+        let layout = check_repo_layout(repo.path());
+        assert!(!layout.is_valid());
+        assert_eq!(
+            layout.missing,
+            vec!["data/Economic_Calendar", "data/event_history_index"]
+        );
+
+        let calendar_root = repo.path().join("data").join("Economic_Calendar");
+        fs::create_dir_all(calendar_root.join("2025")).unwrap();
+        fs::create_dir_all(calendar_root.join("2026")).unwrap();
+        fs::write(calendar_root.join("2026").join("2026_calendar.json"), "[]").unwrap();
+        fs::create_dir_all(repo.path().join("data").join("event_history_index")).unwrap();
+        let layout = check_repo_layout(repo.path());
+        assert!(layout.is_valid());
+        assert_eq!(layout.year_dirs, vec![2026]);
+    }
+
+    #[test]
+    fn data_layout_finds_moved_calendar_folder() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(
+            inspect_data_layout(root.path()).describe(),
+            "data/ is missing"
+        );
+
+        let data = root.path().join("data");
+        fs::create_dir_all(data.join("event_history_index")).unwrap();
+        fs::create_dir_all(
+            data.join("calendars")
+                .join("economic_calendar")
+                .join("2026"),
+        )
+        .unwrap();
+        let findings = inspect_data_layout(root.path());
+        assert_eq!(findings.data_dirs, vec!["calendars", "event_history_index"]);
+        assert!(!findings.calendar_at_expected_path);
+        assert_eq!(
+            findings.calendar_elsewhere,
+            vec!["data/calendars/economic_calendar"]
+        );
+        assert_eq!(
+            findings.describe(),
+            "data/ contains: calendars, event_history_index; data/Economic_Calendar is missing; \
+             calendar folder found at: data/calendars/economic_calendar"
+        );
+
+        fs::create_dir_all(data.join("Economic_Calendar")).unwrap();
+        let findings = inspect_data_layout(root.path());
+        assert!(findings.calendar_at_expected_path);
+        assert_eq!(
+            findings.calendar_elsewhere,
+            vec!["data/calendars/economic_calendar"]
+        );
+    }
+
+    #[test]
+    fn complete_data_passes() {
+        let dir = data_root_with_current_year(r#"[ {"Date": "2026-01-01"} ]"#);
+        assert!(local_data_looks_complete(dir.path()));
+    }
+
+    #[test]
+    fn deleted_current_year_file_forces_pull() {
+        let dir = data_root_with_current_year("[]");
+        let year = chrono::Local::now().year();
+        fs::remove_file(
+            dir.path()
+                .join("Economic_Calendar")
+                .join(year.to_string())
+                .join(format!("{year}_calendar.json")),
+        )
+        .unwrap();
+        assert!(!local_data_looks_complete(dir.path()));
+        assert!(local_data_issue(dir.path()).unwrap().contains("missing"));
+    }
+
+    #[test]
+    fn zero_byte_or_empty_array_forces_pull() {
+        let dir = data_root_with_current_year("");
+        assert!(local_data_issue(dir.path()).unwrap().ends_with("is empty"));
+        let dir = data_root_with_current_year("[\n]");
+        assert!(local_data_issue(dir.path())
+            .unwrap()
+            .ends_with("has no rows"));
+    }
+
+    #[test]
+    fn gzipped_current_year_counts_as_complete() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let dir = data_root_with_current_year("");
+        let year = chrono::Local::now().year();
+        let year_dir = dir.path().join("Economic_Calendar").join(year.to_string());
+        fs::remove_file(year_dir.join(format!("{year}_calendar.json"))).unwrap();
+        let write_gz = |text: &str| {
+            let file = fs::File::create(year_dir.join(format!("{year}_calendar.json.gz")));
+            let mut encoder = GzEncoder::new(file.unwrap(), Default::default());
+            encoder.write_all(text.as_bytes()).unwrap();
+            encoder.finish().unwrap();
+        };
+        write_gz(r#"[ {"Date": "2026-01-01"}, {"Date": "2026-01-02"} ]"#);
+        assert_eq!(local_data_issue(dir.path()), None);
+        write_gz("[]");
+        assert!(local_data_issue(dir.path())
+            .unwrap()
+            .ends_with("has no rows"));
+    }
+
+    #[test]
+    fn time_confirmed_defaults_true_and_reads_either_field() {
+        assert!(time_confirmed(None, None));
+        assert!(!time_confirmed(None, Some("Tentative")));
+        assert!(time_confirmed(None, Some("Confirmed")));
+        assert!(!time_confirmed(
+            Some(&Value::Bool(false)),
+            Some("Confirmed")
+        ));
+        assert!(time_confirmed(
+            Some(&Value::String("yes".to_string())),
+            Some("Tentative")
+        ));
+        assert!(!time_confirmed(Some(&Value::Null), Some("tbd")));
+
+        let raw: Vec<RawEvent> = serde_json::from_str(
+            r#"[{"Date": "2026-01-02", "Time": "13:30", "Event": "NFP", "Cur.": "USD", "TimeStatus": "Tentative"},
+                {"Date": "2026-01-02", "Time": "15:00", "Event": "ISM", "Cur.": "USD"}]"#,
+        )
+        .unwrap();
+        let events = convert_raw_events(
+            raw,
+            DEFAULT_SOURCE_DATE_FORMAT,
+            &mut FileLoadStats::default(),
+        );
+        assert!(!events[0].time_confirmed);
+        assert!(events[1].time_confirmed);
+    }
+
+    #[test]
+    fn locates_event_source_line() {
+        let dir = data_root_with_current_year("[]");
+        let year = chrono::Local::now().year();
+        let path = dir
+            .path()
+            .join("Economic_Calendar")
+            .join(year.to_string())
+            .join(format!("{year}_calendar.json"));
+        let rows = format!(
+            r#"[
+    {{
+        "Date":"{year}-01-02",
+        "Time":"All Day",
+        "Cur.":null,
+        "Event":"Holiday {{\"quoted\"}}"
+    }},
+    {{
+        "Date":"{year}-01-02",
+        "Time":"13:30",
+        "Cur.":"USD",
+        "Event":"Non-Farm Payrolls"
+    }}
+]"#
+        );
+        fs::write(&path, rows).unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        fs::create_dir_all(repo.path().join("data")).unwrap();
+        fs::rename(
+            dir.path().join("Economic_Calendar"),
+            repo.path().join("data").join("Economic_Calendar"),
+        )
+        .unwrap();
+
+        let (events, _) = load_year_file(
+            &repo
+                .path()
+                .join("data")
+                .join("Economic_Calendar")
+                .join(year.to_string())
+                .join(format!("{year}_calendar.json")),
+            DEFAULT_SOURCE_DATE_FORMAT,
+            &FileLimits::default(),
+        );
+        let nfp = events.iter().find(|e| e.currency == "USD").unwrap();
+        let limits = FileLimits::default();
+        let (found, found_year, line) =
+            locate_event_source(repo.path(), nfp, DEFAULT_SOURCE_DATE_FORMAT, &limits).unwrap();
+        assert!(found.ends_with(format!("{year}_calendar.json")));
+        assert_eq!(found_year, year);
+        assert_eq!(line, Some(8));
+        assert_eq!(
+            locate_event_source(repo.path(), &events[0], DEFAULT_SOURCE_DATE_FORMAT, &limits)
+                .unwrap()
+                .2,
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn custom_events_merge_after_calendar_rows() {
+        let year = chrono::Local::now().year();
+        let repo = tempfile::tempdir().unwrap();
+        let year_dir = repo
+            .path()
+            .join("data")
+            .join("Economic_Calendar")
+            .join(year.to_string());
+        fs::create_dir_all(&year_dir).unwrap();
+        fs::write(
+            year_dir.join(format!("{year}_calendar.json")),
+            format!(
+                r#"[{{"Date":"{year}-03-06","Time":"13:30","Cur.":"USD","Event":"Non-Farm Payrolls"}},
+                    {{"Date":"{year}-03-07","Time":"09:00","Cur.":"EUR","Event":"German CPI"}}]"#
+            ),
+        )
+        .unwrap();
+        let custom = repo.path().join("custom_events.json");
+        fs::write(
+            &custom,
+            format!(
+                r#"[{{"Date":"{year}-03-06","Time":"13:30","Cur.":"USD","Event":"Close gold longs"}},
+                    {{"Date":"{year}-03-01","Time":"All Day","Cur.":"","Event":"Broker maintenance"}}]"#
+            ),
+        )
+        .unwrap();
+        let options = CalendarLoadOptions {
+            custom_events: Some(custom),
+            ..CalendarLoadOptions::default()
+        };
+        let events = load_calendar(repo.path(), &options).events;
+        let order: Vec<(&str, &str)> = events
+            .iter()
+            .map(|e| (e.event.as_str(), e.source.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("Broker maintenance", "custom"),
+                ("Non-Farm Payrolls", "calendar"),
+                ("Close gold longs", "custom"),
+                ("German CPI", "calendar"),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_rows_dropped_for_bad_dates() {
+        let year = chrono::Local::now().year();
+        let repo = tempfile::tempdir().unwrap();
+        let year_dir = repo
+            .path()
+            .join("data")
+            .join("Economic_Calendar")
+            .join(year.to_string());
+        fs::create_dir_all(&year_dir).unwrap();
+        fs::write(
+            year_dir.join(format!("{year}_calendar.json")),
+            format!(
+                r#"[{{"Date":"{year}-03-06","Time":"13:30","Cur.":"USD","Event":"Non-Farm Payrolls"}},
+                    {{"Date":"{year}-13-45","Time":"13:30","Cur.":"usd","Event":"Non-Farm Payrolls"}},
+                    {{"Date":"{year}-13-45","Time":"09:00","Cur.":"EUR","Event":"German CPI"}}]"#
+            ),
+        )
+        .unwrap();
+        let dropped = find_dropped_rows(
+            repo.path(),
+            &CalendarLoadOptions::default(),
+            &|event, cur| event.contains("Payrolls") && cur == "USD",
+        );
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].date, format!("{year}-13-45"));
+        assert_eq!(dropped[0].currency, "USD");
+    }
+}
@@ -0,0 +1,197 @@
+use crate::calendar::CalendarEvent;
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use std::path::Path;
+
+/// User rules in the working data dir, merged over the bundled `categories.json`.
+pub const CATEGORY_OVERRIDE_FILE: &str = "categories_override.json";
+
+const BUNDLED_RULES: &str = include_str!("../categories.json");
+
+#[derive(Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    rules: Vec<RawRule>,
+}
+
+#[derive(Deserialize)]
+struct RawRule {
+    category: String,
+    #[serde(default)]
+    pattern: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CategoryRule {
+    pub category: String,
+    pub pattern: String,
+    /// `bundled` or `override`.
+    pub source: &'static str,
+}
+
+fn parse_rules(text: &str, source: &'static str) -> Result<Vec<CategoryRule>, String> {
+    let file: RulesFile = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    Ok(file
+        .rules
+        .into_iter()
+        .map(|r| CategoryRule {
+            category: r.category.trim().to_string(),
+            pattern: r.pattern.trim().to_string(),
+            source,
+        })
+        .filter(|r| !r.category.is_empty())
+        .collect())
+}
+
+/// Applies `overrides` on top of `base`: a rule for an existing category replaces its pattern
+/// in place, a new category is appended, and an empty pattern removes the category.
+pub fn merge_rules(base: Vec<CategoryRule>, overrides: Vec<CategoryRule>) -> Vec<CategoryRule> {
+    let mut merged = base;
+    for rule in overrides {
+        match merged.iter().position(|r| r.category == rule.category) {
+            Some(idx) if rule.pattern.is_empty() => {
+                merged.remove(idx);
+            }
+            Some(idx) => merged[idx] = rule,
+            None if !rule.pattern.is_empty() => merged.push(rule),
+            None => {}
+        }
+    }
+    merged
+}
+
+/// Compiled category taxonomy. Build once per calendar load and reuse for every event.
+pub struct CategoryRules {
+    rules: Vec<(CategoryRule, Regex)>,
+}
+
+impl CategoryRules {
+    /// Compiles `rules` case-insensitively; invalid patterns are skipped with a warning.
+    pub fn compile(rules: Vec<CategoryRule>, warnings: &mut Vec<String>) -> Self {
+        let mut compiled = vec![];
+        for rule in rules {
+            match RegexBuilder::new(&rule.pattern)
+                .case_insensitive(true)
+                .build()
+            {
+                Ok(re) => compiled.push((rule, re)),
+                Err(err) => {
+                    warnings.push(format!("Category rule '{}' ignored: {err}", rule.category))
+                }
+            }
+        }
+        Self { rules: compiled }
+    }
+
+    /// Bundled rules merged with the override file at `override_path`, if present.
+    pub fn load(override_path: Option<&Path>, warnings: &mut Vec<String>) -> Self {
+        let bundled = parse_rules(BUNDLED_RULES, "bundled").unwrap_or_default();
+        let overrides = match override_path.filter(|p| p.exists()) {
+            Some(path) => match std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| parse_rules(&text, "override"))
+            {
+                Ok(rules) => rules,
+                Err(err) => {
+                    warnings.push(format!(
+                        "Category overrides ignored ({}): {err}",
+                        path.display()
+                    ));
+                    vec![]
+                }
+            },
+            None => vec![],
+        };
+        Self::compile(merge_rules(bundled, overrides), warnings)
+    }
+
+    /// Every category whose rule matches `event`, in taxonomy order.
+    pub fn categorize(&self, event: &str) -> Vec<String> {
+        self.rules
+            .iter()
+            .filter(|(_, re)| re.is_match(event))
+            .map(|(rule, _)| rule.category.clone())
+            .collect()
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &CategoryRule> {
+        self.rules.iter().map(|(rule, _)| rule)
+    }
+}
+
+/// True when `filter` is empty or the event carries at least one of its categories.
+pub fn matches_filter(event: &CalendarEvent, filter: &[String]) -> bool {
+    filter.is_empty() || event.categories.iter().any(|c| filter.contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(category: &str, pattern: &str, source: &'static str) -> CategoryRule {
+        CategoryRule {
+            category: category.to_string(),
+            pattern: pattern.to_string(),
+            source,
+        }
+    }
+
+    #[test]
+    fn bundled_rules_tag_multiple_categories() {
+        let rules = CategoryRules::load(None, &mut vec![]);
+        assert_eq!(rules.categorize("CPI m/m"), vec!["inflation"]);
+        assert_eq!(
+            rules.categorize("Fed Chair Powell Speaks on Inflation Outlook"),
+            vec!["inflation", "speeches"]
+        );
+        assert_eq!(
+            rules.categorize("FOMC Meeting Minutes"),
+            vec!["central_bank"]
+        );
+        assert!(rules.categorize("Bank Holiday").is_empty());
+        // Word boundaries keep short acronyms from matching inside other words.
+        assert!(rules.categorize("Atlanta Fed GDPNow").is_empty());
+    }
+
+    #[test]
+    fn overrides_take_precedence_over_bundled_rules() {
+        let base = vec![
+            rule("inflation", "CPI", "bundled"),
+            rule("speeches", "Speaks", "bundled"),
+            rule("housing", "Housing", "bundled"),
+        ];
+        let overrides = vec![
+            rule("inflation", "CPI|Price Index", "override"),
+            rule("housing", "", "override"),
+            rule("energy", "Crude Oil|Natural Gas", "override"),
+        ];
+        let merged = merge_rules(base, overrides);
+        let names: Vec<&str> = merged.iter().map(|r| r.category.as_str()).collect();
+        assert_eq!(names, vec!["inflation", "speeches", "energy"]);
+        assert_eq!(merged[0].source, "override");
+
+        let mut warnings = vec![];
+        let rules = CategoryRules::compile(merged, &mut warnings);
+        assert!(warnings.is_empty());
+        assert_eq!(rules.categorize("Import Price Index"), vec!["inflation"]);
+        assert_eq!(rules.categorize("Crude Oil Inventories"), vec!["energy"]);
+        assert!(rules.categorize("Pending Housing Sales").is_empty());
+    }
+
+    #[test]
+    fn override_file_is_merged_and_bad_patterns_warn() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CATEGORY_OVERRIDE_FILE);
+        std::fs::write(
+            &path,
+            r#"{"rules":[{"category":"energy","pattern":"Crude"},{"category":"broken","pattern":"("}]}"#,
+        )
+        .unwrap();
+        let mut warnings = vec![];
+        let rules = CategoryRules::load(Some(&path), &mut warnings);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("broken"));
+        assert_eq!(rules.categorize("Crude Oil Inventories"), vec!["energy"]);
+        assert!(rules.rules().any(|r| r.category == "inflation"));
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+use std::hint::black_box;
+use std::time::Instant;
+
+const DEFAULT_RUNS: u64 = 3;
+
+/// Runs `f` `runs` times and reports min/median wall time in milliseconds.
+fn time_runs(runs: usize, mut f: impl FnMut()) -> Value {
+    let mut samples: Vec<f64> = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed().as_secs_f64() * 1000.0
+        })
+        .collect();
+    let median = super::history::median(&mut samples).unwrap_or_default();
+    let round = |ms: f64| (ms * 100.0).round() / 100.0;
+    json!({"minMs": round(samples[0]), "medianMs": round(median)})
+}
+
+/// Times loading and rendering the calendar and building the history index, off the async
+/// runtime since each step runs up to ten times.
+#[tauri::command]
+pub async fn benchmark(app: tauri::AppHandle, payload: Value) -> Value {
+    tauri::async_runtime::spawn_blocking(move || run_benchmark(&app, &payload))
+        .await
+        .unwrap_or_else(|err| json!({"ok": false, "message": err.to_string()}))
+}
+
+fn run_benchmark(app: &tauri::AppHandle, payload: &Value) -> Value {
+    let state = app.state::<Mutex<RuntimeState>>();
+    let runs = payload
+        .get("runs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_RUNS)
+        .clamp(1, 10) as usize;
+    let cfg = config::load_config();
+    let Some(repo_path) = resolve_calendar_repo_path(&cfg) else {
+        return json!({"ok": false, "message": "Calendar repo is not available yet. Run Pull first."});
+    };
+    let options = CalendarLoadOptions::from_config(&cfg);
+    let (tz_mode, utc_offset_minutes) = get_calendar_settings(&cfg);
+    let no_time_label = config::get_str(&cfg, "no_time_label");
+    let currency = {
+        let runtime = state.lock().expect("runtime lock");
+        runtime.currency.clone()
+    };
+
+    let load = time_runs(runs, || {
+        black_box(load_calendar_events(&repo_path, &options));
+    });
+    let events = load_calendar_events(&repo_path, &options);
+    let last_prints = super::history::build_last_prints(&events);
+    let next_options = NextEventsOptions {
+        currency: &currency,
+        tz_mode: &tz_mode,
+        utc_offset_minutes,
+        source_utc_offset_minutes: CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
+        no_time_label: &no_time_label,
+        last_prints: &last_prints,
+        min_forecast_change_pct: crate::snapshot::min_forecast_change_pct(&cfg),
+    };
+    let render_next = time_runs(runs, || {
+        black_box(render_next_events(&events, &next_options));
+    });
+    let render_past = time_runs(runs, || {
+        black_box(render_past_events(
+            &events,
+            &currency,
+            &tz_mode,
+            utc_offset_minutes,
+            CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
+            &no_time_label,
+            crate::snapshot::recent_highlight_hours(&cfg),
+        ));
+    });
+    let ndjson_path =
+        super::history::resolve_history_dir(&cfg, &repo_path).join(super::history::HISTORY_NDJSON);
+    let build_index = if ndjson_path.exists() {
+        // Pulls rewrite the NDJSON under the exclusive lock; never time a half-written file.
+        match file_lock::lock_shared(&ndjson_path, file_lock::READ_WAIT) {
+            Some(_guard) => time_runs(runs, || {
+                black_box(super::history::build_index_from_ndjson(
+                    &ndjson_path,
+                    &crate::calendar::FileLimits::from_config(&cfg),
+                ));
+            }),
+            None => json!({"skipped": "event history is being updated"}),
+        }
+    } else {
+        Value::Null
+    };
+
+    let summary = format!(
+        "Benchmark ({runs} runs, {} events): load {}ms, next {}ms, past {}ms, index {}ms (median)",
+        events.len(),
+        load["medianMs"],
+        render_next["medianMs"],
+        render_past["medianMs"],
+        build_index.get("medianMs").unwrap_or(&Value::Null)
+    );
+    let mut runtime = state.lock().expect("runtime lock");
+    push_log(&mut runtime, &summary, "INFO");
+    json!({
+        "ok": true,
+        "runs": runs,
+        "eventCount": events.len(),
+        "timings": {
+            "loadEvents": load,
+            "renderNextEvents": render_next,
+            "renderPastEvents": render_past,
+            "buildHistoryIndex": build_index,
+        }
+    })
+}
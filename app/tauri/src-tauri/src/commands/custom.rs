@@ -0,0 +1,87 @@
+use super::*;
+use crate::custom_events::{self, CustomEvent, CUSTOM_EVENTS_FILE};
+
+fn custom_events_path() -> PathBuf {
+    config::appdata_dir().join(CUSTOM_EVENTS_FILE)
+}
+
+fn payload_str(payload: &Value, key: &str) -> String {
+    payload
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string()
+}
+
+/// Forces the next snapshot to reload so the change shows up immediately.
+fn reload_calendar(app: tauri::AppHandle, state: tauri::State<'_, Mutex<RuntimeState>>) {
+    state
+        .lock()
+        .expect("runtime lock")
+        .calendar
+        .last_loaded_at_ms = 0;
+    ensure_calendar_loaded(app, config::load_config(), state);
+}
+
+#[tauri::command]
+pub fn list_custom_events() -> Value {
+    match custom_events::read_custom_events(&custom_events_path()) {
+        Ok(events) => json!({"ok": true, "events": events}),
+        Err(err) => json!({"ok": false, "message": err}),
+    }
+}
+
+#[tauri::command]
+pub fn add_custom_event(
+    app: tauri::AppHandle,
+    payload: Value,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    let draft = CustomEvent {
+        id: String::new(),
+        date: payload_str(&payload, "date"),
+        time: payload_str(&payload, "time"),
+        event: payload_str(&payload, "event"),
+        currency: payload_str(&payload, "currency"),
+        importance: payload_str(&payload, "importance"),
+    };
+    match custom_events::add_custom_event(&custom_events_path(), draft) {
+        Ok(event) => {
+            {
+                let mut runtime = state.lock().expect("runtime lock");
+                push_log(
+                    &mut runtime,
+                    &format!("Custom event added: {} {}", event.date, event.event),
+                    "INFO",
+                );
+            }
+            reload_calendar(app, state);
+            json!({"ok": true, "event": event})
+        }
+        Err(err) => json!({"ok": false, "message": err}),
+    }
+}
+
+#[tauri::command]
+pub fn remove_custom_event(
+    app: tauri::AppHandle,
+    payload: Value,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    let id = payload_str(&payload, "id").trim().to_string();
+    if id.is_empty() {
+        return json!({"ok": false, "message": "id is required"});
+    }
+    match custom_events::remove_custom_event(&custom_events_path(), &id) {
+        Ok(true) => {
+            {
+                let mut runtime = state.lock().expect("runtime lock");
+                push_log(&mut runtime, &format!("Custom event removed: {id}"), "INFO");
+            }
+            reload_calendar(app, state);
+            json!({"ok": true})
+        }
+        Ok(false) => json!({"ok": false, "message": "Custom event not found"}),
+        Err(err) => json!({"ok": false, "message": err}),
+    }
+}
@@ -0,0 +1,121 @@
+use super::*;
+
+fn free_space_entry(path: &Path) -> Value {
+    match sync_util::available_space(path) {
+        Ok(bytes) => json!({
+            "path": path.to_string_lossy(),
+            "availableBytes": bytes,
+            "availableMb": bytes / (1024 * 1024),
+        }),
+        Err(err) => json!({
+            "path": path.to_string_lossy(),
+            "availableBytes": Value::Null,
+            "error": err,
+        }),
+    }
+}
+
+/// Environment details for support requests.
+#[tauri::command]
+pub fn get_diagnostics(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let cfg = config::load_config();
+    let (boot, github_rate_limit, github_token_scopes) = {
+        let runtime = state.lock().expect("runtime lock");
+        (
+            runtime.boot.clone(),
+            runtime.github_rate_limit.clone(),
+            runtime.github_token_scopes.clone(),
+        )
+    };
+    json!({
+        "ok": true,
+        "version": env!("APP_VERSION"),
+        "bootTimeline": boot,
+        "uptimeMs": crate::boot::since_start_ms(),
+        "systemDndActive": crate::platform::system_dnd_active(),
+        "respectSystemDnd": AppSettings::from_config(&cfg).respect_system_dnd,
+        "minFreeSpaceMb": min_free_space_mb(&cfg),
+        "freeSpace": {
+            "workingData": free_space_entry(&config::working_data_dir(&cfg)),
+            "temp": free_space_entry(&std::env::temp_dir()),
+        },
+        "networkActivity": crate::net_activity::global().snapshot(),
+        "githubRateLimit": github_rate_limit,
+        "githubTokenScopes": github_token_scopes,
+    })
+}
+
+/// Each effective config key with `"default"` or `"file"` as its source, for explaining why a
+/// setting is not taking effect. Secrets are redacted.
+#[tauri::command]
+pub fn get_config_provenance() -> Value {
+    let keys = config::load_config_provenance();
+    let customized = keys
+        .values()
+        .filter(|entry| entry["source"] == "file")
+        .count();
+    json!({
+        "ok": true,
+        "path": config::config_path().to_string_lossy(),
+        "customized": customized,
+        "keys": keys,
+    })
+}
+
+/// Bytes used under the app data dir per category, for deciding what to prune.
+#[tauri::command(async)]
+pub fn get_storage_usage() -> Value {
+    let root = config::appdata_dir();
+    let (usage, total) = sync_util::storage_usage(&root);
+    let categories: Vec<Value> = usage
+        .into_iter()
+        .map(|(category, bytes)| json!({"category": category, "bytes": bytes}))
+        .collect();
+    json!({
+        "ok": true,
+        "root": root.to_string_lossy(),
+        "categories": categories,
+        "totalBytes": total,
+    })
+}
+
+/// Consistency report for the raw year file of `year`: out-of-year dates, invalid times, unknown
+/// currencies and exact duplicate rows, each with a few sample rows.
+#[tauri::command(async)]
+pub fn check_data_integrity(payload: Value) -> Value {
+    let Some(year) = payload
+        .get("year")
+        .and_then(|v| v.as_i64())
+        .and_then(|y| i32::try_from(y).ok())
+    else {
+        return json!({"ok": false, "message": "year is required"});
+    };
+    let cfg = config::load_config();
+    let Some(repo_path) = resolve_calendar_repo_path(&cfg) else {
+        return json!({"ok": false, "message": "Calendar data not found; pull first"});
+    };
+    let calendar_root = repo_path.join("data").join("Economic_Calendar");
+    let Some(path) = crate::calendar::year_file_path(&calendar_root, year) else {
+        return json!({"ok": false, "message": format!("No calendar file for {year}")});
+    };
+    match crate::calendar::check_year_integrity(
+        &path,
+        year,
+        &crate::time_util::source_date_format(&cfg),
+        &crate::calendar::FileLimits::from_config(&cfg),
+    ) {
+        Ok(report) => json!({
+            "ok": true,
+            "year": year,
+            "path": path.to_string_lossy(),
+            "report": report,
+        }),
+        Err(err) => json!({"ok": false, "message": err}),
+    }
+}
+
+/// Requests per outbound host this session, with the time and status of the latest.
+#[tauri::command]
+pub fn get_network_activity() -> Value {
+    json!({"ok": true, "hosts": crate::net_activity::global().snapshot()})
+}
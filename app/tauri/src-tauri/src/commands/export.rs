@@ -0,0 +1,266 @@
+use super::*;
+use crate::calendar::CalendarEvent;
+use crate::snapshot::next_event_id;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+fn escape_ics_text(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for ch in raw.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            ',' => out.push_str("\\,"),
+            ';' => out.push_str("\\;"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Folds a content line at 75 octets (RFC 5545 section 3.1) without splitting UTF-8 sequences.
+fn fold_ics_line(line: &str, out: &mut String) {
+    let mut width = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += len;
+    }
+    out.push_str("\r\n");
+}
+
+fn display_or_dash(value: &str) -> String {
+    let value = value.trim();
+    if value.is_empty() {
+        "--".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Events of `currency` ("ALL" for every one) in `scope`: "upcoming" keeps events released in
+/// the last few minutes, "past" everything before `now`, "all" both.
+fn select_export_events<'a>(
+    events: &'a [CalendarEvent],
+    currency: &str,
+    scope: &str,
+    now: DateTime<Utc>,
+) -> Vec<&'a CalendarEvent> {
+    let grace_window = chrono::Duration::minutes(3);
+    events
+        .iter()
+        .filter(|e| currency == "ALL" || e.currency.to_uppercase() == currency)
+        .filter(|e| match scope {
+            "upcoming" => e.dt_utc >= now - grace_window,
+            "past" => e.dt_utc < now,
+            _ => true,
+        })
+        .collect()
+}
+
+fn render_ics(events: &[&CalendarEvent], now: DateTime<Utc>) -> String {
+    let stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut seen: HashMap<String, i32> = HashMap::new();
+    let mut out = String::new();
+    for line in [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        "PRODID:-//XAUUSD Calendar Agent//EN",
+        "CALSCALE:GREGORIAN",
+    ] {
+        fold_ics_line(line, &mut out);
+    }
+    for e in events {
+        let uid = next_event_id(e, &mut seen);
+        let cur = display_or_dash(&e.currency.to_uppercase());
+        let all_day = !e.time_label.contains(':');
+        let dtstart = if all_day {
+            format!("DTSTART;VALUE=DATE:{}", e.dt_utc.format("%Y%m%d"))
+        } else {
+            format!("DTSTART:{}", e.dt_utc.format("%Y%m%dT%H%M%SZ"))
+        };
+        let description = format!(
+            "Impact: {}\nForecast: {}\nPrevious: {}",
+            display_or_dash(&e.importance),
+            display_or_dash(&e.forecast),
+            display_or_dash(&e.previous)
+        );
+        fold_ics_line("BEGIN:VEVENT", &mut out);
+        fold_ics_line(&format!("UID:{uid}@xauusd-calendar-agent"), &mut out);
+        fold_ics_line(&format!("DTSTAMP:{stamp}"), &mut out);
+        fold_ics_line(&dtstart, &mut out);
+        fold_ics_line(
+            &format!("SUMMARY:{}", escape_ics_text(&format!("{cur} {}", e.event))),
+            &mut out,
+        );
+        fold_ics_line(
+            &format!("DESCRIPTION:{}", escape_ics_text(&description)),
+            &mut out,
+        );
+        fold_ics_line("END:VEVENT", &mut out);
+    }
+    fold_ics_line("END:VCALENDAR", &mut out);
+    out
+}
+
+#[tauri::command]
+pub fn export_ics(payload: Value, state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let path = payload
+        .get("path")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    if path.is_empty() {
+        return json!({"ok": false, "message": "path is required"});
+    }
+    let currency = payload
+        .get("currency")
+        .and_then(|v| v.as_str())
+        .unwrap_or("ALL")
+        .trim()
+        .to_uppercase();
+    let scope = payload
+        .get("scope")
+        .and_then(|v| v.as_str())
+        .unwrap_or("upcoming")
+        .trim()
+        .to_lowercase();
+    if !["upcoming", "past", "all"].contains(&scope.as_str()) {
+        return json!({"ok": false, "message": "scope must be upcoming, past, or all"});
+    }
+
+    let events = {
+        let runtime = state.lock().expect("runtime lock");
+        runtime.calendar.events.clone()
+    };
+    let now_utc = crate::time_util::now_utc();
+    let selected = select_export_events(&events, &currency, &scope, now_utc);
+    if selected.is_empty() {
+        return json!({"ok": false, "message": "No events match the selected currency and scope"});
+    }
+
+    let text = render_ics(&selected, now_utc);
+    let target = PathBuf::from(&path);
+    if let Some(parent) = target.parent() {
+        if !parent.as_os_str().is_empty() {
+            if let Err(err) = std::fs::create_dir_all(parent) {
+                return json!({"ok": false, "message": err.to_string()});
+            }
+        }
+    }
+    if let Err(err) = std::fs::write(&target, text) {
+        return json!({"ok": false, "message": err.to_string()});
+    }
+    let mut runtime = state.lock().expect("runtime lock");
+    push_log(
+        &mut runtime,
+        &format!("Exported {} events to {path}", selected.len()),
+        "INFO",
+    );
+    json!({"ok": true, "path": path, "count": selected.len()})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::EventSource;
+    use chrono::TimeZone;
+    use std::collections::HashSet;
+
+    fn event(cur: &str, name: &str, dt_utc: DateTime<Utc>, time_label: &str) -> CalendarEvent {
+        CalendarEvent {
+            dt_utc,
+            time_label: time_label.to_string(),
+            event: name.to_string(),
+            currency: cur.to_string(),
+            importance: "High".to_string(),
+            actual: String::new(),
+            forecast: "0.3%".to_string(),
+            previous: String::new(),
+            time_confirmed: true,
+            categories: vec![],
+            source: EventSource::Calendar,
+        }
+    }
+
+    #[test]
+    fn selects_events_by_currency_and_scope() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 18, 12, 0, 0).unwrap();
+        let events = vec![
+            event("USD", "CPI m/m", now - chrono::Duration::hours(2), "10:00"),
+            event(
+                "usd",
+                "Core CPI m/m",
+                now - chrono::Duration::minutes(2),
+                "11:58",
+            ),
+            event("EUR", "GDP q/q", now + chrono::Duration::hours(1), "13:00"),
+            event(
+                "USD",
+                "FOMC Statement",
+                now + chrono::Duration::days(1),
+                "18:00",
+            ),
+        ];
+        let names = |currency: &str, scope: &str| -> Vec<String> {
+            select_export_events(&events, currency, scope, now)
+                .iter()
+                .map(|e| e.event.clone())
+                .collect()
+        };
+        // Just-released events stay in "upcoming" for the grace window.
+        assert_eq!(names("USD", "upcoming"), ["Core CPI m/m", "FOMC Statement"]);
+        assert_eq!(names("USD", "past"), ["CPI m/m", "Core CPI m/m"]);
+        assert_eq!(names("ALL", "upcoming").len(), 3);
+        assert_eq!(names("ALL", "all").len(), 4);
+        assert!(names("JPY", "all").is_empty());
+    }
+
+    #[test]
+    fn renders_a_valid_calendar() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 18, 12, 0, 0).unwrap();
+        let timed = event(
+            "USD",
+            "CPI m/m; core, ex food",
+            Utc.with_ymd_and_hms(2026, 3, 19, 12, 30, 0).unwrap(),
+            "20:30",
+        );
+        let all_day = event(
+            "",
+            "Bank Holiday",
+            Utc.with_ymd_and_hms(2026, 3, 20, 0, 0, 0).unwrap(),
+            "All Day",
+        );
+        let ics = render_ics(&[&timed, &timed, &all_day], now);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+        assert_eq!(ics.matches("DTSTAMP:20260318T120000Z").count(), 3);
+        assert!(ics.contains("DTSTART:20260319T123000Z\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20260320\r\n"));
+        assert!(ics.contains("SUMMARY:USD CPI m/m\\; core\\, ex food\r\n"));
+        assert!(ics.contains("SUMMARY:-- Bank Holiday\r\n"));
+        assert!(ics.contains("DESCRIPTION:Impact: High\\nForecast: 0.3%\\nPrevious: --\r\n"));
+        // Duplicate rows still get distinct UIDs.
+        let uids: HashSet<&str> = ics.lines().filter(|l| l.starts_with("UID:")).collect();
+        assert_eq!(uids.len(), 3);
+    }
+
+    #[test]
+    fn folds_long_lines_without_splitting_characters() {
+        let mut out = String::new();
+        let line = format!("SUMMARY:{}", "€".repeat(40));
+        fold_ics_line(&line, &mut out);
+        let parts: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n ").collect();
+        assert!(parts.len() > 1);
+        assert!(parts.iter().all(|part| part.len() <= 75));
+        assert_eq!(parts.concat(), line);
+    }
+}
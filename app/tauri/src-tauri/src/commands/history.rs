@@ -1,8 +1,13 @@
 use super::*;
-use std::collections::HashMap;
+use crate::analytics::{actual_correlation, align_by_date, MIN_CORRELATION_POINTS};
+use crate::calendar::{parse_numeric, CalendarEvent, EventSource, FileLimits};
+use crate::snapshot::{LastPrint, LastPrints};
+use chrono::Datelike;
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
-use std::path::Path;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 
 const MONTH_ALIASES: &[(&str, &str)] = &[
     ("january", "jan"),
@@ -168,6 +173,9 @@ fn normalize_metric_key(value: &str) -> String {
     normalized.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
+/// Suffix on `get_event_history` cache keys for lookups merged with the loaded calendar.
+const MERGE_KEY_SUFFIX: &str = "#merge";
+
 fn normalize_event_id(value: &str) -> String {
     let mut parts = value.split("::");
     let cur = parts.next().unwrap_or("").trim().to_lowercase();
@@ -180,9 +188,71 @@ fn normalize_event_id(value: &str) -> String {
     format!("{cur}::{metric_norm}::{freq}")
 }
 
-fn load_event_history_index(path: &Path) -> Option<HashMap<String, u64>> {
-    let text = std::fs::read_to_string(path).ok()?;
-    let payload: Value = serde_json::from_str(&text).ok()?;
+/// The normalized id a metric's history is cached and looked up under.
+pub(super) fn history_event_id(cur: &str, event: &str) -> String {
+    normalize_event_id(&build_event_id(cur, event).0)
+}
+
+/// Drops both cached `get_event_history` responses for `event_id`.
+pub(super) fn forget_event_history(runtime: &RuntimeState, event_id: &str) {
+    runtime.history_flights.forget(event_id);
+    runtime
+        .history_flights
+        .forget(&format!("{event_id}{MERGE_KEY_SUFFIX}"));
+}
+
+/// Latest released print per normalized metric, keyed by every loaded name that maps to it.
+/// `events` is sorted by time, so the last row with an actual wins.
+pub(super) fn build_last_prints(events: &[CalendarEvent]) -> LastPrints {
+    let mut metric_of: HashMap<(String, String), String> = HashMap::new();
+    let mut latest: HashMap<String, &CalendarEvent> = HashMap::new();
+    for e in events {
+        let metric = metric_of
+            .entry((e.currency.clone(), e.event.clone()))
+            .or_insert_with(|| history_event_id(&e.currency, &e.event));
+        if !e.actual.is_empty() {
+            latest.insert(metric.clone(), e);
+        }
+    }
+    metric_of
+        .into_iter()
+        .filter_map(|(name, metric)| {
+            let e = latest.get(&metric)?;
+            Some((
+                name,
+                LastPrint {
+                    dt_utc: e.dt_utc,
+                    actual: e.actual.clone(),
+                    forecast: e.forecast.clone(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Writes WARN lines straight to app.log; the index is built outside the runtime lock, so
+/// `push_log` is not at hand.
+fn log_history_warnings(messages: Vec<String>) {
+    if messages.is_empty() {
+        return;
+    }
+    let lines: Vec<(String, String)> = messages
+        .into_iter()
+        .map(|message| ("WARN".to_string(), message))
+        .collect();
+    let _ = crate::log_file::append_lines(
+        &lines,
+        &crate::log_file::LogFilter::from_config(&config::load_config()),
+    );
+}
+
+fn load_event_history_index(path: &Path, limits: &FileLimits) -> Option<HashMap<String, u64>> {
+    if let Err(err) = limits.check_size(path) {
+        log_history_warnings(vec![format!("Event history index ignored: {err}")]);
+        return None;
+    }
+    let file = File::open(path).ok()?;
+    let payload: Value = serde_json::from_reader(BufReader::new(file)).ok()?;
     let index = payload.get("index")?.as_object()?;
     let mut map = HashMap::new();
     for (key, value) in index {
@@ -200,33 +270,85 @@ fn insert_index_variants(map: &mut HashMap<String, u64>, key: &str, offset: u64)
     map.entry(normalized).or_insert(offset);
 }
 
-fn build_index_from_ndjson(path: &Path) -> Option<HashMap<String, u64>> {
+/// Reads one NDJSON line into `line` without ever buffering more than `max_bytes` of it.
+/// Returns the bytes consumed (the whole line, newline included) and whether it fit.
+fn read_capped_line(
+    reader: &mut impl BufRead,
+    max_bytes: u64,
+    line: &mut Vec<u8>,
+) -> std::io::Result<(usize, bool)> {
+    line.clear();
+    let read = (&mut *reader).take(max_bytes).read_until(b'\n', line)?;
+    if read == 0 || line.ends_with(b"\n") || (read as u64) < max_bytes {
+        return Ok((read, true));
+    }
+    let rest = reader.skip_until(b'\n')?;
+    line.clear();
+    Ok((read + rest, false))
+}
+
+/// Offsets of every record in the NDJSON file. Lines over `limits.max_bytes` are skipped, and
+/// indexing stops after `limits.max_events` records.
+pub(super) fn build_index_from_ndjson(
+    path: &Path,
+    limits: &FileLimits,
+) -> Option<HashMap<String, u64>> {
     let file = File::open(path).ok()?;
     let mut reader = BufReader::new(file);
     let mut map = HashMap::new();
     let mut offset: u64 = 0;
+    let mut records = 0;
+    let mut line = vec![];
+    let mut warnings = vec![];
+    let mut invalid: Option<(u64, usize)> = None;
     loop {
-        let mut line = String::new();
-        let bytes = reader.read_line(&mut line).ok()?;
+        let (bytes, fits) = read_capped_line(&mut reader, limits.max_bytes, &mut line).ok()?;
         if bytes == 0 {
             break;
         }
-        if line.trim().is_empty() {
+        if !fits {
+            warnings.push(format!(
+                "Event history line at offset {offset} is over the size limit; skipped"
+            ));
+            offset = offset.saturating_add(bytes as u64);
+            continue;
+        }
+        if line.trim_ascii().is_empty() {
             offset = offset.saturating_add(bytes as u64);
             continue;
         }
-        match serde_json::from_str::<Value>(&line) {
+        if records == limits.max_events {
+            warnings.push(format!(
+                "Event history holds more than {} records; the rest are not indexed (max_events_per_file)",
+                limits.max_events
+            ));
+            break;
+        }
+        records += 1;
+        match serde_json::from_slice::<Value>(&line) {
             Ok(payload) => {
                 if let Some(event_id) = payload.get("eventId").and_then(|v| v.as_str()) {
                     insert_index_variants(&mut map, event_id, offset);
                 }
             }
-            Err(err) => {
-                eprintln!("Invalid event history line at offset {offset}: {err}");
-            }
+            Err(err) => match invalid.as_mut() {
+                Some((_, count)) => *count += 1,
+                None => {
+                    warnings.push(format!(
+                        "Invalid event history line at offset {offset}: {err}"
+                    ));
+                    invalid = Some((offset, 1));
+                }
+            },
         }
         offset = offset.saturating_add(bytes as u64);
     }
+    if let Some((first, count)) = invalid.filter(|(_, count)| *count > 1) {
+        warnings.push(format!(
+            "{count} invalid event history lines in all, the first at offset {first}"
+        ));
+    }
+    log_history_warnings(warnings);
     Some(map)
 }
 
@@ -234,7 +356,10 @@ fn write_index_file(path: &Path, index: &HashMap<String, u64>) -> std::io::Resul
     let mut entries: Vec<(&String, &u64)> = index.iter().collect();
     entries.sort_by(|a, b| a.0.cmp(b.0));
     let payload = json!({
-        "generated_at": chrono::Utc::now().format("%d-%m-%Y %H:%M").to_string(),
+        // Always day-first, like the upstream indexes, whatever the display `date_format`.
+        "generated_at": crate::time_util::now_utc()
+            .format(crate::time_util::DateFormat::Dmy.date_time_pattern())
+            .to_string(),
         "version": 3,
         "index": entries
             .into_iter()
@@ -250,29 +375,35 @@ fn write_index_file(path: &Path, index: &HashMap<String, u64>) -> std::io::Resul
 fn rebuild_index_and_persist(
     ndjson_path: &Path,
     index_path: &Path,
+    limits: &FileLimits,
 ) -> Option<HashMap<String, u64>> {
-    let index = build_index_from_ndjson(ndjson_path)?;
+    let index = build_index_from_ndjson(ndjson_path, limits)?;
     if let Err(err) = write_index_file(index_path, &index) {
-        eprintln!("Failed to write event history index: {err}");
+        log_history_warnings(vec![format!("Failed to write event history index: {err}")]);
     }
     Some(index)
 }
 
-fn read_ndjson_line(path: &Path, offset: u64) -> Option<String> {
+fn read_ndjson_line(path: &Path, offset: u64, limits: &FileLimits) -> Option<Vec<u8>> {
     let mut file = File::open(path).ok()?;
     file.seek(SeekFrom::Start(offset)).ok()?;
     let mut reader = BufReader::new(file);
-    let mut line = String::new();
-    reader.read_line(&mut line).ok()?;
-    if line.trim().is_empty() {
+    let mut line = vec![];
+    let (_, fits) = read_capped_line(&mut reader, limits.max_bytes, &mut line).ok()?;
+    if !fits || line.trim_ascii().is_empty() {
         return None;
     }
     Some(line)
 }
 
-fn read_payload_at_offset(path: &Path, offset: u64, candidates: &[String]) -> Option<Value> {
-    let line = read_ndjson_line(path, offset)?;
-    let payload = serde_json::from_str::<Value>(&line).ok()?;
+fn read_payload_at_offset(
+    path: &Path,
+    offset: u64,
+    candidates: &[String],
+    limits: &FileLimits,
+) -> Option<Value> {
+    let line = read_ndjson_line(path, offset, limits)?;
+    let payload = serde_json::from_slice::<Value>(&line).ok()?;
     if payload_event_id_matches(&payload, candidates) {
         return Some(payload);
     }
@@ -355,93 +486,343 @@ fn payload_event_id_matches(payload: &Value, candidates: &[String]) -> bool {
         .any(|candidate| event_id_matches(candidate, actual))
 }
 
-#[tauri::command]
-pub fn get_event_history(_payload: Value) -> Value {
-    let event = _payload
-        .get("event")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .trim()
-        .to_string();
-    let cur = _payload
-        .get("cur")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .trim()
-        .to_uppercase();
-    if event.is_empty() || cur.is_empty() {
-        return json!({"ok": false, "message": "event and cur are required"});
+pub(super) const HISTORY_NDJSON: &str = "event_history_by_event.ndjson";
+/// Gzipped NDJSON, read when the plain file is absent.
+pub(super) const HISTORY_NDJSON_GZ: &str = "event_history_by_event.ndjson.gz";
+/// Decompressed copies of gzipped NDJSON files, under the appdata dir.
+const HISTORY_GZ_CACHE_DIR: &str = "history_cache";
+
+fn has_history_ndjson(history_dir: &Path) -> bool {
+    history_dir.join(HISTORY_NDJSON).exists() || history_dir.join(HISTORY_NDJSON_GZ).exists()
+}
+
+/// `gz` decompressed to `cache`, rewritten when the copy is missing or older than `gz`. Index
+/// offsets refer to the decompressed stream, so lookups seek in this copy. A stream over
+/// `limits.max_bytes` is refused. Each writer gets its own temp file, so readers holding the
+/// shared lock can refresh the copy at the same time.
+fn decompressed_ndjson(gz: &Path, cache: &Path, limits: &FileLimits) -> Result<PathBuf, String> {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    if matches!((modified(cache), modified(gz)), (Some(c), Some(g)) if c >= g) {
+        return Ok(cache.to_path_buf());
+    }
+    let dir = cache.parent().unwrap_or_else(|| Path::new("."));
+    let io_err = |err: std::io::Error| format!("{}: {err}", gz.display());
+    std::fs::create_dir_all(dir).map_err(io_err)?;
+    // Dropped (and deleted) on every early return below.
+    let mut tmp = tempfile::NamedTempFile::new_in(dir).map_err(io_err)?;
+    let input = flate2::read::GzDecoder::new(File::open(gz).map_err(io_err)?);
+    let copied = std::io::copy(&mut input.take(limits.max_bytes + 1), &mut tmp).map_err(io_err)?;
+    if copied > limits.max_bytes {
+        return Err(format!(
+            "{} decompresses to over the {} byte limit (max_calendar_file_mb); not read",
+            gz.display(),
+            limits.max_bytes
+        ));
     }
+    tmp.persist(cache).map_err(|err| io_err(err.error))?;
+    Ok(cache.to_path_buf())
+}
 
-    let cfg = config::load_config();
-    let repo_path = resolve_calendar_repo_path(&cfg);
-    let Some(repo_path) = repo_path else {
-        return json!({"ok": false, "message": "Calendar repo is not available yet. Run Pull first."});
+/// The NDJSON to read in `history_dir`: the plain file, or when only the gzipped one ships, its
+/// decompressed copy in the appdata cache (one per history dir).
+fn readable_ndjson(history_dir: &Path, limits: &FileLimits) -> Result<PathBuf, String> {
+    let plain = history_dir.join(HISTORY_NDJSON);
+    let gz = history_dir.join(HISTORY_NDJSON_GZ);
+    if plain.exists() || !gz.exists() {
+        return Ok(plain);
+    }
+    let key = format!(
+        "{:x}",
+        Sha1::digest(history_dir.to_string_lossy().as_bytes())
+    );
+    let cache = config::appdata_dir()
+        .join(HISTORY_GZ_CACHE_DIR)
+        .join(format!("{}.ndjson", &key[..16]));
+    decompressed_ndjson(&gz, &cache, limits)
+}
+
+/// `data/event_history_index` under `root`.
+pub(super) fn history_dir_in(root: &Path) -> PathBuf {
+    root.join("data").join("event_history_index")
+}
+
+/// Roots that may hold the history, most preferred first.
+///
+/// With `history_slim_mode` the temporary path keeps only the calendar files and pulls mirror
+/// the NDJSON into the appdata root, so appdata comes first. The calendar root (working copy or
+/// install seed) is always the last candidate, which is also the full-layout location.
+pub(super) fn history_roots(cfg: &Value, calendar_root: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![];
+    if AppSettings::from_config(cfg).history_slim_mode {
+        roots.push(config::appdata_dir());
+    }
+    if !roots.iter().any(|r| r == calendar_root) {
+        roots.push(calendar_root.to_path_buf());
+    }
+    roots
+}
+
+/// History dir of the first root whose NDJSON exists, else of the last root.
+pub(super) fn pick_history_dir(roots: &[PathBuf]) -> Option<PathBuf> {
+    roots
+        .iter()
+        .map(|root| history_dir_in(root))
+        .find(|dir| has_history_ndjson(dir))
+        .or_else(|| roots.last().map(|root| history_dir_in(root)))
+}
+
+/// History dir to read alongside the calendar loaded from `calendar_root`.
+pub(super) fn resolve_history_dir(cfg: &Value, calendar_root: &Path) -> PathBuf {
+    pick_history_dir(&history_roots(cfg, calendar_root))
+        .unwrap_or_else(|| history_dir_in(calendar_root))
+}
+
+#[derive(serde::Deserialize)]
+struct IndexHeader {
+    generated_at: Option<String>,
+}
+
+/// `generated_at` of the history index, without materializing the index itself.
+pub(super) fn history_index_generated_at(history_dir: &Path) -> Option<String> {
+    let file = File::open(history_dir.join("event_history_by_event.index.json")).ok()?;
+    let header: IndexHeader = serde_json::from_reader(BufReader::new(file)).ok()?;
+    header.generated_at
+}
+
+/// What `reload_index` found.
+pub(super) struct IndexReload {
+    /// Distinct NDJSON lines the index points at, i.e. indexed events.
+    pub events: usize,
+    pub generated_at: Option<String>,
+    pub rebuilt: bool,
+}
+
+/// Whether `ndjson` changed after `index` was written, as when the NDJSON is regenerated
+/// outside the app.
+fn index_older_than_ndjson(index: &Path, ndjson: &Path) -> bool {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    matches!((modified(index), modified(ndjson)), (Some(i), Some(n)) if i < n)
+}
+
+/// Re-reads the index next to the NDJSON in `history_dir`. It is rebuilt from the NDJSON when
+/// missing, unreadable, older than the NDJSON, or when `force` is set.
+pub(super) fn reload_index(
+    history_dir: &Path,
+    force: bool,
+    limits: &FileLimits,
+) -> Result<IndexReload, String> {
+    let index_path = history_dir.join("event_history_by_event.index.json");
+    let lock_path = history_dir.join(HISTORY_NDJSON);
+    if !has_history_ndjson(history_dir) {
+        return Err(format!("{} not found", lock_path.display()));
+    }
+    let _guard = file_lock::lock_exclusive(&lock_path, file_lock::WRITE_WAIT)
+        .ok_or("event history is being updated; try again")?;
+    let ndjson_path = readable_ndjson(history_dir, limits)?;
+    let current = (!force && !index_older_than_ndjson(&index_path, &ndjson_path))
+        .then(|| load_event_history_index(&index_path, limits))
+        .flatten();
+    let rebuilt = current.is_none();
+    let index = match current {
+        Some(index) => index,
+        None => rebuild_index_and_persist(&ndjson_path, &index_path, limits)
+            .ok_or_else(|| format!("could not index {}", ndjson_path.display()))?,
     };
+    Ok(IndexReload {
+        events: index.values().collect::<HashSet<_>>().len(),
+        generated_at: history_index_generated_at(history_dir),
+        rebuilt,
+    })
+}
+
+/// What `repair_history` cut from the end of the NDJSON.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct NdjsonTrim {
+    pub bytes: u64,
+    /// Non-blank lines dropped.
+    pub lines: usize,
+}
 
-    let (event_id, metric, period) = build_event_id(&cur, &event);
-    let history_dir = repo_path.join("data").join("event_history_index");
+/// Byte length of `path` up to and including its last valid JSON line, and what lies past it.
+/// A crash mid-append leaves a partial record there, which `build_index_from_ndjson` only warns
+/// about; invalid lines further up are left to it.
+fn ndjson_valid_prefix(path: &Path, limits: &FileLimits) -> Result<(u64, NdjsonTrim), String> {
+    let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let (mut offset, mut valid_end, mut tail_lines) = (0u64, 0u64, 0usize);
+    let mut line = vec![];
+    loop {
+        let (bytes, fits) = read_capped_line(&mut reader, limits.max_bytes, &mut line)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        if bytes == 0 {
+            break;
+        }
+        offset = offset.saturating_add(bytes as u64);
+        if fits && line.trim_ascii().is_empty() {
+            continue;
+        }
+        if fits && serde_json::from_slice::<Value>(&line).is_ok() {
+            valid_end = offset;
+            tail_lines = 0;
+        } else {
+            tail_lines += 1;
+        }
+    }
+    let trim = NdjsonTrim {
+        bytes: offset - valid_end,
+        lines: tail_lines,
+    };
+    Ok((valid_end, trim))
+}
+
+/// Rewrites `path` as its first `len` bytes, through a temp file and a rename.
+fn truncate_atomically(path: &Path, len: u64) -> Result<(), String> {
+    let tmp = path.with_extension(format!("tmp-{}", std::process::id()));
+    let written = (|| -> std::io::Result<()> {
+        let mut out = File::create(&tmp)?;
+        std::io::copy(&mut File::open(path)?.take(len), &mut out)?;
+        out.flush()?;
+        out.sync_all()?;
+        std::fs::rename(&tmp, path)
+    })();
+    written.map_err(|err| {
+        let _ = std::fs::remove_file(&tmp);
+        format!("{}: {err}", path.display())
+    })
+}
+
+/// Drops a trailing incomplete or invalid record from the NDJSON in `history_dir`, then
+/// rebuilds the index under the same lock. Only the plain file is repaired: a gzipped one is
+/// decompressed whole or not at all, so its cached copy cannot end mid-record.
+pub(super) fn repair_history(
+    history_dir: &Path,
+    limits: &FileLimits,
+) -> Result<(NdjsonTrim, IndexReload), String> {
+    let ndjson_path = history_dir.join(HISTORY_NDJSON);
+    if !ndjson_path.exists() {
+        return Err(if history_dir.join(HISTORY_NDJSON_GZ).exists() {
+            "Only the gzipped history is present; pull again to replace it".to_string()
+        } else {
+            format!("{} not found", ndjson_path.display())
+        });
+    }
+    let _guard = file_lock::lock_exclusive(&ndjson_path, file_lock::WRITE_WAIT)
+        .ok_or("event history is being updated; try again")?;
+    let (valid_len, trim) = ndjson_valid_prefix(&ndjson_path, limits)?;
+    if trim.bytes > 0 {
+        truncate_atomically(&ndjson_path, valid_len)?;
+    }
+    let index_path = history_dir.join("event_history_by_event.index.json");
+    let index = rebuild_index_and_persist(&ndjson_path, &index_path, limits)
+        .ok_or_else(|| format!("could not index {}", ndjson_path.display()))?;
+    let reload = IndexReload {
+        events: index.values().collect::<HashSet<_>>().len(),
+        generated_at: history_index_generated_at(history_dir),
+        rebuilt: true,
+    };
+    Ok((trim, reload))
+}
+
+pub(super) struct IndexSample {
+    pub entries: usize,
+    pub checked: usize,
+    pub mismatched: Vec<String>,
+}
+
+/// Reads up to `sample` evenly spaced index entries and reports those whose offset does not
+/// land on a line with the same eventId.
+pub(super) fn sample_history_index(
+    history_dir: &Path,
+    sample: usize,
+    limits: &FileLimits,
+) -> Result<IndexSample, String> {
+    let index_path = history_dir.join("event_history_by_event.index.json");
+    let lock_path = history_dir.join(HISTORY_NDJSON);
+    if !has_history_ndjson(history_dir) {
+        return Err(format!("{} not found", lock_path.display()));
+    }
+    let _guard = file_lock::lock_shared(&lock_path, file_lock::READ_WAIT)
+        .ok_or("event history is being updated; try again")?;
+    let ndjson_path = readable_ndjson(history_dir, limits)?;
+    limits.check_size(&index_path)?;
+    let file = File::open(&index_path).map_err(|e| e.to_string())?;
+    let payload: Value =
+        serde_json::from_reader(BufReader::new(file)).map_err(|e| e.to_string())?;
+    let index = payload
+        .get("index")
+        .and_then(|v| v.as_object())
+        .ok_or("index object missing")?;
+
+    let entries: Vec<(&String, &Value)> = index.iter().collect();
+    let step = (entries.len() / sample.max(1)).max(1);
+    let mut result = IndexSample {
+        entries: entries.len(),
+        checked: 0,
+        mismatched: vec![],
+    };
+    for (key, value) in entries.into_iter().step_by(step).take(sample) {
+        result.checked += 1;
+        let matches = value
+            .as_u64()
+            .and_then(|offset| read_ndjson_line(&ndjson_path, offset, limits))
+            .and_then(|line| serde_json::from_slice::<Value>(&line).ok())
+            .and_then(|line| {
+                line.get("eventId")
+                    .and_then(|v| v.as_str())
+                    .map(|id| event_id_matches(key, id))
+            })
+            .unwrap_or(false);
+        if !matches {
+            result.mismatched.push(key.clone());
+        }
+    }
+    Ok(result)
+}
+
+fn indexed_history_payload(
+    history_dir: &Path,
+    candidates: &[String],
+    limits: &FileLimits,
+) -> Option<Value> {
     let index_path = history_dir.join("event_history_by_event.index.json");
-    let ndjson_path = history_dir.join("event_history_by_event.ndjson");
-    let mut candidates = vec![event_id.clone(), event_id.to_lowercase()];
-    candidates.push(normalize_event_id(&event_id));
-    if ndjson_path.exists() {
-        let mut index = if index_path.exists() {
-            load_event_history_index(&index_path)
+    let lock_path = history_dir.join(HISTORY_NDJSON);
+    if !has_history_ndjson(history_dir) {
+        return None;
+    }
+    let ndjson_path = {
+        let _guard = file_lock::lock_shared(&lock_path, file_lock::READ_WAIT)?;
+        let ndjson_path = readable_ndjson(history_dir, limits).ok()?;
+        let index = if index_path.exists() {
+            load_event_history_index(&index_path, limits)
         } else {
             None
         };
-        if index.is_none() {
-            index = rebuild_index_and_persist(&ndjson_path, &index_path);
-        }
         if let Some(index) = index {
-            if let Some(offset) = candidates.iter().find_map(|key| index.get(key).copied()) {
-                if let Some(payload) = read_payload_at_offset(&ndjson_path, offset, &candidates) {
-                    let points = points_from_payload(&payload);
-                    if !points.is_empty() {
-                        return json!({
-                            "ok": true,
-                            "eventId": payload.get("eventId").and_then(|v| v.as_str()).unwrap_or(&event_id),
-                            "metric": metric,
-                            "frequency": detect_frequency(&event),
-                            "period": period,
-                            "cur": cur,
-                            "points": points,
-                            "cached": true
-                        });
-                    }
-                } else if let Some(fresh_index) =
-                    rebuild_index_and_persist(&ndjson_path, &index_path)
-                {
-                    if let Some(offset) = candidates
-                        .iter()
-                        .find_map(|key| fresh_index.get(key).copied())
-                    {
-                        if let Some(payload) =
-                            read_payload_at_offset(&ndjson_path, offset, &candidates)
-                        {
-                            let points = points_from_payload(&payload);
-                            if !points.is_empty() {
-                                return json!({
-                                    "ok": true,
-                                    "eventId": payload.get("eventId").and_then(|v| v.as_str()).unwrap_or(&event_id),
-                                    "metric": metric,
-                                    "frequency": detect_frequency(&event),
-                                    "period": period,
-                                    "cur": cur,
-                                    "points": points,
-                                    "cached": true
-                                });
-                            }
-                        }
-                    }
-                }
+            let offset = candidates.iter().find_map(|key| index.get(key).copied())?;
+            if let Some(payload) = read_payload_at_offset(&ndjson_path, offset, candidates, limits)
+            {
+                return Some(payload);
             }
         }
-    }
+        ndjson_path
+    };
+    // Missing or stale index: rebuild under the exclusive lock and retry once.
+    let _guard = file_lock::lock_exclusive(&lock_path, file_lock::READ_WAIT)?;
+    let fresh_index = rebuild_index_and_persist(&ndjson_path, &index_path, limits)?;
+    let offset = candidates
+        .iter()
+        .find_map(|key| fresh_index.get(key).copied())?;
+    read_payload_at_offset(&ndjson_path, offset, candidates, limits)
+}
 
+fn calendar_history_points(
+    repo_path: &Path,
+    options: &CalendarLoadOptions,
+    cur: &str,
+    event: &str,
+) -> Vec<Value> {
     let mut points = vec![];
-    for item in load_calendar_events(&repo_path) {
+    for item in load_calendar_events(repo_path, options) {
         if item.currency.to_uppercase() != cur {
             continue;
         }
@@ -456,25 +837,988 @@ pub fn get_event_history(_payload: Value) -> Value {
             "previous": item.previous
         }));
     }
+    points
+}
+
+struct HistoryLookup {
+    event_id: String,
+    metric: String,
+    period: String,
+    points: Vec<Value>,
+    cached: bool,
+    /// Indexed points were combined with the calendar window (`merge_recent`).
+    merged: bool,
+}
+
+/// Union of indexed and calendar-window points, one per date with the indexed point winning,
+/// sorted by date. Fills in releases a stale index has not picked up yet.
+fn merge_recent_points(indexed: Vec<Value>, calendar: Vec<Value>) -> Vec<Value> {
+    let date = |point: &Value| point["date"].as_str().unwrap_or("").to_string();
+    let mut by_date: BTreeMap<String, Value> = BTreeMap::new();
+    for point in calendar {
+        by_date.insert(date(&point), point);
+    }
+    for point in indexed {
+        by_date.insert(date(&point), point);
+    }
+    by_date.into_values().collect()
+}
 
+/// Resolves history points for `(cur, event)`: the NDJSON index first, then the loaded
+/// calendar window. With `merge_recent` an index hit is topped up with the calendar window.
+/// `Err` carries the response payload to return as-is.
+fn lookup_event_history(
+    cur: &str,
+    event: &str,
+    merge_recent: bool,
+) -> Result<HistoryLookup, Value> {
+    let cfg = config::load_config();
+    let repo_path = resolve_calendar_repo_path(&cfg);
+    let Some(repo_path) = repo_path else {
+        return Err(
+            json!({"ok": false, "message": "Calendar repo is not available yet. Run Pull first."}),
+        );
+    };
+
+    let (event_id, metric, period) = build_event_id(cur, event);
+    let history_dir = resolve_history_dir(&cfg, &repo_path);
+    let candidates = vec![
+        event_id.clone(),
+        event_id.to_lowercase(),
+        normalize_event_id(&event_id),
+    ];
+    let options = CalendarLoadOptions::from_config(&cfg);
+    if let Some(payload) = indexed_history_payload(&history_dir, &candidates, &options.limits) {
+        let mut points = points_from_payload(&payload);
+        if !points.is_empty() {
+            if merge_recent {
+                let recent = calendar_history_points(&repo_path, &options, cur, event);
+                points = merge_recent_points(points, recent);
+            }
+            return Ok(HistoryLookup {
+                event_id: payload
+                    .get("eventId")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(&event_id)
+                    .to_string(),
+                metric,
+                period,
+                points,
+                cached: true,
+                merged: merge_recent,
+            });
+        }
+    }
+
+    let points = calendar_history_points(&repo_path, &options, cur, event);
     if points.is_empty() {
-        return json!({
+        return Err(json!({
             "ok": false,
             "eventId": event_id,
             "metric": event,
             "cur": cur,
             "message": "No history points found in the event history index or loaded calendar window."
-        });
+        }));
     }
+    Ok(HistoryLookup {
+        event_id,
+        metric: event.to_string(),
+        period,
+        points,
+        cached: false,
+        merged: false,
+    })
+}
+
+fn event_and_cur_from_payload(payload: &Value) -> (String, String) {
+    let event = payload
+        .get("event")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    let cur = payload
+        .get("cur")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_uppercase();
+    (event, cur)
+}
 
+/// Runs concurrently off the main thread; identical in-flight requests share one lookup.
+#[tauri::command(async)]
+pub fn get_event_history(payload: Value, state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let (event, cur) = event_and_cur_from_payload(&payload);
+    if event.is_empty() || cur.is_empty() {
+        return json!({"ok": false, "message": "event and cur are required"});
+    }
+
+    let merge_recent = payload
+        .get("mergeRecent")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let flights = state.lock().expect("runtime lock").history_flights.clone();
+    let mut key = history_event_id(&cur, &event);
+    if merge_recent {
+        key.push_str(MERGE_KEY_SUFFIX);
+    }
+    let response = flights.run(&key, || {
+        let lookup = lookup_event_history(&cur, &event, merge_recent)?;
+        Ok(json!({
+            "ok": true,
+            "eventId": lookup.event_id,
+            "metric": lookup.metric,
+            "frequency": detect_frequency(&event),
+            "period": lookup.period,
+            "cur": cur,
+            "points": lookup.points,
+            "cached": lookup.cached,
+            "merged": lookup.merged
+        }))
+    });
+    response.unwrap_or_else(|err| err)
+}
+
+/// Drops cached history responses and re-keys the data fingerprint after the index changed.
+fn apply_history_reload(runtime: &mut RuntimeState, generated_at: &str) {
+    runtime.history_flights.clear();
+    runtime.calendar.fingerprint = data_fingerprint(&runtime.calendar.events, generated_at);
+    runtime.calendar.history_generated_at = generated_at.to_string();
+}
+
+/// Re-reads the history index (rebuilding it from the NDJSON when missing or stale, or with
+/// `rebuild: true`) and drops cached history responses, without a pull.
+#[tauri::command(async)]
+pub fn reload_history_index(
+    payload: Option<Value>,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    let force = payload
+        .as_ref()
+        .and_then(|p| p.get("rebuild"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let cfg = config::load_config();
+    let Some(repo_path) = resolve_calendar_repo_path(&cfg) else {
+        return json!({"ok": false, "message": "Calendar repo is not available yet. Run Pull first."});
+    };
+    let history_dir = resolve_history_dir(&cfg, &repo_path);
+    let reload = reload_index(&history_dir, force, &FileLimits::from_config(&cfg));
+    let mut runtime = state.lock().expect("runtime lock");
+    let reload = match reload {
+        Ok(reload) => reload,
+        Err(err) => {
+            push_log(
+                &mut runtime,
+                &format!("History index reload failed: {err}"),
+                "WARN",
+            );
+            return json!({"ok": false, "message": err});
+        }
+    };
+    let generated_at = reload.generated_at.unwrap_or_default();
+    apply_history_reload(&mut runtime, &generated_at);
+    push_log(
+        &mut runtime,
+        &format!(
+            "History index {} ({} events, generated {generated_at})",
+            if reload.rebuilt {
+                "rebuilt"
+            } else {
+                "reloaded"
+            },
+            reload.events
+        ),
+        "INFO",
+    );
     json!({
         "ok": true,
-        "eventId": event_id,
-        "metric": event,
-        "frequency": detect_frequency(&event),
-        "period": period,
+        "indexed": reload.events,
+        "generatedAt": generated_at,
+        "rebuilt": reload.rebuilt,
+    })
+}
+
+/// Trims a partial or invalid record left at the end of the history NDJSON (e.g. by a crash
+/// mid-append) and rebuilds the index, so lookups stop pointing at stale offsets.
+#[tauri::command(async)]
+pub fn repair_history_ndjson(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let cfg = config::load_config();
+    let Some(repo_path) = resolve_calendar_repo_path(&cfg) else {
+        return json!({"ok": false, "message": "Calendar repo is not available yet. Run Pull first."});
+    };
+    let history_dir = resolve_history_dir(&cfg, &repo_path);
+    let repaired = repair_history(&history_dir, &FileLimits::from_config(&cfg));
+    let mut runtime = state.lock().expect("runtime lock");
+    let (trim, reload) = match repaired {
+        Ok(repaired) => repaired,
+        Err(err) => {
+            push_log(
+                &mut runtime,
+                &format!("History repair failed: {err}"),
+                "WARN",
+            );
+            return json!({"ok": false, "message": err});
+        }
+    };
+    let generated_at = reload.generated_at.unwrap_or_default();
+    apply_history_reload(&mut runtime, &generated_at);
+    let message = if trim.bytes == 0 {
+        format!(
+            "History NDJSON intact; index rebuilt ({} events)",
+            reload.events
+        )
+    } else {
+        format!(
+            "History NDJSON repaired: trimmed {} bytes ({} lines); index rebuilt ({} events)",
+            trim.bytes, trim.lines, reload.events
+        )
+    };
+    push_log(
+        &mut runtime,
+        &message,
+        if trim.bytes == 0 { "INFO" } else { "WARN" },
+    );
+    json!({
+        "ok": true,
+        "trimmedBytes": trim.bytes,
+        "trimmedLines": trim.lines,
+        "indexed": reload.events,
+        "generatedAt": generated_at,
+    })
+}
+
+/// Distinct event names loaded for `cur` (or every currency with `ALL`), with occurrence counts,
+/// for the history lookup picker.
+#[tauri::command]
+pub fn list_events(payload: Value, state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let cur = payload
+        .get("cur")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_uppercase();
+    if cur.is_empty() {
+        return json!({"ok": false, "message": "cur is required"});
+    }
+    let events = state.lock().expect("runtime lock").calendar.events.clone();
+    let mut counts: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for e in events
+        .iter()
+        .filter(|e| e.source != EventSource::Placeholder)
+    {
+        let event_cur = e.currency.to_uppercase();
+        if cur != "ALL" && event_cur != cur {
+            continue;
+        }
+        let name = e.event.trim();
+        if name.is_empty() {
+            continue;
+        }
+        *counts.entry((name.to_string(), event_cur)).or_default() += 1;
+    }
+    let items: Vec<Value> = counts
+        .into_iter()
+        .map(|((event, cur), count)| json!({"event": event, "cur": cur, "count": count}))
+        .collect();
+    json!({"ok": true, "cur": cur, "events": items})
+}
+
+/// Resolves an explicit `eventId` through the NDJSON index only.
+fn lookup_history_by_id(event_id: &str) -> Result<HistoryLookup, Value> {
+    let cfg = config::load_config();
+    let Some(repo_path) = resolve_calendar_repo_path(&cfg) else {
+        return Err(
+            json!({"ok": false, "message": "Calendar repo is not available yet. Run Pull first."}),
+        );
+    };
+    let history_dir = resolve_history_dir(&cfg, &repo_path);
+    let candidates = vec![
+        event_id.to_string(),
+        event_id.to_lowercase(),
+        normalize_event_id(event_id),
+    ];
+    let payload =
+        indexed_history_payload(&history_dir, &candidates, &FileLimits::from_config(&cfg));
+    let points = payload
+        .as_ref()
+        .map(points_from_payload)
+        .unwrap_or_default();
+    let Some(payload) = payload.filter(|_| !points.is_empty()) else {
+        return Err(json!({
+            "ok": false,
+            "eventId": event_id,
+            "message": "No history points found in the event history index."
+        }));
+    };
+    let event_id = payload
+        .get("eventId")
+        .and_then(|v| v.as_str())
+        .unwrap_or(event_id)
+        .to_string();
+    let metric = payload
+        .get("metric")
+        .and_then(|v| v.as_str())
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| event_id.split("::").nth(1).unwrap_or("").to_string());
+    Ok(HistoryLookup {
+        event_id,
+        metric,
+        period: String::new(),
+        points,
+        cached: true,
+        merged: false,
+    })
+}
+
+/// One side of a comparison: `{eventId}` or `{event, cur}`. Returns the lookup plus its
+/// series metadata.
+fn compare_side(side: &Value) -> Result<(HistoryLookup, Value), Value> {
+    let explicit_id = side
+        .get("eventId")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    let (lookup, cur, frequency) = if !explicit_id.is_empty() {
+        let lookup = lookup_history_by_id(&explicit_id)?;
+        let mut parts = lookup.event_id.split("::");
+        let cur = parts.next().unwrap_or("").to_uppercase();
+        let frequency = parts.nth(1).unwrap_or("").to_string();
+        (lookup, cur, frequency)
+    } else {
+        let (event, cur) = event_and_cur_from_payload(side);
+        if event.is_empty() || cur.is_empty() {
+            return Err(json!({"ok": false, "message": "eventId or event and cur are required"}));
+        }
+        (
+            lookup_event_history(&cur, &event, false)?,
+            cur,
+            detect_frequency(&event),
+        )
+    };
+    let meta = json!({
+        "eventId": lookup.event_id,
+        "metric": lookup.metric,
+        "frequency": frequency,
+        "period": lookup.period,
         "cur": cur,
+        "cached": lookup.cached,
+        "count": lookup.points.len()
+    });
+    Ok((lookup, meta))
+}
+
+#[tauri::command]
+pub fn compare_event_histories(payload: Value) -> Value {
+    let mut sides = vec![];
+    for key in ["a", "b"] {
+        let side = payload.get(key).cloned().unwrap_or(Value::Null);
+        match compare_side(&side) {
+            Ok(resolved) => sides.push(resolved),
+            Err(mut response) => {
+                if let Some(obj) = response.as_object_mut() {
+                    obj.insert("side".to_string(), json!(key));
+                }
+                return response;
+            }
+        }
+    }
+    let (b, meta_b) = sides.pop().expect("side b");
+    let (a, meta_a) = sides.pop().expect("side a");
+
+    let rows = align_by_date(&a.points, &b.points);
+    let (overlap, correlation) = actual_correlation(&rows);
+    let points: Vec<Value> = rows
+        .into_iter()
+        .map(|row| {
+            json!({
+                "date": row.date.format("%Y-%m-%d").to_string(),
+                "a": row.a,
+                "b": row.b
+            })
+        })
+        .collect();
+    json!({
+        "ok": true,
+        "seriesA": meta_a,
+        "seriesB": meta_b,
         "points": points,
-        "cached": false
+        "overlap": overlap,
+        "correlation": correlation,
+        "minCorrelationPoints": MIN_CORRELATION_POINTS
     })
 }
+
+/// The pieces `build_event_id` derives from a calendar event name, for metadata chips.
+fn event_name_parts(cur: &str, event: &str) -> Value {
+    let (event_id, metric, period) = build_event_id(cur, event);
+    json!({
+        "eventId": event_id,
+        "metric": metric,
+        "frequency": detect_frequency(event.trim()),
+        "period": period,
+        "strippedName": strip_known_suffixes(event),
+    })
+}
+
+#[tauri::command]
+pub fn parse_event_name(payload: Value) -> Value {
+    let (event, cur) = event_and_cur_from_payload(&payload);
+    if event.is_empty() {
+        return json!({"ok": false, "message": "event is required"});
+    }
+    let mut result = event_name_parts(&cur, &event);
+    if let Some(obj) = result.as_object_mut() {
+        obj.insert("ok".to_string(), json!(true));
+    }
+    result
+}
+
+const QUARTERS: &[&str] = &["q1", "q2", "q3", "q4"];
+
+fn point_text(point: &Value, key: &str) -> String {
+    point
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string()
+}
+
+pub(super) fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    } else {
+        Some(values[mid])
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Years of prints a seasonality group needs before it is trusted.
+const SEASONALITY_MIN_YEARS: usize = 3;
+
+/// Groups history points by month (or quarter for quarterly metrics) and aggregates the
+/// actual prints per group. Points without a period fall back to the month of their date.
+/// Each group is `lowConfidence` with prints from fewer than `SEASONALITY_MIN_YEARS` years;
+/// the whole result is when every group is.
+fn compute_seasonality(points: &[Value]) -> Value {
+    let periods: Vec<String> = points
+        .iter()
+        .map(|p| normalize_period(&point_text(p, "period")))
+        .collect();
+    let quarterly = periods.iter().any(|p| QUARTERS.contains(&p.as_str()))
+        && periods
+            .iter()
+            .all(|p| p.is_empty() || QUARTERS.contains(&p.as_str()));
+    let buckets: &[&str] = if quarterly { QUARTERS } else { MONTHS };
+
+    let mut groups: Vec<Vec<(i32, &Value)>> = vec![vec![]; buckets.len()];
+    let mut years: Vec<i32> = vec![];
+    for (point, period) in points.iter().zip(periods.iter()) {
+        let Ok(date) = chrono::NaiveDate::parse_from_str(&point_text(point, "date"), "%Y-%m-%d")
+        else {
+            continue;
+        };
+        let month_idx = date.month0() as usize;
+        let bucket = match buckets.iter().position(|b| b == period) {
+            Some(idx) => idx,
+            None if quarterly => month_idx / 3,
+            None => month_idx,
+        };
+        groups[bucket].push((date.year(), point));
+        years.push(date.year());
+    }
+    years.sort();
+    years.dedup();
+
+    let mut rendered = vec![];
+    for (bucket, items) in buckets.iter().zip(groups.iter()) {
+        if items.is_empty() {
+            continue;
+        }
+        let mut actuals = vec![];
+        let mut surprises = vec![];
+        let mut values = vec![];
+        for (year, point) in items {
+            let actual = parse_numeric(&point_text(point, "actual"));
+            let forecast = parse_numeric(&point_text(point, "forecast"));
+            if let Some(a) = actual {
+                actuals.push(a);
+                if let Some(f) = forecast {
+                    surprises.push(a - f);
+                }
+            }
+            values.push(json!({
+                "year": year,
+                "date": point_text(point, "date"),
+                "actual": actual,
+                "forecast": forecast,
+            }));
+        }
+        let mean_actual = mean(&actuals);
+        let mut group_years: Vec<i32> = items.iter().map(|(year, _)| *year).collect();
+        group_years.sort();
+        group_years.dedup();
+        rendered.push(json!({
+            "period": bucket,
+            "count": items.len(),
+            "years": group_years.len(),
+            "lowConfidence": group_years.len() < SEASONALITY_MIN_YEARS,
+            "meanActual": mean_actual,
+            "medianActual": median(&mut actuals),
+            "meanSurprise": mean(&surprises),
+            "values": values,
+        }));
+    }
+
+    json!({
+        "grouping": if quarterly { "quarter" } else { "month" },
+        "years": years.len(),
+        "lowConfidence": rendered.iter().all(|group| group["lowConfidence"] == true),
+        "groups": rendered,
+    })
+}
+
+#[tauri::command]
+pub fn get_event_seasonality(payload: Value) -> Value {
+    let (event, cur) = event_and_cur_from_payload(&payload);
+    if event.is_empty() || cur.is_empty() {
+        return json!({"ok": false, "message": "event and cur are required"});
+    }
+    let lookup = match lookup_event_history(&cur, &event, false) {
+        Ok(lookup) => lookup,
+        Err(response) => return response,
+    };
+    let mut result = compute_seasonality(&lookup.points);
+    if let Some(obj) = result.as_object_mut() {
+        obj.insert("ok".to_string(), Value::Bool(true));
+        obj.insert("eventId".to_string(), Value::String(lookup.event_id));
+        obj.insert("metric".to_string(), Value::String(lookup.metric));
+        obj.insert("cur".to_string(), Value::String(cur));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(date: &str, actual: &str, forecast: &str, period: &str) -> Value {
+        json!({
+            "date": date,
+            "time": "12:30",
+            "actual": actual,
+            "forecast": forecast,
+            "previous": "",
+            "period": if period.is_empty() { Value::Null } else { Value::String(period.to_string()) }
+        })
+    }
+
+    fn write_history(root: &Path, event_id: &str) {
+        let dir = history_dir_in(root);
+        std::fs::create_dir_all(&dir).unwrap();
+        let line = json!({"eventId": event_id, "points": []}).to_string();
+        std::fs::write(dir.join(HISTORY_NDJSON), format!("{line}\n")).unwrap();
+    }
+
+    fn write_calendar(root: &Path) {
+        std::fs::create_dir_all(root.join("data").join("Economic_Calendar").join("2026")).unwrap();
+    }
+
+    #[test]
+    fn history_index_skips_oversized_lines_and_caps_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(HISTORY_NDJSON);
+        let line = |id: &str, pad: usize| {
+            json!({"eventId": id, "points": [], "pad": "x".repeat(pad)}).to_string()
+        };
+        let lines = [
+            line("usd-a", 0),
+            line("usd-huge", 400),
+            line("usd-b", 0),
+            line("usd-c", 0),
+        ];
+        std::fs::write(&path, format!("{}\n", lines.join("\n"))).unwrap();
+        let limits = |max_events| FileLimits {
+            max_bytes: 128,
+            max_events,
+        };
+
+        let index = build_index_from_ndjson(&path, &limits(100)).unwrap();
+        assert!(index.contains_key("usd-a") && index.contains_key("usd-c"));
+        assert!(!index.contains_key("usd-huge"));
+        let offset = index["usd-b"];
+        assert_eq!(offset as usize, lines[0].len() + lines[1].len() + 2);
+        let candidates = vec!["usd-b".to_string()];
+        assert!(read_payload_at_offset(&path, offset, &candidates, &limits(100)).is_some());
+        assert!(read_ndjson_line(&path, lines[0].len() as u64 + 1, &limits(100)).is_none());
+
+        let index = build_index_from_ndjson(&path, &limits(2)).unwrap();
+        assert!(index.contains_key("usd-b"));
+        assert!(!index.contains_key("usd-c"));
+
+        let index_path = dir.path().join("event_history_by_event.index.json");
+        std::fs::write(
+            &index_path,
+            json!({"index": {"usd-a": 0, "pad": "x".repeat(200)}}).to_string(),
+        )
+        .unwrap();
+        assert!(load_event_history_index(&index_path, &limits(100)).is_none());
+    }
+
+    #[test]
+    fn gzipped_history_is_read_through_a_decompressed_copy() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let gz = dir.path().join(HISTORY_NDJSON_GZ);
+        let write_gz = |ids: &[&str]| {
+            let lines: Vec<String> = ids
+                .iter()
+                .map(|id| json!({"eventId": id, "points": []}).to_string())
+                .collect();
+            let mut encoder = GzEncoder::new(File::create(&gz).unwrap(), Default::default());
+            encoder
+                .write_all(format!("{}\n", lines.join("\n")).as_bytes())
+                .unwrap();
+            encoder.finish().unwrap();
+        };
+        write_gz(&["usd-a", "usd-b"]);
+        assert!(has_history_ndjson(dir.path()));
+
+        let cache = dir.path().join("cache").join("copy.ndjson");
+        let limits = FileLimits::default();
+        let copy = decompressed_ndjson(&gz, &cache, &limits).unwrap();
+        let index = build_index_from_ndjson(&copy, &limits).unwrap();
+        // Offsets are positions in the decompressed stream.
+        let offset = index["usd-b"];
+        assert_eq!(
+            offset,
+            json!({"eventId": "usd-a", "points": []}).to_string().len() as u64 + 1
+        );
+        let candidates = vec!["usd-b".to_string()];
+        assert!(read_payload_at_offset(&copy, offset, &candidates, &limits).is_some());
+
+        // A newer archive replaces the copy; an unchanged one reuses it.
+        write_gz(&["usd-c"]);
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        File::options()
+            .write(true)
+            .open(&gz)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        let copy = decompressed_ndjson(&gz, &cache, &limits).unwrap();
+        let index = build_index_from_ndjson(&copy, &limits).unwrap();
+        assert!(index.contains_key("usd-c") && !index.contains_key("usd-a"));
+        std::fs::write(&cache, "stale but current\n").unwrap();
+        File::options()
+            .write(true)
+            .open(&cache)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        decompressed_ndjson(&gz, &cache, &limits).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&cache).unwrap(),
+            "stale but current\n"
+        );
+
+        // A stream over the limit is refused and leaves no temp files behind.
+        let small = FileLimits {
+            max_bytes: 8,
+            ..FileLimits::default()
+        };
+        let other = dir.path().join("cache").join("other.ndjson");
+        let err = decompressed_ndjson(&gz, &other, &small).unwrap_err();
+        assert!(err.contains("byte limit"), "{err}");
+        assert!(!other.exists());
+        assert_eq!(
+            std::fs::read_dir(dir.path().join("cache")).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn merge_recent_fills_in_dates_the_index_lacks() {
+        let indexed = vec![
+            point("2026-01-12", "0.3%", "0.2%", "Dec"),
+            point("2026-02-12", "0.4%", "0.3%", "Jan"),
+        ];
+        let calendar = vec![
+            point("2026-03-12", "0.5%", "0.4%", ""),
+            point("2026-02-12", "0.9%", "0.3%", ""),
+        ];
+        let merged = merge_recent_points(indexed, calendar);
+        let dates: Vec<&str> = merged.iter().filter_map(|p| p["date"].as_str()).collect();
+        assert_eq!(dates, vec!["2026-01-12", "2026-02-12", "2026-03-12"]);
+        // The indexed print wins on a shared date.
+        assert_eq!(merged[1]["actual"], "0.4%");
+        assert_eq!(merged[1]["period"], "Jan");
+        assert_eq!(merged[2]["actual"], "0.5%");
+    }
+
+    #[test]
+    fn event_name_parts_split_metric_frequency_and_period() {
+        let parts = event_name_parts("usd", "Core CPI (YoY)  (Jan)");
+        assert_eq!(parts["eventId"], "USD::Core CPI::y/y");
+        assert_eq!(parts["metric"], "Core CPI");
+        assert_eq!(parts["frequency"], "y/y");
+        assert_eq!(parts["period"], "Jan");
+        assert_eq!(parts["strippedName"], "Core CPI");
+
+        let plain = event_name_parts("", "Fed Chair Powell Speaks");
+        assert_eq!(plain["eventId"], "NA::Fed Chair Powell Speaks::none");
+        assert_eq!(plain["frequency"], "");
+        assert_eq!(plain["period"], "");
+    }
+
+    fn calendar_event(day: u32, event: &str, actual: &str) -> CalendarEvent {
+        use chrono::TimeZone;
+        CalendarEvent {
+            dt_utc: chrono::Utc
+                .with_ymd_and_hms(2026, day, 12, 12, 30, 0)
+                .unwrap(),
+            time_label: "12:30".to_string(),
+            event: event.to_string(),
+            currency: "USD".to_string(),
+            importance: "High".to_string(),
+            actual: actual.to_string(),
+            forecast: "0.3%".to_string(),
+            previous: String::new(),
+            time_confirmed: true,
+            categories: vec![],
+            source: Default::default(),
+        }
+    }
+
+    #[test]
+    fn last_prints_pick_the_latest_release_across_period_suffixes() {
+        let events = vec![
+            calendar_event(1, "CPI (MoM) (Dec)", "0.2%"),
+            calendar_event(2, "CPI (MoM) (Jan)", "0.4%"),
+            calendar_event(2, "Core CPI (MoM) (Jan)", "0.3%"),
+            calendar_event(3, "CPI (MoM) (Feb)", ""),
+            calendar_event(3, "Retail Sales (MoM) (Feb)", ""),
+        ];
+        let prints = build_last_prints(&events);
+        let upcoming = prints
+            .get(&("USD".to_string(), "CPI (MoM) (Feb)".to_string()))
+            .unwrap();
+        assert_eq!(upcoming.actual, "0.4%");
+        assert_eq!(upcoming.dt_utc, events[1].dt_utc);
+        assert_eq!(
+            prints[&("USD".to_string(), "CPI (MoM) (Dec)".to_string())],
+            *upcoming
+        );
+        assert_eq!(
+            prints[&("USD".to_string(), "Core CPI (MoM) (Jan)".to_string())].actual,
+            "0.3%"
+        );
+        assert!(!prints.contains_key(&("USD".to_string(), "Retail Sales (MoM) (Feb)".to_string())));
+    }
+
+    #[test]
+    fn full_layout_reads_history_next_to_the_calendar() {
+        let work = tempfile::tempdir().unwrap();
+        write_calendar(work.path());
+        write_history(work.path(), "usd-cpi-m/m");
+
+        let dir = pick_history_dir(&[work.path().to_path_buf()]).unwrap();
+        assert_eq!(dir, history_dir_in(work.path()));
+        let candidates = vec!["usd-cpi-m/m".to_string()];
+        let payload = indexed_history_payload(&dir, &candidates, &FileLimits::default()).unwrap();
+        assert_eq!(payload["eventId"], "usd-cpi-m/m");
+    }
+
+    #[test]
+    fn slim_layout_reads_history_from_appdata() {
+        let appdata = tempfile::tempdir().unwrap();
+        let temp = tempfile::tempdir().unwrap();
+        write_calendar(temp.path());
+        write_history(appdata.path(), "usd-cpi-m/m");
+        let roots = [appdata.path().to_path_buf(), temp.path().to_path_buf()];
+
+        let dir = pick_history_dir(&roots).unwrap();
+        assert_eq!(dir, history_dir_in(appdata.path()));
+        let candidates = vec!["usd-cpi-m/m".to_string()];
+        assert!(indexed_history_payload(&dir, &candidates, &FileLimits::default()).is_some());
+
+        // A stale full copy left in the temporary path does not shadow appdata.
+        write_history(temp.path(), "usd-stale");
+        assert_eq!(pick_history_dir(&roots).unwrap(), dir);
+
+        // Before the first slim pull the calendar root's copy is still used.
+        std::fs::remove_dir_all(history_dir_in(appdata.path())).unwrap();
+        assert_eq!(
+            pick_history_dir(&roots).unwrap(),
+            history_dir_in(temp.path())
+        );
+    }
+
+    #[test]
+    fn seasonality_groups_by_period_token_and_falls_back_to_date_month() {
+        let points = vec![
+            point("2021-04-13", "0.6%", "0.5%", "mar"),
+            point("2022-04-12", "1.2%", "1.0%", "mar"),
+            point("2023-04-12", "0.1%", "0.2%", "mar"),
+            point("2023-05-10", "0.4%", "", ""),
+        ];
+        let result = compute_seasonality(&points);
+        assert_eq!(result["grouping"], "month");
+        assert_eq!(result["years"], 3);
+        assert_eq!(result["lowConfidence"], false);
+
+        let groups = result["groups"].as_array().unwrap();
+        assert_eq!(groups.len(), 2);
+        let march = &groups[0];
+        assert_eq!(march["period"], "mar");
+        assert_eq!(march["count"], 3);
+        assert_eq!(march["years"], 3);
+        assert_eq!(march["lowConfidence"], false);
+        assert!((march["meanActual"].as_f64().unwrap() - 0.633333).abs() < 1e-4);
+        assert!((march["medianActual"].as_f64().unwrap() - 0.6).abs() < 1e-9);
+        assert!((march["meanSurprise"].as_f64().unwrap() - 0.066666).abs() < 1e-4);
+        assert_eq!(march["values"].as_array().unwrap().len(), 3);
+        assert_eq!(groups[1]["period"], "may");
+        assert!(groups[1]["meanSurprise"].is_null());
+        assert_eq!(groups[1]["lowConfidence"], true);
+    }
+
+    #[test]
+    fn seasonality_confidence_counts_years_per_period() {
+        // Three years of history, but no month was printed in more than one of them.
+        let points = vec![
+            point("2021-02-10", "0.1%", "", "jan"),
+            point("2022-03-10", "0.2%", "", "feb"),
+            point("2023-04-12", "0.3%", "", "mar"),
+        ];
+        let result = compute_seasonality(&points);
+        assert_eq!(result["years"], 3);
+        assert_eq!(result["lowConfidence"], true);
+        let groups = result["groups"].as_array().unwrap();
+        assert!(groups
+            .iter()
+            .all(|g| g["years"] == 1 && g["lowConfidence"] == true));
+    }
+
+    #[test]
+    fn seasonality_uses_quarters_for_quarterly_metrics() {
+        let points = vec![
+            point("2022-04-28", "1.5%", "1.1%", "q1"),
+            point("2023-04-27", "1.1%", "2.0%", "q1"),
+            point("2023-07-27", "2.4%", "1.8%", ""),
+        ];
+        let result = compute_seasonality(&points);
+        assert_eq!(result["grouping"], "quarter");
+        assert_eq!(result["lowConfidence"], true);
+        let groups = result["groups"].as_array().unwrap();
+        assert_eq!(groups[0]["period"], "q1");
+        assert_eq!(groups[0]["count"], 2);
+        assert_eq!(groups[0]["lowConfidence"], true);
+        // No period token: July falls into q3 by date.
+        assert_eq!(groups[1]["period"], "q3");
+    }
+
+    #[test]
+    fn reload_index_rebuilds_only_when_missing_or_stale() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = history_dir_in(root.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        let ndjson = dir.join(HISTORY_NDJSON);
+        let lines = ["USD-CPI_M_M", "USD-Nonfarm_Payrolls"]
+            .map(|id| json!({"eventId": id, "points": []}).to_string());
+        std::fs::write(&ndjson, format!("{}\n", lines.join("\n"))).unwrap();
+        let limits = FileLimits::default();
+
+        let first = reload_index(&dir, false, &limits).unwrap();
+        assert!(first.rebuilt);
+        // Each event is indexed under several key variants but counts once.
+        assert_eq!(first.events, 2);
+        assert!(first.generated_at.is_some());
+
+        let again = reload_index(&dir, false, &limits).unwrap();
+        assert!(!again.rebuilt);
+        assert_eq!(again.events, 2);
+        assert!(reload_index(&dir, true, &limits).unwrap().rebuilt);
+
+        // An NDJSON regenerated after the index makes the index stale.
+        let extra = json!({"eventId": "EUR-CPI_Y_Y", "points": []}).to_string();
+        std::fs::write(&ndjson, format!("{}\n{extra}\n", lines.join("\n"))).unwrap();
+        File::options()
+            .write(true)
+            .open(&ndjson)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        let stale = reload_index(&dir, false, &limits).unwrap();
+        assert!(stale.rebuilt);
+        assert_eq!(stale.events, 3);
+
+        std::fs::remove_file(&ndjson).unwrap();
+        assert!(reload_index(&dir, false, &limits).is_err());
+    }
+
+    #[test]
+    fn repair_trims_a_partial_last_record() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = history_dir_in(root.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        let ndjson = dir.join(HISTORY_NDJSON);
+        let lines = ["USD-CPI_M_M", "USD-Nonfarm_Payrolls"]
+            .map(|id| json!({"eventId": id, "points": []}).to_string());
+        // A bad line in the middle is left for the index to skip; only the tail is trimmed.
+        let intact = format!("{}\nnot json\n{}\n", lines[0], lines[1]);
+        let partial = r#"{"eventId": "EUR-CPI_Y_Y", "poi"#;
+        std::fs::write(&ndjson, format!("{intact}{partial}")).unwrap();
+        let limits = FileLimits::default();
+
+        let (trim, reload) = repair_history(&dir, &limits).unwrap();
+        assert_eq!(
+            trim,
+            NdjsonTrim {
+                bytes: partial.len() as u64,
+                lines: 1
+            }
+        );
+        assert!(reload.rebuilt);
+        assert_eq!(reload.events, 2);
+        assert_eq!(std::fs::read_to_string(&ndjson).unwrap(), intact);
+        let index =
+            load_event_history_index(&dir.join("event_history_by_event.index.json"), &limits)
+                .unwrap();
+        let offset = index["USD-Nonfarm_Payrolls"];
+        assert_eq!(offset as usize, lines[0].len() + "\nnot json\n".len());
+        let candidates = vec!["USD-Nonfarm_Payrolls".to_string()];
+        assert!(read_payload_at_offset(&ndjson, offset, &candidates, &limits).is_some());
+
+        // Trailing garbage with a newline and blank lines go too; a clean file is left alone.
+        std::fs::write(&ndjson, format!("{intact}\x00\x00\n\n")).unwrap();
+        let (trim, _) = repair_history(&dir, &limits).unwrap();
+        assert_eq!((trim.bytes, trim.lines), (4, 1));
+        let (trim, _) = repair_history(&dir, &limits).unwrap();
+        assert_eq!(trim, NdjsonTrim::default());
+        assert_eq!(std::fs::read_to_string(&ndjson).unwrap(), intact);
+        let leftovers = std::fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".tmp-"))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+}
@@ -1,16 +1,257 @@
 use super::*;
+use crate::runtime_store;
+use crate::status_file;
+use crate::time_util::{local_utc_offset_minutes, timezone_mismatch};
+
+fn format_utc_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let minutes = minutes.abs();
+    format!("UTC{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// `{configured, detected}` when the pinned calendar offset disagrees with the system clock,
+/// unless the hint was dismissed for this detected offset.
+pub(super) fn timezone_hint(cfg: &Value, local_offset_minutes: i32) -> Option<Value> {
+    let (tz_mode, utc_offset_minutes) = get_calendar_settings(cfg);
+    let (configured, detected) =
+        timezone_mismatch(&tz_mode, utc_offset_minutes, local_offset_minutes)?;
+    let dismissed = cfg
+        .get("timezone_hint_dismissed_offset")
+        .and_then(|v| v.as_i64());
+    if dismissed == Some(detected as i64) {
+        return None;
+    }
+    Some(json!({"configured": configured, "detected": detected}))
+}
+
+/// Logs a warning when the timezone hint is active; run at startup and daily.
+pub(super) fn check_timezone_mismatch(app: &tauri::AppHandle) {
+    let cfg = config::load_config();
+    let Some(hint) = timezone_hint(&cfg, local_utc_offset_minutes()) else {
+        return;
+    };
+    let configured = hint["configured"].as_i64().unwrap_or(0) as i32;
+    let detected = hint["detected"].as_i64().unwrap_or(0) as i32;
+    let state = app.state::<Mutex<RuntimeState>>();
+    let mut runtime = state.lock().expect("runtime lock");
+    push_log(
+        &mut runtime,
+        &format!(
+            "Calendar offset {} differs from system timezone {}; review Settings",
+            format_utc_offset(configured),
+            format_utc_offset(detected)
+        ),
+        "WARN",
+    );
+}
+
+/// Startup work that must not delay the first paint: retired portable data cleanup, the
+/// autostart registration and the initial token probe.
+fn deferred_startup_work(app: &tauri::AppHandle) {
+    {
+        let state = app.state::<Mutex<RuntimeState>>();
+        let mut runtime = state.lock().expect("runtime lock");
+        crate::boot::mark(&mut runtime.boot.deferred_startup_ms);
+    }
+    // A portable folder retired by `convert_to_installed` while still in use.
+    if let Some(dir) = config::portable_dir_candidate() {
+        if dir.join(crate::portable::RETIRED_MARKER).exists() {
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+    }
+
+    let cfg = config::load_config();
+    // Ensure startup setting is applied (Windows: HKCU Run entry).
+    let run_on_startup = AppSettings::from_config(&cfg).run_on_startup;
+    let _ = startup::set_run_on_startup(run_on_startup);
+
+    let token = config::get_str(&cfg, "github_token");
+    if !token.is_empty() {
+        super::update::try_begin_github_token_check(app.clone(), token);
+    }
+}
+
+fn deferred_startup_once(app: &tauri::AppHandle) -> Arc<crate::boot::RunOnce> {
+    let state = app.state::<Mutex<RuntimeState>>();
+    let runtime = state.lock().expect("runtime lock");
+    runtime.deferred_startup.clone()
+}
+
+/// Runs the deferred startup work now unless it already ran.
+pub(super) fn run_deferred_startup(app: &tauri::AppHandle) {
+    deferred_startup_once(app).run(|| deferred_startup_work(app));
+}
+
+/// Arms the fallback that runs the deferred startup work if `frontend_boot_complete` never
+/// arrives.
+pub fn schedule_deferred_startup(app: &tauri::AppHandle) {
+    let handle = app.clone();
+    crate::boot::spawn_fallback(
+        deferred_startup_once(app),
+        crate::boot::DEFERRED_STARTUP_FALLBACK,
+        move || deferred_startup_work(&handle),
+    );
+}
+
+/// Shows a modal pointing at a crash report left by the previous session, once per report.
+pub fn surface_previous_crash(app: &tauri::AppHandle) {
+    let mut cfg = config::load_config();
+    let seen = config::get_str(&cfg, "crash_report_seen");
+    let Some(path) = crate::crash::unseen_crash_report(&config::log_dir(), &seen) else {
+        return;
+    };
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let _ = config::set_string(&mut cfg, "crash_report_seen", name.clone());
+    let _ = config::save_config(&cfg);
+    let path = path.to_string_lossy().to_string();
+    let state = app.state::<Mutex<RuntimeState>>();
+    let mut runtime = state.lock().expect("runtime lock");
+    push_log(
+        &mut runtime,
+        &format!("Previous session crashed; report saved to {path}"),
+        "WARN",
+    );
+    runtime.set_modal(
+        Modal::new(
+            format!("crash-{name}"),
+            "Previous Session Crashed",
+            format!(
+                "The app closed unexpectedly last time.\n\nA crash report was saved to {path}."
+            ),
+            ModalTone::Error,
+        )
+        .with_open_path(path),
+    );
+}
+
+#[tauri::command]
+pub fn dismiss_timezone_hint(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let detected = local_utc_offset_minutes();
+    let mut cfg = config::load_config();
+    if let Err(err) =
+        config::set_number(&mut cfg, "timezone_hint_dismissed_offset", detected as i64)
+    {
+        return json!({"ok": false, "message": err});
+    }
+    if let Err(err) = config::save_config(&cfg) {
+        return json!({"ok": false, "message": err});
+    }
+    let mut runtime = state.lock().expect("runtime lock");
+    push_log(
+        &mut runtime,
+        &format!(
+            "Timezone hint dismissed for {}",
+            format_utc_offset(detected)
+        ),
+        "INFO",
+    );
+    json!({"ok": true, "detected": detected})
+}
 
 #[tauri::command]
 pub fn dismiss_modal(payload: Value, state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
     let id = payload.get("id").and_then(|v| v.as_str()).unwrap_or("");
     let mut runtime = state.lock().expect("runtime lock");
-    let current_id = runtime
-        .modal
-        .get("id")
-        .and_then(|v| v.as_str())
-        .unwrap_or("");
-    if !id.is_empty() && id == current_id {
-        runtime.modal = Value::Null;
+    if !id.is_empty() {
+        runtime.clear_modal(id);
     }
     json!({"ok": true})
 }
+
+/// Modals shown this session, newest first, including dismissed ones.
+#[tauri::command]
+pub fn get_modal_history(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let runtime = state.lock().expect("runtime lock");
+    json!({"ok": true, "modals": runtime.modal_history})
+}
+
+/// Persists the durable runtime subset; used on exit, where the debounce does not apply.
+pub fn save_runtime_state(app: &tauri::AppHandle) {
+    let durable = {
+        let state = app.state::<Mutex<RuntimeState>>();
+        let runtime = state.lock().expect("runtime lock");
+        runtime_store::DurableState::capture(&runtime)
+    };
+    let _ = runtime_store::save(&runtime_store::runtime_state_path(), &durable);
+}
+
+/// Rewrites `status.json` when `status_file_enabled` is on. The runtime lock is held only to
+/// clone the reported fields; failures are logged at most hourly.
+pub fn refresh_status_file(app: &tauri::AppHandle) {
+    let cfg = config::load_config();
+    if !AppSettings::from_config(&cfg).status_file_enabled {
+        return;
+    }
+    let state = app.state::<Mutex<RuntimeState>>();
+    let mut inputs = {
+        let runtime = state.lock().expect("runtime lock");
+        status_file::StatusInputs::capture(&runtime)
+    };
+    // Until the first snapshot seeds them, the runtime times are only in config.
+    for (field, key) in [
+        (&mut inputs.last_pull_at, "last_pull_at"),
+        (&mut inputs.last_sync_at, "last_sync_at"),
+    ] {
+        if field.is_empty() {
+            *field = config::get_str(&cfg, key);
+        }
+    }
+    let path = status_file::status_file_path(&cfg);
+    let status = status_file::StatusFile::build(&inputs, chrono::Utc::now());
+    if let Err(err) = status_file::write(&path, &status) {
+        if status_file::take_failure_log_slot() {
+            let mut runtime = state.lock().expect("runtime lock");
+            push_log(
+                &mut runtime,
+                &format!("Writing status file {} failed: {err}", path.display()),
+                "WARN",
+            );
+        }
+    }
+}
+
+/// `refresh_status_file` on a blocking task, for callers that may hold the runtime lock.
+pub(super) fn spawn_status_file_refresh(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || refresh_status_file(&app));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timezone_hint_respects_dismissal_until_offset_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let mut cfg = config::load_config_from(&path);
+        assert!(cfg["timezone_hint_dismissed_offset"].is_null());
+        config::set_string(&mut cfg, "calendar_timezone_mode", "system".to_string()).unwrap();
+        config::set_number(&mut cfg, "calendar_utc_offset_minutes", 480).unwrap();
+        assert_eq!(
+            timezone_hint(&cfg, 60),
+            Some(json!({"configured": 480, "detected": 60}))
+        );
+
+        config::set_number(&mut cfg, "timezone_hint_dismissed_offset", 60).unwrap();
+        config::save_config_to(&path, &cfg).unwrap();
+        let saved = config::load_config_from(&path);
+        assert_eq!(saved["timezone_hint_dismissed_offset"], 60);
+        assert_eq!(timezone_hint(&saved, 60), None);
+        assert_eq!(
+            timezone_hint(&saved, -300),
+            Some(json!({"configured": 480, "detected": -300}))
+        );
+        assert_eq!(timezone_hint(&saved, 480), None);
+    }
+
+    #[test]
+    fn formats_utc_offsets() {
+        assert_eq!(format_utc_offset(480), "UTC+08:00");
+        assert_eq!(format_utc_offset(-210), "UTC-03:30");
+        assert_eq!(format_utc_offset(0), "UTC+00:00");
+    }
+}
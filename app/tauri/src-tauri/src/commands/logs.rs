@@ -29,3 +29,85 @@ pub fn clear_logs(state: tauri::State<'_, Mutex<RuntimeState>>) -> Result<Value,
     runtime.logs.clear();
     Ok(json!({"ok": true}))
 }
+
+/// Most recent `limit` (default 100) entries of `audit.log`, oldest first.
+#[tauri::command]
+pub fn get_audit_log(payload: Option<Value>) -> Value {
+    let limit = payload
+        .as_ref()
+        .and_then(|p| p.get("limit"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(100)
+        .clamp(1, 1000) as usize;
+    let entries = config::read_audit_log(&config::audit_log_path(), limit);
+    json!({"ok": true, "entries": entries})
+}
+
+/// Entries of `logs` tagged with `session_id`.
+fn session_entries(logs: &[Value], session_id: &str) -> Vec<Value> {
+    logs.iter()
+        .filter(|entry| entry.get("sessionId").and_then(|v| v.as_str()) == Some(session_id))
+        .cloned()
+        .collect()
+}
+
+/// The in-memory log buffer, newest first. `sinceBoot: true` keeps only this session's entries.
+#[tauri::command]
+pub fn get_logs(payload: Option<Value>, state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let since_boot = payload
+        .as_ref()
+        .and_then(|p| p.get("sinceBoot"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let runtime = state.lock().expect("runtime lock");
+    let entries = if since_boot {
+        session_entries(&runtime.logs, &runtime.session_id)
+    } else {
+        runtime.logs.clone()
+    };
+    json!({"ok": true, "sessionId": runtime.session_id, "entries": entries})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_keeps_the_configured_number_of_entries() {
+        let mut runtime = RuntimeState::default();
+        for idx in 0..250 {
+            push_log(&mut runtime, &format!("entry {idx}"), "INFO");
+        }
+        assert_eq!(runtime.logs.len(), DEFAULT_LOG_BUFFER_ENTRIES);
+
+        runtime.log_buffer_entries = crate::log_file::log_buffer_entries(&json!({
+            "log_buffer_entries": 5000
+        }));
+        assert_eq!(runtime.log_buffer_entries, 2000);
+        for idx in 0..2100 {
+            push_log(&mut runtime, &format!("entry {idx}"), "INFO");
+        }
+        assert_eq!(runtime.logs.len(), 2000);
+        assert_eq!(runtime.logs[0]["message"], "entry 2099");
+
+        runtime.log_buffer_entries = 100;
+        push_log(&mut runtime, "after shrink", "INFO");
+        assert_eq!(runtime.logs.len(), 100);
+    }
+
+    #[test]
+    fn session_filter_drops_other_sessions() {
+        let mut runtime = RuntimeState {
+            session_id: "old".to_string(),
+            ..RuntimeState::default()
+        };
+        push_log(&mut runtime, "before restart", "INFO");
+        runtime.session_id = "current".to_string();
+        push_log(&mut runtime, "after restart", "WARN");
+
+        let entries = session_entries(&runtime.logs, "current");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["message"], "after restart");
+        assert_eq!(entries[0]["sessionId"], "current");
+    }
+}
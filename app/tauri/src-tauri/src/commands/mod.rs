@@ -1,11 +1,23 @@
-use crate::calendar::{currency_options, load_calendar_events, CALENDAR_SOURCE_UTC_OFFSET_MINUTES};
+use crate::app_settings::AppSettings;
+use crate::calendar::{
+    currency_options, data_fingerprint, generate_placeholder_events, load_calendar,
+    load_calendar_events, CalendarLoad, CalendarLoadOptions, CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
+};
 use crate::config;
+use crate::file_lock;
 use crate::git_ops;
-use crate::snapshot::{render_next_events, render_past_events};
+use crate::log_file::DEFAULT_LOG_BUFFER_ENTRIES;
+use crate::modal::{Modal, ModalAction, ModalTone};
+use crate::platform::open_target;
+use crate::snapshot::{
+    build_event_index, render_next_events, render_past_events, EventIndex, LastPrints,
+    NextEventsOptions,
+};
 use crate::startup;
 use crate::state::{CalendarCache, RuntimeState};
 use crate::sync_util;
 use crate::time_util::{display_time_from_iso, now_display_time, now_iso_time};
+use crate::version::{cmp_versions, normalize_version_tag};
 use serde_json::{json, Value};
 use std::cmp::Ordering;
 use std::path::{Path, PathBuf};
@@ -16,16 +28,24 @@ use tauri::Emitter;
 use tauri::Manager;
 use tauri_plugin_dialog::DialogExt;
 
+pub(crate) mod benchmark;
+pub(crate) mod custom;
+pub(crate) mod diagnostics;
+pub(crate) mod export;
 pub(crate) mod history;
 pub(crate) mod lifecycle;
 pub(crate) mod logs;
+pub(crate) mod notify;
 pub(crate) mod open;
+pub(crate) mod portable;
 pub(crate) mod pull;
+pub(crate) mod reset;
 pub(crate) mod settings;
 pub(crate) mod snapshot_cmd;
 pub(crate) mod sync;
 pub(crate) mod ui;
 pub(crate) mod update;
+pub(crate) mod verify;
 
 fn now_ms() -> i64 {
     SystemTime::now()
@@ -35,17 +55,23 @@ fn now_ms() -> i64 {
 }
 
 fn push_log(state: &mut RuntimeState, message: &str, level: &str) {
+    if !state.log_filter.allows(level) {
+        return;
+    }
     state.logs.insert(
         0,
         json!({
             "time": now_display_time(),
             "message": message,
             "level": level,
+            "sessionId": state.session_id,
         }),
     );
-    if state.logs.len() > 200 {
-        state.logs.truncate(200);
-    }
+    let cap = match state.log_buffer_entries {
+        0 => DEFAULT_LOG_BUFFER_ENTRIES,
+        entries => entries,
+    };
+    state.logs.truncate(cap);
 }
 
 fn set_object_string(root: &mut Value, key: &str, subkey: &str, value: &str) {
@@ -59,52 +85,14 @@ fn set_object_string(root: &mut Value, key: &str, subkey: &str, value: &str) {
     }
 }
 
-fn normalize_version_tag(tag: &str) -> String {
-    let trimmed = tag.trim();
-    if let Some(rest) = trimmed.strip_prefix('v') {
-        rest.trim().to_string()
-    } else {
-        trimmed.to_string()
-    }
-}
-
-fn parse_version_numbers(v: &str) -> Option<Vec<u32>> {
-    let v = v.trim();
-    if v.is_empty() {
-        return None;
-    }
-    let core = v.split('-').next().unwrap_or(v);
-    let mut nums = vec![];
-    for part in core.split('.') {
-        let part = part.trim();
-        if part.is_empty() {
-            return None;
-        }
-        nums.push(part.parse::<u32>().ok()?);
-    }
-    Some(nums)
-}
-
-fn cmp_versions(a: &str, b: &str) -> Ordering {
-    let a = parse_version_numbers(a).unwrap_or_default();
-    let b = parse_version_numbers(b).unwrap_or_default();
-    let max_len = a.len().max(b.len());
-    for i in 0..max_len {
-        let ai = *a.get(i).unwrap_or(&0);
-        let bi = *b.get(i).unwrap_or(&0);
-        match ai.cmp(&bi) {
-            Ordering::Equal => continue,
-            other => return other,
-        }
-    }
-    Ordering::Equal
-}
-
 fn resolve_calendar_repo_path(cfg: &Value) -> Option<PathBuf> {
-    // Prefer the working copy (user-writable) so pull/sync never touches the install dir.
+    // Prefer the working copy (user-writable) so pull/sync never touches the install dir. In
+    // slim mode its history may live in the appdata root instead (see `history::history_roots`).
     let work_root = config::working_root_dir(cfg);
     if config::path_is_usable_dir(&work_root.join("data").join("Economic_Calendar"))
-        && work_root.join("data").join("event_history_index").exists()
+        && history::history_roots(cfg, &work_root)
+            .iter()
+            .any(|root| history::history_dir_in(root).exists())
     {
         return Some(work_root);
     }
@@ -137,10 +125,13 @@ fn ensure_calendar_loaded(
         if runtime.calendar.status.is_empty() {
             runtime.calendar = CalendarCache {
                 status: "empty".to_string(),
-                last_loaded_at_ms: 0,
-                events: Arc::new(vec![]),
+                ..CalendarCache::default()
             };
         }
+        // A reset is rewriting the data; the next snapshot after it reloads.
+        if runtime.resetting {
+            return;
+        }
         let stale = runtime.calendar.last_loaded_at_ms == 0
             || (now_ms() - runtime.calendar.last_loaded_at_ms) > 90_000;
         let loading = runtime.calendar.status == "loading";
@@ -156,28 +147,106 @@ fn ensure_calendar_loaded(
 
     tauri::async_runtime::spawn(async move {
         let repo_path = resolve_calendar_repo_path(&cfg);
-        let events = repo_path
-            .as_deref()
-            .map(load_calendar_events)
-            .unwrap_or_default();
+        let load = load_calendar_cache(repo_path.as_deref(), &cfg);
+        let never_pulled = config::get_str(&cfg, "last_pull_at").is_empty();
         let runtime_state = app.state::<Mutex<RuntimeState>>();
         let mut runtime = runtime_state.lock().expect("runtime lock");
-        runtime.calendar.last_loaded_at_ms = now_ms();
-        if events.is_empty() {
-            runtime.calendar.status = "empty".to_string();
-            runtime.calendar.events = Arc::new(vec![]);
-            return;
+        apply_calendar_load(&mut runtime, load);
+        if never_pulled {
+            show_placeholders_if_empty(&mut runtime);
         }
-        runtime.calendar.status = "loaded".to_string();
-        runtime.calendar.events = Arc::new(events);
     });
 }
 
+/// Until a pull has ever succeeded, an empty calendar shows placeholder rows instead of a
+/// blank list. The next real load replaces them.
+fn show_placeholders_if_empty(runtime: &mut RuntimeState) {
+    if runtime.calendar.status != "empty" || !runtime.last_pull_at.is_empty() {
+        return;
+    }
+    let events = generate_placeholder_events(PLACEHOLDER_DAYS);
+    runtime.calendar.status = "placeholder".to_string();
+    runtime.calendar.event_index = Arc::new(build_event_index(&events));
+    runtime.calendar.events = Arc::new(events);
+}
+
+/// Days of placeholder rows shown before the first successful pull.
+const PLACEHOLDER_DAYS: i64 = 7;
+
+struct CacheLoad {
+    load: CalendarLoad,
+    last_prints: LastPrints,
+    event_index: EventIndex,
+    fingerprint: String,
+    history_generated_at: String,
+}
+
+/// Loads events plus the derived cache data, outside the runtime lock.
+fn load_calendar_cache(repo_path: Option<&Path>, cfg: &Value) -> CacheLoad {
+    let options = CalendarLoadOptions::from_config(cfg);
+    let load = repo_path
+        .map(|path| load_calendar(path, &options))
+        .unwrap_or_default();
+    let history_generated_at = repo_path
+        .and_then(|path| {
+            history::history_index_generated_at(&history::resolve_history_dir(cfg, path))
+        })
+        .unwrap_or_default();
+    let fingerprint = data_fingerprint(&load.events, &history_generated_at);
+    CacheLoad {
+        last_prints: history::build_last_prints(&load.events),
+        event_index: build_event_index(&load.events),
+        load,
+        fingerprint,
+        history_generated_at,
+    }
+}
+
+/// Stores a fresh load in the calendar cache. Warnings are logged once until they change.
+fn apply_calendar_load(runtime: &mut RuntimeState, cache_load: CacheLoad) {
+    let CacheLoad {
+        load,
+        last_prints,
+        event_index,
+        fingerprint,
+        history_generated_at,
+    } = cache_load;
+    runtime.calendar.last_loaded_at_ms = now_ms();
+    crate::boot::mark(&mut runtime.boot.calendar_loaded_ms);
+    if runtime.calendar.fingerprint != fingerprint {
+        runtime.history_flights.clear();
+    }
+    runtime.calendar.fingerprint = fingerprint;
+    runtime.calendar.history_generated_at = history_generated_at;
+    for warning in &load.warnings {
+        if !runtime.calendar.warnings.contains(warning) {
+            push_log(runtime, warning, "WARN");
+        }
+    }
+    runtime.calendar.warnings = load.warnings;
+    if load.events.is_empty() {
+        runtime.calendar.status = "empty".to_string();
+        runtime.calendar.events = Arc::new(vec![]);
+        runtime.calendar.last_prints = Arc::default();
+        runtime.calendar.event_index = Arc::default();
+        return;
+    }
+    runtime.calendar.status = "loaded".to_string();
+    runtime.calendar.events = Arc::new(load.events);
+    runtime.calendar.last_prints = Arc::new(last_prints);
+    runtime.calendar.event_index = Arc::new(event_index);
+}
+
 fn get_calendar_settings(cfg: &Value) -> (String, i32) {
-    let tz_mode = config::get_str(cfg, "calendar_timezone_mode");
-    let tz_mode = if tz_mode == "utc" { "utc" } else { "system" }.to_string();
-    let minutes = config::get_i32(cfg, "calendar_utc_offset_minutes", 0);
-    (tz_mode, minutes)
+    let settings = AppSettings::from_config(cfg);
+    (
+        settings.timezone_mode().to_string(),
+        settings.calendar_utc_offset_minutes as i32,
+    )
+}
+
+fn min_free_space_mb(cfg: &Value) -> u64 {
+    AppSettings::from_config(cfg).min_free_space_mb.max(0) as u64
 }
 
 fn file_mtime_ms(path: &Path) -> Option<i64> {
@@ -187,26 +256,71 @@ fn file_mtime_ms(path: &Path) -> Option<i64> {
     Some(dur.as_millis() as i64)
 }
 
-fn open_target(target: &str) -> bool {
-    #[cfg(target_os = "windows")]
-    {
-        std::process::Command::new("cmd")
-            .args(["/c", "start", "", target])
-            .spawn()
-            .is_ok()
-    }
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .arg(target)
-            .spawn()
-            .is_ok()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{CalendarEvent, EventSource};
+
+    fn cache_load(events: Vec<CalendarEvent>) -> CacheLoad {
+        CacheLoad {
+            load: CalendarLoad {
+                events,
+                ..CalendarLoad::default()
+            },
+            last_prints: LastPrints::default(),
+            event_index: EventIndex::default(),
+            fingerprint: String::new(),
+            history_generated_at: String::new(),
+        }
     }
-    #[cfg(target_os = "linux")]
-    {
-        std::process::Command::new("xdg-open")
-            .arg(target)
-            .spawn()
-            .is_ok()
+
+    #[test]
+    fn placeholders_only_fill_an_empty_never_pulled_calendar() {
+        let mut runtime = RuntimeState::default();
+        apply_calendar_load(&mut runtime, cache_load(vec![]));
+        show_placeholders_if_empty(&mut runtime);
+        assert_eq!(runtime.calendar.status, "placeholder");
+        assert_eq!(runtime.calendar.events.len(), PLACEHOLDER_DAYS as usize);
+        assert!(runtime
+            .calendar
+            .events
+            .iter()
+            .all(|e| e.source == EventSource::Placeholder && e.currency == "--"));
+
+        let rendered = crate::snapshot::render_next_events(
+            &runtime.calendar.events,
+            &crate::snapshot::NextEventsOptions {
+                currency: "USD",
+                tz_mode: "utc",
+                utc_offset_minutes: 0,
+                source_utc_offset_minutes: 0,
+                no_time_label: "All Day",
+                last_prints: &LastPrints::default(),
+                min_forecast_change_pct: None,
+            },
+        );
+        assert_eq!(rendered.len(), PLACEHOLDER_DAYS as usize);
+        assert!(rendered.iter().all(|row| row["placeholder"] == true));
+
+        // Any real event replaces them, and they do not come back.
+        let mut real = generate_placeholder_events(1);
+        real[0].source = EventSource::Calendar;
+        real[0].event = "CPI m/m".to_string();
+        real[0].currency = "USD".to_string();
+        apply_calendar_load(&mut runtime, cache_load(real));
+        show_placeholders_if_empty(&mut runtime);
+        assert_eq!(runtime.calendar.status, "loaded");
+        assert!(runtime
+            .calendar
+            .events
+            .iter()
+            .all(|e| e.source != EventSource::Placeholder));
+
+        // Once a pull has succeeded an empty load stays empty.
+        runtime.last_pull_at = "2026-03-01T10:00:00+00:00".to_string();
+        apply_calendar_load(&mut runtime, cache_load(vec![]));
+        show_placeholders_if_empty(&mut runtime);
+        assert_eq!(runtime.calendar.status, "empty");
+        assert!(runtime.calendar.events.is_empty());
     }
 }
@@ -0,0 +1,453 @@
+use super::*;
+use crate::calendar::{impact_rank, CalendarEvent};
+use crate::notifications::{fire_due_unless_dnd, muted, Alert, DndProbe, Notifier};
+use crate::snapshot::next_event_id;
+use crate::tray_alert::TrayAlertChange;
+use chrono::Utc;
+use std::collections::HashMap;
+
+fn event_alert(e: &CalendarEvent, event_id: String) -> Alert {
+    let starts = e.dt_utc.with_timezone(&chrono::Local).format("%H:%M");
+    Alert {
+        event_id,
+        title: format!("{} {}", e.currency.to_uppercase(), e.event),
+        body: format!("{} impact, starts at {starts}", e.importance.trim()),
+        event_at: e.dt_utc,
+    }
+}
+
+/// OS notification layer: a WinRT toast with actions on Windows, a plain notification elsewhere.
+struct SystemNotifier {
+    app: tauri::AppHandle,
+    /// `instance_label`, prefixed to every title.
+    label: String,
+}
+
+impl SystemNotifier {
+    fn new(app: &tauri::AppHandle, cfg: &Value) -> Self {
+        Self {
+            app: app.clone(),
+            label: super::ui::instance_label(cfg),
+        }
+    }
+}
+
+#[cfg(not(windows))]
+impl Notifier for SystemNotifier {
+    fn show(&self, alert: &Alert) -> Result<(), String> {
+        use tauri_plugin_notification::NotificationExt;
+        self.app
+            .notification()
+            .builder()
+            .title(super::ui::notification_title(&self.label, &alert.title))
+            .body(&alert.body)
+            .show()
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(windows)]
+impl Notifier for SystemNotifier {
+    fn show(&self, alert: &Alert) -> Result<(), String> {
+        use tauri_winrt_notification::Toast;
+        let app = self.app.clone();
+        let snoozed = alert.clone();
+        let event_id = alert.event_id.clone();
+        Toast::new(&self.app.config().identifier)
+            .title(&super::ui::notification_title(&self.label, &alert.title))
+            .text1(&alert.body)
+            .add_button("Open", "open")
+            .add_button(
+                &format!("Snooze {} min", crate::notifications::SNOOZE_MINUTES),
+                "snooze",
+            )
+            .on_activated(move |action| {
+                if action.as_deref() == Some("snooze") {
+                    snooze_alert(&app, snoozed.clone());
+                } else {
+                    focus_event_row(&app, &event_id);
+                }
+                Ok(())
+            })
+            .show()
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// The OS do-not-disturb state, ignored when `respect_system_dnd` is off, or the app's own mute.
+struct SystemDnd {
+    respect: bool,
+    muted: bool,
+}
+
+impl DndProbe for SystemDnd {
+    fn active(&self) -> bool {
+        self.muted || (self.respect && crate::platform::system_dnd_active())
+    }
+}
+
+#[cfg(windows)]
+fn snooze_alert(app: &tauri::AppHandle, alert: Alert) {
+    let state = app.state::<Mutex<RuntimeState>>();
+    let mut runtime = state.lock().expect("runtime lock");
+    let title = alert.title.clone();
+    if runtime.alerts.snooze(
+        alert,
+        crate::time_util::now_utc(),
+        crate::notifications::SNOOZE_MINUTES,
+    ) {
+        push_log(&mut runtime, &format!("Alert snoozed: {title}"), "INFO");
+    } else {
+        push_log(
+            &mut runtime,
+            &format!("Alert not snoozed (event starts too soon): {title}"),
+            "INFO",
+        );
+    }
+}
+
+/// Raises the window and queues `event_id` for the next snapshot, which scrolls to that row.
+pub(crate) fn focus_event_row(app: &tauri::AppHandle, event_id: &str) {
+    {
+        let state = app.state::<Mutex<RuntimeState>>();
+        let mut runtime = state.lock().expect("runtime lock");
+        runtime.pending_focus_event_id = Some(event_id.to_string());
+    }
+    crate::show_main_window(app);
+}
+
+/// Shows the window on the row with stable id `eventId`, like clicking its notification.
+#[tauri::command]
+pub fn focus_event(app: tauri::AppHandle, payload: Value) -> Value {
+    let event_id = payload
+        .get("eventId")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    if event_id.is_empty() {
+        return json!({"ok": false, "message": "eventId is required"});
+    }
+    focus_event_row(&app, &event_id);
+    json!({"ok": true})
+}
+
+/// Whether `e` passes the alert filters: a timed event at or above `min_rank` for `currency`
+/// (empty or `ALL` matches every currency).
+pub(super) fn alert_qualifies(e: &CalendarEvent, currency: &str, min_rank: u8) -> bool {
+    if !e.time_label.contains(':') || impact_rank(&e.importance) < min_rank {
+        return false;
+    }
+    currency.is_empty() || currency == "ALL" || e.currency.to_uppercase() == currency
+}
+
+/// Queues alerts for upcoming events inside the lead window and shows the ones that are due.
+pub(super) fn tick_event_alerts(app: &tauri::AppHandle) {
+    let cfg = config::load_config();
+    let settings = AppSettings::from_config(&cfg);
+    if !settings.event_alerts_enabled {
+        return;
+    }
+    let lead = chrono::Duration::minutes(settings.event_alert_minutes_before);
+    let min_rank = impact_rank(&settings.event_alert_min_impact).max(1);
+    let state = app.state::<Mutex<RuntimeState>>();
+    let (events, currency) = {
+        let runtime = state.lock().expect("runtime lock");
+        (runtime.calendar.events.clone(), runtime.currency.clone())
+    };
+    let currency = currency.trim().to_uppercase();
+    let now = crate::time_util::now_utc();
+    let mut seen: HashMap<String, i32> = HashMap::new();
+    let mut upcoming = Vec::new();
+    for e in events.iter() {
+        let event_id = next_event_id(e, &mut seen);
+        if e.dt_utc <= now || e.dt_utc > now + lead {
+            continue;
+        }
+        if !alert_qualifies(e, &currency, min_rank) {
+            continue;
+        }
+        upcoming.push(event_alert(e, event_id));
+    }
+
+    let notifier = SystemNotifier::new(app, &cfg);
+    let mut runtime = state.lock().expect("runtime lock");
+    for alert in upcoming {
+        let fire_at = alert.event_at - lead;
+        runtime.alerts.schedule(alert, fire_at);
+    }
+    let dnd = SystemDnd {
+        respect: settings.respect_system_dnd,
+        muted: muted(runtime.notifications_muted_until, now),
+    };
+    let outcome = fire_due_unless_dnd(&mut runtime.alerts, now, &notifier, &dnd);
+    if outcome.shown > 0 {
+        push_log(
+            &mut runtime,
+            &format!("Event alerts shown: {}", outcome.shown),
+            "INFO",
+        );
+    }
+    if outcome.deferred > 0 {
+        push_log(
+            &mut runtime,
+            &format!(
+                "Event alerts deferred while do-not-disturb or mute is on: {}",
+                outcome.deferred
+            ),
+            "DEBUG",
+        );
+    }
+}
+
+/// Shows the real alert for the nearest upcoming event that passes the alert filters, bypassing
+/// the scheduler so its dedupe state is untouched. `cur` overrides the selected currency.
+#[tauri::command]
+pub fn fire_test_alert(
+    app: tauri::AppHandle,
+    payload: Option<Value>,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    let cfg = config::load_config();
+    let min_rank = impact_rank(&AppSettings::from_config(&cfg).event_alert_min_impact).max(1);
+    let (events, selected) = {
+        let runtime = state.lock().expect("runtime lock");
+        (runtime.calendar.events.clone(), runtime.currency.clone())
+    };
+    let currency = payload
+        .as_ref()
+        .and_then(|p| p.get("cur"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .unwrap_or(selected)
+        .trim()
+        .to_uppercase();
+    let now = crate::time_util::now_utc();
+    let mut seen: HashMap<String, i32> = HashMap::new();
+    let nearest = events.iter().find_map(|e| {
+        let event_id = next_event_id(e, &mut seen);
+        (e.dt_utc > now && alert_qualifies(e, &currency, min_rank)).then_some((e, event_id))
+    });
+    let Some((event, event_id)) = nearest else {
+        return json!({"ok": false, "message": "No upcoming event matches the alert filters"});
+    };
+    let alert = event_alert(event, event_id);
+    let notifier = SystemNotifier::new(&app, &cfg);
+    let shown = notifier.show(&alert);
+    let mut runtime = state.lock().expect("runtime lock");
+    let event_json = json!({
+        "eventId": alert.event_id,
+        "title": alert.title,
+        "body": alert.body,
+        "eventAt": alert.event_at.to_rfc3339(),
+    });
+    match shown {
+        Ok(()) => {
+            push_log(
+                &mut runtime,
+                &format!("Test alert shown: {}", alert.title),
+                "INFO",
+            );
+            json!({"ok": true, "event": event_json})
+        }
+        Err(err) => {
+            push_log(&mut runtime, &format!("Test alert failed: {err}"), "ERROR");
+            json!({"ok": false, "message": err, "event": event_json})
+        }
+    }
+}
+
+const TRAY_ICON: &[u8] = include_bytes!("../../icons/tray.png");
+const TRAY_ALERT_ICON: &[u8] = include_bytes!("../../icons/tray-alert.png");
+
+/// Swaps the tray icon while a high-impact event for the selected currency is within
+/// `tray_alert_minutes` (0 disables), flashing the window once on Windows when it first does.
+pub(super) fn tick_tray_alert(app: &tauri::AppHandle) {
+    let minutes = AppSettings::from_config(&config::load_config())
+        .tray_alert_minutes
+        .max(0);
+    let state = app.state::<Mutex<RuntimeState>>();
+    let change = {
+        let mut runtime = state.lock().expect("runtime lock");
+        let now = crate::time_util::now_utc();
+        let imminent = minutes > 0
+            && !muted(
+                runtime.notifications_muted_until,
+                crate::time_util::now_utc(),
+            )
+            && crate::tray_alert::high_impact_within(
+                &runtime.calendar.events,
+                &runtime.currency,
+                now,
+                chrono::Duration::minutes(minutes),
+            );
+        runtime.tray_alert.update(imminent)
+    };
+    let Some(change) = change else {
+        return;
+    };
+    let raised = change == TrayAlertChange::Raise;
+    if let Some(tray) = app.tray_by_id("main") {
+        let bytes = if raised { TRAY_ALERT_ICON } else { TRAY_ICON };
+        if let Ok(icon) = tauri::image::Image::from_bytes(bytes) {
+            let _ = tray.set_icon(Some(icon));
+        }
+    }
+    #[cfg(windows)]
+    if raised {
+        if let Some(win) = app.get_webview_window("main") {
+            let _ = win.request_user_attention(Some(tauri::UserAttentionType::Informational));
+        }
+    }
+}
+
+/// Longest mute `mute_notifications_for` takes in minutes; longer silences belong in settings.
+const MUTE_MAX_MINUTES: i64 = 24 * 60;
+
+/// Tray "Mute notifications" presets, by menu id.
+pub(crate) const TRAY_MUTE_ITEMS: &[(&str, &str)] = &[
+    ("tray:mute:60", "1 hour"),
+    ("tray:mute:240", "4 hours"),
+    ("tray:mute:day", "Rest of day"),
+];
+pub(crate) const TRAY_UNMUTE_ID: &str = "tray:unmute";
+
+/// When a mute requested with `{minutes}` or `{restOfDay: true}` ends. The rest of the day ends
+/// at the next midnight of the calendar's display timezone.
+fn mute_until(
+    payload: &Value,
+    now: chrono::DateTime<Utc>,
+    tz_mode: &str,
+    utc_offset_minutes: i32,
+) -> Result<chrono::DateTime<Utc>, String> {
+    if payload.get("restOfDay").and_then(|v| v.as_bool()) == Some(true) {
+        return Ok(crate::time_util::end_of_display_day(
+            now,
+            tz_mode,
+            utc_offset_minutes,
+        ));
+    }
+    match payload.get("minutes").and_then(|v| v.as_i64()) {
+        Some(minutes) if (1..=MUTE_MAX_MINUTES).contains(&minutes) => {
+            Ok(now + chrono::Duration::minutes(minutes))
+        }
+        _ => Err(format!(
+            "minutes must be between 1 and {MUTE_MAX_MINUTES}, or restOfDay true"
+        )),
+    }
+}
+
+/// Stores the mute, saves it with the session state so a restart honors it, and re-evaluates
+/// the tray icon at once.
+fn set_notifications_muted_until(app: &tauri::AppHandle, until: Option<chrono::DateTime<Utc>>) {
+    {
+        let state = app.state::<Mutex<RuntimeState>>();
+        let mut runtime = state.lock().expect("runtime lock");
+        runtime.notifications_muted_until = until;
+        let message = match until {
+            Some(at) => format!(
+                "Notifications muted until {}",
+                at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+            ),
+            None => "Notifications unmuted".to_string(),
+        };
+        push_log(&mut runtime, &message, "INFO");
+    }
+    super::lifecycle::save_runtime_state(app);
+    tick_tray_alert(app);
+}
+
+fn mute_from_payload(app: &tauri::AppHandle, payload: &Value) -> Value {
+    let (tz_mode, utc_offset_minutes) = get_calendar_settings(&config::load_config());
+    match mute_until(
+        payload,
+        crate::time_util::now_utc(),
+        &tz_mode,
+        utc_offset_minutes,
+    ) {
+        Ok(until) => {
+            set_notifications_muted_until(app, Some(until));
+            json!({"ok": true, "notificationsMutedUntil": until.to_rfc3339()})
+        }
+        Err(message) => json!({"ok": false, "message": message}),
+    }
+}
+
+/// Silences event alerts, update announcements and the tray alert icon for `minutes`, or until
+/// the end of the day with `restOfDay: true`. A new mute replaces the previous one.
+#[tauri::command]
+pub fn mute_notifications_for(app: tauri::AppHandle, payload: Value) -> Value {
+    mute_from_payload(&app, &payload)
+}
+
+#[tauri::command]
+pub fn unmute_notifications(app: tauri::AppHandle) -> Value {
+    set_notifications_muted_until(&app, None);
+    json!({"ok": true})
+}
+
+/// Handles the tray mute submenu; other menu ids are ignored.
+pub(crate) fn handle_tray_mute(app: &tauri::AppHandle, id: &str) {
+    if id == TRAY_UNMUTE_ID {
+        set_notifications_muted_until(app, None);
+        return;
+    }
+    let Some(preset) = id.strip_prefix("tray:mute:") else {
+        return;
+    };
+    let payload = match preset.parse::<i64>() {
+        Ok(minutes) => json!({"minutes": minutes}),
+        Err(_) => json!({"restOfDay": true}),
+    };
+    mute_from_payload(app, &payload);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn mute_expires_at_its_end() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 18, 9, 0, 0).unwrap();
+        let until = mute_until(&json!({"minutes": 60}), now, "utc", 0).unwrap();
+        assert_eq!(until, now + chrono::Duration::hours(1));
+        assert!(muted(Some(until), until - chrono::Duration::seconds(1)));
+        assert!(!muted(Some(until), until));
+        assert!(!muted(None, now));
+        for bad in [json!({"minutes": 0}), json!({"minutes": 2000}), json!({})] {
+            assert!(mute_until(&bad, now, "utc", 0).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn rest_of_day_ends_at_display_midnight() {
+        let rest = json!({"restOfDay": true});
+        let late = Utc.with_ymd_and_hms(2026, 3, 18, 23, 59, 59).unwrap();
+        assert_eq!(
+            mute_until(&rest, late, "utc", 0).unwrap(),
+            Utc.with_ymd_and_hms(2026, 3, 19, 0, 0, 0).unwrap()
+        );
+        // At UTC+8, 16:00 UTC is already midnight on the 19th: the next day is the 20th.
+        let midnight_east = Utc.with_ymd_and_hms(2026, 3, 18, 16, 0, 0).unwrap();
+        assert_eq!(
+            mute_until(&rest, midnight_east, "system", 480).unwrap(),
+            Utc.with_ymd_and_hms(2026, 3, 19, 16, 0, 0).unwrap()
+        );
+        assert_eq!(
+            mute_until(
+                &rest,
+                midnight_east - chrono::Duration::seconds(1),
+                "system",
+                480
+            )
+            .unwrap(),
+            midnight_east
+        );
+        // UTC mode ignores the offset, as the calendar's date column does.
+        assert_eq!(
+            mute_until(&rest, late, "utc", 480).unwrap(),
+            Utc.with_ymd_and_hms(2026, 3, 19, 0, 0, 0).unwrap()
+        );
+    }
+}
@@ -1,4 +1,8 @@
 use super::*;
+use crate::calendar::{locate_event_source, EventSource, FileLimits};
+use crate::snapshot::next_event_id;
+use chrono::Datelike;
+use std::collections::HashMap;
 
 #[tauri::command]
 pub fn open_log() -> Value {
@@ -55,3 +59,63 @@ pub fn open_release_notes(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value
         json!({"ok": false, "message": "failed to open release notes"})
     }
 }
+
+/// Opens the GitHub source file (with a line anchor when the row is found) for a rendered event.
+#[tauri::command]
+pub fn open_event_source(payload: Value, state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let stable_id = payload
+        .get("stableId")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    if stable_id.is_empty() {
+        return json!({"ok": false, "message": "stableId is required"});
+    }
+    let events = {
+        let runtime = state.lock().expect("runtime lock");
+        runtime.calendar.events.clone()
+    };
+    let mut seen: HashMap<String, i32> = HashMap::new();
+    let Some(event) = events
+        .iter()
+        .find(|e| next_event_id(e, &mut seen) == stable_id)
+    else {
+        return json!({"ok": false, "message": "Event not found in the loaded calendar"});
+    };
+    if event.source == EventSource::Custom {
+        return json!({"ok": false, "message": "Custom events have no upstream source"});
+    }
+
+    let cfg = config::load_config();
+    let repo_slug = config::get_str(&cfg, "github_repo");
+    let branch = config::get_str(&cfg, "github_branch");
+    let date_format = crate::time_util::source_date_format(&cfg);
+    let limits = FileLimits::from_config(&cfg);
+    let located = resolve_calendar_repo_path(&cfg)
+        .and_then(|repo| locate_event_source(&repo, event, &date_format, &limits));
+    let (year, file_name, line) = match located {
+        Some((path, year, line)) => (
+            year,
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| format!("{year}_calendar.json")),
+            line,
+        ),
+        None => {
+            let year = event.dt_utc.year();
+            (year, format!("{year}_calendar.json"), None)
+        }
+    };
+    let mut url = format!(
+        "https://github.com/{repo_slug}/blob/{branch}/data/Economic_Calendar/{year}/{file_name}"
+    );
+    if let Some(line) = line {
+        url.push_str(&format!("#L{line}"));
+    }
+    if open_target(&url) {
+        json!({"ok": true, "url": url})
+    } else {
+        json!({"ok": false, "message": "failed to open source url", "url": url})
+    }
+}
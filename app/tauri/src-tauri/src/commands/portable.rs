@@ -0,0 +1,156 @@
+use super::*;
+use crate::portable::{check_writable, convert_data_dir, retire_portable_dir};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Direction {
+    ToPortable,
+    ToInstalled,
+}
+
+impl Direction {
+    fn label(self) -> &'static str {
+        match self {
+            Direction::ToPortable => "portable",
+            Direction::ToInstalled => "installed",
+        }
+    }
+}
+
+/// Copies `src` into `target` on a background thread, emitting `xauusd:portable-progress`, and
+/// asks for a restart once the new data dir is in place.
+fn spawn_conversion(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+    direction: Direction,
+    src: PathBuf,
+    target: PathBuf,
+) -> Value {
+    {
+        let mut runtime = state.lock().expect("runtime lock");
+        if runtime.conversion_active {
+            return json!({"ok": false, "message": "A data conversion is already running"});
+        }
+        runtime.conversion_active = true;
+        push_log(
+            &mut runtime,
+            &format!(
+                "Converting to {} mode: copying {} to {}",
+                direction.label(),
+                src.display(),
+                target.display()
+            ),
+            "INFO",
+        );
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let progress_app = app.clone();
+        let mut progress = |copied: usize, total: usize| {
+            let _ = progress_app.emit(
+                "xauusd:portable-progress",
+                json!({"copied": copied, "total": total}),
+            );
+        };
+        let result = convert_data_dir(&src, &target, &mut progress).and_then(|copied| {
+            if direction == Direction::ToInstalled {
+                let kept = retire_portable_dir(&src)?;
+                return Ok((copied, kept));
+            }
+            Ok((copied, None))
+        });
+
+        let runtime_state = app.state::<Mutex<RuntimeState>>();
+        let mut runtime = runtime_state.lock().expect("runtime lock");
+        runtime.conversion_active = false;
+        match result {
+            Ok((copied, kept)) => {
+                push_log(
+                    &mut runtime,
+                    &format!(
+                        "Converted to {} mode ({copied} files copied)",
+                        direction.label()
+                    ),
+                    "INFO",
+                );
+                if let Some(err) = kept {
+                    push_log(
+                        &mut runtime,
+                        &format!(
+                            "{} could not be removed ({err}); it will be removed on the next start",
+                            src.display()
+                        ),
+                        "WARN",
+                    );
+                }
+                runtime.set_modal(
+                    Modal::new(
+                        format!("convert-{}", direction.label()),
+                        "Restart Required",
+                        format!(
+                            "Your data was copied to {}.\n\nRestart the app to finish switching to {} mode.",
+                            target.display(),
+                            direction.label()
+                        ),
+                        ModalTone::Info,
+                    )
+                    .with_open_path(target.to_string_lossy()),
+                );
+            }
+            Err(err) => {
+                push_log(
+                    &mut runtime,
+                    &format!("Conversion to {} mode failed: {err}", direction.label()),
+                    "ERROR",
+                );
+            }
+        }
+    });
+    json!({"ok": true})
+}
+
+/// Copies the AppData data dir into `user-data/` next to the executable.
+#[tauri::command]
+pub fn convert_to_portable(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    if config::data_dir_overridden() {
+        return json!({"ok": false, "message": "The data folder is pinned by XAUUSD_CALENDAR_AGENT_DATA_DIR"});
+    }
+    if config::portable_data_dir().is_some() {
+        return json!({"ok": false, "message": "Already running in portable mode"});
+    }
+    let Some(target) = config::portable_dir_candidate() else {
+        return json!({"ok": false, "message": "Executable folder not found"});
+    };
+    if let Err(err) = check_writable(&config::install_dir()) {
+        return json!({"ok": false, "message": err});
+    }
+    spawn_conversion(
+        app,
+        state,
+        Direction::ToPortable,
+        config::appdata_dir(),
+        target,
+    )
+}
+
+/// Moves portable data back to AppData and removes `user-data/`.
+#[tauri::command]
+pub fn convert_to_installed(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    if config::data_dir_overridden() {
+        return json!({"ok": false, "message": "The data folder is pinned by XAUUSD_CALENDAR_AGENT_DATA_DIR"});
+    }
+    if config::portable_forced() {
+        return json!({"ok": false, "message": "Portable mode is forced by XAUUSD_CALENDAR_AGENT_PORTABLE=1; unset it first"});
+    }
+    let Some(src) = config::portable_data_dir() else {
+        return json!({"ok": false, "message": "Not running in portable mode"});
+    };
+    let Some(target) = config::installed_data_dir() else {
+        return json!({"ok": false, "message": "No AppData folder is available on this system"});
+    };
+    spawn_conversion(app, state, Direction::ToInstalled, src, target)
+}
@@ -1,40 +1,190 @@
 use super::*;
+use crate::backup;
+use crate::calendar::{
+    inspect_data_layout, load_calendar_events, local_data_issue, local_data_looks_complete,
+    CalendarEvent, EventSource,
+};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 
+struct PullOutcome {
+    sha: String,
+    load: CacheLoad,
+    /// Why the pulled data loaded no events.
+    layout_issue: Option<String>,
+    /// Upstream came back empty and the previous data was kept instead of mirrored over.
+    kept_previous: bool,
+}
+
+type RowKey<'a> = (&'a str, &'a str, DateTime<Utc>);
+type RowNumbers<'a> = (&'a str, &'a str, &'a str);
+
+fn row_numbers(events: &[CalendarEvent]) -> HashMap<RowKey<'_>, RowNumbers<'_>> {
+    events
+        .iter()
+        .filter(|e| e.source == EventSource::Calendar)
+        .map(|e| {
+            (
+                (e.currency.as_str(), e.event.as_str(), e.dt_utc),
+                (e.actual.as_str(), e.forecast.as_str(), e.previous.as_str()),
+            )
+        })
+        .collect()
+}
+
+/// Normalized history ids (as `get_event_history` uses them) of the metrics whose actual,
+/// forecast or previous changed between two loads, sorted. A row that appears or disappears
+/// counts when it carries a number. Reminders and placeholders have no history.
+pub(super) fn history_updated_ids(
+    before: &[CalendarEvent],
+    after: &[CalendarEvent],
+) -> Vec<String> {
+    let old = row_numbers(before);
+    let new = row_numbers(after);
+    let blank = ("", "", "");
+    old.keys()
+        .chain(new.keys())
+        .filter(|key| old.get(*key).unwrap_or(&blank) != new.get(*key).unwrap_or(&blank))
+        .map(|(cur, event, _)| history::history_event_id(cur, event))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Drops the cached history of metrics the new load changes, then stores the load. Returns the
+/// changed ids for `emit_history_updated`.
+fn apply_pull_load(runtime: &mut RuntimeState, load: CacheLoad) -> Vec<String> {
+    let updated = history_updated_ids(&runtime.calendar.events, &load.load.events);
+    for id in &updated {
+        history::forget_event_history(runtime, id);
+    }
+    apply_calendar_load(runtime, load);
+    updated
+}
+
+/// Why a clone at `tmp` loads no events: its layout findings, and whether `branch` still
+/// exists upstream.
+fn describe_empty_pull(tmp: &Path, repo_slug: &str, branch: &str) -> String {
+    let branch_check = match git_ops::ls_remote_head_sha(repo_slug, branch, &AtomicBool::new(false))
+    {
+        Ok(_) => format!("branch {branch} exists"),
+        Err(err) => format!("ls-remote for branch {branch} failed: {err}"),
+    };
+    format!("{}; {branch_check}", inspect_data_layout(tmp).describe())
+}
+
+/// Records a finished pull: timestamps, its log line, the load and, when the pulled data
+/// loaded nothing, the data-layout modal. Returns the changed ids for `emit_history_updated`.
+fn apply_pull_outcome(runtime: &mut RuntimeState, outcome: PullOutcome) -> Vec<String> {
+    runtime.last_pull_error.clear();
+    runtime.last_pull = now_display_time();
+    runtime.last_pull_at = now_iso_time();
+    let short = outcome.sha.chars().take(7).collect::<String>();
+    if outcome.kept_previous {
+        push_log(
+            runtime,
+            &format!("Upstream data empty ({short}); keeping previous"),
+            "WARN",
+        );
+    } else {
+        push_log(runtime, &format!("Pull finished ({short})"), "INFO");
+    }
+
+    let updated = apply_pull_load(runtime, outcome.load);
+    if let Some(issue) = outcome.layout_issue {
+        push_log(
+            runtime,
+            &format!("Pull loaded no calendar events: {issue}"),
+            "ERROR",
+        );
+        runtime.set_modal(Modal::new(
+            "data-layout",
+            "Calendar Data Empty",
+            "Data layout changed upstream — see log for details",
+            ModalTone::Error,
+        ));
+    }
+    updated
+}
+
+/// Tells an open history dialog which metrics to re-fetch.
+fn emit_history_updated(app: &tauri::AppHandle, event_ids: Vec<String>) {
+    if !event_ids.is_empty() {
+        let _ = app.emit("xauusd:history-updated", json!({"eventIds": event_ids}));
+    }
+}
+
+/// Starts a pull unless one is already running. `force` fetches even when upstream is still at
+/// `last_pull_sha`.
 pub(super) fn spawn_pull(
     app: tauri::AppHandle,
     state: tauri::State<'_, Mutex<RuntimeState>>,
     reason: &str,
+    force: bool,
 ) {
     let cfg = config::load_config();
-    let repo_slug = config::get_str(&cfg, "github_repo");
-    let branch = config::get_str(&cfg, "github_branch");
+    let settings = AppSettings::from_config(&cfg);
+    let repo_slug = settings.github_repo;
+    let branch = settings.github_branch;
     let work_data_dir = config::working_data_dir(&cfg);
     let work_root = config::working_root_dir(&cfg);
-    {
+    let free_space_floor_mb = min_free_space_mb(&cfg);
+    let backup_keep = settings.backup_keep_count.max(0) as usize;
+    // Slim mode only differs when the working copy lives outside appdata (temporary path).
+    let slim = settings.history_slim_mode && work_root != config::appdata_dir();
+    let history_dst = if slim {
+        history::history_dir_in(&config::appdata_dir())
+    } else {
+        history::history_dir_in(&work_root)
+    };
+    let pull_cancel = {
         let mut runtime = state.lock().expect("runtime lock");
-        if runtime.pull_active {
+        if runtime.pull_active || runtime.resetting {
             return;
         }
         runtime.pull_active = true;
+        runtime.pull_applying = false;
+        runtime.pull_cancel = Arc::new(AtomicBool::new(false));
         push_log(&mut runtime, reason, "INFO");
-    }
+        runtime.pull_cancel.clone()
+    };
     tauri::async_runtime::spawn_blocking(move || {
-        let result = (|| -> Result<String, String> {
+        let result = (|| -> Result<PullOutcome, String> {
             // Pull only fetches `data/` (no full-repo checkout), and never persists a visible `repo/`
             // directory under `user-data/`.
-            let remote_sha = git_ops::ls_remote_head_sha(&repo_slug, &branch).unwrap_or_default();
-            let last_sha = {
+            let remote_sha =
+                git_ops::ls_remote_head_sha(&repo_slug, &branch, &pull_cancel).unwrap_or_default();
+            let last_sha = if force {
+                String::new()
+            } else {
                 let cfg = config::load_config();
                 config::get_str(&cfg, "last_pull_sha")
             };
-            if !remote_sha.is_empty()
-                && !last_sha.is_empty()
-                && remote_sha == last_sha
-                && work_data_dir.join("Economic_Calendar").exists()
-            {
-                return Ok(remote_sha);
+            if !remote_sha.is_empty() && !last_sha.is_empty() && remote_sha == last_sha {
+                let Some(issue) = local_data_issue(&work_data_dir) else {
+                    return Ok(PullOutcome {
+                        sha: remote_sha,
+                        load: load_calendar_cache(Some(&work_root), &cfg),
+                        layout_issue: None,
+                        kept_previous: false,
+                    });
+                };
+                let runtime_state = app.state::<Mutex<RuntimeState>>();
+                let mut runtime = runtime_state.lock().expect("runtime lock");
+                push_log(
+                    &mut runtime,
+                    &format!("Remote unchanged but local data incomplete ({issue}); pulling again"),
+                    "WARN",
+                );
             }
 
+            // Checked before the temp clone exists: both the clone and the mirror need room.
+            sync_util::ensure_min_free_space("pull", &std::env::temp_dir(), free_space_floor_mb)?;
+            sync_util::ensure_min_free_space("pull", &work_data_dir, free_space_floor_mb)?;
+            if slim {
+                sync_util::ensure_min_free_space("pull", &history_dst, free_space_floor_mb)?;
+            }
             let tmp = std::env::temp_dir().join(format!(
                 "xauusd-calendar-agent-pull-{}-{}",
                 std::process::id(),
@@ -43,55 +193,463 @@ pub(super) fn spawn_pull(
             if tmp.exists() {
                 let _ = std::fs::remove_dir_all(&tmp);
             }
-            let sha = git_ops::clone_sparse_data(&tmp, &repo_slug, &branch)?;
+            // A failed or cancelled clone leaves a partial checkout; never keep it around.
+            let sha = git_ops::clone_sparse_data(&tmp, &repo_slug, &branch, &pull_cancel)
+                .inspect_err(|_| {
+                    let _ = std::fs::remove_dir_all(&tmp);
+                })?;
+            // A bad upstream push that empties `data/` must not wipe data that loaded before.
+            let had_events = !app
+                .state::<Mutex<RuntimeState>>()
+                .lock()
+                .expect("runtime lock")
+                .calendar
+                .events
+                .is_empty();
+            let upstream_options = CalendarLoadOptions {
+                custom_events: None,
+                ..CalendarLoadOptions::from_config(&cfg)
+            };
+            if had_events && load_calendar_events(&tmp, &upstream_options).is_empty() {
+                let layout_issue = describe_empty_pull(&tmp, &repo_slug, &branch);
+                let _ = std::fs::remove_dir_all(&tmp);
+                return Ok(PullOutcome {
+                    sha,
+                    load: load_calendar_cache(Some(&work_root), &cfg),
+                    layout_issue: Some(layout_issue),
+                    kept_previous: true,
+                });
+            }
+            // Past this point the working data changes, so a cancel no longer applies.
+            {
+                let runtime_state = app.state::<Mutex<RuntimeState>>();
+                let mut runtime = runtime_state.lock().expect("runtime lock");
+                if pull_cancel.load(AtomicOrdering::SeqCst) {
+                    drop(runtime);
+                    let _ = std::fs::remove_dir_all(&tmp);
+                    return Err("cancelled".to_string());
+                }
+                runtime.pull_applying = true;
+            }
             let src = tmp.join("data");
             let dst = work_data_dir;
+            if src.exists() && backup_keep > 0 && local_data_looks_complete(&dst) {
+                let backed_up = backup::create_backup(
+                    &dst,
+                    &backup::backups_dir(),
+                    backup_keep,
+                    crate::time_util::now_utc(),
+                );
+                if let Err(err) = backed_up {
+                    let runtime_state = app.state::<Mutex<RuntimeState>>();
+                    let mut runtime = runtime_state.lock().expect("runtime lock");
+                    push_log(
+                        &mut runtime,
+                        &format!("Pre-pull backup failed: {err}"),
+                        "WARN",
+                    );
+                }
+            }
             if src.exists() {
-                let _ = sync_util::mirror_sync(&src, &dst);
+                // Hold the history lock so lookups never read a half-copied ndjson.
+                let history_ndjson = history_dst.join(history::HISTORY_NDJSON);
+                let Some(_guard) =
+                    file_lock::lock_exclusive(&history_ndjson, file_lock::WRITE_WAIT)
+                else {
+                    let _ = std::fs::remove_dir_all(&tmp);
+                    return Err("event history is being updated; try again".to_string());
+                };
+                let cancel = AtomicBool::new(false);
+                if slim {
+                    // Calendar files go to the temporary path, history to appdata. A full copy
+                    // left there from before slim mode is dropped once appdata has its own.
+                    let calendar = "Economic_Calendar";
+                    let _ =
+                        sync_util::mirror_sync(&src.join(calendar), &dst.join(calendar), &cancel);
+                    let _ = sync_util::mirror_sync(
+                        &src.join("event_history_index"),
+                        &history_dst,
+                        &cancel,
+                    );
+                    if history_ndjson.exists() {
+                        let _ = std::fs::remove_dir_all(dst.join("event_history_index"));
+                    }
+                } else {
+                    let _ = sync_util::mirror_sync(&src, &dst, &cancel);
+                }
             }
+            let load = load_calendar_cache(Some(&work_root), &cfg);
+            // A pull that loads nothing usually means upstream moved the files; say where.
+            let layout_issue = load
+                .load
+                .events
+                .is_empty()
+                .then(|| describe_empty_pull(&tmp, &repo_slug, &branch));
             let _ = std::fs::remove_dir_all(&tmp);
-            Ok(sha)
+            Ok(PullOutcome {
+                sha,
+                load,
+                layout_issue,
+                kept_previous: false,
+            })
         })();
         let runtime_state = app.state::<Mutex<RuntimeState>>();
         let mut runtime = runtime_state.lock().expect("runtime lock");
+        // Cleared only here, once the temp clone is gone, so a new pull never starts while this
+        // worker can still touch the disk.
         runtime.pull_active = false;
+        runtime.pull_applying = false;
+        if pull_cancel.load(AtomicOrdering::SeqCst) {
+            // `cancel_pull` already logged it; drop whatever came back.
+            return;
+        }
         match result {
-            Ok(sha) => {
-                let last_pull_at = now_iso_time();
-                runtime.last_pull = now_display_time();
-                runtime.last_pull_at = last_pull_at.clone();
-                let short = sha.chars().take(7).collect::<String>();
-                push_log(&mut runtime, &format!("Pull finished ({short})"), "INFO");
-
-                let events = load_calendar_events(&work_root);
-                runtime.calendar.last_loaded_at_ms = now_ms();
-                if events.is_empty() {
-                    runtime.calendar.status = "empty".to_string();
-                    runtime.calendar.events = Arc::new(vec![]);
-                } else {
-                    runtime.calendar.status = "loaded".to_string();
-                    runtime.calendar.events = Arc::new(events);
-                }
+            Ok(outcome) => {
+                let sha = outcome.sha.clone();
+                let kept_previous = outcome.kept_previous;
+                let updated = apply_pull_outcome(&mut runtime, outcome);
+                let last_pull_at = runtime.last_pull_at.clone();
 
                 // Persist last pull.
                 drop(runtime);
                 let mut cfg = config::load_config();
-                let _ = config::set_string(&mut cfg, "last_pull_at", last_pull_at.clone());
-                let _ = config::set_string(&mut cfg, "last_pull_sha", sha.clone());
+                let _ = config::set_string(&mut cfg, "last_pull_at", last_pull_at);
+                // Left unchanged after an empty upstream so the next pull tries again.
+                if !kept_previous {
+                    let _ = config::set_string(&mut cfg, "last_pull_sha", sha);
+                }
                 let _ = config::save_config(&cfg);
+                emit_history_updated(&app, updated);
             }
             Err(err) => {
                 push_log(&mut runtime, &format!("Pull failed: {err}"), "ERROR");
+                runtime.last_pull_error = err;
             }
         }
+        super::lifecycle::spawn_status_file_refresh(&app);
     });
 }
 
+/// Stops a running pull: kills its git child. The pull stays active until its worker has
+/// removed the temp clone, so the next pull cannot start underneath it. Refused once the pull
+/// is mirroring into the working data, so a cancel never leaves a half-updated copy.
+#[tauri::command]
+pub fn cancel_pull(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let mut runtime = state.lock().expect("runtime lock");
+    if !runtime.pull_active {
+        return json!({"ok": false, "message": "No pull running"});
+    }
+    if runtime.pull_applying {
+        return json!({
+            "ok": false,
+            "message": "The pull is already updating the working data; it will finish shortly"
+        });
+    }
+    if runtime.pull_cancel.swap(true, AtomicOrdering::SeqCst) {
+        return json!({"ok": false, "message": "The pull is already stopping"});
+    }
+    push_log(&mut runtime, "Pull cancelled", "INFO");
+    json!({"ok": true})
+}
+
+/// Pre-pull backups of the working data, newest first.
+#[tauri::command]
+pub fn list_backups() -> Value {
+    json!({"ok": true, "backups": backup::list_backups(&backup::backups_dir())})
+}
+
+/// Puts the working data back as it was before the last pull, from the newest backup, and
+/// reloads the calendar. `last_pull_sha` is cleared so the next pull fetches again.
+#[tauri::command(async)]
+pub fn rollback_last_pull(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    let Some(latest) = backup::list_backups(&backup::backups_dir())
+        .into_iter()
+        .next()
+    else {
+        return json!({"ok": false, "message": "No pre-pull backup to roll back to"});
+    };
+    let cfg = config::load_config();
+    let work_root = config::working_root_dir(&cfg);
+    let work_data_dir = config::working_data_dir(&cfg);
+    {
+        let mut runtime = state.lock().expect("runtime lock");
+        if runtime.pull_active || runtime.sync_active || runtime.resetting {
+            return json!({"ok": false, "message": "Wait for the running pull or sync to finish"});
+        }
+        // Blocks pulls, syncs and resets while the data is rewritten; nothing to cancel.
+        runtime.pull_active = true;
+        runtime.pull_applying = true;
+    }
+    let restored = {
+        let history_ndjson = history::history_dir_in(&work_root).join(history::HISTORY_NDJSON);
+        match file_lock::lock_exclusive(&history_ndjson, file_lock::WRITE_WAIT) {
+            Some(_guard) => backup::restore_backup(&latest.path, &work_data_dir),
+            None => Err("event history is being updated; try again".to_string()),
+        }
+    };
+    let load = restored
+        .is_ok()
+        .then(|| load_calendar_cache(Some(&work_root), &cfg));
+    if restored.is_ok() {
+        let _ =
+            config::update_config(|cfg| config::set_string(cfg, "last_pull_sha", String::new()));
+    }
+    let mut runtime = state.lock().expect("runtime lock");
+    runtime.pull_active = false;
+    runtime.pull_applying = false;
+    match restored {
+        Ok(files) => {
+            let updated = load
+                .map(|load| apply_pull_load(&mut runtime, load))
+                .unwrap_or_default();
+            push_log(
+                &mut runtime,
+                &format!("Rolled back to {} ({files} files)", latest.name),
+                "INFO",
+            );
+            drop(runtime);
+            emit_history_updated(&app, updated);
+            super::lifecycle::spawn_status_file_refresh(&app);
+            json!({"ok": true, "backup": latest, "files": files})
+        }
+        Err(err) => {
+            push_log(&mut runtime, &format!("Rollback failed: {err}"), "ERROR");
+            json!({"ok": false, "message": err})
+        }
+    }
+}
+
 #[tauri::command]
 pub fn pull_now(
     app: tauri::AppHandle,
     state: tauri::State<'_, Mutex<RuntimeState>>,
 ) -> Result<Value, String> {
-    spawn_pull(app, state, "Manual pull started");
+    spawn_pull(app, state, "Manual pull started", false);
     Ok(json!({"ok": true}))
 }
+
+/// How far ahead `schedule_pull` accepts a time.
+const SCHEDULE_PULL_MAX_AHEAD_DAYS: i64 = 7;
+
+/// `atIso` as a schedulable time: RFC 3339, in the future, at most a week out.
+fn parse_scheduled_pull_at(at_iso: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let at = DateTime::parse_from_rfc3339(at_iso.trim())
+        .map_err(|_| format!("Not an ISO 8601 time: {}", at_iso.trim()))?
+        .with_timezone(&Utc);
+    if at <= now {
+        return Err("Scheduled pull time is in the past".to_string());
+    }
+    if at - now > chrono::Duration::days(SCHEDULE_PULL_MAX_AHEAD_DAYS) {
+        return Err(format!(
+            "Scheduled pull must be within {SCHEDULE_PULL_MAX_AHEAD_DAYS} days"
+        ));
+    }
+    Ok(at)
+}
+
+/// Clears and returns the scheduled pull once it is due. While another pull, or a reset, is
+/// running it stays queued and fires when that finishes.
+pub(super) fn take_due_scheduled_pull(
+    runtime: &mut RuntimeState,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if runtime.pull_active || runtime.resetting {
+        return None;
+    }
+    let at = runtime.scheduled_pull_at.filter(|at| *at <= now)?;
+    runtime.scheduled_pull_at = None;
+    Some(at)
+}
+
+/// Queues one forced pull at `atIso` (e.g. just after a release), replacing any earlier one.
+/// It runs on top of the hourly timer, not instead of it.
+#[tauri::command]
+pub fn schedule_pull(payload: Value, state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let at_iso = payload.get("atIso").and_then(|v| v.as_str()).unwrap_or("");
+    let at = match parse_scheduled_pull_at(at_iso, crate::time_util::now_utc()) {
+        Ok(at) => at,
+        Err(message) => return json!({"ok": false, "message": message}),
+    };
+    let mut runtime = state.lock().expect("runtime lock");
+    runtime.scheduled_pull_at = Some(at);
+    push_log(
+        &mut runtime,
+        &format!("Pull scheduled for {}", at.format("%Y-%m-%d %H:%M:%S UTC")),
+        "INFO",
+    );
+    json!({"ok": true, "scheduledPullAt": at.to_rfc3339()})
+}
+
+#[tauri::command]
+pub fn cancel_scheduled_pull(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let mut runtime = state.lock().expect("runtime lock");
+    let cancelled = runtime.scheduled_pull_at.take();
+    if let Some(at) = cancelled {
+        push_log(
+            &mut runtime,
+            &format!(
+                "Scheduled pull for {} cancelled",
+                at.format("%Y-%m-%d %H:%M:%S UTC")
+            ),
+            "INFO",
+        );
+    }
+    json!({"ok": true, "cancelled": cancelled.is_some()})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(cur: &str, name: &str, day: u32, actual: &str) -> CalendarEvent {
+        CalendarEvent {
+            dt_utc: Utc.with_ymd_and_hms(2026, 3, day, 13, 30, 0).unwrap(),
+            time_label: "13:30".to_string(),
+            event: name.to_string(),
+            currency: cur.to_string(),
+            importance: "high".to_string(),
+            actual: actual.to_string(),
+            forecast: "0.3%".to_string(),
+            previous: "0.2%".to_string(),
+            time_confirmed: true,
+            categories: vec![],
+            source: EventSource::Calendar,
+        }
+    }
+
+    #[test]
+    fn scheduled_pull_fires_once_when_due() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 18, 13, 20, 0).unwrap();
+        let at = parse_scheduled_pull_at("2026-03-18T21:25:00+08:00", now).unwrap();
+        assert_eq!(at, Utc.with_ymd_and_hms(2026, 3, 18, 13, 25, 0).unwrap());
+        assert!(parse_scheduled_pull_at("2026-03-18T13:19:59Z", now).is_err());
+        assert!(parse_scheduled_pull_at("2026-03-26T13:20:00Z", now).is_err());
+        assert!(parse_scheduled_pull_at("13:25", now).is_err());
+
+        let mut runtime = RuntimeState {
+            scheduled_pull_at: Some(at),
+            ..RuntimeState::default()
+        };
+        assert_eq!(take_due_scheduled_pull(&mut runtime, now), None);
+        let due = at + chrono::Duration::seconds(3);
+        runtime.pull_active = true;
+        assert_eq!(take_due_scheduled_pull(&mut runtime, due), None);
+        runtime.pull_active = false;
+        assert_eq!(take_due_scheduled_pull(&mut runtime, due), Some(at));
+        assert_eq!(runtime.scheduled_pull_at, None);
+        assert_eq!(take_due_scheduled_pull(&mut runtime, due), None);
+    }
+
+    #[test]
+    fn changed_rows_map_to_history_ids() {
+        let before = vec![
+            event("USD", "CPI m/m (Feb)", 5, ""),
+            event("USD", "Non-Farm Employment Change", 6, "151K"),
+            event("EUR", "German Flash Manufacturing PMI", 6, "46.1"),
+        ];
+        let mut after = before.clone();
+        after[0].actual = "0.4%".to_string();
+        after.push(event("usd", "Core PCE Price Index m/m", 7, ""));
+        let mut reminder = event("USD", "Custom Reminder", 7, "1");
+        reminder.source = EventSource::Custom;
+        after.push(reminder);
+
+        let ids = history_updated_ids(&before, &after);
+        assert_eq!(
+            ids,
+            vec![
+                "usd::core pce price index m/m::m/m".to_string(),
+                "usd::cpi m/m::m/m".to_string(),
+            ]
+        );
+        // The id is the one `get_event_history` caches the dialog's lookup under.
+        assert_eq!(ids[1], history::history_event_id("USD", "CPI m/m (Feb)"));
+        assert!(history_updated_ids(&after, &after).is_empty());
+    }
+
+    fn cache_load(events: Vec<CalendarEvent>) -> CacheLoad {
+        CacheLoad {
+            last_prints: LastPrints::default(),
+            event_index: build_event_index(&events),
+            load: CalendarLoad {
+                events,
+                ..CalendarLoad::default()
+            },
+            fingerprint: String::new(),
+            history_generated_at: String::new(),
+        }
+    }
+
+    fn log_messages(runtime: &RuntimeState) -> Vec<String> {
+        runtime
+            .logs
+            .iter()
+            .map(|entry| format!("{} {}", entry["level"], entry["message"]))
+            .collect()
+    }
+
+    #[test]
+    fn kept_previous_pull_still_reports_the_layout() {
+        let previous = vec![event("USD", "CPI m/m", 10, "0.4%")];
+        let mut runtime = RuntimeState::default();
+        apply_calendar_load(&mut runtime, cache_load(previous.clone()));
+
+        let updated = apply_pull_outcome(
+            &mut runtime,
+            PullOutcome {
+                sha: "abcdef123456".to_string(),
+                load: cache_load(previous),
+                layout_issue: Some("data/Economic_Calendar missing".to_string()),
+                kept_previous: true,
+            },
+        );
+        assert!(updated.is_empty());
+        assert_eq!(runtime.calendar.events.len(), 1);
+        assert_eq!(
+            runtime.modal.as_ref().map(|m| m.id.as_str()),
+            Some("data-layout")
+        );
+        let logs = log_messages(&runtime);
+        assert!(logs
+            .iter()
+            .any(|l| l.contains("WARN") && l.contains("Upstream data empty (abcdef1)")));
+        assert!(logs
+            .iter()
+            .any(|l| l.contains("ERROR") && l.contains("data/Economic_Calendar missing")));
+    }
+
+    #[test]
+    fn applied_pull_replaces_the_load_without_a_modal() {
+        let mut runtime = RuntimeState {
+            last_pull_error: "network down".to_string(),
+            ..RuntimeState::default()
+        };
+        apply_calendar_load(
+            &mut runtime,
+            cache_load(vec![event("USD", "CPI m/m", 10, "")]),
+        );
+
+        let updated = apply_pull_outcome(
+            &mut runtime,
+            PullOutcome {
+                sha: "1234567890".to_string(),
+                load: cache_load(vec![event("USD", "CPI m/m", 10, "0.4%")]),
+                layout_issue: None,
+                kept_previous: false,
+            },
+        );
+        assert_eq!(updated, vec![history::history_event_id("USD", "CPI m/m")]);
+        assert_eq!(runtime.calendar.events[0].actual, "0.4%");
+        assert!(runtime.modal.is_none());
+        assert!(runtime.last_pull_error.is_empty());
+        assert!(!runtime.last_pull_at.is_empty());
+        assert_eq!(
+            log_messages(&runtime),
+            ["\"INFO\" \"Pull finished (1234567)\""]
+        );
+    }
+}
@@ -0,0 +1,295 @@
+use super::*;
+use std::sync::atomic::Ordering as AtomicOrdering;
+
+/// `reset_working_data` only runs with `{"confirm": RESET_CONFIRM_TOKEN}`.
+pub const RESET_CONFIRM_TOKEN: &str = "RESET";
+
+/// How long a forced reset waits for a running pull or sync to finish.
+const RESET_WAIT: Duration = Duration::from_secs(30);
+
+/// Outcome of one reset step, reported back and logged.
+struct ResetStep {
+    step: &'static str,
+    ok: bool,
+    message: String,
+}
+
+impl ResetStep {
+    fn new(step: &'static str, result: Result<String, String>) -> Self {
+        let (ok, message) = match result {
+            Ok(message) => (true, message),
+            Err(message) => (false, message),
+        };
+        Self { step, ok, message }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({"step": self.step, "ok": self.ok, "message": self.message})
+    }
+}
+
+/// Why a reset cannot start now, if it cannot.
+fn reset_blocked(pull_active: bool, sync_active: bool, force: bool) -> Option<String> {
+    let busy = match (pull_active, sync_active) {
+        (true, true) => "A pull and a sync are",
+        (true, false) => "A pull is",
+        (false, true) => "A sync is",
+        (false, false) => return None,
+    };
+    (!force).then(|| format!("{busy} running; retry when it finishes or pass force"))
+}
+
+/// Refuses anything that does not look like a working `data` dir: relative paths, filesystem
+/// roots, the home dir, and the bundled seed in `install_dir`.
+fn check_reset_target(dir: &Path, install_dir: &Path) -> Result<(), String> {
+    let refuse = |why: &str| Err(format!("Refusing to reset {}: {why}", dir.display()));
+    if !dir.is_absolute() {
+        return refuse("not an absolute path");
+    }
+    if dir.file_name().is_none_or(|name| name != "data") {
+        return refuse("not a data folder");
+    }
+    if dir.parent().and_then(Path::parent).is_none() {
+        return refuse("too close to the filesystem root");
+    }
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    if home.is_some_and(|home| Path::new(&home) == dir) {
+        return refuse("it is the home folder");
+    }
+    if install_dir.join("data") == dir || install_dir.starts_with(dir) {
+        return refuse("it holds the bundled seed data");
+    }
+    Ok(())
+}
+
+/// Deletes everything inside `dir`, keeping `dir` itself. Returns the number of entries removed.
+fn clear_dir_contents(dir: &Path) -> Result<usize, String> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let is_dir = std::fs::symlink_metadata(&path)
+            .map(|meta| meta.is_dir())
+            .unwrap_or(false);
+        let result = if is_dir {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        result.map_err(|e| format!("{}: {e}", path.display()))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Copies the bundled `data/` from `install_dir` into `data_dir`, when the build ships one.
+fn seed_working_data(install_dir: &Path, data_dir: &Path) -> Result<String, String> {
+    let seed = install_dir.join("data");
+    if !config::path_is_usable_dir(&seed.join("Economic_Calendar")) {
+        return Ok("No bundled seed data; the pull will download everything".to_string());
+    }
+    let result =
+        sync_util::mirror_sync(&seed, data_dir, &std::sync::atomic::AtomicBool::new(false))?;
+    Ok(format!(
+        "Seeded {} files from the install folder",
+        result.copied
+    ))
+}
+
+/// The file-system half of a reset: check, delete, re-seed. Stops at the first failure.
+fn reset_data_dir(data_dir: &Path, install_dir: &Path) -> Vec<ResetStep> {
+    let mut steps = vec![];
+    let check = check_reset_target(data_dir, install_dir)
+        .map(|_| format!("{} is a working data folder", data_dir.display()));
+    steps.push(ResetStep::new("check", check));
+    if !steps[0].ok {
+        return steps;
+    }
+    let delete = clear_dir_contents(data_dir)
+        .map(|removed| format!("Removed {removed} entries from {}", data_dir.display()));
+    let deleted = delete.is_ok();
+    steps.push(ResetStep::new("delete", delete));
+    if deleted {
+        steps.push(ResetStep::new(
+            "seed",
+            seed_working_data(install_dir, data_dir),
+        ));
+    }
+    steps
+}
+
+/// Waits for a running pull/sync to finish, asking the sync to stop first.
+fn wait_for_idle(state: &tauri::State<'_, Mutex<RuntimeState>>) -> Result<String, String> {
+    let started = std::time::Instant::now();
+    loop {
+        {
+            let runtime = state.lock().expect("runtime lock");
+            if !runtime.pull_active && !runtime.sync_active {
+                return Ok(format!(
+                    "No pull or sync running (waited {}s)",
+                    started.elapsed().as_secs()
+                ));
+            }
+            runtime.sync_cancel.store(true, AtomicOrdering::SeqCst);
+        }
+        if started.elapsed() >= RESET_WAIT {
+            return Err(format!(
+                "A pull or sync is still running after {}s",
+                RESET_WAIT.as_secs()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+}
+
+/// Wipes the working data, re-seeds it from the install dir and starts a fresh pull. Needs
+/// `{"confirm": "RESET"}`; `force: true` waits out a running pull/sync instead of refusing.
+#[tauri::command(async)]
+pub fn reset_working_data(
+    app: tauri::AppHandle,
+    payload: Value,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    if payload.get("confirm").and_then(|v| v.as_str()) != Some(RESET_CONFIRM_TOKEN) {
+        return json!({
+            "ok": false,
+            "message": format!("Pass confirm: \"{RESET_CONFIRM_TOKEN}\" to reset the working data")
+        });
+    }
+    let force = payload
+        .get("force")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    {
+        let mut runtime = state.lock().expect("runtime lock");
+        if runtime.resetting {
+            return json!({"ok": false, "message": "A reset is already running"});
+        }
+        if let Some(reason) = reset_blocked(runtime.pull_active, runtime.sync_active, force) {
+            return json!({"ok": false, "message": reason});
+        }
+        runtime.resetting = true;
+        push_log(&mut runtime, "Resetting working data", "INFO");
+    }
+
+    let cfg = config::load_config();
+    let mut steps = vec![ResetStep::new("wait", wait_for_idle(&state))];
+    if steps[0].ok {
+        steps.extend(reset_data_dir(
+            &config::working_data_dir(&cfg),
+            &config::install_dir(),
+        ));
+    }
+    let files_ok = steps.iter().all(|s| s.ok);
+    if files_ok {
+        let mut cfg = cfg;
+        let cleared = config::set_string(&mut cfg, "last_pull_sha", String::new())
+            .and_then(|_| config::save_config(&cfg))
+            .map(|_| "Cleared last_pull_sha".to_string());
+        steps.push(ResetStep::new("clear_sha", cleared));
+    }
+
+    {
+        let mut runtime = state.lock().expect("runtime lock");
+        runtime.resetting = false;
+        // Whatever happened on disk, the cached events no longer describe it.
+        runtime.calendar = CalendarCache::default();
+        runtime.history_flights.clear();
+        for step in &steps {
+            let level = if step.ok { "INFO" } else { "ERROR" };
+            push_log(
+                &mut runtime,
+                &format!("Reset {}: {}", step.step, step.message),
+                level,
+            );
+        }
+    }
+
+    let ok = steps.iter().all(|s| s.ok);
+    if ok {
+        pull::spawn_pull(app, state, "Pull started after reset", false);
+        steps.push(ResetStep::new("pull", Ok("Fresh pull started".to_string())));
+    }
+    json!({
+        "ok": ok,
+        "steps": steps.iter().map(ResetStep::to_json).collect::<Vec<_>>(),
+        "message": if ok { "Working data reset".to_string() } else { "Reset did not complete; see steps".to_string() },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "[]").unwrap();
+    }
+
+    #[test]
+    fn active_work_blocks_a_reset_unless_forced() {
+        assert_eq!(reset_blocked(false, false, false), None);
+        let err = reset_blocked(true, false, false).unwrap_or_default();
+        assert!(err.starts_with("A pull is running"), "{err}");
+        assert!(reset_blocked(false, true, false).is_some());
+        assert_eq!(reset_blocked(true, true, true), None);
+    }
+
+    #[test]
+    fn reset_wipes_and_reseeds_the_working_data() {
+        let appdata = tempfile::tempdir().unwrap();
+        let install = tempfile::tempdir().unwrap();
+        let data = appdata.path().join("data");
+        write(&data.join("Economic_Calendar/2026/2026_calendar.json"));
+        write(&data.join("event_history_index/broken.ndjson"));
+        write(&data.join("stray.tmp"));
+
+        let steps = reset_data_dir(&data, install.path());
+        let names: Vec<_> = steps.iter().map(|s| (s.step, s.ok)).collect();
+        assert_eq!(
+            names,
+            vec![("check", true), ("delete", true), ("seed", true)]
+        );
+        assert!(steps[1].message.starts_with("Removed 3 entries"));
+        assert!(data.is_dir());
+        assert_eq!(std::fs::read_dir(&data).unwrap().count(), 0);
+
+        write(
+            &install
+                .path()
+                .join("data/Economic_Calendar/2026/2026_calendar.json"),
+        );
+        write(&data.join("stray.tmp"));
+        let steps = reset_data_dir(&data, install.path());
+        assert_eq!(steps[2].message, "Seeded 1 files from the install folder");
+        assert!(data
+            .join("Economic_Calendar/2026/2026_calendar.json")
+            .exists());
+        assert!(!data.join("stray.tmp").exists());
+    }
+
+    #[test]
+    fn reset_refuses_paths_that_are_not_working_data() {
+        let install = tempfile::tempdir().unwrap();
+        let appdata = tempfile::tempdir().unwrap();
+        write(&install.path().join("data/keep.json"));
+        for bad in [
+            PathBuf::from("data"),
+            PathBuf::from("/data"),
+            appdata.path().join("logs"),
+            install.path().join("data"),
+        ] {
+            let steps = reset_data_dir(&bad, install.path());
+            assert_eq!(steps.len(), 1, "{}", bad.display());
+            assert!(!steps[0].ok);
+            assert!(
+                steps[0].message.starts_with("Refusing"),
+                "{}",
+                steps[0].message
+            );
+        }
+        assert!(install.path().join("data/keep.json").exists());
+    }
+}
@@ -1,57 +1,212 @@
 use super::*;
+use crate::calendar::impact_rank;
+use crate::log_file::{log_buffer_entries, LogFilter, LogLevel};
+use crate::time_util::{
+    format_utc_offset, parse_source_dt_to_utc, set_date_format, source_date_format,
+    timezone_offsets, valid_date_format, DateFormat,
+};
+use serde_json::Map;
 
 #[tauri::command]
-pub fn get_settings(_state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
-    let cfg = config::load_config();
-    let autostart_launch_mode = {
-        let v = config::get_str(&cfg, "autostart_launch_mode");
-        if v == "show" { "show" } else { "tray" }.to_string()
-    };
-    let close_behavior = {
-        let v = config::get_str(&cfg, "close_behavior");
-        if v == "tray" { "tray" } else { "exit" }.to_string()
-    };
-    let theme = {
-        let v = config::get_str(&cfg, "theme_preference");
-        if v == "dark" || v == "light" {
-            v
-        } else {
-            "system".to_string()
-        }
-    };
-    let calendar_timezone_mode = {
-        let v = config::get_str(&cfg, "calendar_timezone_mode");
-        if v == "utc" { "utc" } else { "system" }.to_string()
+pub fn get_settings(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let (tray_supported, install_dir_writable, rate_limit, scopes) = {
+        let runtime = state.lock().expect("runtime lock");
+        (
+            runtime.tray_supported,
+            runtime.install_dir_writable,
+            runtime.github_rate_limit.clone(),
+            runtime.github_token_scopes.clone(),
+        )
     };
-    json!({
-        "autoSyncAfterPull": config::get_bool(&cfg, "auto_sync_after_pull", true),
-        "autoUpdateEnabled": config::get_bool(&cfg, "auto_update_enabled", true),
-        "runOnStartup": config::get_bool(&cfg, "run_on_startup", true),
-        "autostartLaunchMode": autostart_launch_mode,
-        "closeBehavior": close_behavior,
-        "traySupported": true,
-        "debug": config::get_bool(&cfg, "debug", false),
-        "autoSave": config::get_bool(&cfg, "settings_auto_save", true),
-        "splitRatio": cfg.get("split_ratio").and_then(|v| v.as_f64()).unwrap_or(0.66),
-        "enableSystemTheme": config::get_bool(&cfg, "enable_system_theme", false),
-        "theme": theme,
-        "calendarTimezoneMode": calendar_timezone_mode,
-        "calendarUtcOffsetMinutes": config::get_i64(&cfg, "calendar_utc_offset_minutes", 0),
-        "enableTemporaryPath": config::get_bool(&cfg, "enable_temporary_path", false),
-        "temporaryPath": config::get_str(&cfg, "temporary_path"),
+    let mut payload =
+        settings_payload(&config::load_config(), tray_supported, install_dir_writable);
+    // Runtime readings rather than settings; `save_settings` ignores them.
+    payload["githubRateLimit"] = json!(rate_limit);
+    payload["githubTokenScopes"] = json!(scopes);
+    payload
+}
+
+/// The camelCase settings payload the frontend reads; `save_settings` accepts the same names.
+fn settings_payload(cfg: &Value, tray_supported: bool, install_dir_writable: bool) -> Value {
+    let settings = AppSettings::from_config(cfg);
+    let portable_unavailable_reason = (!install_dir_writable).then(|| {
+        format!(
+            "{} is not writable, so portable data cannot be stored next to the app. Move the \
+             app to a user-writable folder to enable portable mode.",
+            config::install_dir().display()
+        )
+    });
+    let file_limits = crate::calendar::FileLimits::from_config(cfg);
+    // Two objects rather than one: a single `json!` this large passes the macro recursion limit.
+    let mut payload = json!({
+        "autoSyncAfterPull": settings.auto_sync_after_pull,
+        "autoUpdateEnabled": settings.auto_update_enabled,
+        "updateNotifyMode": settings.notify_mode(),
+        "noTimeLabel": settings.no_time_label,
+        "recentHighlightHours": crate::snapshot::recent_highlight_hours(cfg),
+        "eventNameMaxLen": crate::snapshot::event_name_max_len(cfg),
+        "minForecastChangePct": crate::snapshot::min_forecast_change_pct(cfg).unwrap_or(0.0),
+        "dateFormat": DateFormat::from_config(cfg).as_str(),
+        "sourceDateFormat": source_date_format(cfg),
+        "githubRepo": settings.github_repo,
+        "eventAlertsEnabled": settings.event_alerts_enabled,
+        "eventAlertMinutesBefore": settings.event_alert_minutes_before,
+        "eventAlertMinImpact": settings.event_alert_min_impact,
+        "syncTimeoutSecs": settings.sync_timeout_secs,
+        "syncIntervalMinutes": settings.sync_interval_minutes,
+        "syncAtomic": settings.sync_atomic,
+        "syncScope": settings.sync_scope(),
+        "minFreeSpaceMb": settings.min_free_space_mb,
+        "backupKeepCount": settings.backup_keep_count,
+        "trayAlertMinutes": settings.tray_alert_minutes,
+        "respectSystemDnd": settings.respect_system_dnd,
+        "historySlimMode": settings.history_slim_mode,
+        "statusFileEnabled": settings.status_file_enabled,
+        "statusFilePath": settings.status_file_path,
+        "instanceLabel": settings.instance_label,
+        "runOnStartup": settings.run_on_startup,
+        "autostartLaunchMode": if settings.launch_to_tray() { "tray" } else { "show" },
+        "closeBehavior": if settings.close_to_tray() { "tray" } else { "exit" },
+        "secondInstanceAction": crate::commands::ui::second_instance_action(cfg),
+    });
+    let environment = json!({
+        "traySupported": tray_supported,
+        "portableMode": config::portable_data_dir().is_some(),
+        "portableUnavailableReason": portable_unavailable_reason,
+        "debug": settings.debug,
+        "logMinLevel": LogFilter::from_config(cfg).min_level.as_str(),
+        "logBufferEntries": log_buffer_entries(cfg),
+        "maxCalendarFileMb": file_limits.max_bytes / (1024 * 1024),
+        "maxEventsPerFile": file_limits.max_events,
+        "autoSave": settings.settings_auto_save,
+        "refreshOnFocus": settings.refresh_on_focus,
+        "splitRatio": settings.split_ratio,
+        "enableSystemTheme": settings.enable_system_theme,
+        "theme": settings.theme(),
+        "calendarTimezoneMode": settings.timezone_mode(),
+        "calendarUtcOffsetMinutes": settings.calendar_utc_offset_minutes,
+        "enableTemporaryPath": settings.enable_temporary_path,
+        "temporaryPath": settings.temporary_path,
         "repoPath": config::install_dir().to_string_lossy().to_string(),
         "logPath": config::log_dir().join("app.log").to_string_lossy().to_string(),
-    })
+    });
+    if let (Some(obj), Value::Object(more)) = (payload.as_object_mut(), environment) {
+        obj.extend(more);
+    }
+    payload
 }
 
-#[tauri::command]
-pub fn save_settings(
-    payload: Value,
-    state: tauri::State<'_, Mutex<RuntimeState>>,
-) -> Result<Value, String> {
-    let mut cfg = config::load_config();
+/// Inclusive bounds numeric settings are clamped to, by config key.
+const SETTING_RANGES: &[(&str, i64, i64)] = &[
+    ("recent_highlight_hours", 0, 168),
+    ("event_name_max_len", 0, 500),
+    ("event_alert_minutes_before", 1, 120),
+    ("log_buffer_entries", 100, 2000),
+    ("max_calendar_file_mb", 1, 1024),
+    ("max_events_per_file", 1000, 10_000_000),
+    ("backup_keep_count", 0, 50),
+    ("min_free_space_mb", 0, 100_000),
+    ("tray_alert_minutes", 0, 240),
+    ("sync_timeout_secs", 10, 3600),
+    ("sync_interval_minutes", 0, 24 * 60),
+];
+
+/// `value` as it is stored under the config `key`: numbers clamped to `SETTING_RANGES`, names
+/// checked against their choices, text trimmed. Both `save_settings` and `import_settings` go
+/// through it; keys without a rule pass unchanged.
+fn checked_setting(key: &str, value: &Value) -> Result<Value, String> {
+    if let Some((_, min, max)) = SETTING_RANGES.iter().find(|(k, ..)| *k == key) {
+        let n = value
+            .as_i64()
+            .ok_or_else(|| format!("{key} must be a whole number"))?;
+        return Ok(json!(n.clamp(*min, *max)));
+    }
+    match (key, value.as_str()) {
+        ("second_instance_action", Some(action)) => {
+            if !matches!(action, "focus" | "ignore" | "notify") {
+                return Err(format!(
+                    "Unknown second instance action: {action} (expected focus, ignore or notify)"
+                ));
+            }
+            Ok(json!(action))
+        }
+        ("update_notify_mode", Some(mode)) => {
+            if !matches!(mode, "modal" | "log" | "silent") {
+                return Err(format!(
+                    "Unknown update notify mode: {mode} (expected modal, log or silent)"
+                ));
+            }
+            Ok(json!(mode))
+        }
+        ("sync_scope", Some(scope)) => {
+            if !matches!(scope, "full" | "current") {
+                return Err(format!(
+                    "Unknown sync scope: {scope} (expected full or current)"
+                ));
+            }
+            Ok(json!(scope))
+        }
+        ("event_alert_min_impact", Some(impact)) => match impact_rank(impact) {
+            3 => Ok(json!("High")),
+            2 => Ok(json!("Medium")),
+            1 => Ok(json!("Low")),
+            _ => Err(format!(
+                "Unknown alert impact: {} (expected High, Medium or Low)",
+                impact.trim()
+            )),
+        },
+        ("date_format", Some(format)) => {
+            let Some(format) = DateFormat::parse(format) else {
+                return Err(format!(
+                    "Unknown date format: {format} (expected dmy, mdy or iso)"
+                ));
+            };
+            Ok(json!(format.as_str()))
+        }
+        ("source_date_format", Some(format)) => {
+            if !valid_date_format(format) {
+                return Err(format!("Invalid source date format: {format}"));
+            }
+            Ok(json!(format.trim()))
+        }
+        ("no_time_label", Some(label)) => Ok(json!(label.trim())),
+        ("min_forecast_change_pct", _) => value
+            .as_f64()
+            .filter(|pct| pct.is_finite())
+            .map(|pct| json!(pct.max(0.0)))
+            .ok_or_else(|| "min_forecast_change_pct must be a number".to_string()),
+        _ => Ok(value.clone()),
+    }
+}
+
+fn set_checked(cfg: &mut Value, key: &str, value: Value) -> Result<(), String> {
+    let value = checked_setting(key, &value)?;
+    let obj = cfg.as_object_mut().ok_or("config invalid")?;
+    obj.insert(key.to_string(), value);
+    Ok(())
+}
+
+/// The settings an export file may change: allowlisted, of the right type, and valid under
+/// `checked_setting` (clamped where it clamps). Returns them with the keys that were dropped.
+fn importable_settings(incoming: &Map<String, Value>) -> (Map<String, Value>, Vec<String>) {
+    let (accepted, mut ignored) = config::validate_portable_settings(incoming);
+    let mut checked = Map::new();
+    for (key, value) in accepted {
+        match checked_setting(&key, &value) {
+            Ok(value) => {
+                checked.insert(key, value);
+            }
+            Err(_) => ignored.push(key),
+        }
+    }
+    (checked, ignored)
+}
+
+/// Applies a `save_settings` payload (camelCase names, as `settings_payload` sends them) to
+/// `cfg`. Fails without a partial save when a value is refused.
+fn apply_settings_payload(cfg: &mut Value, payload: &Value) -> Result<(), String> {
     config::set_bool(
-        &mut cfg,
+        cfg,
         "auto_sync_after_pull",
         payload
             .get("autoSyncAfterPull")
@@ -59,20 +214,108 @@ pub fn save_settings(
             .unwrap_or(true),
     )?;
     config::set_bool(
-        &mut cfg,
+        cfg,
         "auto_update_enabled",
         payload
             .get("autoUpdateEnabled")
             .and_then(|v| v.as_bool())
             .unwrap_or(true),
     )?;
+    if let Some(action) = payload.get("secondInstanceAction").and_then(|v| v.as_str()) {
+        set_checked(cfg, "second_instance_action", json!(action))?;
+    }
+    if let Some(mode) = payload.get("updateNotifyMode").and_then(|v| v.as_str()) {
+        set_checked(cfg, "update_notify_mode", json!(mode))?;
+    }
+    if let Some(label) = payload.get("noTimeLabel").and_then(|v| v.as_str()) {
+        set_checked(cfg, "no_time_label", json!(label))?;
+    }
+    if let Some(hours) = payload.get("recentHighlightHours").and_then(|v| v.as_i64()) {
+        set_checked(cfg, "recent_highlight_hours", json!(hours))?;
+    }
+    if let Some(chars) = payload.get("eventNameMaxLen").and_then(|v| v.as_i64()) {
+        set_checked(cfg, "event_name_max_len", json!(chars))?;
+    }
+    if let Some(enabled) = payload.get("statusFileEnabled").and_then(|v| v.as_bool()) {
+        config::set_bool(cfg, "status_file_enabled", enabled)?;
+    }
+    if let Some(path) = payload.get("statusFilePath").and_then(|v| v.as_str()) {
+        config::set_string(cfg, "status_file_path", path.trim().to_string())?;
+    }
+    if let Some(label) = payload.get("instanceLabel").and_then(|v| v.as_str()) {
+        config::set_string(cfg, "instance_label", label.trim().to_string())?;
+    }
+    if let Some(enabled) = payload.get("eventAlertsEnabled").and_then(|v| v.as_bool()) {
+        config::set_bool(cfg, "event_alerts_enabled", enabled)?;
+    }
+    if let Some(minutes) = payload
+        .get("eventAlertMinutesBefore")
+        .and_then(|v| v.as_i64())
+    {
+        set_checked(cfg, "event_alert_minutes_before", json!(minutes))?;
+    }
+    if let Some(impact) = payload.get("eventAlertMinImpact").and_then(|v| v.as_str()) {
+        set_checked(cfg, "event_alert_min_impact", json!(impact))?;
+    }
+    if let Some(atomic) = payload.get("syncAtomic").and_then(|v| v.as_bool()) {
+        config::set_bool(cfg, "sync_atomic", atomic)?;
+    }
+    if let Some(scope) = payload.get("syncScope").and_then(|v| v.as_str()) {
+        set_checked(cfg, "sync_scope", json!(scope))?;
+    }
+    if let Some(format) = payload.get("dateFormat").and_then(|v| v.as_str()) {
+        set_checked(cfg, "date_format", json!(format))?;
+    }
+    if let Some(repo) = payload.get("githubRepo").and_then(|v| v.as_str()) {
+        let slug = git_ops::normalize_repo_slug(repo)?;
+        config::set_string(cfg, "github_repo", slug)?;
+    }
+    if let Some(format) = payload.get("sourceDateFormat").and_then(|v| v.as_str()) {
+        set_checked(cfg, "source_date_format", json!(format))?;
+    }
+    if let Some(level) = payload.get("logMinLevel").and_then(|v| v.as_str()) {
+        let Some(level) = LogLevel::parse(level) else {
+            return Err(format!("Unknown log level: {level}"));
+        };
+        config::set_string(cfg, "log_min_level", level.as_str().to_string())?;
+    }
+    if let Some(entries) = payload.get("logBufferEntries").and_then(|v| v.as_i64()) {
+        set_checked(cfg, "log_buffer_entries", json!(entries))?;
+    }
+    if let Some(mb) = payload.get("maxCalendarFileMb").and_then(|v| v.as_i64()) {
+        set_checked(cfg, "max_calendar_file_mb", json!(mb))?;
+    }
+    if let Some(events) = payload.get("maxEventsPerFile").and_then(|v| v.as_i64()) {
+        set_checked(cfg, "max_events_per_file", json!(events))?;
+    }
+    if let Some(keep) = payload.get("backupKeepCount").and_then(|v| v.as_i64()) {
+        set_checked(cfg, "backup_keep_count", json!(keep))?;
+    }
+    if let Some(mb) = payload.get("minFreeSpaceMb").and_then(|v| v.as_i64()) {
+        set_checked(cfg, "min_free_space_mb", json!(mb))?;
+    }
+    if let Some(respect) = payload.get("respectSystemDnd").and_then(|v| v.as_bool()) {
+        config::set_bool(cfg, "respect_system_dnd", respect)?;
+    }
+    if let Some(slim) = payload.get("historySlimMode").and_then(|v| v.as_bool()) {
+        config::set_bool(cfg, "history_slim_mode", slim)?;
+    }
+    if let Some(minutes) = payload.get("trayAlertMinutes").and_then(|v| v.as_i64()) {
+        set_checked(cfg, "tray_alert_minutes", json!(minutes))?;
+    }
+    if let Some(secs) = payload.get("syncTimeoutSecs").and_then(|v| v.as_i64()) {
+        set_checked(cfg, "sync_timeout_secs", json!(secs))?;
+    }
+    if let Some(minutes) = payload.get("syncIntervalMinutes").and_then(|v| v.as_i64()) {
+        set_checked(cfg, "sync_interval_minutes", json!(minutes))?;
+    }
     let run_on_startup = payload
         .get("runOnStartup")
         .and_then(|v| v.as_bool())
         .unwrap_or(true);
-    config::set_bool(&mut cfg, "run_on_startup", run_on_startup)?;
+    config::set_bool(cfg, "run_on_startup", run_on_startup)?;
     config::set_string(
-        &mut cfg,
+        cfg,
         "autostart_launch_mode",
         payload
             .get("autostartLaunchMode")
@@ -81,7 +324,7 @@ pub fn save_settings(
             .to_string(),
     )?;
     config::set_string(
-        &mut cfg,
+        cfg,
         "close_behavior",
         payload
             .get("closeBehavior")
@@ -90,7 +333,7 @@ pub fn save_settings(
             .to_string(),
     )?;
     config::set_bool(
-        &mut cfg,
+        cfg,
         "debug",
         payload
             .get("debug")
@@ -98,19 +341,29 @@ pub fn save_settings(
             .unwrap_or(false),
     )?;
     config::set_bool(
-        &mut cfg,
+        cfg,
         "settings_auto_save",
         payload
             .get("autoSave")
             .and_then(|v| v.as_bool())
             .unwrap_or(true),
     )?;
+    if let Some(refresh) = payload.get("refreshOnFocus").and_then(|v| v.as_bool()) {
+        config::set_bool(cfg, "refresh_on_focus", refresh)?;
+    }
     if let Some(v) = payload.get("splitRatio").and_then(|v| v.as_f64()) {
         let obj = cfg.as_object_mut().ok_or("config invalid")?;
         obj.insert("split_ratio".to_string(), json!(v));
     }
+    if let Some(pct) = payload
+        .get("minForecastChangePct")
+        .and_then(|v| v.as_f64())
+        .filter(|pct| pct.is_finite())
+    {
+        set_checked(cfg, "min_forecast_change_pct", json!(pct))?;
+    }
     config::set_bool(
-        &mut cfg,
+        cfg,
         "enable_system_theme",
         payload
             .get("enableSystemTheme")
@@ -118,7 +371,7 @@ pub fn save_settings(
             .unwrap_or(false),
     )?;
     config::set_string(
-        &mut cfg,
+        cfg,
         "theme_preference",
         payload
             .get("theme")
@@ -127,7 +380,7 @@ pub fn save_settings(
             .to_string(),
     )?;
     config::set_string(
-        &mut cfg,
+        cfg,
         "calendar_timezone_mode",
         payload
             .get("calendarTimezoneMode")
@@ -139,55 +392,125 @@ pub fn save_settings(
         .get("calendarUtcOffsetMinutes")
         .and_then(|v| v.as_i64())
     {
-        config::set_number(&mut cfg, "calendar_utc_offset_minutes", minutes)?;
+        config::set_number(cfg, "calendar_utc_offset_minutes", minutes)?;
     }
     config::set_bool(
-        &mut cfg,
+        cfg,
         "enable_temporary_path",
         payload
             .get("enableTemporaryPath")
             .and_then(|v| v.as_bool())
             .unwrap_or(false),
     )?;
-    config::set_string(
-        &mut cfg,
+    config::set_path_setting(
+        cfg,
         "temporary_path",
         payload
             .get("temporaryPath")
             .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string(),
+            .unwrap_or(""),
     )?;
     if let Some(repo_path) = payload.get("repoPath").and_then(|v| v.as_str()) {
-        config::set_string(&mut cfg, "repo_path", repo_path.to_string())?;
+        config::set_path_setting(cfg, "repo_path", repo_path)?;
     }
     if let Some(output_dir) = payload.get("outputDir").and_then(|v| v.as_str()) {
-        config::set_string(&mut cfg, "output_dir", output_dir.to_string())?;
+        config::set_path_setting(cfg, "output_dir", output_dir)?;
     }
+    Ok(())
+}
 
-    config::save_config(&cfg)?;
+#[tauri::command]
+pub fn save_settings(
+    app: tauri::AppHandle,
+    payload: Value,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Result<Value, String> {
+    // Under the config lock, so a concurrent writer's change is not overwritten.
+    let (before, cfg) = config::update_config(|cfg| apply_settings_payload(cfg, &payload))?;
+    let _ = config::record_config_change(&before, &cfg, "save_settings");
     {
         let mut runtime = state.lock().expect("runtime lock");
         runtime.repo_path = config::get_str(&cfg, "repo_path");
         runtime.output_dir = config::get_str(&cfg, "output_dir");
     }
-    startup::set_run_on_startup(run_on_startup)?;
+    apply_saved_settings(&app, &state, &before, &cfg)?;
     Ok(json!({"ok": true}))
 }
 
+/// Pushes a saved config into the runtime, the date format, the window title and the startup
+/// registration. Shared by `save_settings` and `import_settings` so both apply the same effects.
+fn apply_saved_settings(
+    app: &tauri::AppHandle,
+    state: &Mutex<RuntimeState>,
+    before: &Value,
+    cfg: &Value,
+) -> Result<(), String> {
+    {
+        let mut runtime = state.lock().expect("runtime lock");
+        runtime.log_filter = LogFilter::from_config(cfg);
+        runtime.log_buffer_entries = log_buffer_entries(cfg);
+        if source_date_format(cfg) != source_date_format(before) {
+            // Rows the old format dropped (or the new one drops) change on the next load.
+            runtime.calendar.last_loaded_at_ms = 0;
+        }
+    }
+    set_date_format(DateFormat::from_config(cfg));
+    super::ui::apply_window_title(app, cfg);
+    startup::set_run_on_startup(AppSettings::from_config(cfg).run_on_startup)
+}
+
+/// The currency filter `set_currency` stores: blank means USD, anything outside
+/// `currency_options` is refused with the allowed list.
+fn parse_currency(value: &str) -> Result<String, Value> {
+    if value.trim().is_empty() {
+        return Ok("USD".to_string());
+    }
+    crate::calendar::normalize_currency(value).ok_or_else(|| {
+        json!({
+            "ok": false,
+            "message": format!("Unknown currency: {}", value.trim()),
+            "allowed": currency_options(),
+        })
+    })
+}
+
 #[tauri::command]
 pub fn set_currency(
     value: String,
     state: tauri::State<'_, Mutex<RuntimeState>>,
 ) -> Result<Value, String> {
-    let value = value.trim().to_string();
+    let currency = match parse_currency(&value) {
+        Ok(currency) => currency,
+        Err(refused) => return Ok(refused),
+    };
     let mut runtime = state.lock().expect("runtime lock");
-    runtime.currency = if value.is_empty() {
-        "USD".to_string()
+    runtime.currency = currency.clone();
+    Ok(json!({"ok": true, "currency": currency}))
+}
+
+/// Sets `min_forecast_change_pct`; 0 or less turns the Next Events filter off.
+#[tauri::command]
+pub fn set_min_forecast_change_pct(
+    value: f64,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Result<Value, String> {
+    if !value.is_finite() {
+        return Err("Minimum forecast change must be a number".to_string());
+    }
+    let value = value.max(0.0);
+    config::update_config(|cfg| {
+        let obj = cfg.as_object_mut().ok_or("config invalid")?;
+        obj.insert("min_forecast_change_pct".to_string(), json!(value));
+        Ok(())
+    })?;
+    let mut runtime = state.lock().expect("runtime lock");
+    let message = if value > 0.0 {
+        format!("Next Events now needs a forecast change above {value}%")
     } else {
-        value
+        "Next Events forecast change filter off".to_string()
     };
-    Ok(json!({"ok": true}))
+    push_log(&mut runtime, &message, "INFO");
+    Ok(json!({"ok": true, "value": value}))
 }
 
 #[tauri::command]
@@ -202,6 +525,108 @@ pub fn get_temporary_path_task() -> Value {
     })
 }
 
+/// Checks whether `path` looks like a calendar data repo, for immediate feedback in Settings.
+#[tauri::command]
+pub fn validate_repo(payload: Value) -> Value {
+    let path = payload
+        .get("path")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    if path.is_empty() {
+        return json!({"ok": false, "message": "path is required"});
+    }
+    let layout = crate::calendar::check_repo_layout(Path::new(&path));
+    json!({
+        "ok": true,
+        "valid": layout.is_valid(),
+        "missing": layout.missing,
+        "yearDirs": layout.year_dirs,
+    })
+}
+
+/// Cleans a pasted repository URL down to `owner/repo`, as `save_settings` does.
+#[tauri::command]
+pub fn normalize_repo_slug(payload: Value) -> Value {
+    let input = payload.get("input").and_then(|v| v.as_str()).unwrap_or("");
+    match git_ops::normalize_repo_slug(input) {
+        Ok(slug) => json!({"ok": true, "slug": slug}),
+        Err(err) => json!({"ok": false, "message": err}),
+    }
+}
+
+/// Parses `sample` (plus an optional `time`) with `format`, or `source_date_format` when none
+/// is given, and reports the UTC instant it yields.
+#[tauri::command]
+pub fn test_parse_date(payload: Value) -> Value {
+    let text = |key: &str| {
+        payload
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .trim()
+            .to_string()
+    };
+    let sample = text("sample");
+    if sample.is_empty() {
+        return json!({"ok": false, "message": "sample is required"});
+    }
+    let format = match text("format") {
+        format if format.is_empty() => source_date_format(&config::load_config()),
+        format => format,
+    };
+    if !valid_date_format(&format) {
+        return json!({"ok": false, "message": format!("Invalid date format: {format}")});
+    }
+    let parsed = parse_source_dt_to_utc(
+        &sample,
+        &text("time"),
+        &format,
+        crate::calendar::CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
+    );
+    json!({
+        "ok": true,
+        "format": format,
+        "parsed": parsed.is_some(),
+        "utc": parsed.map(|dt| dt.to_rfc3339()),
+        "message": if parsed.is_some() { String::new() } else { format!("'{sample}' does not match {format}") },
+    })
+}
+
+/// Selectable IANA zones with their current offset, flat or (`grouped: true`) keyed by the
+/// region before the first `/`. Reads nothing but the clock.
+#[tauri::command]
+pub fn list_timezones(payload: Option<Value>) -> Value {
+    let grouped = payload
+        .as_ref()
+        .and_then(|p| p.get("grouped"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let zones: Vec<Value> = timezone_offsets(crate::time_util::now_utc())
+        .into_iter()
+        .map(|(name, offset)| {
+            let offset_label = format_utc_offset(offset);
+            json!({
+                "name": name,
+                "offsetMinutes": offset,
+                "offsetLabel": offset_label,
+                "label": format!("{name} ({offset_label})"),
+            })
+        })
+        .collect();
+    if !grouped {
+        return json!({"ok": true, "zones": zones});
+    }
+    let mut groups: std::collections::BTreeMap<String, Vec<Value>> = Default::default();
+    for zone in zones {
+        let name = zone["name"].as_str().unwrap_or("");
+        let region = name.split_once('/').map_or("Other", |(region, _)| region);
+        groups.entry(region.to_string()).or_default().push(zone);
+    }
+    json!({"ok": true, "groups": groups})
+}
+
 #[tauri::command]
 pub fn probe_temporary_path(payload: Value) -> Value {
     let path = payload
@@ -248,9 +673,9 @@ pub fn set_temporary_path(
     path: String,
     state: tauri::State<'_, Mutex<RuntimeState>>,
 ) -> Result<Value, String> {
-    let mut cfg = config::load_config();
-    config::set_string(&mut cfg, "temporary_path", path.clone())?;
-    config::save_config(&cfg)?;
+    let (before, cfg) =
+        config::update_config(|cfg| config::set_path_setting(cfg, "temporary_path", &path))?;
+    let _ = config::record_config_change(&before, &cfg, "set_temporary_path");
     let _ = state;
     Ok(json!({"ok": true}))
 }
@@ -269,10 +694,322 @@ pub fn set_output_dir(
     path: String,
     state: tauri::State<'_, Mutex<RuntimeState>>,
 ) -> Result<Value, String> {
-    let mut cfg = config::load_config();
-    config::set_string(&mut cfg, "output_dir", path.clone())?;
-    config::save_config(&cfg)?;
+    let (before, cfg) =
+        config::update_config(|cfg| config::set_path_setting(cfg, "output_dir", &path))?;
+    let _ = config::record_config_change(&before, &cfg, "set_output_dir");
     let mut runtime = state.lock().expect("runtime lock");
     runtime.output_dir = path;
     Ok(json!({"ok": true}))
 }
+
+fn check_path_kind(kind: &str) -> Result<(), String> {
+    if config::PATH_HISTORY_KINDS.contains(&kind) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown path kind: {kind} (expected one of {})",
+            config::PATH_HISTORY_KINDS.join(", ")
+        ))
+    }
+}
+
+/// Recently used values of a path setting (`output_dir`, `temporary_path` or `repo_path`),
+/// most recent first, for a dropdown next to the browse button.
+#[tauri::command]
+pub fn get_path_history(kind: String) -> Result<Value, String> {
+    check_path_kind(&kind)?;
+    let cfg = config::load_config();
+    Ok(json!({
+        "ok": true,
+        "kind": kind,
+        "current": config::get_str(&cfg, &kind),
+        "history": config::path_history(&cfg, &kind),
+    }))
+}
+
+/// Switches a path setting to entry `index` of its history. Folders that no longer exist are
+/// refused (the temporary path is created on demand, so it is exempt).
+#[tauri::command]
+pub fn select_path_from_history(
+    kind: String,
+    index: usize,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Result<Value, String> {
+    check_path_kind(&kind)?;
+    let mut picked = String::new();
+    let (before, cfg) = config::update_config(|cfg| {
+        picked = config::path_history(cfg, &kind)
+            .get(index)
+            .cloned()
+            .ok_or_else(|| format!("No {kind} history entry {index}"))?;
+        if kind != "temporary_path" && !config::path_is_usable_dir(Path::new(&picked)) {
+            return Err(format!("{picked} is no longer a folder"));
+        }
+        config::set_path_setting(cfg, &kind, &picked)
+    })?;
+    let _ = config::record_config_change(&before, &cfg, "select_path_from_history");
+    let mut runtime = state.lock().expect("runtime lock");
+    runtime.repo_path = config::get_str(&cfg, "repo_path");
+    runtime.output_dir = config::get_str(&cfg, "output_dir");
+    push_log(
+        &mut runtime,
+        &format!("{kind} set to {picked} from history"),
+        "INFO",
+    );
+    Ok(json!({"ok": true, "path": picked, "history": config::path_history(&cfg, &kind)}))
+}
+
+const SETTINGS_EXPORT_FILE: &str = "xauusd-calendar-settings.json";
+
+/// Writes the portable settings subset to `path`, or to a file picked in a save dialog.
+#[tauri::command]
+pub fn export_settings(app: tauri::AppHandle, payload: Value) -> Value {
+    let path = match payload.get("path").and_then(|v| v.as_str()) {
+        Some(path) if !path.trim().is_empty() => PathBuf::from(path.trim()),
+        _ => {
+            let picked = app
+                .dialog()
+                .file()
+                .set_file_name(SETTINGS_EXPORT_FILE)
+                .add_filter("JSON", &["json"])
+                .blocking_save_file();
+            match picked.and_then(|p| p.into_path().ok()) {
+                Some(path) => path,
+                None => return json!({"ok": true, "cancelled": true}),
+            }
+        }
+    };
+    let cfg = config::load_config();
+    let settings = config::portable_settings(&cfg);
+    let count = settings.len();
+    let export = json!({
+        "app": "XAUUSD Calendar Agent",
+        "version": env!("APP_VERSION"),
+        "settings": settings,
+    });
+    let written = serde_json::to_string_pretty(&export)
+        .map_err(|e| e.to_string())
+        .and_then(|text| std::fs::write(&path, text).map_err(|e| e.to_string()));
+    match written {
+        Ok(()) => json!({"ok": true, "path": path.to_string_lossy(), "count": count}),
+        Err(err) => json!({"ok": false, "message": format!("Export failed: {err}")}),
+    }
+}
+
+/// Reads an exported settings file. With `dryRun` only the diff is returned; otherwise the
+/// changes are saved under the config lock and applied through `apply_saved_settings`, as
+/// `save_settings` does.
+#[tauri::command]
+pub fn import_settings(
+    app: tauri::AppHandle,
+    payload: Value,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    let dry_run = payload
+        .get("dryRun")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let path = match payload.get("path").and_then(|v| v.as_str()) {
+        Some(path) if !path.trim().is_empty() => PathBuf::from(path.trim()),
+        _ => {
+            let picked = app
+                .dialog()
+                .file()
+                .add_filter("JSON", &["json"])
+                .blocking_pick_file();
+            match picked.and_then(|p| p.into_path().ok()) {
+                Some(path) => path,
+                None => return json!({"ok": true, "cancelled": true}),
+            }
+        }
+    };
+    let parsed = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| serde_json::from_str::<Value>(&text).map_err(|e| e.to_string()));
+    let file = match parsed {
+        Ok(file) => file,
+        Err(err) => return json!({"ok": false, "message": format!("Import failed: {err}")}),
+    };
+    let Some(incoming) = file.get("settings").and_then(|v| v.as_object()) else {
+        return json!({"ok": false, "message": "Import failed: not a settings export"});
+    };
+    let (accepted, ignored) = importable_settings(incoming);
+    let preview = config::settings_diff(&config::load_config(), &accepted);
+    if dry_run || preview.is_empty() {
+        return json!({"ok": true, "dryRun": dry_run, "changes": preview, "ignored": ignored});
+    }
+
+    // Under the config lock, so a concurrent writer's change is not overwritten.
+    let written = config::update_config(|cfg| {
+        if let Some(obj) = cfg.as_object_mut() {
+            obj.extend(accepted.clone());
+        }
+        Ok(())
+    });
+    let (before, cfg) = match written {
+        Ok(written) => written,
+        Err(err) => return json!({"ok": false, "message": format!("Import failed: {err}")}),
+    };
+    let changes = config::settings_diff(&before, &accepted);
+    let _ = config::record_config_change(&before, &cfg, "import_settings");
+    let startup = apply_saved_settings(&app, &state, &before, &cfg);
+    let mut runtime = state.lock().expect("runtime lock");
+    push_log(
+        &mut runtime,
+        &format!(
+            "Settings imported from {} ({} changed)",
+            path.display(),
+            changes.len()
+        ),
+        "INFO",
+    );
+    if let Err(err) = &startup {
+        push_log(
+            &mut runtime,
+            &format!("Startup registration failed: {err}"),
+            "WARN",
+        );
+    }
+    json!({"ok": true, "dryRun": false, "changes": changes, "ignored": ignored})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn currency_is_validated_and_canonicalized() {
+        assert_eq!(parse_currency("eur").unwrap(), "EUR");
+        assert_eq!(parse_currency("  gBp \t").unwrap(), "GBP");
+        assert_eq!(parse_currency("all").unwrap(), "ALL");
+        assert_eq!(parse_currency("   ").unwrap(), "USD");
+        for bad in ["usd;drop table", "XAU", "💵", &"U".repeat(50)] {
+            let refused = parse_currency(bad).unwrap_err();
+            assert_eq!(refused["ok"], false, "{bad}");
+            assert_eq!(refused["allowed"], json!(currency_options()));
+        }
+    }
+
+    #[test]
+    fn imports_are_clamped_and_validated_like_saves() {
+        let incoming = json!({
+            "sync_timeout_secs": 1,
+            "backup_keep_count": 500,
+            "update_notify_mode": "popup",
+            "date_format": "ISO",
+            "no_time_label": "  All Day ",
+            "min_forecast_change_pct": -2.5,
+            "event_alerts_enabled": true
+        });
+        let (accepted, mut ignored) = importable_settings(incoming.as_object().unwrap());
+        assert_eq!(
+            Value::Object(accepted),
+            json!({
+                "sync_timeout_secs": 10,
+                "backup_keep_count": 50,
+                "date_format": "iso",
+                "no_time_label": "All Day",
+                "min_forecast_change_pct": 0.0,
+                "event_alerts_enabled": true
+            })
+        );
+        ignored.sort();
+        assert_eq!(ignored, ["update_notify_mode"]);
+
+        assert_eq!(
+            checked_setting("sync_scope", &json!("partial")).unwrap_err(),
+            "Unknown sync scope: partial (expected full or current)"
+        );
+        assert!(checked_setting("sync_interval_minutes", &json!(1.5)).is_err());
+        assert_eq!(
+            checked_setting("event_alert_min_impact", &json!(" medium ")).unwrap(),
+            json!("Medium")
+        );
+        assert_eq!(
+            checked_setting("event_alert_min_impact", &json!("Critical")).unwrap_err(),
+            "Unknown alert impact: Critical (expected High, Medium or Low)"
+        );
+        assert_eq!(
+            checked_setting("github_repo", &json!("a/b")).unwrap(),
+            json!("a/b")
+        );
+    }
+
+    #[test]
+    fn settings_payload_keeps_its_wire_names() {
+        let mut cfg = config::default_config();
+        cfg["settings_auto_save"] = json!(false);
+        cfg["theme_preference"] = json!("dark");
+        cfg["autostart_launch_mode"] = json!("bogus");
+        cfg["sync_timeout_secs"] = json!(45);
+        let payload = settings_payload(&cfg, true, false);
+        let mut keys: Vec<&str> = payload
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            vec![
+                "autoSave",
+                "autoSyncAfterPull",
+                "autoUpdateEnabled",
+                "autostartLaunchMode",
+                "backupKeepCount",
+                "calendarTimezoneMode",
+                "calendarUtcOffsetMinutes",
+                "closeBehavior",
+                "dateFormat",
+                "debug",
+                "enableSystemTheme",
+                "enableTemporaryPath",
+                "eventAlertMinImpact",
+                "eventAlertMinutesBefore",
+                "eventAlertsEnabled",
+                "eventNameMaxLen",
+                "githubRepo",
+                "historySlimMode",
+                "instanceLabel",
+                "logBufferEntries",
+                "logMinLevel",
+                "logPath",
+                "maxCalendarFileMb",
+                "maxEventsPerFile",
+                "minForecastChangePct",
+                "minFreeSpaceMb",
+                "noTimeLabel",
+                "portableMode",
+                "portableUnavailableReason",
+                "recentHighlightHours",
+                "refreshOnFocus",
+                "repoPath",
+                "respectSystemDnd",
+                "runOnStartup",
+                "secondInstanceAction",
+                "sourceDateFormat",
+                "splitRatio",
+                "statusFileEnabled",
+                "statusFilePath",
+                "syncAtomic",
+                "syncIntervalMinutes",
+                "syncScope",
+                "syncTimeoutSecs",
+                "temporaryPath",
+                "theme",
+                "trayAlertMinutes",
+                "traySupported",
+                "updateNotifyMode",
+            ]
+        );
+        assert_eq!(payload["autoSave"], false);
+        assert_eq!(payload["theme"], "dark");
+        assert_eq!(payload["autostartLaunchMode"], "tray");
+        assert_eq!(payload["closeBehavior"], "exit");
+        assert_eq!(payload["syncTimeoutSecs"], 45);
+        assert_eq!(payload["splitRatio"], 0.66);
+        assert_eq!(payload["traySupported"], true);
+        assert!(payload["portableUnavailableReason"].is_string());
+    }
+}
@@ -1,11 +1,80 @@
 use super::*;
+use crate::calendar::{
+    event_year_file, find_dropped_rows, find_rows_on_disk, impact_rank, CalendarEvent, EventSource,
+};
+use crate::categories::{matches_filter, CategoryRules};
+use crate::event_window::{events_window, WindowFilter};
+use crate::snapshot::{
+    event_visibility, impact_selected, min_forecast_change_pct, next_event_id, render_status_line,
+    visibility_checks, EventVisibility, STATUS_LINE_FORMAT,
+};
+use crate::snapshot::{last_released, render_last_released, render_today_agenda};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// `categoryFilter` as a single name or a list; empty means no filtering.
+fn category_filter(payload: Option<&Value>) -> Vec<String> {
+    match payload.and_then(|p| p.get("categoryFilter")) {
+        Some(Value::String(name)) if !name.trim().is_empty() => vec![name.trim().to_string()],
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Takes the row queued by `notify::focus_event_row`, so only one snapshot reports it, with where
+/// it is now: "upcoming", "past" (it has since started) or "gone" (no longer loaded).
+fn take_focus_event(
+    runtime: &mut RuntimeState,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<(String, &'static str)> {
+    let event_id = runtime.pending_focus_event_id.take()?;
+    let mut seen: HashMap<String, i32> = HashMap::new();
+    let location = match runtime
+        .calendar
+        .events
+        .iter()
+        .find(|e| next_event_id(e, &mut seen) == event_id)
+    {
+        Some(e) if e.dt_utc > now => "upcoming",
+        Some(_) => "past",
+        None => "gone",
+    };
+    Some((event_id, location))
+}
+
+/// `impactFilter` as lower-cased impact names; empty means no filtering.
+fn impact_filter(payload: &Value) -> Vec<String> {
+    payload
+        .get("impactFilter")
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|v| v.trim().to_lowercase())
+                .filter(|v| !v.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
 #[tauri::command]
-pub fn get_snapshot(app: tauri::AppHandle, state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+pub fn get_snapshot(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+    payload: Option<Value>,
+) -> Value {
     let cfg = config::load_config();
+    let category_filter = category_filter(payload.as_ref());
     ensure_calendar_loaded(app.clone(), cfg.clone(), state.clone());
 
     let (tz_mode, utc_offset_minutes) = get_calendar_settings(&cfg);
+    let no_time_label = config::get_str(&cfg, "no_time_label");
     let currency_opts = currency_options();
 
     // Keep lock scope small to avoid UI stalls (especially when rendering large history lists).
@@ -15,19 +84,29 @@ pub fn get_snapshot(app: tauri::AppHandle, state: tauri::State<'_, Mutex<Runtime
         repo_path,
         last_pull,
         last_pull_at,
+        last_pull_error,
         last_sync,
         last_sync_at,
         logs,
         modal,
         pull_active,
         sync_active,
+        sync_cancellable,
+        pull_cancellable,
+        sync_pending_deletions,
         calendar_status,
         calendar_events,
+        last_prints,
+        test_clock,
+        focus,
+        scheduled_pull_at,
+        notifications_muted_until,
     ) = {
         let mut runtime = state.lock().expect("runtime lock");
-        if runtime.currency.is_empty() {
-            runtime.currency = "USD".to_string();
-        }
+        crate::boot::mark(&mut runtime.boot.first_snapshot_ms);
+        // Defensive: a filter that bypassed `set_currency` falls back to USD.
+        runtime.currency = crate::calendar::normalize_currency(&runtime.currency)
+            .unwrap_or_else(|| "USD".to_string());
         if runtime.update_state.is_null() {
             runtime.update_state = super::update::default_update_state();
         }
@@ -72,7 +151,9 @@ pub fn get_snapshot(app: tauri::AppHandle, state: tauri::State<'_, Mutex<Runtime
         } else {
             runtime.last_sync.clone()
         };
-        let calendar_status = if runtime.calendar.status.is_empty() {
+        let calendar_status = if runtime.resetting {
+            "resetting".to_string()
+        } else if runtime.calendar.status.is_empty() {
             "empty".to_string()
         } else {
             runtime.calendar.status.clone()
@@ -84,54 +165,789 @@ pub fn get_snapshot(app: tauri::AppHandle, state: tauri::State<'_, Mutex<Runtime
             runtime.repo_path.clone(),
             last_pull,
             runtime.last_pull_at.clone(),
+            runtime.last_pull_error.clone(),
             last_sync,
             runtime.last_sync_at.clone(),
-            runtime.logs.clone(),
+            // The buffer may be configured larger; the snapshot stays at the default size.
+            runtime
+                .logs
+                .iter()
+                .take(DEFAULT_LOG_BUFFER_ENTRIES)
+                .cloned()
+                .collect::<Vec<_>>(),
             runtime.modal.clone(),
             runtime.pull_active,
             runtime.sync_active,
+            runtime.sync_active
+                && !runtime
+                    .sync_cancel
+                    .load(std::sync::atomic::Ordering::SeqCst),
+            runtime.pull_active
+                && !runtime.pull_applying
+                && !runtime
+                    .pull_cancel
+                    .load(std::sync::atomic::Ordering::SeqCst),
+            runtime
+                .sync_deletion_prompt
+                .as_ref()
+                .map(|prompt| prompt.to_json()),
             calendar_status,
             runtime.calendar.events.clone(),
+            runtime.calendar.last_prints.clone(),
+            runtime.test_clock,
+            take_focus_event(&mut runtime, crate::time_util::now_utc()),
+            runtime.scheduled_pull_at,
+            runtime.notifications_muted_until.filter(|until| {
+                crate::notifications::muted(Some(*until), crate::time_util::now_utc())
+            }),
         )
     };
 
-    let next_events = render_next_events(
-        calendar_events.as_slice(),
-        &currency,
-        &tz_mode,
-        utc_offset_minutes,
-        CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
+    let derived_status = if pull_active && calendar_events.is_empty() {
+        "downloading".to_string()
+    } else {
+        calendar_status
+    };
+
+    // Filtering before rendering keeps row ids stable: identical rows share categories.
+    let calendar_events: Cow<[CalendarEvent]> = if category_filter.is_empty() {
+        Cow::Borrowed(calendar_events.as_slice())
+    } else {
+        Cow::Owned(
+            calendar_events
+                .iter()
+                .filter(|e| matches_filter(e, &category_filter))
+                .cloned()
+                .collect(),
+        )
+    };
+    let mut next_events = render_next_events(
+        &calendar_events,
+        &NextEventsOptions {
+            currency: &currency,
+            tz_mode: &tz_mode,
+            utc_offset_minutes,
+            source_utc_offset_minutes: CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
+            no_time_label: &no_time_label,
+            last_prints: &last_prints,
+            min_forecast_change_pct: min_forecast_change_pct(&cfg),
+        },
     );
-    let past_events = render_past_events(
-        calendar_events.as_slice(),
+    let mut past_events = render_past_events(
+        &calendar_events,
         &currency,
         &tz_mode,
         utc_offset_minutes,
         CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
+        &no_time_label,
+        crate::snapshot::recent_highlight_hours(&cfg),
     );
-    let derived_status = if pull_active && calendar_events.is_empty() {
-        "downloading".to_string()
-    } else {
-        calendar_status
-    };
+    let name_max_len = crate::snapshot::event_name_max_len(&cfg);
+    crate::snapshot::limit_event_names(&mut next_events, name_max_len);
+    crate::snapshot::limit_event_names(&mut past_events, name_max_len);
 
     json!({
         "lastPull": last_pull,
         "lastSync": last_sync,
         "lastPullAt": last_pull_at,
+        "lastPullError": if last_pull_error.is_empty() { Value::Null } else { json!(last_pull_error) },
         "lastSyncAt": last_sync_at,
         "outputDir": output_dir,
         "repoPath": repo_path,
         "currency": currency,
         "currencyOptions": currency_opts,
+        "categoryFilter": category_filter,
         "events": next_events,
         "pastEvents": past_events,
         "logs": logs,
         "version": env!("APP_VERSION"),
         "pullActive": pull_active,
         "syncActive": sync_active,
+        "syncCancellable": sync_cancellable,
+        "pullCancellable": pull_cancellable,
+        "syncAwaitingConfirmation": sync_pending_deletions.is_some(),
+        "syncPendingDeletions": sync_pending_deletions,
+        "instanceLabel": Some(super::ui::instance_label(&cfg)).filter(|label| !label.is_empty()),
+        "focusEventId": focus.as_ref().map(|(id, _)| id),
+        "focusEventLocation": focus.as_ref().map(|(_, location)| location),
+        "scheduledPullAt": scheduled_pull_at.map(|at| at.to_rfc3339()),
+        "notificationsMutedUntil": notifications_muted_until.map(|at| at.to_rfc3339()),
         "calendarStatus": derived_status,
         "restartInSeconds": 0,
-        "modal": if modal.is_null() { Value::Null } else { modal }
+        "modal": modal,
+        "testClock": test_clock.map(|at| at.to_rfc3339()),
+        "dateFormat": crate::time_util::date_format().as_str(),
+        "timezoneMismatch": super::lifecycle::timezone_hint(
+            &cfg,
+            crate::time_util::local_utc_offset_minutes()
+        ),
+    })
+}
+
+#[tauri::command]
+pub fn get_data_fingerprint(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let runtime = state.lock().expect("runtime lock");
+    if runtime.calendar.last_loaded_at_ms == 0 {
+        return json!({"ok": false, "message": "Calendar data is not loaded yet"});
+    }
+    json!({
+        "ok": true,
+        "fingerprint": runtime.calendar.fingerprint,
+        "eventCount": runtime.calendar.events.len(),
+        "historyGeneratedAt": runtime.calendar.history_generated_at,
+    })
+}
+
+/// Latest past event with an actual (for `cur`, or any currency), rendered like a History row.
+#[tauri::command]
+pub fn get_last_released(
+    app: tauri::AppHandle,
+    payload: Option<Value>,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    let cfg = config::load_config();
+    ensure_calendar_loaded(app, cfg.clone(), state.clone());
+    let currency = payload
+        .as_ref()
+        .and_then(|p| p.get("cur"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("ALL")
+        .to_string();
+    let events = state.lock().expect("runtime lock").calendar.events.clone();
+    let Some(event) = last_released(&events, &currency, crate::time_util::now_utc()) else {
+        return json!({"ok": true, "event": Value::Null});
+    };
+    let (tz_mode, utc_offset_minutes) = get_calendar_settings(&cfg);
+    let row = render_last_released(
+        event,
+        &tz_mode,
+        utc_offset_minutes,
+        CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
+        &config::get_str(&cfg, "no_time_label"),
+    );
+    json!({"ok": true, "event": row})
+}
+
+/// Today's remaining events (display timezone) for `cur`, with live countdowns.
+#[tauri::command]
+pub fn get_today_agenda(
+    app: tauri::AppHandle,
+    payload: Option<Value>,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    let cfg = config::load_config();
+    ensure_calendar_loaded(app, cfg.clone(), state.clone());
+    let (events, runtime_currency) = {
+        let runtime = state.lock().expect("runtime lock");
+        (runtime.calendar.events.clone(), runtime.currency.clone())
+    };
+    let currency = payload
+        .as_ref()
+        .and_then(|p| p.get("cur"))
+        .and_then(|v| v.as_str())
+        .map(|v| v.to_string())
+        .unwrap_or(runtime_currency);
+    let (tz_mode, utc_offset_minutes) = get_calendar_settings(&cfg);
+    let events = render_today_agenda(
+        &events,
+        &currency,
+        &tz_mode,
+        utc_offset_minutes,
+        CALENDAR_SOURCE_UTC_OFFSET_MINUTES,
+        &config::get_str(&cfg, "no_time_label"),
+        crate::time_util::now_utc(),
+    );
+    json!({"ok": true, "currency": currency, "events": events})
+}
+
+fn parse_utc_param(payload: &Value, key: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    let raw = payload
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim();
+    if raw.is_empty() {
+        return Err(format!("{key} is required"));
+    }
+    chrono::DateTime::parse_from_rfc3339(raw)
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .map_err(|err| format!("Invalid {key}: {err}"))
+}
+
+/// Cursors carry a fingerprint prefix so a page request after a reload restarts cleanly
+/// instead of skipping or repeating rows.
+fn parse_window_cursor(cursor: &str, fingerprint: &str) -> Result<usize, String> {
+    let (prefix, index) = cursor
+        .split_once(':')
+        .ok_or_else(|| "Invalid cursor".to_string())?;
+    if !fingerprint.starts_with(prefix) || prefix.is_empty() {
+        return Err("Cursor is stale; calendar data changed".to_string());
+    }
+    index.parse().map_err(|_| "Invalid cursor".to_string())
+}
+
+/// Raw events in `[fromUtc, toUtc)` for timeline views, paged by `cursor`. No display
+/// formatting: times are UTC RFC 3339 and impact is numeric.
+#[tauri::command]
+pub fn get_events_window(
+    app: tauri::AppHandle,
+    payload: Value,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Value {
+    let range = parse_utc_param(&payload, "fromUtc")
+        .and_then(|from| Ok((from, parse_utc_param(&payload, "toUtc")?)));
+    let (from, to) = match range {
+        Ok((from, to)) if from < to => (from, to),
+        Ok(_) => return json!({"ok": false, "message": "fromUtc must be before toUtc"}),
+        Err(message) => return json!({"ok": false, "message": message}),
+    };
+    let currency = payload
+        .get("currency")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_uppercase();
+    let min_impact = match payload.get("impact").and_then(|v| v.as_str()) {
+        None | Some("") => 0,
+        Some(impact) => match impact_rank(impact) {
+            0 => return json!({"ok": false, "message": format!("Unknown impact: {impact}")}),
+            rank => rank,
+        },
+    };
+    let limit = payload
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(500)
+        .clamp(1, 2000) as usize;
+
+    let cfg = config::load_config();
+    ensure_calendar_loaded(app, cfg, state.clone());
+    let (events, fingerprint) = {
+        let runtime = state.lock().expect("runtime lock");
+        (
+            runtime.calendar.events.clone(),
+            runtime.calendar.fingerprint.clone(),
+        )
+    };
+    let start = match payload.get("cursor").and_then(|v| v.as_str()) {
+        Some(cursor) if !cursor.is_empty() => match parse_window_cursor(cursor, &fingerprint) {
+            Ok(start) => start,
+            Err(message) => return json!({"ok": false, "message": message}),
+        },
+        _ => 0,
+    };
+    let filter = WindowFilter {
+        from,
+        to,
+        currency: if currency == "ALL" {
+            String::new()
+        } else {
+            currency
+        },
+        min_impact,
+        categories: category_filter(Some(&payload)),
+    };
+    let page = events_window(&events, &filter, start, limit);
+    let mut seen: HashMap<String, i32> = HashMap::new();
+    let items: Vec<Value> = page
+        .items
+        .iter()
+        .map(|e| {
+            json!({
+                "id": next_event_id(e, &mut seen),
+                "dtUtc": e.dt_utc.to_rfc3339(),
+                "event": e.event,
+                "currency": e.currency,
+                "importance": e.importance,
+                "importanceLevel": impact_rank(&e.importance),
+                "actual": e.actual,
+                "forecast": e.forecast,
+                "previous": e.previous,
+                "isAllDay": !e.time_label.contains(':'),
+                "timeConfirmed": e.time_confirmed,
+                "categories": e.categories,
+                "source": e.source.as_str(),
+            })
+        })
+        .collect();
+    let prefix: String = fingerprint.chars().take(8).collect();
+    json!({
+        "ok": true,
+        "events": items,
+        "nextCursor": page.next.map(|idx| format!("{prefix}:{idx}")),
+    })
+}
+
+/// Active category taxonomy with how many upcoming loaded events carry each category.
+#[tauri::command]
+pub fn get_categories(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let cfg = config::load_config();
+    let options = CalendarLoadOptions::from_config(&cfg);
+    let mut warnings = vec![];
+    let rules = CategoryRules::load(options.category_override.as_deref(), &mut warnings);
+    let events = state.lock().expect("runtime lock").calendar.events.clone();
+    let now = crate::time_util::now_utc();
+    let categories: Vec<Value> = rules
+        .rules()
+        .map(|rule| {
+            let upcoming = events
+                .iter()
+                .filter(|e| e.dt_utc >= now && e.categories.contains(&rule.category))
+                .count();
+            json!({
+                "name": rule.category,
+                "pattern": rule.pattern,
+                "source": rule.source,
+                "upcoming": upcoming,
+            })
+        })
+        .collect();
+    json!({"ok": true, "categories": categories, "warnings": warnings})
+}
+
+/// `event` lower-cased and `cur` upper-cased from an explain payload; `None` without an event.
+fn event_query(payload: &Value) -> Option<(String, String)> {
+    let text = |key: &str| {
+        payload
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .trim()
+            .to_string()
+    };
+    let needle = text("event").to_lowercase();
+    (!needle.is_empty()).then(|| (needle, text("cur").to_uppercase()))
+}
+
+/// The list filters in effect, as `explain_visibility` checks them.
+struct ListFilters {
+    currency: String,
+    categories: Vec<String>,
+    impact: Vec<String>,
+    min_forecast_change_pct: Option<f64>,
+}
+
+fn check_json(check: &str, passed: bool, detail: String) -> Value {
+    json!({"check": check, "passed": passed, "detail": detail})
+}
+
+/// Every filter check for each loaded event accepted by `matches`, plus two data checks: whether
+/// any loaded event matched at all and whether the date parser dropped matching rows. Each match
+/// also names the filter that hides it (`reason`/`detail`). With `search_disk` and no loaded
+/// match, every year file on disk is searched too.
+fn explain_visibility(
+    events: &[CalendarEvent],
+    matches: &dyn Fn(&str, &str) -> bool,
+    filters: &ListFilters,
+    repo: Option<(&Path, &CalendarLoadOptions)>,
+    search_disk: bool,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Value {
+    const MAX_MATCHES: usize = 50;
+    let matched: Vec<usize> = (0..events.len())
+        .filter(|&idx| matches(&events[idx].event, &events[idx].currency.to_uppercase()))
+        .collect();
+    let items: Vec<Value> = matched
+        .iter()
+        .take(MAX_MATCHES)
+        .map(|&idx| {
+            let e = &events[idx];
+            let mut visibility = event_visibility(
+                events,
+                idx,
+                &filters.currency,
+                &filters.categories,
+                filters.min_forecast_change_pct,
+                now,
+            );
+            if visibility.is_shown() && !impact_selected(&filters.impact, e) {
+                visibility = EventVisibility::Importance;
+            }
+            let checks = visibility_checks(
+                events,
+                idx,
+                &filters.currency,
+                &filters.categories,
+                &filters.impact,
+                filters.min_forecast_change_pct,
+                now,
+            );
+            let file = match e.source {
+                EventSource::Calendar => repo
+                    .and_then(|(root, _)| event_year_file(root, e))
+                    .map(|(path, _)| path),
+                EventSource::Custom => repo.and_then(|(_, options)| options.custom_events.clone()),
+                EventSource::Placeholder => None,
+            };
+            json!({
+                "time": e.dt_utc.to_rfc3339(),
+                "cur": e.currency,
+                "impact": e.importance,
+                "event": e.event,
+                "source": e.source.as_str(),
+                "file": file.map(|path| path.to_string_lossy().to_string()),
+                "shown": checks.iter().all(|check| check.passed),
+                "reason": visibility.as_str(),
+                "detail": visibility.describe(),
+                "checks": checks.iter().map(|check| check.to_json()).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    let (mut on_disk, mut dropped) = (vec![], vec![]);
+    if let (Some((root, options)), true) = (repo, search_disk) {
+        if matched.is_empty() {
+            on_disk = find_rows_on_disk(root, options, matches)
+                .into_iter()
+                .take(MAX_MATCHES)
+                .map(|(file, e)| {
+                    json!({
+                        "file": file.to_string_lossy(),
+                        "time": e.dt_utc.to_rfc3339(),
+                        "cur": e.currency,
+                        "event": e.event,
+                    })
+                })
+                .collect();
+        }
+        dropped = find_dropped_rows(root, options, matches)
+            .into_iter()
+            .take(MAX_MATCHES)
+            .map(|row| {
+                json!({
+                    "file": row.file.to_string_lossy(),
+                    "date": row.date,
+                    "time": row.time,
+                    "cur": row.currency,
+                    "event": row.event,
+                    "reason": "parse",
+                    "detail": "Dropped while loading: the date/time could not be parsed",
+                })
+            })
+            .collect();
+    }
+
+    let found = if !matched.is_empty() {
+        check_json(
+            "found",
+            true,
+            format!("{} loaded events match", matched.len()),
+        )
+    } else if !on_disk.is_empty() {
+        check_json(
+            "found",
+            false,
+            format!(
+                "Not in the loaded events, but {} rows match in the year files on disk",
+                on_disk.len()
+            ),
+        )
+    } else if repo.is_some() && search_disk {
+        check_json(
+            "found",
+            false,
+            "No loaded event or year file row matches; it is missing from the data".to_string(),
+        )
+    } else {
+        check_json(
+            "found",
+            false,
+            "No loaded event matches; it may be outside the loaded years or missing from the data"
+                .to_string(),
+        )
+    };
+    let parse = match (repo.is_some() && search_disk, dropped.len()) {
+        (false, _) => check_json(
+            "parse",
+            true,
+            "The year files were not searched for unparseable rows".to_string(),
+        ),
+        (true, 0) => check_json(
+            "parse",
+            true,
+            "No matching rows were dropped for an unparseable date".to_string(),
+        ),
+        (true, n) => check_json(
+            "parse",
+            false,
+            format!(
+                "{n} matching rows were dropped while loading: the date/time could not be parsed"
+            ),
+        ),
+    };
+    let message = if matched.is_empty() {
+        found["detail"].clone()
+    } else {
+        json!("")
+    };
+    json!({
+        "currency": filters.currency,
+        "message": message,
+        "checks": [found, parse],
+        "matches": items,
+        "total": matched.len(),
+        "onDisk": on_disk,
+        "dropped": dropped,
     })
 }
+
+/// Answers "why don't I see this event": for loaded events whose name contains `event`
+/// (case-insensitive, optionally limited to `cur`), whether the UI shows each, every list filter
+/// as a pass/fail check with a sentence, and the file each came from. `categoryFilter` and
+/// `impactFilter` take the UI's current filters. Unless `searchDisk` is false, the year files are
+/// also searched for rows the date parser dropped and, with no loaded match, for matching rows.
+#[tauri::command(async)]
+pub fn explain_event(state: tauri::State<'_, Mutex<RuntimeState>>, payload: Value) -> Value {
+    let Some((needle, cur)) = event_query(&payload) else {
+        return json!({"ok": false, "message": "event is required"});
+    };
+    let matches = |event: &str, currency: &str| {
+        event.to_lowercase().contains(&needle) && (cur.is_empty() || currency == cur)
+    };
+    let (events, selected, warnings) = {
+        let runtime = state.lock().expect("runtime lock");
+        (
+            runtime.calendar.events.clone(),
+            runtime.currency.clone(),
+            runtime.calendar.warnings.clone(),
+        )
+    };
+    let cfg = config::load_config();
+    let filters = ListFilters {
+        currency: if selected.is_empty() {
+            "USD".to_string()
+        } else {
+            selected
+        },
+        categories: category_filter(Some(&payload)),
+        impact: impact_filter(&payload),
+        min_forecast_change_pct: min_forecast_change_pct(&cfg),
+    };
+    let search_disk = payload
+        .get("searchDisk")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let repo_path = resolve_calendar_repo_path(&cfg);
+    let options = CalendarLoadOptions::from_config(&cfg);
+    let mut result = explain_visibility(
+        &events,
+        &matches,
+        &filters,
+        repo_path.as_deref().map(|path| (path, &options)),
+        search_disk,
+        crate::time_util::now_utc(),
+    );
+    result["ok"] = json!(true);
+    result["loadWarnings"] = json!(warnings);
+    result
+}
+
+/// One-line text for status bars: the nearest upcoming event passing the alert filters, rendered
+/// with `format` (see `render_status_line`). `cur` overrides the selected currency. Empty when
+/// nothing qualifies.
+#[tauri::command]
+pub fn get_status_line(
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+    payload: Option<Value>,
+) -> String {
+    let cfg = config::load_config();
+    let min_rank = impact_rank(&AppSettings::from_config(&cfg).event_alert_min_impact).max(1);
+    let (events, selected) = {
+        let runtime = state.lock().expect("runtime lock");
+        (runtime.calendar.events.clone(), runtime.currency.clone())
+    };
+    let text = |key: &str| {
+        payload
+            .as_ref()
+            .and_then(|p| p.get(key))
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.trim().is_empty())
+            .map(str::to_string)
+    };
+    let currency = text("cur").unwrap_or(selected).trim().to_uppercase();
+    let format = text("format").unwrap_or_else(|| STATUS_LINE_FORMAT.to_string());
+    let now = crate::time_util::now_utc();
+    events
+        .iter()
+        .find(|e| e.dt_utc > now && super::notify::alert_qualifies(e, &currency, min_rank))
+        .map(|e| render_status_line(&format, e, now))
+        .unwrap_or_default()
+}
+
+/// Countdown and state per row id, cheap enough to poll between full snapshots. `ids` limits
+/// the answer to those rows; without it every upcoming row is returned.
+#[tauri::command]
+pub fn get_countdowns(
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+    payload: Option<Value>,
+) -> Value {
+    let ids: Option<Vec<String>> = payload
+        .as_ref()
+        .and_then(|p| p.get("ids"))
+        .and_then(|v| v.as_array())
+        .map(|ids| {
+            ids.iter()
+                .filter_map(|id| id.as_str().map(str::to_string))
+                .collect()
+        });
+    let (events, index) = {
+        let runtime = state.lock().expect("runtime lock");
+        (
+            runtime.calendar.events.clone(),
+            runtime.calendar.event_index.clone(),
+        )
+    };
+    let now = crate::time_util::now_utc();
+    json!({
+        "ok": true,
+        "countdowns": crate::snapshot::countdowns(&events, &index, ids.as_deref(), now),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Duration, Utc};
+
+    fn write_year(repo: &Path, year: i32, rows: &str) {
+        let dir = repo.join(format!("data/Economic_Calendar/{year}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(format!("{year}_calendar.json")), rows).unwrap();
+    }
+
+    fn checks(result: &Value) -> Vec<(String, bool)> {
+        result["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| {
+                (
+                    c["check"].as_str().unwrap().to_string(),
+                    c["passed"] == true,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn focused_event_is_reported_once() {
+        let now = Utc::now();
+        let event = |hours: i64| CalendarEvent {
+            dt_utc: now + Duration::hours(hours),
+            time_label: "12:30".to_string(),
+            event: format!("CPI {hours}"),
+            currency: "USD".to_string(),
+            importance: "high".to_string(),
+            actual: String::new(),
+            forecast: String::new(),
+            previous: String::new(),
+            time_confirmed: true,
+            categories: vec![],
+            source: EventSource::Calendar,
+        };
+        let mut runtime = RuntimeState::default();
+        runtime.calendar.events = std::sync::Arc::new(vec![event(2), event(-2)]);
+        let mut seen: HashMap<String, i32> = HashMap::new();
+        let ids: Vec<String> = runtime
+            .calendar
+            .events
+            .iter()
+            .map(|e| next_event_id(e, &mut seen))
+            .collect();
+
+        assert_eq!(take_focus_event(&mut runtime, now), None);
+        runtime.pending_focus_event_id = Some(ids[0].clone());
+        assert_eq!(
+            take_focus_event(&mut runtime, now),
+            Some((ids[0].clone(), "upcoming"))
+        );
+        assert_eq!(take_focus_event(&mut runtime, now), None);
+
+        runtime.pending_focus_event_id = Some(ids[1].clone());
+        assert_eq!(
+            take_focus_event(&mut runtime, now),
+            Some((ids[1].clone(), "past"))
+        );
+        runtime.pending_focus_event_id = Some("usd-missing".to_string());
+        assert_eq!(
+            take_focus_event(&mut runtime, now),
+            Some(("usd-missing".to_string(), "gone"))
+        );
+        assert_eq!(runtime.pending_focus_event_id, None);
+    }
+
+    #[test]
+    fn explains_missing_events_from_cache_and_disk() {
+        let repo = tempfile::tempdir().unwrap();
+        let year = Utc::now().year();
+        write_year(
+            repo.path(),
+            year,
+            r#"[{"Date": "not a date", "Time": "13:30", "Event": "Non-Farm Payrolls", "Cur.": "USD"}]"#,
+        );
+        write_year(
+            repo.path(),
+            2019,
+            r#"[{"Date": "2019-03-08", "Time": "13:30", "Event": "Non-Farm Payrolls", "Cur.": "USD"}]"#,
+        );
+        let options = CalendarLoadOptions::default();
+        let filters = ListFilters {
+            currency: "EUR".to_string(),
+            categories: vec![],
+            impact: vec![],
+            min_forecast_change_pct: None,
+        };
+        let nfp = |event: &str, _: &str| event.to_lowercase().contains("payrolls");
+
+        let result = explain_visibility(
+            &[],
+            &nfp,
+            &filters,
+            Some((repo.path(), &options)),
+            true,
+            Utc::now(),
+        );
+        assert_eq!(
+            checks(&result),
+            vec![("found".to_string(), false), ("parse".to_string(), false)]
+        );
+        assert_eq!(result["onDisk"].as_array().unwrap().len(), 1);
+        assert!(result["onDisk"][0]["file"]
+            .as_str()
+            .unwrap()
+            .ends_with("2019_calendar.json"));
+        assert_eq!(result["dropped"][0]["date"], "not a date");
+        assert_eq!(result["dropped"][0]["reason"], "parse");
+        assert!(result["message"]
+            .as_str()
+            .unwrap()
+            .contains("rows match in the year files on disk"));
+
+        let (events, _) = crate::calendar::load_year_file(
+            &repo
+                .path()
+                .join("data/Economic_Calendar/2019/2019_calendar.json"),
+            &options.source_date_format,
+            &options.limits,
+        );
+        let mut events = events;
+        events[0].dt_utc = Utc::now() + Duration::hours(2);
+        let result = explain_visibility(&events, &nfp, &filters, None, true, Utc::now());
+        assert_eq!(
+            checks(&result),
+            vec![("found".to_string(), true), ("parse".to_string(), true)]
+        );
+        let item = &result["matches"][0];
+        assert_eq!(item["shown"], false);
+        assert_eq!(item["reason"], "currency");
+        assert_eq!(result["message"], "");
+        let failed: Vec<_> = item["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|c| c["passed"] == false)
+            .map(|c| c["check"].as_str().unwrap())
+            .collect();
+        assert_eq!(failed, vec!["currency"]);
+    }
+}
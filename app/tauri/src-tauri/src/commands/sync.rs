@@ -1,77 +1,539 @@
 use super::*;
+use crate::sync_util::{DeleteThreshold, DeletionDecision, DeletionPrompt, SyncPlan};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+
+const DELETION_MODAL_ID: &str = "sync-deletions";
+
+fn deletion_modal(prompt: &DeletionPrompt, timeout: Duration) -> Modal {
+    let more = prompt.count.saturating_sub(prompt.examples.len());
+    let mut examples = prompt.examples.join(", ");
+    if more > 0 {
+        examples.push_str(&format!(" and {more} more"));
+    }
+    Modal::new(
+        DELETION_MODAL_ID,
+        "Confirm Sync Deletions",
+        format!(
+            "Sync would delete {} files from {} that are not in the working data ({examples}). \
+             Files copied so far are kept. Without an answer within {} min the files are kept.",
+            prompt.count,
+            prompt.destination.display(),
+            timeout.as_secs().div_ceil(60)
+        ),
+        ModalTone::Warn,
+    )
+    .with_action(ModalAction::command(
+        "Delete files",
+        "confirm_sync_deletions",
+    ))
+    .with_action(ModalAction::command("Keep files", "cancel_sync_deletions"))
+}
+
+/// The `sync_scope` `output_dir` was last synced with. Builds before the setting always
+/// mirrored everything, so an unrecorded dir counts as "full".
+fn last_sync_scope<'a>(cfg: &'a Value, output_dir: &str) -> &'a str {
+    cfg.get("output_dir_sync_scope")
+        .and_then(|scopes| scopes.get(output_dir))
+        .and_then(|v| v.as_str())
+        .unwrap_or("full")
+}
+
+/// The configured threshold, except that the first sync after a `sync_scope` change asks
+/// before deleting anything: the destination still holds the other scope's files.
+fn delete_threshold_for(cfg: &Value, output_dir: &str, scope: &str) -> DeleteThreshold {
+    if last_sync_scope(cfg, output_dir) != scope {
+        DeleteThreshold::every_deletion()
+    } else {
+        DeleteThreshold::from_config(cfg)
+    }
+}
+
+/// Lets `plan`'s delete pass run at once when it stays under `threshold`. Otherwise the sync
+/// pauses here: the prompt goes on `RuntimeState` (and a modal) until it is answered or
+/// `timeout` passes, which keeps the files. `paused` is raised once the prompt ends so the
+/// sync timeout can leave the wait out.
+fn approve_deletions(
+    app: &tauri::AppHandle,
+    plan: &SyncPlan,
+    threshold: DeleteThreshold,
+    timeout: Duration,
+    cancel: &AtomicBool,
+    paused: &AtomicBool,
+) -> bool {
+    if !threshold.exceeded(plan.delete.len(), plan.destination_files) {
+        return true;
+    }
+    if cancel.load(AtomicOrdering::SeqCst) {
+        return false;
+    }
+    let prompt = Arc::new(DeletionPrompt::new(plan));
+    let state = app.state::<Mutex<RuntimeState>>();
+    {
+        let mut runtime = state.lock().expect("runtime lock");
+        runtime.sync_deletion_prompt = Some(prompt.clone());
+        push_log(
+            &mut runtime,
+            &format!(
+                "Sync paused: {} files would be deleted from {}; waiting for confirmation",
+                prompt.count,
+                prompt.destination.display()
+            ),
+            "WARN",
+        );
+        runtime.set_modal(deletion_modal(&prompt, timeout));
+    }
+    let decision = prompt.wait(timeout);
+    paused.store(true, AtomicOrdering::SeqCst);
+    let mut runtime = state.lock().expect("runtime lock");
+    if runtime
+        .sync_deletion_prompt
+        .as_ref()
+        .is_some_and(|current| Arc::ptr_eq(current, &prompt))
+    {
+        runtime.sync_deletion_prompt = None;
+    }
+    runtime.clear_modal(DELETION_MODAL_ID);
+    let message = match decision {
+        DeletionDecision::Confirm => "Sync deletions confirmed",
+        DeletionDecision::Cancel => "Sync deletions cancelled; the files were kept",
+        DeletionDecision::TimedOut => "Sync deletions not confirmed in time; the files were kept",
+    };
+    push_log(&mut runtime, message, "INFO");
+    decision == DeletionDecision::Confirm && !cancel.load(AtomicOrdering::SeqCst)
+}
+
+/// Answers the pending deletion prompt, if any.
+fn resolve_sync_deletions(runtime: &RuntimeState, decision: DeletionDecision) -> Value {
+    let Some(prompt) = runtime.sync_deletion_prompt.as_ref() else {
+        return json!({"ok": false, "message": "No sync is waiting for confirmation"});
+    };
+    if !prompt.resolve(decision) {
+        return json!({"ok": false, "message": "The deletions were already answered"});
+    }
+    json!({"ok": true})
+}
+
+#[tauri::command]
+pub fn confirm_sync_deletions(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let runtime = state.lock().expect("runtime lock");
+    resolve_sync_deletions(&runtime, DeletionDecision::Confirm)
+}
+
+#[tauri::command]
+pub fn cancel_sync_deletions(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let runtime = state.lock().expect("runtime lock");
+    resolve_sync_deletions(&runtime, DeletionDecision::Cancel)
+}
+
+/// Tray "Sync deletions" answers, by menu id.
+pub(crate) const TRAY_SYNC_DELETIONS_ITEMS: &[(&str, &str)] = &[
+    ("tray:sync-deletions:confirm", "Delete files"),
+    ("tray:sync-deletions:cancel", "Keep files"),
+];
+
+fn tray_deletion_decision(id: &str) -> Option<DeletionDecision> {
+    match id.strip_prefix("tray:sync-deletions:")? {
+        "confirm" => Some(DeletionDecision::Confirm),
+        "cancel" => Some(DeletionDecision::Cancel),
+        _ => None,
+    }
+}
+
+/// Answers the deletion prompt from the tray; logs when there is nothing to answer.
+pub(crate) fn handle_tray_sync_deletions(app: &tauri::AppHandle, id: &str) {
+    let Some(decision) = tray_deletion_decision(id) else {
+        return;
+    };
+    let state = app.state::<Mutex<RuntimeState>>();
+    let mut runtime = state.lock().expect("runtime lock");
+    let result = resolve_sync_deletions(&runtime, decision);
+    if let Some(message) = result.get("message").and_then(|v| v.as_str()) {
+        let message = message.to_string();
+        push_log(&mut runtime, &message, "INFO");
+    }
+}
 
 #[tauri::command]
 pub fn sync_now(
     app: tauri::AppHandle,
     state: tauri::State<'_, Mutex<RuntimeState>>,
 ) -> Result<Value, String> {
+    spawn_sync(app, state, "Sync started");
+    Ok(json!({"ok": true}))
+}
+
+/// Waits for the sync worker's result. After `timeout` with no result, `cancel` is raised so
+/// the worker stops, and the sync fails as timed out; a timeout during which
+/// `waiting_on_user` holds starts a new wait instead.
+fn await_sync_result(
+    rx: &Receiver<Result<sync_util::SyncResult, String>>,
+    timeout: Duration,
+    cancel: &AtomicBool,
+    mut waiting_on_user: impl FnMut() -> bool,
+) -> Result<sync_util::SyncResult, String> {
+    loop {
+        match rx.recv_timeout(timeout) {
+            Ok(result) => return result,
+            Err(RecvTimeoutError::Timeout) if waiting_on_user() => continue,
+            Err(_) => {
+                cancel.store(true, AtomicOrdering::SeqCst);
+                return Err(format!("timed out after {}s", timeout.as_secs()));
+            }
+        }
+    }
+}
+
+/// Logs how a sync ended and clears `sync_active`. Returns the result of a sync that finished,
+/// whose time is then persisted.
+fn record_sync_result(
+    runtime: &mut RuntimeState,
+    result: Result<sync_util::SyncResult, String>,
+) -> Option<sync_util::SyncResult> {
+    runtime.sync_active = false;
+    match result {
+        Ok(res) if res.cancelled => {
+            push_log(
+                runtime,
+                &format!("Sync cancelled after copying {} files", res.copied),
+                "WARN",
+            );
+            None
+        }
+        Ok(res) => {
+            runtime.last_sync_error.clear();
+            runtime.last_sync = now_display_time();
+            runtime.last_sync_at = now_iso_time();
+            let mut message = format!(
+                "Sync finished (copied {}, deleted {}, skipped {})",
+                res.copied, res.deleted, res.skipped
+            );
+            if res.deletions_declined > 0 {
+                message.push_str(&format!(
+                    "; kept {} files that were not confirmed for deletion",
+                    res.deletions_declined
+                ));
+            }
+            push_log(runtime, &message, "INFO");
+            Some(res)
+        }
+        Err(err) if err == sync_util::SYNC_CANCELLED => {
+            // Atomic mode discards the staged tree, leaving the destination as it was.
+            push_log(runtime, "Sync cancelled after copying 0 files", "WARN");
+            None
+        }
+        Err(err) => {
+            push_log(runtime, &format!("Sync failed: {err}"), "ERROR");
+            runtime.last_sync_error = err;
+            None
+        }
+    }
+}
+
+/// Mirrors the working data into the output dir in the background; a no-op while a sync runs.
+pub(super) fn spawn_sync(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+    reason: &str,
+) {
     let cfg = config::load_config();
     let output_dir = config::get_str(&cfg, "output_dir");
     let output_dir_key = output_dir.clone();
-    {
+    let settings = AppSettings::from_config(&cfg);
+    let atomic = settings.sync_atomic;
+    let scope = settings.sync_scope();
+    let timeout = Duration::from_secs(settings.sync_timeout_secs.max(1) as u64);
+    let threshold = delete_threshold_for(&cfg, &output_dir, scope);
+    let confirm_timeout =
+        Duration::from_secs(settings.sync_delete_confirm_timeout_secs.max(1) as u64);
+    let cancel = {
         let mut runtime = state.lock().expect("runtime lock");
+        if runtime.sync_active || runtime.resetting {
+            return;
+        }
         runtime.sync_active = true;
-        push_log(&mut runtime, "Sync started", "INFO");
-    }
-    tauri::async_runtime::spawn(async move {
-        let result = (|| -> Result<sync_util::SyncResult, String> {
-            if output_dir.trim().is_empty() {
-                return Err("Output dir not configured".to_string());
-            }
-            let base_src = config::working_data_dir(&cfg);
-            let base_dst = PathBuf::from(output_dir).join("data");
-
-            let mut total = sync_util::SyncResult::default();
-
-            let cal_src = base_src.join("Economic_Calendar");
-            let cal_dst = base_dst.join("Economic_Calendar");
-            let cal = sync_util::mirror_sync(&cal_src, &cal_dst)?;
-            total.copied += cal.copied;
-            total.deleted += cal.deleted;
-            total.skipped += cal.skipped;
-
-            let hist_src = base_src.join("event_history_index");
-            let hist_dst = base_dst.join("event_history_index");
-            let hist = sync_util::mirror_sync(&hist_src, &hist_dst)?;
-            total.copied += hist.copied;
-            total.deleted += hist.deleted;
-            total.skipped += hist.skipped;
-
-            Ok(total)
-        })();
+        runtime.sync_cancel = Arc::new(AtomicBool::new(false));
+        push_log(&mut runtime, reason, "INFO");
+        runtime.sync_cancel.clone()
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        // The copy runs on its own thread so a hung mount cannot pin `sync_active`; on timeout
+        // the worker is told to stop and its late result is dropped.
+        let (tx, rx) = std::sync::mpsc::channel();
+        let worker_cancel = cancel.clone();
+        let worker_app = app.clone();
+        let paused = Arc::new(AtomicBool::new(false));
+        let worker_paused = paused.clone();
+        std::thread::spawn(move || {
+            let approve = |plan: &SyncPlan| {
+                approve_deletions(
+                    &worker_app,
+                    plan,
+                    threshold,
+                    confirm_timeout,
+                    &worker_cancel,
+                    &worker_paused,
+                )
+            };
+            let result = (|| -> Result<sync_util::SyncResult, String> {
+                if output_dir.trim().is_empty() {
+                    return Err("Output dir not configured".to_string());
+                }
+                let base_src = config::working_data_dir(&cfg);
+                let base_dst = PathBuf::from(output_dir).join("data");
+
+                let cal_src = base_src.join("Economic_Calendar");
+                let cal_dst = base_dst.join("Economic_Calendar");
+                let hist_src = history::resolve_history_dir(&cfg, &config::working_root_dir(&cfg));
+                let hist_dst = base_dst.join("event_history_index");
+                // "current" copies the nearby calendar years and leaves the history out.
+                let include = (scope == "current")
+                    .then(|| sync_util::current_scope_years(chrono::Utc::now()));
+                let mut pairs = vec![(cal_src.as_path(), cal_dst.as_path())];
+                if include.is_none() {
+                    pairs.push((&hist_src, &hist_dst));
+                }
+                sync_util::ensure_free_space(&pairs, &base_dst, sync_util::available_space)?;
+
+                let mut total = sync_util::SyncResult::default();
+                total.add(sync_util::mirror_sync_filtered(
+                    &cal_src,
+                    &cal_dst,
+                    include.as_ref(),
+                    atomic,
+                    &worker_cancel,
+                    &approve,
+                )?);
+                if !total.cancelled && include.is_none() {
+                    total.add(sync_util::mirror_sync_filtered(
+                        &hist_src,
+                        &hist_dst,
+                        None,
+                        atomic,
+                        &worker_cancel,
+                        &approve,
+                    )?);
+                }
+                if !total.cancelled {
+                    sync_util::write_sync_manifest(&base_dst)
+                        .map_err(|e| format!("writing {}: {e}", sync_util::SYNC_MANIFEST_FILE))?;
+                }
+
+                Ok(total)
+            })();
+            let _ = tx.send(result);
+        });
+        // Time spent waiting for a deletion answer does not count against the timeout.
+        let awaiting_answer = || {
+            let runtime_state = app.state::<Mutex<RuntimeState>>();
+            let runtime = runtime_state.lock().expect("runtime lock");
+            runtime.sync_deletion_prompt.is_some()
+        };
+        let result = await_sync_result(&rx, timeout, &cancel, || {
+            paused.swap(false, AtomicOrdering::SeqCst) || awaiting_answer()
+        });
         let runtime_state = app.state::<Mutex<RuntimeState>>();
         let mut runtime = runtime_state.lock().expect("runtime lock");
-        runtime.sync_active = false;
-        match result {
-            Ok(res) => {
-                runtime.last_sync = now_display_time();
-                let last_sync_at = now_iso_time();
-                runtime.last_sync_at = last_sync_at.clone();
-                push_log(
-                    &mut runtime,
-                    &format!(
-                        "Sync finished (copied {}, deleted {}, skipped {})",
-                        res.copied, res.deleted, res.skipped
-                    ),
-                    "INFO",
-                );
-
-                // Persist last sync per output dir.
-                drop(runtime);
-                let mut cfg = config::load_config();
-                let _ = config::set_string(&mut cfg, "last_sync_at", last_sync_at.clone());
-                set_object_string(
-                    &mut cfg,
-                    "output_dir_last_sync_at",
-                    &output_dir_key,
-                    &last_sync_at,
-                );
-                let _ = config::save_config(&cfg);
-            }
-            Err(err) => {
-                push_log(&mut runtime, &format!("Sync failed: {err}"), "ERROR");
+        if let Some(res) = record_sync_result(&mut runtime, result) {
+            let last_sync_at = runtime.last_sync_at.clone();
+            // Persist last sync per output dir.
+            drop(runtime);
+            let mut cfg = config::load_config();
+            let _ = config::set_string(&mut cfg, "last_sync_at", last_sync_at.clone());
+            set_object_string(
+                &mut cfg,
+                "output_dir_last_sync_at",
+                &output_dir_key,
+                &last_sync_at,
+            );
+            // A declined delete pass leaves the old scope's files, so it asks again next time.
+            if res.deletions_declined == 0 {
+                set_object_string(&mut cfg, "output_dir_sync_scope", &output_dir_key, scope);
             }
+            let _ = config::save_config(&cfg);
         }
+        super::lifecycle::spawn_status_file_refresh(&app);
     });
-    Ok(json!({"ok": true}))
+}
+
+#[tauri::command]
+pub fn cancel_sync(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    request_sync_cancel(&state.lock().expect("runtime lock"))
+}
+
+fn request_sync_cancel(runtime: &RuntimeState) -> Value {
+    if !runtime.sync_active {
+        return json!({"ok": false, "message": "No sync running"});
+    }
+    runtime.sync_cancel.store(true, AtomicOrdering::SeqCst);
+    // A sync paused on the deletion prompt would otherwise sleep until it times out.
+    if let Some(prompt) = runtime.sync_deletion_prompt.as_ref() {
+        prompt.resolve(DeletionDecision::Cancel);
+    }
+    json!({"ok": true})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deletion_prompt_resolves_once_from_commands() {
+        let mut runtime = RuntimeState::default();
+        assert_eq!(
+            resolve_sync_deletions(&runtime, DeletionDecision::Confirm)["ok"],
+            false
+        );
+
+        let plan = SyncPlan {
+            delete: vec![("2019/2019_calendar.json".to_string(), PathBuf::new())],
+            dst_dir: PathBuf::from("/out/data/Economic_Calendar"),
+            destination_files: 2,
+            ..SyncPlan::default()
+        };
+        for (decision, approved) in [
+            (DeletionDecision::Confirm, true),
+            (DeletionDecision::Cancel, false),
+        ] {
+            let prompt = Arc::new(DeletionPrompt::new(&plan));
+            runtime.sync_deletion_prompt = Some(prompt.clone());
+            let waiter = prompt.clone();
+            let worker = std::thread::spawn(move || {
+                waiter.wait(Duration::from_secs(10)) == DeletionDecision::Confirm
+            });
+            assert_eq!(resolve_sync_deletions(&runtime, decision)["ok"], true);
+            assert_eq!(worker.join().unwrap(), approved);
+            // The other button, pressed late, does not change the outcome.
+            let late = resolve_sync_deletions(&runtime, DeletionDecision::Confirm);
+            assert_eq!(late["ok"], false);
+            assert_eq!(prompt.wait(Duration::ZERO), decision);
+        }
+
+        let tray: Vec<_> = TRAY_SYNC_DELETIONS_ITEMS
+            .iter()
+            .map(|(id, _)| tray_deletion_decision(id))
+            .collect();
+        assert_eq!(
+            tray,
+            vec![
+                Some(DeletionDecision::Confirm),
+                Some(DeletionDecision::Cancel)
+            ]
+        );
+        assert_eq!(tray_deletion_decision("tray:mute:60"), None);
+
+        let modal = deletion_modal(&DeletionPrompt::new(&plan), Duration::from_secs(300));
+        assert_eq!(modal.id, DELETION_MODAL_ID);
+        assert_eq!(modal.actions.len(), 2);
+        let message = &modal.message;
+        assert!(message.contains("delete 1 files from /out/data/Economic_Calendar"));
+        assert!(message.contains("2019/2019_calendar.json"));
+        assert!(message.contains("within 5 min"));
+    }
+
+    #[test]
+    fn a_stalled_sync_times_out_and_stops_its_worker() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let cancel = AtomicBool::new(false);
+        let result = await_sync_result(&rx, Duration::from_millis(20), &cancel, || false);
+        assert_eq!(result.err().as_deref(), Some("timed out after 0s"));
+        assert!(cancel.load(AtomicOrdering::SeqCst));
+
+        // Waits spent on the user do not count; the result still arrives.
+        let cancel = AtomicBool::new(false);
+        let mut prompts = 0;
+        let sender = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(60));
+            let _ = tx.send(Ok(sync_util::SyncResult {
+                copied: 3,
+                ..Default::default()
+            }));
+        });
+        let result = await_sync_result(&rx, Duration::from_millis(20), &cancel, || {
+            prompts += 1;
+            true
+        });
+        sender.join().unwrap();
+        assert_eq!(result.unwrap().copied, 3);
+        assert!(prompts > 0);
+        assert!(!cancel.load(AtomicOrdering::SeqCst));
+
+        let mut runtime = RuntimeState {
+            sync_active: true,
+            ..RuntimeState::default()
+        };
+        let finished = record_sync_result(&mut runtime, Err("timed out after 120s".to_string()));
+        assert!(finished.is_none());
+        assert!(!runtime.sync_active);
+        assert_eq!(runtime.last_sync_error, "timed out after 120s");
+        assert_eq!(runtime.logs[0]["level"], "ERROR");
+        assert_eq!(
+            runtime.logs[0]["message"],
+            "Sync failed: timed out after 120s"
+        );
+    }
+
+    #[test]
+    fn cancel_sync_stops_the_running_sync() {
+        let mut runtime = RuntimeState::default();
+        assert_eq!(request_sync_cancel(&runtime)["ok"], false);
+
+        runtime.sync_active = true;
+        let prompt = Arc::new(DeletionPrompt::new(&SyncPlan::default()));
+        runtime.sync_deletion_prompt = Some(prompt.clone());
+        assert_eq!(request_sync_cancel(&runtime)["ok"], true);
+        assert!(runtime.sync_cancel.load(AtomicOrdering::SeqCst));
+        // A sync paused on the deletion prompt is released rather than left to time out.
+        assert_eq!(prompt.wait(Duration::ZERO), DeletionDecision::Cancel);
+
+        let partial = sync_util::SyncResult {
+            copied: 2,
+            cancelled: true,
+            ..Default::default()
+        };
+        assert!(record_sync_result(&mut runtime, Ok(partial)).is_none());
+        assert!(!runtime.sync_active);
+        assert!(runtime.last_sync_at.is_empty());
+        assert_eq!(runtime.logs[0]["level"], "WARN");
+        assert_eq!(
+            runtime.logs[0]["message"],
+            "Sync cancelled after copying 2 files"
+        );
+
+        runtime.sync_active = true;
+        let staged = Err(sync_util::SYNC_CANCELLED.to_string());
+        assert!(record_sync_result(&mut runtime, staged).is_none());
+        assert!(runtime.last_sync_error.is_empty());
+        assert_eq!(
+            runtime.logs[0]["message"],
+            "Sync cancelled after copying 0 files"
+        );
+    }
+
+    #[test]
+    fn a_scope_change_asks_before_any_deletion() {
+        let configured = DeleteThreshold::from_config(&json!({}));
+        // A pre-existing full mirror: ten years of files, the switch would drop most of them.
+        let (planned, destination) = (1, 1000);
+        assert!(!configured.exceeded(planned, destination));
+
+        let unrecorded = json!({});
+        assert_eq!(
+            delete_threshold_for(&unrecorded, "D:/mt5", "full"),
+            configured
+        );
+        assert!(
+            delete_threshold_for(&unrecorded, "D:/mt5", "current").exceeded(planned, destination)
+        );
+
+        let recorded = json!({"output_dir_sync_scope": {"D:/mt5": "current"}});
+        assert_eq!(
+            delete_threshold_for(&recorded, "D:/mt5", "current"),
+            configured
+        );
+        assert!(delete_threshold_for(&recorded, "D:/mt5", "full").exceeded(planned, destination));
+        // Another output dir keeps its own record.
+        assert!(delete_threshold_for(&recorded, "E:/mt5", "current").exceeded(planned, destination));
+        assert!(!DeleteThreshold::every_deletion().exceeded(0, destination));
+    }
 }
@@ -1,4 +1,116 @@
 use super::*;
+use crate::runtime_store;
+use crate::time_util::random_jitter;
+use chrono::{DateTime, Utc};
+
+/// Fraction of a background interval that scheduled runs may drift by, either way.
+const SCHEDULE_JITTER: f64 = 0.1;
+
+/// Tick of the resume watcher. Waking more than `RESUME_FACTOR` ticks late means the machine
+/// slept in between.
+const RESUME_TICK: Duration = Duration::from_secs(60);
+const SCHEDULED_PULL_TICK: Duration = Duration::from_secs(5);
+const RESUME_FACTOR: i32 = 2;
+
+/// Whether an iteration that slept from `previous` and woke at `now` missed its wake time by
+/// enough to count as a resume from sleep. Wall-clock time is used because monotonic clocks
+/// stop during suspend on some platforms.
+fn resumed_from_sleep(previous: DateTime<Utc>, now: DateTime<Utc>, tick: chrono::Duration) -> bool {
+    now - previous > tick * RESUME_FACTOR
+}
+
+/// What the app knew before sleeping, for deciding what to catch up on.
+#[derive(Debug, Default)]
+struct ResumeStatus {
+    last_pull_at: Option<DateTime<Utc>>,
+    /// `check_interval_minutes`.
+    pull_interval: chrono::Duration,
+    /// Time of the last loaded event; once passed, the calendar has nothing ahead to show.
+    calendar_horizon: Option<DateTime<Utc>>,
+    update_checked_at: Option<DateTime<Utc>>,
+    /// `auto_update_interval_minutes`, or `None` when auto updates are off.
+    update_interval: Option<chrono::Duration>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct ResumeActions {
+    pull: bool,
+    check_updates: bool,
+}
+
+fn older_than(at: Option<DateTime<Utc>>, now: DateTime<Utc>, age: chrono::Duration) -> bool {
+    at.is_none_or(|at| now - at >= age)
+}
+
+/// Catch-up work after a resume: pull when the last pull is older than the check interval or
+/// the calendar horizon has passed, and check for updates when one is due.
+fn handle_resume(now: DateTime<Utc>, status: &ResumeStatus) -> ResumeActions {
+    let horizon_passed = status.calendar_horizon.is_none_or(|at| at <= now);
+    ResumeActions {
+        pull: horizon_passed || older_than(status.last_pull_at, now, status.pull_interval),
+        check_updates: status
+            .update_interval
+            .is_some_and(|interval| older_than(status.update_checked_at, now, interval)),
+    }
+}
+
+fn resume_status(app: &tauri::AppHandle) -> ResumeStatus {
+    let cfg = config::load_config();
+    let settings = AppSettings::from_config(&cfg);
+    let minutes = |minutes: i64| chrono::Duration::minutes(minutes.max(1));
+    let state = app.state::<Mutex<RuntimeState>>();
+    let runtime = state.lock().expect("runtime lock");
+    let last_pull_at = if runtime.last_pull_at.is_empty() {
+        config::get_str(&cfg, "last_pull_at")
+    } else {
+        runtime.last_pull_at.clone()
+    };
+    ResumeStatus {
+        last_pull_at: DateTime::parse_from_rfc3339(last_pull_at.trim())
+            .ok()
+            .map(|at| at.with_timezone(&Utc)),
+        pull_interval: minutes(settings.check_interval_minutes),
+        calendar_horizon: runtime.calendar.events.last().map(|e| e.dt_utc),
+        update_checked_at: runtime.update_checked_at,
+        update_interval: settings
+            .auto_update_enabled
+            .then(|| minutes(settings.auto_update_interval_minutes)),
+    }
+}
+
+fn catch_up_after_resume(app: &tauri::AppHandle, slept: chrono::Duration) {
+    let now = Utc::now();
+    let actions = handle_resume(now, &resume_status(app));
+    let state = app.state::<Mutex<RuntimeState>>();
+    {
+        let mut runtime = state.lock().expect("runtime lock");
+        push_log(
+            &mut runtime,
+            &format!(
+                "Resumed after {} min; pull: {}, update check: {}",
+                slept.num_minutes(),
+                if actions.pull { "yes" } else { "not due" },
+                if actions.check_updates {
+                    "yes"
+                } else {
+                    "not due"
+                }
+            ),
+            "INFO",
+        );
+    }
+    if actions.pull {
+        super::pull::spawn_pull(
+            app.clone(),
+            state.clone(),
+            "Pull after resume started",
+            false,
+        );
+    }
+    if actions.check_updates {
+        let _ = super::update::check_updates(app.clone(), state);
+    }
+}
 
 #[tauri::command]
 pub fn frontend_boot_complete(
@@ -12,15 +124,23 @@ pub fn frontend_boot_complete(
         if !runtime.boot_logged {
             runtime.boot_logged = true;
             push_log(&mut runtime, "Boot complete", "INFO");
+            let message = format!(
+                "Session started: version {}, data root {}, session {}",
+                env!("APP_VERSION"),
+                config::appdata_dir().display(),
+                runtime.session_id
+            );
+            push_log(&mut runtime, &message, "INFO");
         }
         !(runtime.auto_pull_started || runtime.pull_active)
     };
+    super::lifecycle::run_deferred_startup(&app);
     if should_auto_pull {
         {
             let mut runtime = state.lock().expect("runtime lock");
             runtime.auto_pull_started = true;
         }
-        super::pull::spawn_pull(app.clone(), state.clone(), "Auto pull started");
+        super::pull::spawn_pull(app.clone(), state.clone(), "Auto pull started", false);
     }
     let should_check_updates = {
         let mut runtime = state.lock().expect("runtime lock");
@@ -37,30 +157,250 @@ pub fn frontend_boot_complete(
     Ok(json!({"ok": true}))
 }
 
+/// Records the startup probe of the install dir and warns once when it is read-only.
+pub fn set_install_dir_writable(app: &tauri::AppHandle, writable: bool) {
+    let state = app.state::<Mutex<RuntimeState>>();
+    let mut runtime = state.lock().expect("runtime lock");
+    runtime.install_dir_writable = writable;
+    if !writable && config::portable_data_dir().is_none() {
+        push_log(
+            &mut runtime,
+            "Install directory is not writable; using AppData for all data",
+            "WARN",
+        );
+    }
+}
+
+/// Records whether the tray is usable. Without one, tray-based close and launch behavior would
+/// hide the window with no way back, so both fall back to a visible window.
+pub fn set_tray_supported(app: &tauri::AppHandle, supported: bool) {
+    let state = app.state::<Mutex<RuntimeState>>();
+    let mut runtime = state.lock().expect("runtime lock");
+    runtime.tray_supported = supported;
+    if !supported {
+        push_log(
+            &mut runtime,
+            "System tray unavailable; closing the window exits the app",
+            "WARN",
+        );
+    }
+}
+
+pub(crate) const APP_TITLE: &str = "XAUUSD Calendar Agent";
+
+/// `instance_label` without surrounding brackets, e.g. "DEV" for a dev build run next to the
+/// installed release. Empty when unset.
+pub(crate) fn instance_label(cfg: &Value) -> String {
+    config::get_str(cfg, "instance_label")
+        .trim()
+        .trim_matches(|c| c == '[' || c == ']')
+        .trim()
+        .to_string()
+}
+
+/// `"XAUUSD Calendar Agent [DEV]"` for label "DEV", the bare app name without one.
+pub(crate) fn window_title(label: &str) -> String {
+    if label.is_empty() {
+        APP_TITLE.to_string()
+    } else {
+        format!("{APP_TITLE} [{label}]")
+    }
+}
+
+/// Prefixes a notification title with `[label]` so alerts say which instance raised them.
+pub(crate) fn notification_title(label: &str, title: &str) -> String {
+    if label.is_empty() {
+        title.to_string()
+    } else {
+        format!("[{label}] {title}")
+    }
+}
+
+/// Titles the main window after `instance_label`.
+pub(crate) fn apply_window_title(app: &tauri::AppHandle, cfg: &Value) {
+    if let Some(win) = app.get_webview_window("main") {
+        let _ = win.set_title(&window_title(&instance_label(cfg)));
+    }
+}
+
+/// `second_instance_action`: "focus" (raise the window), "ignore", or "notify" (a notification
+/// instead of raising). Anything else falls back to "focus".
+pub fn second_instance_action(cfg: &Value) -> &'static str {
+    match config::get_str(cfg, "second_instance_action").as_str() {
+        "ignore" => "ignore",
+        "notify" => "notify",
+        _ => "focus",
+    }
+}
+
+/// Runs when the app is launched again while already running.
+pub fn handle_second_instance(app: &tauri::AppHandle) {
+    let cfg = config::load_config();
+    match second_instance_action(&cfg) {
+        "ignore" => {}
+        "notify" => {
+            use tauri_plugin_notification::NotificationExt;
+            let shown = app
+                .notification()
+                .builder()
+                .title(window_title(&instance_label(&cfg)))
+                .body("Already running in the background")
+                .show();
+            if let Err(err) = shown {
+                let state = app.state::<Mutex<RuntimeState>>();
+                let mut runtime = state.lock().expect("runtime lock");
+                push_log(
+                    &mut runtime,
+                    &format!("Second launch notification failed: {err}"),
+                    "WARN",
+                );
+            }
+        }
+        _ => crate::show_main_window(app),
+    }
+}
+
+/// `close_behavior` as configured, or "exit" when the tray is unavailable.
+pub fn effective_close_behavior(app: &tauri::AppHandle) -> String {
+    let close_to_tray = AppSettings::from_config(&config::load_config()).close_to_tray();
+    let state = app.state::<Mutex<RuntimeState>>();
+    let tray_supported = state.lock().expect("runtime lock").tray_supported;
+    if close_to_tray && tray_supported {
+        "tray".to_string()
+    } else {
+        "exit".to_string()
+    }
+}
+
 pub fn start_background_tasks(app: tauri::AppHandle) {
     let app_handle = app.clone();
     tauri::async_runtime::spawn_blocking(move || {
+        // Jitter spreads clients launched together (e.g. at market open) across the hour so
+        // they do not hit GitHub in lockstep.
         let interval = Duration::from_secs(60 * 60);
+        std::thread::sleep(Duration::from_secs(fastrand::u64(0..=120)));
         loop {
-            std::thread::sleep(interval);
+            let wait = random_jitter(interval, SCHEDULE_JITTER);
+            {
+                let state = app_handle.state::<Mutex<RuntimeState>>();
+                let mut runtime = state.lock().expect("runtime lock");
+                runtime.next_pull_at = Some(
+                    crate::time_util::now_utc()
+                        + chrono::Duration::from_std(wait).unwrap_or_default(),
+                );
+            }
+            std::thread::sleep(wait);
             let state = app_handle.state::<Mutex<RuntimeState>>();
-            super::pull::spawn_pull(app_handle.clone(), state, "Scheduled pull started");
+            super::pull::spawn_pull(app_handle.clone(), state, "Scheduled pull started", false);
         }
     });
 
-    // Watch config changes (portable `user-data/config.json`) so edits (e.g. github_token) reflect
-    // immediately without waiting for a UI snapshot refresh.
+    // One-time pulls queued by `schedule_pull`, checked often enough to land close to the time.
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || loop {
+        std::thread::sleep(SCHEDULED_PULL_TICK);
+        let state = app_handle.state::<Mutex<RuntimeState>>();
+        let due = {
+            let mut runtime = state.lock().expect("runtime lock");
+            super::pull::take_due_scheduled_pull(&mut runtime, crate::time_util::now_utc())
+        };
+        if let Some(at) = due {
+            let reason = format!(
+                "Pull scheduled for {} started",
+                at.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+            super::pull::spawn_pull(app_handle.clone(), state, &reason, true);
+        }
+    });
+
+    // Sync on its own cadence (`sync_interval_minutes`, 0 = off), independent of pulls. An
+    // unreachable output dir (e.g. an unmounted network drive) is retried every minute.
     let app_handle = app.clone();
     tauri::async_runtime::spawn_blocking(move || {
-        let config_path = config::config_path();
-        // Also check once at startup if a token exists and hasn't been seen yet.
-        {
+        let mut last_started: Option<std::time::Instant> = None;
+        loop {
+            std::thread::sleep(Duration::from_secs(60));
             let cfg = config::load_config();
-            let token = config::get_str(&cfg, "github_token");
-            if !token.is_empty() {
-                super::update::try_begin_github_token_check(app_handle.clone(), token);
+            let minutes = AppSettings::from_config(&cfg).sync_interval_minutes;
+            let output_dir = config::get_str(&cfg, "output_dir");
+            if minutes <= 0 || output_dir.is_empty() {
+                continue;
+            }
+            let interval = Duration::from_secs(minutes as u64 * 60);
+            if last_started.is_some_and(|at| at.elapsed() < interval) {
+                continue;
+            }
+            let state = app_handle.state::<Mutex<RuntimeState>>();
+            if !config::path_is_usable_dir(Path::new(&output_dir)) {
+                let mut runtime = state.lock().expect("runtime lock");
+                push_log(
+                    &mut runtime,
+                    &format!("Scheduled sync skipped: {output_dir} is unavailable"),
+                    "DEBUG",
+                );
+                continue;
+            }
+            last_started = Some(std::time::Instant::now());
+            super::sync::spawn_sync(app_handle.clone(), state, "Scheduled sync started");
+        }
+    });
+
+    // The hourly pull timer can be far off after sleep; catch up as soon as the machine wakes.
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let tick = chrono::Duration::from_std(RESUME_TICK).unwrap_or_default();
+        let mut previous = Utc::now();
+        loop {
+            std::thread::sleep(RESUME_TICK);
+            let now = Utc::now();
+            if resumed_from_sleep(previous, now, tick) {
+                catch_up_after_resume(&app_handle, now - previous);
             }
+            previous = now;
+        }
+    });
+
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || loop {
+        super::lifecycle::check_timezone_mismatch(&app_handle);
+        std::thread::sleep(Duration::from_secs(24 * 60 * 60));
+    });
+
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || loop {
+        std::thread::sleep(Duration::from_secs(30));
+        super::notify::tick_event_alerts(&app_handle);
+        super::notify::tick_tray_alert(&app_handle);
+    });
+
+    // `status.json` heartbeat for external monitors; pulls and syncs also refresh it on completion.
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || loop {
+        super::lifecycle::refresh_status_file(&app_handle);
+        std::thread::sleep(crate::status_file::STATUS_FILE_INTERVAL);
+    });
+
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = runtime_store::runtime_state_path();
+        let mut last_saved = String::new();
+        loop {
+            std::thread::sleep(Duration::from_secs(60));
+            let durable = {
+                let state = app_handle.state::<Mutex<RuntimeState>>();
+                let runtime = state.lock().expect("runtime lock");
+                runtime_store::DurableState::capture(&runtime)
+            };
+            let _ = runtime_store::save_if_changed(&path, &durable, &mut last_saved);
         }
+    });
+
+    // Watch config changes (portable `user-data/config.json`) so edits (e.g. github_token) reflect
+    // immediately without waiting for a UI snapshot refresh.
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        // The startup token check is part of the deferred startup work.
+        let config_path = config::config_path();
         let mut last_mtime = file_mtime_ms(&config_path).unwrap_or(0);
         loop {
             std::thread::sleep(Duration::from_millis(250));
@@ -70,6 +410,13 @@ pub fn start_background_tasks(app: tauri::AppHandle) {
             }
             last_mtime = mtime;
             let cfg = config::load_config();
+            {
+                let state = app_handle.state::<Mutex<RuntimeState>>();
+                let mut runtime = state.lock().expect("runtime lock");
+                runtime.log_filter = crate::log_file::LogFilter::from_config(&cfg);
+                runtime.log_buffer_entries = crate::log_file::log_buffer_entries(&cfg);
+            }
+            crate::time_util::set_date_format(crate::time_util::DateFormat::from_config(&cfg));
             let token = config::get_str(&cfg, "github_token");
             if !token.is_empty() {
                 super::update::try_begin_github_token_check(app_handle.clone(), token);
@@ -82,3 +429,120 @@ pub fn start_background_tasks(app: tauri::AppHandle) {
 pub fn set_ui_state(_payload: Value) -> Result<Value, String> {
     Ok(json!({"ok": true}))
 }
+
+/// Debug only: pins `now_utc()` to `iso` (RFC 3339) so current/past boundaries can be
+/// reproduced; an empty `iso` restores the real clock.
+#[tauri::command]
+pub fn set_test_clock(payload: Value, state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    if !AppSettings::from_config(&config::load_config()).debug {
+        return json!({"ok": false, "message": "set_test_clock requires debug mode"});
+    }
+    let iso = payload
+        .get("iso")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim();
+    let at = if iso.is_empty() {
+        None
+    } else {
+        match chrono::DateTime::parse_from_rfc3339(iso) {
+            Ok(at) => Some(at.with_timezone(&chrono::Utc)),
+            Err(err) => return json!({"ok": false, "message": format!("Invalid time: {err}")}),
+        }
+    };
+    crate::time_util::set_test_clock(at);
+    let mut runtime = state.lock().expect("runtime lock");
+    runtime.test_clock = at;
+    let message = match at {
+        Some(at) => format!("Test clock set to {}", at.to_rfc3339()),
+        None => "Test clock cleared".to_string(),
+    };
+    push_log(&mut runtime, &message, "WARN");
+    json!({"ok": true, "testClock": at.map(|at| at.to_rfc3339())})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn second_instance_action_defaults_to_focus() {
+        let action = |v: &str| second_instance_action(&json!({"second_instance_action": v}));
+        assert_eq!(action("ignore"), "ignore");
+        assert_eq!(action("notify"), "notify");
+        assert_eq!(action("focus"), "focus");
+        assert_eq!(action("bogus"), "focus");
+        assert_eq!(second_instance_action(&json!({})), "focus");
+    }
+
+    #[test]
+    fn instance_label_marks_title_and_notifications() {
+        let label = |v: &str| instance_label(&json!({"instance_label": v}));
+        assert_eq!(label(""), "");
+        assert_eq!(label(" [DEV] "), "DEV");
+        assert_eq!(window_title(""), "XAUUSD Calendar Agent");
+        assert_eq!(window_title(&label("DEV")), "XAUUSD Calendar Agent [DEV]");
+        assert_eq!(notification_title("", "USD CPI m/m"), "USD CPI m/m");
+        assert_eq!(
+            notification_title(&label("DEV"), "USD CPI m/m"),
+            "[DEV] USD CPI m/m"
+        );
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 6, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn only_long_gaps_count_as_resume() {
+        let tick = chrono::Duration::minutes(1);
+        assert!(!resumed_from_sleep(at(9, 0), at(9, 1), tick));
+        assert!(!resumed_from_sleep(at(9, 0), at(9, 2), tick));
+        assert!(resumed_from_sleep(at(9, 0), at(9, 3), tick));
+        assert!(resumed_from_sleep(at(1, 0), at(9, 0), tick));
+    }
+
+    #[test]
+    fn resume_pulls_when_stale_and_checks_updates_when_due() {
+        let status = ResumeStatus {
+            last_pull_at: Some(at(2, 0)),
+            pull_interval: chrono::Duration::minutes(360),
+            calendar_horizon: Some(at(23, 0)),
+            update_checked_at: Some(at(8, 30)),
+            update_interval: Some(chrono::Duration::minutes(60)),
+        };
+        assert_eq!(handle_resume(at(7, 0), &status), ResumeActions::default());
+        assert_eq!(
+            handle_resume(at(9, 0), &status),
+            ResumeActions {
+                pull: true,
+                check_updates: false
+            }
+        );
+        assert_eq!(
+            handle_resume(at(9, 30), &status),
+            ResumeActions {
+                pull: true,
+                check_updates: true
+            }
+        );
+
+        // A passed horizon forces a pull even right after the last one.
+        let fresh = ResumeStatus {
+            last_pull_at: Some(at(8, 55)),
+            calendar_horizon: Some(at(8, 0)),
+            update_interval: None,
+            ..status
+        };
+        assert_eq!(
+            handle_resume(at(9, 0), &fresh),
+            ResumeActions {
+                pull: true,
+                check_updates: false
+            }
+        );
+        let never = ResumeStatus::default();
+        assert!(handle_resume(at(9, 0), &never).pull);
+    }
+}
@@ -1,4 +1,6 @@
 use super::*;
+use crate::version::{parse_version_numbers, version_relation};
+use serde::{Deserialize, Serialize};
 
 pub fn default_update_state() -> Value {
     json!({
@@ -37,6 +39,408 @@ fn set_update_state(
     }
 }
 
+fn update_notify_mode(cfg: &Value) -> String {
+    AppSettings::from_config(cfg).notify_mode().to_string()
+}
+
+/// Announces an available update according to `update_notify_mode`. Returns the modal payload
+/// to emit, if any. Each version is announced once (the prompted version survives restarts);
+/// while notifications are muted it waits for the first check after the mute.
+fn maybe_prompt_update(runtime: &mut RuntimeState, available: &str, mode: &str) -> Option<Modal> {
+    if mode == "silent"
+        || runtime.update_prompted_version == available
+        || crate::notifications::muted(
+            runtime.notifications_muted_until,
+            crate::time_util::now_utc(),
+        )
+    {
+        return None;
+    }
+    runtime.update_prompted_version = available.to_string();
+    push_log(runtime, &format!("Update available: {available}"), "INFO");
+    if mode != "modal" {
+        return None;
+    }
+    let mut modal = Modal::new(
+        format!("update-{available}"),
+        "Update Available",
+        format!("Version {available} is available.\n\nOpen Settings to update."),
+        ModalTone::Info,
+    )
+    .with_action(ModalAction::open_settings());
+    let release_url = runtime.update_release_url.trim();
+    if !release_url.is_empty() {
+        modal = modal.with_action(ModalAction::url("Release Notes", release_url));
+    }
+    runtime.set_modal(modal);
+    runtime.modal.clone()
+}
+
+/// Case-insensitive glob match supporting `*` and `?`.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            p = star_p + 1;
+            n = star_n + 1;
+            backtrack = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Asset name pattern for `platform`: the `github_release_assets` entry, else the legacy
+/// `github_release_asset_name`.
+fn release_asset_pattern(cfg: &Value, platform: &str) -> String {
+    cfg.get("github_release_assets")
+        .and_then(|v| v.get(platform))
+        .and_then(|v| v.as_str())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| config::get_str(cfg, "github_release_asset_name"))
+}
+
+struct ReleaseAsset {
+    name: String,
+    url: String,
+}
+
+/// Picks the first release asset whose name matches `pattern`.
+fn select_release_asset(assets: &[Value], pattern: &str) -> Option<ReleaseAsset> {
+    if pattern.is_empty() {
+        return None;
+    }
+    assets.iter().find_map(|a| {
+        let name = a.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let url = a
+            .get("browser_download_url")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        if name.is_empty() || url.is_empty() || !glob_match(pattern, name) {
+            return None;
+        }
+        Some(ReleaseAsset {
+            name: name.to_string(),
+            url: url.to_string(),
+        })
+    })
+}
+
+/// Release asset holding the bare version string, read to confirm what the installer installs.
+const UPDATE_VERSION_ASSET: &str = "version.txt";
+
+/// What `check_updates` takes from the latest release, kept with the response's `ETag` so an
+/// unchanged release (HTTP 304) is neither downloaded nor parsed again. Persisted as
+/// `github_release_cache`, with the tag itself in `github_release_etag`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CachedRelease {
+    #[serde(skip)]
+    pub etag: String,
+    /// Pattern the assets were selected with; after it changes the release is fetched again.
+    pub asset_pattern: String,
+    pub available_version: String,
+    pub release_url: String,
+    pub asset_name: String,
+    pub asset_url: String,
+    pub version_url: String,
+}
+
+impl CachedRelease {
+    fn from_body(body: &Value, asset_pattern: &str, etag: &str) -> Result<Self, String> {
+        let tag = body.get("tag_name").and_then(|v| v.as_str()).unwrap_or("");
+        let available_version = normalize_version_tag(tag);
+        if available_version.is_empty() {
+            return Err("GitHub release tag_name missing".to_string());
+        }
+        let assets = body
+            .get("assets")
+            .and_then(|v| v.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let asset = select_release_asset(assets, asset_pattern);
+        let version_asset = select_release_asset(assets, UPDATE_VERSION_ASSET);
+        Ok(Self {
+            etag: etag.to_string(),
+            asset_pattern: asset_pattern.to_string(),
+            available_version,
+            release_url: body
+                .get("html_url")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            asset_name: asset.as_ref().map(|a| a.name.clone()).unwrap_or_default(),
+            asset_url: asset.map(|a| a.url).unwrap_or_default(),
+            version_url: version_asset.map(|a| a.url).unwrap_or_default(),
+        })
+    }
+
+    fn from_config(cfg: &Value) -> Option<Self> {
+        let etag = config::get_str(cfg, "github_release_etag");
+        if etag.is_empty() {
+            return None;
+        }
+        let cached: Self = serde_json::from_value(cfg.get("github_release_cache")?.clone()).ok()?;
+        Some(Self { etag, ..cached })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let cache = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        config::update_config(|cfg| {
+            config::set_string(cfg, "github_release_etag", self.etag.clone())?;
+            let obj = cfg.as_object_mut().ok_or("config invalid")?;
+            obj.insert("github_release_cache".to_string(), cache);
+            Ok(())
+        })
+        .map(|_| ())
+    }
+}
+
+/// `X-RateLimit-*` headers of the latest GitHub API response, for diagnostics.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    /// When the window resets, in seconds since the epoch.
+    pub reset_at: Option<u64>,
+    pub recorded_at: String,
+}
+
+impl RateLimit {
+    /// `None` when the response carries none of the headers.
+    fn from_response(resp: &ureq::Response) -> Option<Self> {
+        let header = |name: &str| resp.header(name).and_then(|v| v.trim().parse::<u64>().ok());
+        let limit = Self {
+            limit: header("x-ratelimit-limit"),
+            remaining: header("x-ratelimit-remaining"),
+            reset_at: header("x-ratelimit-reset"),
+            recorded_at: now_iso_time(),
+        };
+        (limit.limit.is_some() || limit.remaining.is_some()).then_some(limit)
+    }
+}
+
+/// Token details GitHub sends back on an API response.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct ApiHeaders {
+    pub rate_limit: Option<RateLimit>,
+    /// `X-OAuth-Scopes`: a classic token's scopes. `None` when the header is absent, as for
+    /// fine-grained tokens and anonymous requests.
+    pub scopes: Option<Vec<String>>,
+}
+
+impl ApiHeaders {
+    fn from_response(resp: &ureq::Response) -> Self {
+        let scopes = resp.header("x-oauth-scopes").map(|raw| {
+            raw.split(',')
+                .map(str::trim)
+                .filter(|scope| !scope.is_empty())
+                .map(str::to_string)
+                .collect()
+        });
+        Self {
+            rate_limit: RateLimit::from_response(resp),
+            scopes,
+        }
+    }
+}
+
+/// Records the headers of a GitHub response. A response without rate-limit headers keeps the
+/// previous reading; the scopes always follow the latest response.
+fn record_api_headers(runtime: &mut RuntimeState, headers: ApiHeaders) {
+    if headers.rate_limit.is_some() {
+        runtime.github_rate_limit = headers.rate_limit;
+    }
+    runtime.github_token_scopes = headers.scopes;
+}
+
+struct ReleaseFetch {
+    release: CachedRelease,
+    /// The server answered 304, so `release` is the cached one.
+    not_modified: bool,
+}
+
+/// Reads a `releases/latest` response. A 304 confirms `cached`, which must be the release whose
+/// ETag was sent.
+fn read_release_response(
+    resp: ureq::Response,
+    asset_pattern: &str,
+    cached: Option<&CachedRelease>,
+) -> Result<ReleaseFetch, String> {
+    if resp.status() == 304 {
+        let release = cached
+            .cloned()
+            .ok_or("GitHub answered 304 Not Modified without a cached release")?;
+        return Ok(ReleaseFetch {
+            release,
+            not_modified: true,
+        });
+    }
+    let etag = resp.header("etag").unwrap_or("").to_string();
+    let body: Value = resp
+        .into_json()
+        .map_err(|e| format!("failed to parse GitHub response: {e}"))?;
+    Ok(ReleaseFetch {
+        release: CachedRelease::from_body(&body, asset_pattern, &etag)?,
+        not_modified: false,
+    })
+}
+
+/// GETs the latest release of `repo_slug`. With a `cached` release selected under the same
+/// `asset_pattern` the request is conditional on its ETag. The response headers come back even
+/// when the request fails.
+fn fetch_latest_release(
+    repo_slug: &str,
+    token: &str,
+    asset_pattern: &str,
+    cached: Option<&CachedRelease>,
+) -> (Option<ApiHeaders>, Result<ReleaseFetch, String>) {
+    let cached = cached.filter(|c| !c.etag.is_empty() && c.asset_pattern == asset_pattern);
+    let url = format!("https://api.github.com/repos/{repo_slug}/releases/latest");
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(std::time::Duration::from_secs(5))
+        .timeout_read(std::time::Duration::from_secs(10))
+        .timeout_write(std::time::Duration::from_secs(10))
+        .build();
+    let mut req = agent
+        .get(&url)
+        .set("User-Agent", "XAUUSDCalendarAgent")
+        .set("Accept", "application/vnd.github+json")
+        .set("X-GitHub-Api-Version", "2022-11-28");
+    if !token.is_empty() {
+        req = req.set("Authorization", &format!("Bearer {token}"));
+    }
+    if let Some(cached) = cached {
+        req = req.set("If-None-Match", &cached.etag);
+    }
+    match crate::net_activity::call(req) {
+        Ok(resp) => (
+            Some(ApiHeaders::from_response(&resp)),
+            read_release_response(resp, asset_pattern, cached),
+        ),
+        Err(ureq::Error::Status(code, resp)) => (
+            Some(ApiHeaders::from_response(&resp)),
+            Err(format!("GitHub request failed: HTTP {code}")),
+        ),
+        Err(err) => (None, Err(format!("GitHub request failed: {err}"))),
+    }
+}
+
+/// Written to appdata just before the installer launches; checked and removed on next launch.
+pub const UPDATE_PENDING_FILE: &str = "update_pending.json";
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingUpdate {
+    expected_version: String,
+    from_version: String,
+    installer: String,
+    started_at: String,
+}
+
+/// First dotted version number in an installer file name, e.g. `0.3.1` in
+/// `XAUUSD-Calendar-Agent_0.3.1_x64-setup.exe`.
+fn version_in_file_name(name: &str) -> Option<String> {
+    name.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .map(|part| part.trim_matches('.'))
+        .find(|part| part.contains('.') && parse_version_numbers(part).is_some())
+        .map(str::to_string)
+}
+
+/// What the installer should install: the `version.txt` asset when there is one, else the
+/// version in the installer's name, else the release tag.
+fn expected_install_version(version_txt: Option<&str>, installer: &str, release: &str) -> String {
+    version_txt
+        .map(|text| normalize_version_tag(text.lines().next().unwrap_or("")))
+        .filter(|v| parse_version_numbers(v).is_some())
+        .or_else(|| version_in_file_name(installer))
+        .unwrap_or_else(|| release.to_string())
+}
+
+fn fetch_version_txt(url: &str) -> Result<String, String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(std::time::Duration::from_secs(10))
+        .timeout_read(std::time::Duration::from_secs(10))
+        .build();
+    crate::net_activity::call(agent.get(url).set("User-Agent", "XAUUSDCalendarAgent"))
+        .map_err(|err| format!("version.txt download failed: {err}"))?
+        .into_string()
+        .map_err(|e| e.to_string())
+}
+
+fn write_pending_update(path: &Path, pending: &PendingUpdate) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(pending).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+/// Reads and removes the marker at `path`.
+fn take_pending_update(path: &Path) -> Option<PendingUpdate> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let _ = std::fs::remove_file(path);
+    serde_json::from_str(&text).ok()
+}
+
+/// Whether the pending update took effect in a build running `current`, with a log line.
+fn pending_update_outcome(pending: &PendingUpdate, current: &str) -> (bool, String) {
+    if cmp_versions(current, &pending.expected_version) != Ordering::Less {
+        return (
+            true,
+            format!(
+                "Update to {} installed (was {})",
+                pending.expected_version, pending.from_version
+            ),
+        );
+    }
+    (
+        false,
+        format!(
+            "Update to {} did not take effect: still running {current} (installer {}, started {})",
+            pending.expected_version, pending.installer, pending.started_at
+        ),
+    )
+}
+
+/// Reports, once, whether the update started last session actually installed.
+pub fn surface_update_outcome(app: &tauri::AppHandle) {
+    let Some(pending) = take_pending_update(&config::appdata_dir().join(UPDATE_PENDING_FILE))
+    else {
+        return;
+    };
+    let current = env!("APP_VERSION");
+    let (took_effect, message) = pending_update_outcome(&pending, current);
+    let state = app.state::<Mutex<RuntimeState>>();
+    let mut runtime = state.lock().expect("runtime lock");
+    if took_effect {
+        push_log(&mut runtime, &message, "INFO");
+        return;
+    }
+    push_log(&mut runtime, &message, "WARN");
+    runtime.set_modal(
+        Modal::new(
+            format!("update-failed-{}", pending.expected_version),
+            "Update Not Installed",
+            format!(
+                "The installer for {} ran, but this is still {current}.\n\nRun the update again or install it manually.",
+                pending.expected_version
+            ),
+            ModalTone::Error,
+        )
+        .with_action(ModalAction::open_settings()),
+    );
+}
+
 pub(super) fn try_begin_github_token_check(app: tauri::AppHandle, token: String) {
     let token = token.trim().to_string();
     let runtime_state = app.state::<Mutex<RuntimeState>>();
@@ -59,45 +463,43 @@ pub(super) fn try_begin_github_token_check(app: tauri::AppHandle, token: String)
     runtime.token_check_started = true;
 
     let modal_id = format!("github-token-{}", now_ms());
-    runtime.modal = json!({
-        "id": modal_id,
-        "title": "GitHub Token",
-        "message": "Checking token...",
-        "tone": "info"
-    });
+    runtime.set_modal(Modal::new(
+        modal_id.clone(),
+        "GitHub Token",
+        "Checking token...",
+        ModalTone::Info,
+    ));
     let modal_payload = runtime.modal.clone();
     drop(runtime);
     let _ = app.emit("xauusd:modal", modal_payload);
 
     let app_handle = app.clone();
     tauri::async_runtime::spawn_blocking(move || {
-        let result = verify_github_token_value(&token);
+        let (headers, result) = verify_github_token_value(&token);
 
         let runtime_state = app_handle.state::<Mutex<RuntimeState>>();
         let state_for_updates = app_handle.state::<Mutex<RuntimeState>>();
         let mut runtime = runtime_state.lock().expect("runtime lock");
+        if let Some(headers) = headers {
+            record_api_headers(&mut runtime, headers);
+        }
 
-        let current_modal_id = runtime
-            .modal
-            .get("id")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let modal_still_active = current_modal_id == modal_id;
+        let modal_still_active = runtime.modal_id() == Some(modal_id.as_str());
 
         match result {
             Ok(true) => {
                 if modal_still_active {
-                    runtime.modal = json!({
-                        "id": modal_id,
-                        "title": "GitHub Token",
-                        "message": "Token verified.\n\nUpdating data...",
-                        "tone": "info"
-                    });
+                    runtime.set_modal(Modal::new(
+                        modal_id,
+                        "GitHub Token",
+                        "Token verified.\n\nUpdating data...",
+                        ModalTone::Info,
+                    ));
                 }
                 push_log(&mut runtime, "GitHub token verified.", "INFO");
                 runtime.token_check_started = false;
                 let modal_payload = if modal_still_active {
-                    Some(runtime.modal.clone())
+                    runtime.modal.clone()
                 } else {
                     None
                 };
@@ -110,23 +512,26 @@ pub(super) fn try_begin_github_token_check(app: tauri::AppHandle, token: String)
             }
             Ok(false) => {
                 if modal_still_active {
-                    runtime.modal = json!({
-                        "id": modal_id,
-                        "title": "GitHub Token",
-                        "message": "Token Invalid.\n\nPlease check github_token in config.json",
-                        "tone": "error"
-                    });
+                    runtime.set_modal(Modal::new(
+                        modal_id,
+                        "GitHub Token",
+                        "Token Invalid.\n\nPlease check github_token in config.json",
+                        ModalTone::Error,
+                    ));
                 }
                 push_log(&mut runtime, "GitHub token invalid.", "ERROR");
             }
             Err(msg) => {
                 if modal_still_active {
-                    runtime.modal = json!({
-                        "id": modal_id,
-                        "title": "GitHub Token",
-                        "message": format!("Token check failed: {msg}\n\nPlease check github_token in config.json"),
-                        "tone": "error"
-                    });
+                    // The check itself failed (often the network); the token may be fine.
+                    runtime.set_modal(Modal::new(
+                        modal_id,
+                        "GitHub Token",
+                        format!(
+                            "Token check failed: {msg}\n\nPlease check github_token in config.json"
+                        ),
+                        ModalTone::Warn,
+                    ));
                 }
                 push_log(
                     &mut runtime,
@@ -159,9 +564,10 @@ pub fn check_updates(
     state: tauri::State<'_, Mutex<RuntimeState>>,
 ) -> Result<Value, String> {
     let cfg = config::load_config();
-    let repo_slug = config::get_str(&cfg, "github_repo");
-    let asset_name = config::get_str(&cfg, "github_release_asset_name");
+    let repo_slug = AppSettings::from_config(&cfg).github_repo;
+    let asset_pattern = release_asset_pattern(&cfg, crate::platform::current_platform());
     let token = config::get_str(&cfg, "github_token");
+    let notify_mode = update_notify_mode(&cfg);
     let mut runtime = state.lock().expect("runtime lock");
     set_update_state(
         &mut runtime,
@@ -172,66 +578,52 @@ pub fn check_updates(
     );
     runtime.update_release_url.clear();
     runtime.update_asset_url.clear();
+    runtime.update_asset_name.clear();
+    runtime.update_download_path.clear();
+    runtime.update_version_url.clear();
+    runtime.update_expected_version.clear();
+    runtime.update_checked_at = Some(chrono::Utc::now());
+    let cached = runtime
+        .release_cache
+        .clone()
+        .or_else(|| CachedRelease::from_config(&cfg));
     drop(runtime);
 
     tauri::async_runtime::spawn_blocking(move || {
-        let parsed: Result<(String, String, String), String> = (|| {
-            let url = format!("https://api.github.com/repos/{repo_slug}/releases/latest");
-            let agent = ureq::AgentBuilder::new()
-                .timeout_connect(std::time::Duration::from_secs(5))
-                .timeout_read(std::time::Duration::from_secs(10))
-                .timeout_write(std::time::Duration::from_secs(10))
-                .build();
-            let mut req = agent
-                .get(&url)
-                .set("User-Agent", "XAUUSDCalendarAgent")
-                .set("Accept", "application/vnd.github+json")
-                .set("X-GitHub-Api-Version", "2022-11-28");
-            if !token.is_empty() {
-                req = req.set("Authorization", &format!("Bearer {token}"));
-            }
-            let resp = req
-                .call()
-                .map_err(|err| format!("GitHub request failed: {err}"))?;
-            let body: serde_json::Value = resp
-                .into_json()
-                .map_err(|e| format!("failed to parse GitHub response: {e}"))?;
-            let tag = body.get("tag_name").and_then(|v| v.as_str()).unwrap_or("");
-            let available = normalize_version_tag(tag);
-            if available.is_empty() {
-                return Err("GitHub release tag_name missing".to_string());
-            }
-            let release_url = body
-                .get("html_url")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-            let mut asset_url = String::new();
-            if let Some(assets) = body.get("assets").and_then(|v| v.as_array()) {
-                for a in assets {
-                    let name = a.get("name").and_then(|v| v.as_str()).unwrap_or("");
-                    if !asset_name.is_empty() && name == asset_name {
-                        asset_url = a
-                            .get("browser_download_url")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-                        break;
-                    }
-                }
-            }
-            if asset_url.is_empty() && !release_url.is_empty() {
-                asset_url = release_url.clone();
+        let (headers, fetched) =
+            fetch_latest_release(&repo_slug, &token, &asset_pattern, cached.as_ref());
+        if let Ok(fetch) = &fetched {
+            if !fetch.not_modified && !fetch.release.etag.is_empty() {
+                let _ = fetch.release.save();
             }
-            Ok((available, release_url, asset_url))
-        })();
+        }
 
         let runtime_state = app.state::<Mutex<RuntimeState>>();
         let mut runtime = runtime_state.lock().expect("runtime lock");
-        match parsed {
-            Ok((available, release_url, asset_url)) => {
-                runtime.update_release_url = release_url.clone();
-                runtime.update_asset_url = asset_url.clone();
+        if let Some(headers) = headers {
+            record_api_headers(&mut runtime, headers);
+        }
+        match fetched {
+            Ok(ReleaseFetch {
+                release,
+                not_modified,
+            }) => {
+                if not_modified {
+                    push_log(
+                        &mut runtime,
+                        &format!(
+                            "Latest release unchanged ({}); using the cached details",
+                            release.available_version
+                        ),
+                        "DEBUG",
+                    );
+                }
+                runtime.update_release_url = release.release_url.clone();
+                runtime.update_asset_name = release.asset_name.clone();
+                runtime.update_asset_url = release.asset_url.clone();
+                runtime.update_version_url = release.version_url.clone();
+                let available = release.available_version.clone();
+                runtime.release_cache = Some(release);
                 let current = env!("APP_VERSION");
                 if cmp_versions(&available, current) == Ordering::Greater {
                     set_update_state(
@@ -241,11 +633,11 @@ pub fn check_updates(
                         true,
                         Some(&available),
                     );
-                    push_log(
-                        &mut runtime,
-                        &format!("Update available: {available}"),
-                        "INFO",
-                    );
+                    let modal_payload = maybe_prompt_update(&mut runtime, &available, &notify_mode);
+                    drop(runtime);
+                    if let Some(payload) = modal_payload {
+                        let _ = app.emit("xauusd:modal", payload);
+                    }
                 } else {
                     set_update_state(&mut runtime, "idle", "Up to date", true, Some(&available));
                 }
@@ -264,27 +656,195 @@ pub fn check_updates(
     Ok(json!({"ok": true}))
 }
 
+fn set_update_progress(runtime: &mut RuntimeState, downloaded: u64, total: Option<u64>) {
+    if let Some(obj) = runtime.update_state.as_object_mut() {
+        let progress = total
+            .filter(|t| *t > 0)
+            .map(|t| ((downloaded * 100) / t).min(100))
+            .unwrap_or(0);
+        obj.insert("progress".to_string(), json!(progress));
+        obj.insert("downloadedBytes".to_string(), json!(downloaded));
+        obj.insert("totalBytes".to_string(), json!(total));
+    }
+}
+
+fn download_update_asset(app: &tauri::AppHandle, url: &str, name: &str) -> Result<PathBuf, String> {
+    use std::io::{Read, Write};
+    let dir = std::env::temp_dir().join("xauusd-calendar-agent-update");
+    sync_util::ensure_min_free_space(
+        "update download",
+        &dir,
+        min_free_space_mb(&config::load_config()),
+    )?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let target = dir.join(name);
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(std::time::Duration::from_secs(10))
+        .timeout_read(std::time::Duration::from_secs(30))
+        .build();
+    let resp = crate::net_activity::call(agent.get(url).set("User-Agent", "XAUUSDCalendarAgent"))
+        .map_err(|err| format!("Download failed: {err}"))?;
+    let total = resp
+        .header("Content-Length")
+        .and_then(|v| v.parse::<u64>().ok());
+    let mut reader = resp.into_reader();
+    let mut file = std::fs::File::create(&target).map_err(|e| e.to_string())?;
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut downloaded: u64 = 0;
+    let mut last_reported: u64 = 0;
+    loop {
+        let n = reader.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n]).map_err(|e| e.to_string())?;
+        downloaded += n as u64;
+        if downloaded - last_reported >= 512 * 1024 {
+            last_reported = downloaded;
+            let state = app.state::<Mutex<RuntimeState>>();
+            let mut runtime = state.lock().expect("runtime lock");
+            set_update_progress(&mut runtime, downloaded, total);
+        }
+    }
+    let state = app.state::<Mutex<RuntimeState>>();
+    let mut runtime = state.lock().expect("runtime lock");
+    set_update_progress(&mut runtime, downloaded, total.or(Some(downloaded)));
+    Ok(target)
+}
+
+/// Downloads the release asset for this platform, or launches it once downloaded.
 #[tauri::command]
-pub fn update_now(state: tauri::State<'_, Mutex<RuntimeState>>) -> Result<Value, String> {
-    let url = {
-        let runtime = state.lock().expect("runtime lock");
-        runtime.update_asset_url.trim().to_string()
-    };
-    if url.is_empty() {
-        return Ok(json!({"ok": false, "message": "Update URL not available"}));
+pub fn update_now(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Mutex<RuntimeState>>,
+) -> Result<Value, String> {
+    let mut runtime = state.lock().expect("runtime lock");
+    let url = runtime.update_asset_url.trim().to_string();
+    let name = runtime.update_asset_name.clone();
+    let downloaded = runtime.update_download_path.clone();
+
+    if !downloaded.is_empty() {
+        let path = PathBuf::from(&downloaded);
+        let expected = runtime.update_expected_version.clone();
+        // Written before the launch: the installer may close this app before it returns.
+        let marker = config::appdata_dir().join(UPDATE_PENDING_FILE);
+        let mut marker_written = false;
+        if !expected.is_empty() {
+            let pending = PendingUpdate {
+                expected_version: expected,
+                from_version: env!("APP_VERSION").to_string(),
+                installer: name.clone(),
+                started_at: now_iso_time(),
+            };
+            match write_pending_update(&marker, &pending) {
+                Ok(()) => marker_written = true,
+                Err(err) => push_log(
+                    &mut runtime,
+                    &format!("Update will not be verified: {err}"),
+                    "WARN",
+                ),
+            }
+        }
+        return match crate::platform::launch_installer(&path) {
+            Ok(()) => {
+                set_update_state(&mut runtime, "restarting", "Installer started", true, None);
+                push_log(
+                    &mut runtime,
+                    &format!("Update installer started: {name}"),
+                    "INFO",
+                );
+                drop(runtime);
+                if crate::platform::current_platform() != "macos" {
+                    app.exit(0);
+                }
+                Ok(json!({"ok": true}))
+            }
+            Err(err) => {
+                if marker_written {
+                    let _ = std::fs::remove_file(&marker);
+                }
+                let message = format!("Failed to start installer: {err}");
+                set_update_state(&mut runtime, "error", &message, false, None);
+                Ok(json!({"ok": false, "message": message}))
+            }
+        };
     }
-    let ok = open_target(&url);
-    if ok {
-        Ok(json!({"ok": true}))
-    } else {
-        Ok(json!({"ok": false, "message": "failed to open update url"}))
+
+    if url.is_empty() || name.is_empty() {
+        let message = "In-app update not supported here";
+        set_update_state(&mut runtime, "unsupported", message, false, None);
+        let release_url = runtime.update_release_url.trim().to_string();
+        drop(runtime);
+        if !release_url.is_empty() {
+            let _ = open_target(&release_url);
+        }
+        return Ok(json!({"ok": false, "message": message}));
     }
+
+    set_update_state(
+        &mut runtime,
+        "downloading",
+        "Downloading update...",
+        true,
+        None,
+    );
+    set_update_progress(&mut runtime, 0, None);
+    let version_url = runtime.update_version_url.clone();
+    let release_version = runtime
+        .update_state
+        .get("availableVersion")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    drop(runtime);
+    let verify = AppSettings::from_config(&config::load_config()).update_verify_install;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = download_update_asset(&app, &url, &name);
+        let version_txt = (verify && result.is_ok() && !version_url.is_empty())
+            .then(|| fetch_version_txt(&version_url));
+        let runtime_state = app.state::<Mutex<RuntimeState>>();
+        let mut runtime = runtime_state.lock().expect("runtime lock");
+        match result {
+            Ok(path) => {
+                runtime.update_download_path = path.to_string_lossy().to_string();
+                if let Some(Err(err)) = &version_txt {
+                    push_log(&mut runtime, err, "WARN");
+                }
+                runtime.update_expected_version = if verify {
+                    let text = version_txt.and_then(Result::ok);
+                    expected_install_version(text.as_deref(), &name, &release_version)
+                } else {
+                    String::new()
+                };
+                set_update_state(
+                    &mut runtime,
+                    "downloaded",
+                    "Update downloaded. Click to install.",
+                    true,
+                    None,
+                );
+                push_log(&mut runtime, &format!("Update downloaded: {name}"), "INFO");
+            }
+            Err(err) => {
+                set_update_state(&mut runtime, "error", &err, false, None);
+                push_log(
+                    &mut runtime,
+                    &format!("Update download failed: {err}"),
+                    "ERROR",
+                );
+            }
+        }
+    });
+    Ok(json!({"ok": true}))
 }
 
-fn verify_github_token_value(token: &str) -> Result<bool, String> {
+/// Checks `token` against `/user`. The response headers also report the quota and, for classic
+/// tokens, the scopes; they come back whenever GitHub answered.
+fn verify_github_token_value(token: &str) -> (Option<ApiHeaders>, Result<bool, String>) {
     let token = token.trim();
     if token.is_empty() {
-        return Ok(false);
+        return (None, Ok(false));
     }
 
     let url = "https://api.github.com/user";
@@ -293,18 +853,282 @@ fn verify_github_token_value(token: &str) -> Result<bool, String> {
         .timeout_read(std::time::Duration::from_secs(8))
         .timeout_write(std::time::Duration::from_secs(8))
         .build();
-    let resp = agent
-        .get(url)
-        .set("User-Agent", "XAUUSDCalendarAgent")
-        .set("Accept", "application/vnd.github+json")
-        .set("X-GitHub-Api-Version", "2022-11-28")
-        .set("Authorization", &format!("Bearer {token}"))
-        .call();
+    let resp = crate::net_activity::call(
+        agent
+            .get(url)
+            .set("User-Agent", "XAUUSDCalendarAgent")
+            .set("Accept", "application/vnd.github+json")
+            .set("X-GitHub-Api-Version", "2022-11-28")
+            .set("Authorization", &format!("Bearer {token}")),
+    );
 
     match resp {
-        Ok(r) => Ok((200..=299).contains(&r.status())),
-        Err(ureq::Error::Status(401, _)) => Ok(false),
-        Err(ureq::Error::Status(code, _)) => Err(format!("GitHub responded with HTTP {code}")),
-        Err(e) => Err(format!("{e}")),
+        Ok(r) => (
+            Some(ApiHeaders::from_response(&r)),
+            Ok((200..=299).contains(&r.status())),
+        ),
+        Err(ureq::Error::Status(401, r)) => (Some(ApiHeaders::from_response(&r)), Ok(false)),
+        Err(ureq::Error::Status(code, r)) => (
+            Some(ApiHeaders::from_response(&r)),
+            Err(format!("GitHub responded with HTTP {code}")),
+        ),
+        Err(e) => (None, Err(format!("{e}"))),
+    }
+}
+
+/// Compares a release tag against the running build for scripted upgrade checks.
+#[tauri::command]
+pub fn compare_version(payload: Value) -> Value {
+    let raw = payload.get("tag").and_then(|v| v.as_str()).unwrap_or("");
+    let tag = normalize_version_tag(raw);
+    let Some(tag_components) = parse_version_numbers(&tag) else {
+        return json!({"ok": false, "message": format!("Unrecognized version tag: {raw}")});
+    };
+    let current = env!("APP_VERSION");
+    json!({
+        "ok": true,
+        "tag": tag,
+        "current": current,
+        "result": version_relation(&tag, current),
+        "tagComponents": tag_components,
+        "currentComponents": parse_version_numbers(current).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(raw: &str) -> ureq::Response {
+        raw.parse().unwrap()
+    }
+
+    #[test]
+    fn not_modified_release_reuses_the_cached_details() {
+        let body = json!({
+            "tag_name": "v1.4.0",
+            "html_url": "https://github.com/o/r/releases/tag/v1.4.0",
+            "assets": [
+                {"name": "Agent-Setup.exe", "browser_download_url": "https://dl/setup.exe"},
+                {"name": "version.txt", "browser_download_url": "https://dl/version.txt"},
+            ],
+        })
+        .to_string();
+        let first = read_release_response(
+            response(&format!(
+                "HTTP/1.1 200 OK\r\nETag: \"abc\"\r\nX-RateLimit-Limit: 60\r\n\
+                 X-RateLimit-Remaining: 59\r\nX-RateLimit-Reset: 1760000000\r\n\r\n{body}"
+            )),
+            "*Setup.exe",
+            None,
+        )
+        .unwrap();
+        assert!(!first.not_modified);
+        let release = first.release;
+        assert_eq!(release.etag, "\"abc\"");
+        assert_eq!(release.available_version, "1.4.0");
+        assert_eq!(release.asset_name, "Agent-Setup.exe");
+        assert_eq!(release.asset_url, "https://dl/setup.exe");
+        assert_eq!(release.version_url, "https://dl/version.txt");
+
+        let not_modified = response(
+            "HTTP/1.1 304 Not Modified\r\nETag: \"abc\"\r\nX-RateLimit-Limit: 60\r\n\
+             X-RateLimit-Remaining: 59\r\n\r\n",
+        );
+        let limit = RateLimit::from_response(&not_modified).unwrap();
+        assert_eq!(limit.limit, Some(60));
+        assert_eq!(limit.remaining, Some(59));
+        assert_eq!(limit.reset_at, None);
+        let second = read_release_response(not_modified, "*Setup.exe", Some(&release)).unwrap();
+        assert!(second.not_modified);
+        assert_eq!(second.release, release);
+
+        let orphan = response("HTTP/1.1 304 Not Modified\r\n\r\n");
+        assert!(RateLimit::from_response(&orphan).is_none());
+        assert!(read_release_response(orphan, "Setup.exe", None).is_err());
+    }
+
+    #[test]
+    fn token_headers_report_quota_and_scopes() {
+        let classic = ApiHeaders::from_response(&response(
+            "HTTP/1.1 200 OK\r\nX-RateLimit-Limit: 5000\r\nX-RateLimit-Remaining: 4987\r\n\
+             X-RateLimit-Reset: 1760000000\r\nX-OAuth-Scopes: repo, read:org\r\n\r\n{}",
+        ));
+        let limit = classic.rate_limit.clone().unwrap();
+        assert_eq!(
+            (limit.limit, limit.remaining, limit.reset_at),
+            (Some(5000), Some(4987), Some(1760000000))
+        );
+        assert_eq!(
+            classic.scopes.as_deref(),
+            Some(["repo".to_string(), "read:org".to_string()].as_slice())
+        );
+
+        // A classic token without scopes sends the header empty.
+        let unscoped =
+            ApiHeaders::from_response(&response("HTTP/1.1 200 OK\r\nX-OAuth-Scopes: \r\n\r\n{}"));
+        assert_eq!(unscoped.scopes, Some(vec![]));
+
+        // Fine-grained tokens send no scopes header.
+        let fine_grained = ApiHeaders::from_response(&response(
+            "HTTP/1.1 200 OK\r\nX-RateLimit-Limit: 5000\r\nX-RateLimit-Remaining: 5000\r\n\r\n{}",
+        ));
+        assert!(fine_grained.rate_limit.is_some());
+        assert_eq!(fine_grained.scopes, None);
+
+        let bare = ApiHeaders::from_response(&response("HTTP/1.1 401 Unauthorized\r\n\r\n{}"));
+        assert_eq!(bare, ApiHeaders::default());
+
+        let mut runtime = RuntimeState::default();
+        record_api_headers(&mut runtime, classic);
+        record_api_headers(&mut runtime, bare);
+        // The last quota reading survives a response without one; the scopes do not.
+        assert_eq!(runtime.github_rate_limit.unwrap().remaining, Some(4987));
+        assert_eq!(runtime.github_token_scopes, None);
+        let payload = json!({
+            "githubRateLimit": fine_grained.rate_limit,
+            "githubTokenScopes": fine_grained.scopes,
+        });
+        assert_eq!(payload["githubRateLimit"]["resetAt"], Value::Null);
+        assert_eq!(payload["githubTokenScopes"], Value::Null);
+    }
+
+    #[test]
+    fn cached_release_round_trips_through_config() {
+        let release = CachedRelease {
+            etag: "W/\"v1\"".to_string(),
+            asset_pattern: "Setup.exe".to_string(),
+            available_version: "1.4.0".to_string(),
+            ..CachedRelease::default()
+        };
+        let mut cfg = json!({"github_release_cache": serde_json::to_value(&release).unwrap()});
+        assert!(cfg["github_release_cache"].get("etag").is_none());
+        // Without an ETag the cached body cannot be revalidated.
+        assert_eq!(CachedRelease::from_config(&cfg), None);
+        cfg["github_release_etag"] = json!(release.etag);
+        assert_eq!(CachedRelease::from_config(&cfg), Some(release));
+    }
+
+    #[test]
+    fn update_prompt_is_kept_in_modal_history() {
+        let mut runtime = RuntimeState::default();
+        for idx in 0..25 {
+            assert!(maybe_prompt_update(&mut runtime, &format!("1.0.{idx}"), "modal").is_some());
+        }
+        assert_eq!(runtime.modal_history.len(), 20);
+        assert_eq!(runtime.modal_history[0]["id"], "update-1.0.24");
+        assert_eq!(runtime.modal_history[0]["tone"], "info");
+        assert!(runtime.modal_history[0]["shownAt"].as_str().is_some());
+        assert_eq!(runtime.modal_history[19]["id"], "update-1.0.5");
+    }
+
+    #[test]
+    fn expected_version_prefers_version_txt_then_file_name() {
+        assert_eq!(
+            expected_install_version(Some("v0.4.2\n"), "Setup_0.4.1.exe", "0.4.0"),
+            "0.4.2"
+        );
+        assert_eq!(
+            expected_install_version(
+                Some("garbage"),
+                "XAUUSD-Calendar-Agent_0.4.1_x64-setup.exe",
+                "0.4.0"
+            ),
+            "0.4.1"
+        );
+        assert_eq!(
+            expected_install_version(None, "XAUUSD-Calendar-Agent-Setup.exe", "0.4.0"),
+            "0.4.0"
+        );
+    }
+
+    #[test]
+    fn pending_update_marker_reports_whether_the_update_took() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(UPDATE_PENDING_FILE);
+        let pending = PendingUpdate {
+            expected_version: "0.4.1".to_string(),
+            from_version: "0.4.0".to_string(),
+            installer: "Setup.exe".to_string(),
+            started_at: "2026-03-01T10:00:00Z".to_string(),
+        };
+        write_pending_update(&path, &pending).unwrap();
+        let read = take_pending_update(&path).unwrap();
+        assert_eq!(read, pending);
+        assert!(!path.exists());
+        assert!(take_pending_update(&path).is_none());
+
+        let (ok, message) = pending_update_outcome(&read, "0.4.1");
+        assert!(ok, "{message}");
+        let (ok, message) = pending_update_outcome(&read, "0.4.0");
+        assert!(!ok);
+        assert!(message.contains("did not take effect: still running 0.4.0"));
+    }
+
+    fn fixture_assets() -> Vec<Value> {
+        [
+            "XAUUSD-Calendar-Agent-Setup.exe",
+            "XAUUSD-Calendar-Agent_0.3.1_aarch64.dmg",
+            "XAUUSD-Calendar-Agent_0.3.1_amd64.AppImage",
+            "XAUUSD-Calendar-Agent_0.3.1_amd64.AppImage.sig",
+            "latest.json",
+        ]
+        .iter()
+        .map(|name| {
+            json!({
+                "name": name,
+                "browser_download_url": format!("https://example.invalid/download/{name}")
+            })
+        })
+        .collect()
+    }
+
+    #[test]
+    fn glob_match_handles_wildcards() {
+        assert!(glob_match("*.dmg", "App_0.3.1_aarch64.dmg"));
+        assert!(glob_match("*.AppImage", "app_amd64.appimage"));
+        assert!(!glob_match("*.AppImage", "app_amd64.AppImage.sig"));
+        assert!(glob_match("Setup.exe", "setup.EXE"));
+        assert!(!glob_match("Setup.exe", "XAUUSD-Setup.exe"));
+        assert!(glob_match("*Setup.exe", "XAUUSD-Setup.exe"));
+        assert!(glob_match("v?.*", "v1.2"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn selects_asset_per_platform_with_legacy_fallback() {
+        let assets = fixture_assets();
+        let cfg = json!({
+            "github_release_asset_name": "XAUUSD-Calendar-Agent-Setup.exe",
+            "github_release_assets": {"macos": "*.dmg", "linux": "*.AppImage", "windows": ""}
+        });
+        let pick = |platform: &str| {
+            select_release_asset(&assets, &release_asset_pattern(&cfg, platform)).map(|a| a.name)
+        };
+        assert_eq!(
+            pick("macos").as_deref(),
+            Some("XAUUSD-Calendar-Agent_0.3.1_aarch64.dmg")
+        );
+        assert_eq!(
+            pick("linux").as_deref(),
+            Some("XAUUSD-Calendar-Agent_0.3.1_amd64.AppImage")
+        );
+        assert_eq!(
+            pick("windows").as_deref(),
+            Some("XAUUSD-Calendar-Agent-Setup.exe")
+        );
+        assert_eq!(
+            pick("other"),
+            Some("XAUUSD-Calendar-Agent-Setup.exe".to_string())
+        );
+
+        let no_match = json!({"github_release_assets": {"linux": "*.deb"}});
+        assert!(
+            select_release_asset(&assets, &release_asset_pattern(&no_match, "linux")).is_none()
+        );
+        assert!(
+            select_release_asset(&assets, &release_asset_pattern(&no_match, "other")).is_none()
+        );
     }
 }
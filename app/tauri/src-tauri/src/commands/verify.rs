@@ -0,0 +1,473 @@
+use super::history::{resolve_history_dir, sample_history_index};
+use super::*;
+use crate::calendar::{list_year_dirs, load_year_file, year_file_path, FileLimits};
+use crate::log_file;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+
+const VERIFY_STEPS: &[&str] = &[
+    "year_files",
+    "row_counts",
+    "date_parse",
+    "history_index",
+    "duplicates",
+    "output_dir",
+];
+
+const INDEX_SAMPLE_SIZE: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "pass",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "fail",
+        }
+    }
+
+    fn log_level(self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "INFO",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "ERROR",
+        }
+    }
+}
+
+struct VerifyCheck {
+    name: &'static str,
+    status: CheckStatus,
+    detail: String,
+}
+
+impl VerifyCheck {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        json!({
+            "name": self.name,
+            "status": self.status.as_str(),
+            "detail": self.detail,
+        })
+    }
+}
+
+/// Runs every integrity check over `work_root`, reading history from `history_dir` (which sits
+/// outside `work_root` in slim mode) and calendar dates with `date_format`, within `limits`. Returns `None` when
+/// cancelled between steps.
+fn run_verify_suite(
+    work_root: &Path,
+    history_dir: &Path,
+    date_format: &str,
+    limits: &FileLimits,
+    output_dir: Option<&Path>,
+    cancel: &AtomicBool,
+    progress: &mut dyn FnMut(usize, &str),
+) -> Option<Vec<VerifyCheck>> {
+    let mut checks = vec![];
+    let calendar_root = work_root.join("data").join("Economic_Calendar");
+
+    let mut step = |idx: usize| -> bool {
+        if cancel.load(AtomicOrdering::SeqCst) {
+            return false;
+        }
+        progress(idx, VERIFY_STEPS[idx]);
+        true
+    };
+
+    if !step(0) {
+        return None;
+    }
+    let mut loaded = vec![];
+    for year in list_year_dirs(&calendar_root) {
+        match year_file_path(&calendar_root, year) {
+            Some(path) => {
+                let (events, stats) = load_year_file(&path, date_format, limits);
+                loaded.push((year, events, Some(stats)));
+            }
+            None => loaded.push((year, vec![], None)),
+        }
+    }
+    let broken: Vec<String> = loaded
+        .iter()
+        .filter_map(|(year, _, stats)| match stats {
+            None => Some(format!("{year}: no .json file")),
+            Some(stats) => stats
+                .parse_error
+                .as_ref()
+                .map(|err| format!("{year}: {err}")),
+        })
+        .collect();
+    checks.push(if loaded.is_empty() {
+        VerifyCheck::new(
+            "year_files",
+            CheckStatus::Fail,
+            format!("No year directories under {}", calendar_root.display()),
+        )
+    } else if broken.is_empty() {
+        VerifyCheck::new(
+            "year_files",
+            CheckStatus::Pass,
+            format!("{} year files parsed", loaded.len()),
+        )
+    } else {
+        VerifyCheck::new("year_files", CheckStatus::Fail, broken.join("; "))
+    });
+
+    if !step(1) {
+        return None;
+    }
+    let counts: Vec<String> = loaded
+        .iter()
+        .map(|(year, _, stats)| format!("{year}: {}", stats.as_ref().map_or(0, |s| s.rows)))
+        .collect();
+    let empty_years = loaded
+        .iter()
+        .any(|(_, _, stats)| stats.as_ref().map_or(0, |s| s.rows) == 0);
+    checks.push(VerifyCheck::new(
+        "row_counts",
+        if empty_years {
+            CheckStatus::Warn
+        } else {
+            CheckStatus::Pass
+        },
+        counts.join(", "),
+    ));
+
+    if !step(2) {
+        return None;
+    }
+    let dropped: Vec<String> = loaded
+        .iter()
+        .filter_map(|(year, _, stats)| {
+            let dropped = stats.as_ref().map_or(0, |s| s.dropped);
+            (dropped > 0).then(|| format!("{year}: {dropped}"))
+        })
+        .collect();
+    checks.push(if dropped.is_empty() {
+        VerifyCheck::new("date_parse", CheckStatus::Pass, "No rows dropped")
+    } else {
+        VerifyCheck::new(
+            "date_parse",
+            CheckStatus::Warn,
+            format!("Rows dropped by the date parser: {}", dropped.join(", ")),
+        )
+    });
+
+    if !step(3) {
+        return None;
+    }
+    checks.push(
+        match sample_history_index(history_dir, INDEX_SAMPLE_SIZE, limits) {
+            Ok(sample) if sample.mismatched.is_empty() => VerifyCheck::new(
+                "history_index",
+                CheckStatus::Pass,
+                format!("{} of {} entries resolved", sample.checked, sample.entries),
+            ),
+            Ok(sample) => VerifyCheck::new(
+                "history_index",
+                CheckStatus::Fail,
+                format!(
+                    "{} of {} sampled entries do not resolve (e.g. {})",
+                    sample.mismatched.len(),
+                    sample.checked,
+                    sample
+                        .mismatched
+                        .iter()
+                        .take(3)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+            Err(err) => VerifyCheck::new("history_index", CheckStatus::Warn, err),
+        },
+    );
+
+    if !step(4) {
+        return None;
+    }
+    let mut seen = HashSet::new();
+    let mut duplicates = 0;
+    for (_, events, _) in loaded.iter() {
+        for e in events {
+            let key = (e.dt_utc, e.currency.clone(), e.event.clone());
+            if !seen.insert(key) {
+                duplicates += 1;
+            }
+        }
+    }
+    checks.push(VerifyCheck::new(
+        "duplicates",
+        if duplicates == 0 {
+            CheckStatus::Pass
+        } else {
+            CheckStatus::Warn
+        },
+        format!("{duplicates} duplicate events"),
+    ));
+
+    if !step(5) {
+        return None;
+    }
+    checks.push(match output_dir {
+        None => VerifyCheck::new("output_dir", CheckStatus::Pass, "Output dir not configured"),
+        Some(output_dir) => {
+            let mut pending = vec![];
+            let mut errors = vec![];
+            for (sub, src) in [
+                ("Economic_Calendar", calendar_root.clone()),
+                ("event_history_index", history_dir.to_path_buf()),
+            ] {
+                let dst = output_dir.join("data").join(sub);
+                match sync_util::plan_mirror(&src, &dst) {
+                    Ok(plan) if plan.is_in_sync() => {}
+                    Ok(plan) => pending.push(format!(
+                        "{sub}: {} to copy, {} to delete",
+                        plan.copy.len(),
+                        plan.delete.len()
+                    )),
+                    Err(err) => errors.push(format!("{sub}: {err}")),
+                }
+            }
+            if !errors.is_empty() {
+                VerifyCheck::new("output_dir", CheckStatus::Fail, errors.join("; "))
+            } else if pending.is_empty() {
+                VerifyCheck::new("output_dir", CheckStatus::Pass, "Output dir matches source")
+            } else {
+                VerifyCheck::new("output_dir", CheckStatus::Warn, pending.join("; "))
+            }
+        }
+    });
+
+    Some(checks)
+}
+
+#[tauri::command]
+pub fn verify_data(app: tauri::AppHandle, state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let cfg = config::load_config();
+    let work_root = config::working_root_dir(&cfg);
+    let history_dir = resolve_history_dir(&cfg, &work_root);
+    let date_format = crate::time_util::source_date_format(&cfg);
+    let limits = FileLimits::from_config(&cfg);
+    let output_dir = config::get_str(&cfg, "output_dir");
+    let cancel = {
+        let mut runtime = state.lock().expect("runtime lock");
+        if runtime.verify_active {
+            return json!({"ok": false, "message": "Verification already running"});
+        }
+        runtime.verify_active = true;
+        runtime.verify_cancel = Arc::new(AtomicBool::new(false));
+        push_log(&mut runtime, "Data verification started", "INFO");
+        runtime.verify_cancel.clone()
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let output_dir = (!output_dir.is_empty()).then(|| PathBuf::from(output_dir));
+        let progress_app = app.clone();
+        let mut progress = |idx: usize, name: &str| {
+            let _ = progress_app.emit(
+                "xauusd:verify-progress",
+                json!({"step": idx + 1, "total": VERIFY_STEPS.len(), "name": name}),
+            );
+        };
+        let checks = run_verify_suite(
+            &work_root,
+            &history_dir,
+            &date_format,
+            &limits,
+            output_dir.as_deref(),
+            &cancel,
+            &mut progress,
+        );
+
+        let runtime_state = app.state::<Mutex<RuntimeState>>();
+        let mut runtime = runtime_state.lock().expect("runtime lock");
+        runtime.verify_active = false;
+        let result = match checks {
+            None => {
+                push_log(&mut runtime, "Data verification cancelled", "WARN");
+                json!({"ok": true, "cancelled": true, "checks": []})
+            }
+            Some(checks) => {
+                let failed = checks
+                    .iter()
+                    .filter(|c| c.status == CheckStatus::Fail)
+                    .count();
+                let warned = checks
+                    .iter()
+                    .filter(|c| c.status == CheckStatus::Warn)
+                    .count();
+                let level = if failed > 0 {
+                    "ERROR"
+                } else if warned > 0 {
+                    "WARN"
+                } else {
+                    "INFO"
+                };
+                push_log(
+                    &mut runtime,
+                    &format!("Data verification finished ({failed} failed, {warned} warnings)"),
+                    level,
+                );
+                let lines: Vec<(String, String)> = checks
+                    .iter()
+                    .map(|c| {
+                        (
+                            c.status.log_level().to_string(),
+                            format!("verify_data {}: {}", c.name, c.detail),
+                        )
+                    })
+                    .collect();
+                if let Err(err) = log_file::append_lines(&lines, &runtime.log_filter) {
+                    push_log(
+                        &mut runtime,
+                        &format!("Failed to write verification results to log file: {err}"),
+                        "WARN",
+                    );
+                }
+                json!({
+                    "ok": true,
+                    "cancelled": false,
+                    "checks": checks.iter().map(VerifyCheck::to_value).collect::<Vec<_>>(),
+                    "finishedAt": now_display_time(),
+                })
+            }
+        };
+        runtime.verify_result = result.clone();
+        drop(runtime);
+        let _ = app.emit("xauusd:verify-result", result);
+    });
+
+    json!({"ok": true, "started": true})
+}
+
+#[tauri::command]
+pub fn cancel_verify_data(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let runtime = state.lock().expect("runtime lock");
+    if !runtime.verify_active {
+        return json!({"ok": false, "message": "No verification running"});
+    }
+    runtime.verify_cancel.store(true, AtomicOrdering::SeqCst);
+    json!({"ok": true})
+}
+
+#[tauri::command]
+pub fn get_verify_data_result(state: tauri::State<'_, Mutex<RuntimeState>>) -> Value {
+    let runtime = state.lock().expect("runtime lock");
+    json!({
+        "ok": true,
+        "active": runtime.verify_active,
+        "result": runtime.verify_result.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::history::history_dir_in;
+    use crate::time_util::DEFAULT_SOURCE_DATE_FORMAT;
+    use std::fs;
+
+    fn write(path: &Path, text: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    fn broken_fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let cal = dir.path().join("data").join("Economic_Calendar");
+        write(&cal.join("2020").join("2020_calendar.json"), "[{not json");
+        write(
+            &cal.join("2021").join("2021_calendar.json"),
+            r#"[
+                {"Date": "2021-03-01", "Time": "13:30", "Event": "CPI", "Cur.": "USD"},
+                {"Date": "2021-03-01", "Time": "13:30", "Event": "CPI", "Cur.": "USD"},
+                {"Date": "2021-13-45", "Time": "13:30", "Event": "PPI", "Cur.": "USD"}
+            ]"#,
+        );
+        let history = dir.path().join("data").join("event_history_index");
+        let line = "{\"eventId\": \"USD::CPI::none\", \"points\": []}\n";
+        write(
+            &history.join("event_history_by_event.ndjson"),
+            &format!("{line}{line}"),
+        );
+        write(
+            &history.join("event_history_by_event.index.json"),
+            r#"{"index": {"USD::CPI::none": 0, "USD::PPI::none": 5}}"#,
+        );
+        dir
+    }
+
+    fn status_of<'a>(checks: &'a [VerifyCheck], name: &str) -> &'a VerifyCheck {
+        checks.iter().find(|c| c.name == name).unwrap()
+    }
+
+    #[test]
+    fn broken_fixture_flags_expected_failures() {
+        let dir = broken_fixture();
+        let output = tempfile::tempdir().unwrap();
+        let cancel = AtomicBool::new(false);
+        let mut steps = vec![];
+        let checks = run_verify_suite(
+            dir.path(),
+            &history_dir_in(dir.path()),
+            DEFAULT_SOURCE_DATE_FORMAT,
+            &FileLimits::default(),
+            Some(output.path()),
+            &cancel,
+            &mut |idx, _| steps.push(idx),
+        )
+        .unwrap();
+
+        assert_eq!(steps, vec![0, 1, 2, 3, 4, 5]);
+        let year_files = status_of(&checks, "year_files");
+        assert_eq!(year_files.status, CheckStatus::Fail);
+        assert!(year_files.detail.starts_with("2020:"));
+        assert_eq!(status_of(&checks, "row_counts").status, CheckStatus::Warn);
+        assert_eq!(status_of(&checks, "date_parse").status, CheckStatus::Warn);
+        assert_eq!(
+            status_of(&checks, "history_index").status,
+            CheckStatus::Fail
+        );
+        assert_eq!(
+            status_of(&checks, "duplicates").detail,
+            "1 duplicate events"
+        );
+        assert_eq!(status_of(&checks, "output_dir").status, CheckStatus::Warn);
+    }
+
+    #[test]
+    fn cancelled_suite_stops_before_next_step() {
+        let dir = broken_fixture();
+        let cancel = AtomicBool::new(false);
+        let mut steps = 0;
+        let checks = run_verify_suite(
+            dir.path(),
+            &history_dir_in(dir.path()),
+            DEFAULT_SOURCE_DATE_FORMAT,
+            &FileLimits::default(),
+            None,
+            &cancel,
+            &mut |_, _| {
+                steps += 1;
+                cancel.store(true, AtomicOrdering::SeqCst);
+            },
+        );
+        assert!(checks.is_none());
+        assert_eq!(steps, 1);
+    }
+}
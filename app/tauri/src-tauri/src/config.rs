@@ -1,3 +1,5 @@
+use crate::app_settings::AppSettings;
+use crate::portable::{check_writable, portable_dir_active, PORTABLE_DIR_NAME};
 use serde_json::{json, Map, Value};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -11,21 +13,39 @@ fn exe_dir() -> Option<PathBuf> {
         .and_then(|p| p.parent().map(|p| p.to_path_buf()))
 }
 
-fn portable_data_dir() -> Option<PathBuf> {
-    // Opt-in portable mode:
-    // - If `user-data/` exists next to the executable, we treat it as portable.
-    // - Or explicitly force portable via env var (useful for dev / zipped builds).
-    if std::env::var("XAUUSD_CALENDAR_AGENT_PORTABLE")
+/// Where portable data lives, whether or not portable mode is active.
+pub fn portable_dir_candidate() -> Option<PathBuf> {
+    exe_dir().map(|dir| dir.join(PORTABLE_DIR_NAME))
+}
+
+/// Portable mode forced by `XAUUSD_CALENDAR_AGENT_PORTABLE=1`.
+pub fn portable_forced() -> bool {
+    std::env::var("XAUUSD_CALENDAR_AGENT_PORTABLE")
         .ok()
         .as_deref()
         == Some("1")
-    {
-        return exe_dir().map(|dir| dir.join("user-data"));
+}
+
+/// The data dir pinned by `XAUUSD_CALENDAR_AGENT_DATA_DIR`, which wins over both modes.
+pub fn data_dir_overridden() -> bool {
+    std::env::var("XAUUSD_CALENDAR_AGENT_DATA_DIR").is_ok_and(|dir| !dir.trim().is_empty())
+}
+
+pub fn portable_data_dir() -> Option<PathBuf> {
+    // Opt-in portable mode:
+    // - If `user-data/` exists next to the executable (and was not retired by
+    //   `convert_to_installed`), we treat it as portable.
+    // - Or explicitly force portable via env var (useful for dev / zipped builds).
+    if portable_forced() {
+        return portable_dir_candidate();
     }
 
-    exe_dir()
-        .map(|dir| dir.join("user-data"))
-        .filter(|p| p.exists())
+    portable_dir_candidate().filter(|p| portable_dir_active(p))
+}
+
+/// The per-user AppData location used when not portable.
+pub fn installed_data_dir() -> Option<PathBuf> {
+    platform_appdata_dir()
 }
 
 pub fn install_dir() -> PathBuf {
@@ -36,6 +56,17 @@ pub fn install_dir() -> PathBuf {
     })
 }
 
+/// Whether a probe file can be created in `dir`.
+pub fn dir_writable(dir: &Path) -> bool {
+    check_writable(dir).is_ok()
+}
+
+/// Probed once at startup (see `RuntimeState::install_dir_writable`). Unzipping into Program
+/// Files leaves the install dir read-only, so portable data cannot live next to the exe.
+pub fn install_dir_writable() -> bool {
+    dir_writable(&install_dir())
+}
+
 #[cfg(target_os = "windows")]
 fn platform_appdata_dir() -> Option<PathBuf> {
     legacy_roaming_dir()
@@ -107,7 +138,6 @@ pub fn log_dir() -> PathBuf {
 }
 
 pub fn load_config() -> Value {
-    let defaults = default_config();
     let path = config_path();
 
     // If we're using `user-data/` but it doesn't have config yet, migrate from the legacy roaming
@@ -130,25 +160,83 @@ pub fn load_config() -> Value {
         }
     }
 
-    let text = fs::read_to_string(&path).unwrap_or_default();
-    let parsed: Value = serde_json::from_str(&text).unwrap_or_else(|_| json!({}));
-    let merged = merge_objects(defaults, parsed);
+    load_config_from(&path)
+}
+
+/// `load_config` for a config file at `path`: merged over the defaults, and written back when
+/// missing or when legacy keys were migrated.
+pub fn load_config_from(path: &Path) -> Value {
+    let (parsed, migrated) = read_config_file(path);
+    let merged = merge_objects(default_config(), parsed);
 
-    if !path.exists() {
-        let _ = save_config(&merged);
+    if !path.exists() || !migrated.is_empty() {
+        let _ = save_config_to(path, &merged);
+    }
+    if !migrated.is_empty() {
+        let lines: Vec<(String, String)> = migrated
+            .into_iter()
+            .map(|message| ("INFO".to_string(), message))
+            .collect();
+        let _ = crate::log_file::append_lines(
+            &lines,
+            &crate::log_file::LogFilter::from_config(&merged),
+        );
     }
     merged
 }
 
+/// The config file as written, with legacy keys moved to their current names; `{}` when it is
+/// missing or unreadable. Also returns one message per migrated key.
+fn read_config_file(path: &Path) -> (Value, Vec<String>) {
+    let text = fs::read_to_string(path).unwrap_or_default();
+    let mut parsed: Value = serde_json::from_str(&text).unwrap_or_else(|_| json!({}));
+    let migrated = parsed
+        .as_object_mut()
+        .map(apply_legacy_aliases)
+        .unwrap_or_default();
+    (parsed, migrated)
+}
+
+/// Keys used by earlier builds and the current key each one became.
+const LEGACY_KEY_ALIASES: &[(&str, &str)] = &[
+    ("timezone_offset", "calendar_utc_offset_minutes"),
+    ("timezone_mode", "calendar_timezone_mode"),
+];
+
+/// Moves legacy keys onto their current names before defaults are merged, so upgrades keep
+/// the user's value. A current key already present wins and the legacy one is just dropped.
+/// Returns one message per migrated key.
+fn apply_legacy_aliases(cfg: &mut Map<String, Value>) -> Vec<String> {
+    let mut migrated = vec![];
+    for (legacy, current) in LEGACY_KEY_ALIASES {
+        let Some(value) = cfg.remove(*legacy) else {
+            continue;
+        };
+        if cfg.contains_key(*current) {
+            migrated.push(format!(
+                "Config: dropped legacy {legacy}; {current} is already set"
+            ));
+            continue;
+        }
+        migrated.push(format!("Config: migrated {legacy} to {current} ({value})"));
+        cfg.insert(current.to_string(), value);
+    }
+    migrated
+}
+
 pub fn save_config(value: &Value) -> Result<(), String> {
-    let path = config_path();
+    save_config_to(&config_path(), value)
+}
+
+/// `save_config` for a config file at `path`.
+pub fn save_config_to(path: &Path, value: &Value) -> Result<(), String> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
     let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
     let tmp = path.with_extension(format!("tmp-{}", std::process::id()));
     fs::write(&tmp, text).map_err(|e| e.to_string())?;
-    fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())?;
     Ok(())
 }
 
@@ -176,17 +264,6 @@ pub fn get_bool(cfg: &Value, key: &str, fallback: bool) -> bool {
     cfg.get(key).and_then(|v| v.as_bool()).unwrap_or(fallback)
 }
 
-pub fn get_i64(cfg: &Value, key: &str, fallback: i64) -> i64 {
-    cfg.get(key).and_then(|v| v.as_i64()).unwrap_or(fallback)
-}
-
-pub fn get_i32(cfg: &Value, key: &str, fallback: i32) -> i32 {
-    cfg.get(key)
-        .and_then(|v| v.as_i64())
-        .map(|v| v as i32)
-        .unwrap_or(fallback)
-}
-
 pub fn set_string(cfg: &mut Value, key: &str, value: String) -> Result<(), String> {
     let obj = cfg.as_object_mut().ok_or("config invalid")?;
     obj.insert(key.to_string(), Value::String(value));
@@ -205,14 +282,85 @@ pub fn set_number(cfg: &mut Value, key: &str, value: i64) -> Result<(), String>
     Ok(())
 }
 
-fn default_config() -> Value {
-    let mut base = Map::<String, Value>::new();
+/// Serializes `update_config` calls so two read-modify-write cycles cannot drop each other's
+/// change.
+static CONFIG_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Loads the config, applies `change` and saves it, all under `CONFIG_LOCK`. Returns the config
+/// before and after the change; nothing is saved when `change` fails.
+pub fn update_config(
+    change: impl FnOnce(&mut Value) -> Result<(), String>,
+) -> Result<(Value, Value), String> {
+    let _guard = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let before = load_config();
+    let mut cfg = before.clone();
+    change(&mut cfg)?;
+    save_config(&cfg)?;
+    Ok((before, cfg))
+}
+
+/// Path settings that keep their previous values in a `{kind}_history` list.
+pub const PATH_HISTORY_KINDS: &[&str] = &["output_dir", "temporary_path", "repo_path"];
+
+/// Entries kept in each `{kind}_history` list.
+pub const PATH_HISTORY_LIMIT: usize = 10;
+
+/// Paths compare equal regardless of trailing separators, and of case on Windows.
+fn same_path(a: &str, b: &str) -> bool {
+    let trim = |p: &str| p.trim().trim_end_matches(['/', '\\']).to_string();
+    if cfg!(windows) {
+        trim(a).eq_ignore_ascii_case(&trim(b))
+    } else {
+        trim(a) == trim(b)
+    }
+}
+
+/// Previously used values of the path setting `kind`, most recent first.
+pub fn path_history(cfg: &Value, kind: &str) -> Vec<String> {
+    cfg.get(format!("{kind}_history"))
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Sets the path setting `kind` to `path` and moves the value it replaces to the front of
+/// `{kind}_history`. The list drops empties, duplicates and the new current value, and keeps at
+/// most `PATH_HISTORY_LIMIT` entries.
+pub fn set_path_setting(cfg: &mut Value, kind: &str, path: &str) -> Result<(), String> {
+    let previous = get_str(cfg, kind);
+    let mut history = path_history(cfg, kind);
+    history.insert(0, previous);
+    let mut kept: Vec<String> = vec![];
+    for entry in history {
+        if entry.trim().is_empty()
+            || same_path(&entry, path)
+            || kept.iter().any(|k| same_path(k, &entry))
+        {
+            continue;
+        }
+        kept.push(entry);
+    }
+    kept.truncate(PATH_HISTORY_LIMIT);
+    set_string(cfg, kind, path.to_string())?;
+    let obj = cfg.as_object_mut().ok_or("config invalid")?;
+    obj.insert(format!("{kind}_history"), json!(kept));
+    Ok(())
+}
+
+/// `AppSettings` defaults plus the bookkeeping keys only the app itself writes.
+pub fn default_config() -> Value {
+    let mut base = AppSettings::default_entries();
     base.insert("schema_version".to_string(), Value::Number(2.into()));
     // `repo_path` is only used as an internal git cache (if enabled).
     // The app reads calendar data from the install-root `data/` folder.
     base.insert("repo_path".to_string(), Value::String("".to_string()));
     base.insert("sync_repo_path".to_string(), Value::String("".to_string()));
-    base.insert("temporary_path".to_string(), Value::String("".to_string()));
     base.insert("enable_sync_repo".to_string(), Value::Bool(false));
     base.insert(
         "sync_repo_confirmed_path".to_string(),
@@ -238,14 +386,10 @@ fn default_config() -> Value {
     base.insert("created_paths".to_string(), json!([]));
     base.insert("output_dir".to_string(), Value::String("".to_string()));
     base.insert("output_dir_last_sync_at".to_string(), json!({}));
+    base.insert("output_dir_sync_scope".to_string(), json!({}));
     base.insert("repo_path_last_pull_at".to_string(), json!({}));
     base.insert("repo_path_last_pull_sha".to_string(), json!({}));
     base.insert("auto_pull_days".to_string(), Value::Number(1.into()));
-    base.insert(
-        "check_interval_minutes".to_string(),
-        Value::Number(360.into()),
-    );
-    base.insert("enable_temporary_path".to_string(), Value::Bool(false));
     base.insert(
         "ui_min_interval_minutes".to_string(),
         Value::Number(10.into()),
@@ -262,64 +406,511 @@ fn default_config() -> Value {
         "background_max_workers".to_string(),
         Value::Number(4.into()),
     );
-    base.insert("auto_sync_after_pull".to_string(), Value::Bool(true));
-    base.insert("debug".to_string(), Value::Bool(false));
     base.insert("last_pull_at".to_string(), Value::String("".to_string()));
     base.insert("last_sync_at".to_string(), Value::String("".to_string()));
     base.insert("last_pull_sha".to_string(), Value::String("".to_string()));
-    base.insert("auto_update_enabled".to_string(), Value::Bool(true));
-    base.insert(
-        "auto_update_interval_minutes".to_string(),
-        Value::Number(60.into()),
-    );
     base.insert(
         "last_update_check_at".to_string(),
         Value::String("".to_string()),
     );
     base.insert(
-        "github_repo".to_string(),
-        Value::String("yiyousiow000814/XAUUSD-Calendar-Agent".to_string()),
+        "github_release_asset_name".to_string(),
+        Value::String("Setup.exe".to_string()),
     );
     base.insert(
-        "github_branch".to_string(),
-        Value::String("main".to_string()),
+        "github_release_assets".to_string(),
+        json!({"windows": "Setup.exe", "macos": "*.dmg", "linux": "*.AppImage"}),
     );
     base.insert(
-        "github_release_asset_name".to_string(),
-        Value::String("Setup.exe".to_string()),
+        "github_release_etag".to_string(),
+        Value::String("".to_string()),
     );
+    base.insert("github_release_cache".to_string(), json!({}));
     base.insert("github_token".to_string(), Value::String("".to_string()));
     base.insert(
         "github_token_last_seen".to_string(),
         Value::String("".to_string()),
     );
-    base.insert("run_on_startup".to_string(), Value::Bool(true));
     base.insert(
-        "autostart_launch_mode".to_string(),
-        Value::String("tray".to_string()),
-    );
-    base.insert(
-        "close_behavior".to_string(),
-        Value::String("exit".to_string()),
-    );
-    base.insert("settings_auto_save".to_string(), Value::Bool(true));
-    base.insert(
-        "theme_preference".to_string(),
-        Value::String("system".to_string()),
-    );
-    base.insert("enable_system_theme".to_string(), Value::Bool(false));
-    base.insert("split_ratio".to_string(), json!(0.66));
-    base.insert(
-        "calendar_timezone_mode".to_string(),
-        Value::String("system".to_string()),
-    );
-    base.insert(
-        "calendar_utc_offset_minutes".to_string(),
-        Value::Number(0.into()),
+        "crash_report_seen".to_string(),
+        Value::String("".to_string()),
     );
+    base.insert("timezone_hint_dismissed_offset".to_string(), Value::Null);
     Value::Object(base)
 }
 
+/// Settings carried by `export_settings`/`import_settings`. Tokens, paths, path histories,
+/// window geometry and bookkeeping stay machine-local; a new setting travels only once it is
+/// listed here.
+pub const PORTABLE_SETTING_KEYS: &[&str] = &[
+    // Sync
+    "auto_sync_after_pull",
+    "sync_timeout_secs",
+    "sync_interval_minutes",
+    "sync_atomic",
+    "sync_scope",
+    "sync_delete_confirm_threshold",
+    "sync_delete_confirm_pct",
+    "sync_delete_confirm_timeout_secs",
+    "backup_keep_count",
+    // Updates
+    "auto_update_enabled",
+    "auto_update_interval_minutes",
+    "update_notify_mode",
+    "update_verify_install",
+    // Notifications
+    "event_alerts_enabled",
+    "event_alert_minutes_before",
+    "event_alert_min_impact",
+    "tray_alert_minutes",
+    "respect_system_dnd",
+    // Timezone
+    "calendar_timezone_mode",
+    "calendar_utc_offset_minutes",
+    // Display and behaviour
+    "calendar_year_fallback",
+    "no_time_label",
+    "event_name_max_len",
+    "recent_highlight_hours",
+    "min_forecast_change_pct",
+    "date_format",
+    "source_date_format",
+    "max_calendar_file_mb",
+    "max_events_per_file",
+    "theme_preference",
+    "enable_system_theme",
+    "settings_auto_save",
+    "refresh_on_focus",
+    "run_on_startup",
+    "autostart_launch_mode",
+    "close_behavior",
+    "second_instance_action",
+];
+
+/// The allowlisted subset of `cfg`.
+pub fn portable_settings(cfg: &Value) -> Map<String, Value> {
+    PORTABLE_SETTING_KEYS
+        .iter()
+        .filter_map(|key| Some((key.to_string(), cfg.get(*key)?.clone())))
+        .collect()
+}
+
+fn same_json_kind(a: &Value, b: &Value) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+/// Keeps allowlisted keys whose type matches the default; returns them with the names of the
+/// keys that were dropped.
+pub fn validate_portable_settings(
+    incoming: &Map<String, Value>,
+) -> (Map<String, Value>, Vec<String>) {
+    let defaults = default_config();
+    let mut accepted = Map::new();
+    let mut rejected = vec![];
+    for (key, value) in incoming {
+        let allowed = PORTABLE_SETTING_KEYS.contains(&key.as_str())
+            && defaults.get(key).is_some_and(|d| same_json_kind(d, value));
+        if allowed {
+            accepted.insert(key.clone(), value.clone());
+        } else {
+            rejected.push(key.clone());
+        }
+    }
+    (accepted, rejected)
+}
+
+/// Keys in `incoming` whose value differs from `cfg`, as `{key, from, to}` rows.
+pub fn settings_diff(cfg: &Value, incoming: &Map<String, Value>) -> Vec<Value> {
+    incoming
+        .iter()
+        .filter(|(key, value)| cfg.get(key.as_str()) != Some(value))
+        .map(|(key, value)| {
+            json!({
+                "key": key,
+                "from": cfg.get(key.as_str()).cloned().unwrap_or(Value::Null),
+                "to": value,
+            })
+        })
+        .collect()
+}
+
+/// Append-only record of persisted setting changes, next to `app.log`.
+pub const AUDIT_LOG_FILE: &str = "audit.log";
+/// `audit.log` rolls over to `audit.log.1` (replacing it) past this size.
+const AUDIT_LOG_MAX_BYTES: u64 = 1024 * 1024;
+const AUDIT_VALUE_MAX_CHARS: usize = 80;
+
+pub fn audit_log_path() -> PathBuf {
+    log_dir().join(AUDIT_LOG_FILE)
+}
+
+fn is_secret_key(key: &str) -> bool {
+    key.contains("token") || key.contains("secret") || key.contains("password")
+}
+
+/// Display form of a value in the audit log: secrets redacted, objects and arrays summarized
+/// by size, long strings cut from the front so paths keep their tail.
+fn audit_value(key: &str, value: Option<&Value>) -> Value {
+    match value {
+        None | Some(Value::Null) => Value::Null,
+        Some(Value::String(s)) if s.is_empty() => json!(""),
+        Some(_) if is_secret_key(key) => json!("[redacted]"),
+        Some(Value::Object(map)) => json!(format!("{{{} entries}}", map.len())),
+        Some(Value::Array(items)) => json!(format!("[{} items]", items.len())),
+        Some(Value::String(s)) => {
+            let count = s.chars().count();
+            if count <= AUDIT_VALUE_MAX_CHARS {
+                json!(s)
+            } else {
+                let tail: String = s
+                    .chars()
+                    .skip(count - (AUDIT_VALUE_MAX_CHARS - 1))
+                    .collect();
+                json!(format!("…{tail}"))
+            }
+        }
+        Some(v) => v.clone(),
+    }
+}
+
+/// Every key of the merged config with its value and where it came from: `"file"` when the
+/// file sets it to something other than the default, else `"default"`. A file that merely
+/// repeats a default (as every saved config does) reads as default. Secrets are redacted.
+pub fn config_provenance(defaults: &Value, file: &Value) -> Map<String, Value> {
+    let empty = Map::new();
+    let defaults = defaults.as_object().unwrap_or(&empty);
+    let file = file.as_object().unwrap_or(&empty);
+    let keys: std::collections::BTreeSet<&String> = defaults.keys().chain(file.keys()).collect();
+    keys.into_iter()
+        .map(|key| {
+            let (value, source) = match file.get(key) {
+                Some(value) if defaults.get(key) != Some(value) => (value, "file"),
+                _ => (defaults.get(key).unwrap_or(&Value::Null), "default"),
+            };
+            let value = match value {
+                Value::String(s) if !s.is_empty() && is_secret_key(key) => json!("[redacted]"),
+                other => other.clone(),
+            };
+            (key.clone(), json!({"value": value, "source": source}))
+        })
+        .collect()
+}
+
+/// `config_provenance` for the config file on disk.
+pub fn load_config_provenance() -> Map<String, Value> {
+    config_provenance(&default_config(), &read_config_file(&config_path()).0)
+}
+
+/// Top-level keys that differ between `old` and `new`, as audit-safe `{key, from, to}` rows.
+pub fn config_changes(old: &Value, new: &Value) -> Vec<Value> {
+    let empty = Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+    let keys: std::collections::BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .map(|key| {
+            json!({
+                "key": key,
+                "from": audit_value(key, old.get(key)),
+                "to": audit_value(key, new.get(key)),
+            })
+        })
+        .collect()
+}
+
+fn append_audit_entry(path: &Path, entry: &Value) -> Result<(), String> {
+    use std::io::Write;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    if fs::metadata(path).map(|m| m.len()).unwrap_or(0) >= AUDIT_LOG_MAX_BYTES {
+        let _ = fs::rename(path, path.with_extension("log.1"));
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{entry}").map_err(|e| e.to_string())
+}
+
+/// Appends one audit line for the changes between `old_cfg` and `new_cfg` made by `source`
+/// (the command name). Nothing is written when no key changed.
+pub fn record_config_change(old_cfg: &Value, new_cfg: &Value, source: &str) -> Result<(), String> {
+    write_config_change(&audit_log_path(), old_cfg, new_cfg, source)
+}
+
+fn write_config_change(
+    path: &Path,
+    old_cfg: &Value,
+    new_cfg: &Value,
+    source: &str,
+) -> Result<(), String> {
+    let changes = config_changes(old_cfg, new_cfg);
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let entry = json!({
+        "at": crate::time_util::now_iso_time(),
+        "source": source,
+        "changes": changes,
+    });
+    append_audit_entry(path, &entry)
+}
+
+/// Last `limit` audit entries, oldest first, across the current and rotated file.
+pub fn read_audit_log(path: &Path, limit: usize) -> Vec<Value> {
+    let mut entries: Vec<Value> = [path.with_extension("log.1"), path.to_path_buf()]
+        .iter()
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .flat_map(|text| {
+            text.lines()
+                .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let skip = entries.len().saturating_sub(limit);
+    entries.drain(..skip);
+    entries
+}
+
 pub fn path_is_usable_dir(path: &Path) -> bool {
     path.exists() && path.is_dir()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_only_dir_is_not_writable() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(dir_writable(dir.path()));
+        assert!(!dir_writable(&dir.path().join("missing")));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let locked = dir.path().join("locked");
+            fs::create_dir(&locked).unwrap();
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o555)).unwrap();
+            // Root ignores permission bits; only assert when the OS actually refuses writes.
+            let refused = fs::write(locked.join("probe"), b"").is_err();
+            let _ = fs::remove_file(locked.join("probe"));
+            assert_eq!(dir_writable(&locked), !refused);
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+    }
+
+    #[test]
+    fn path_history_is_deduped_capped_and_most_recent_first() {
+        let mut cfg = default_config();
+        set_path_setting(&mut cfg, "output_dir", "D:/a").unwrap();
+        assert!(path_history(&cfg, "output_dir").is_empty());
+        set_path_setting(&mut cfg, "output_dir", "D:/b").unwrap();
+        set_path_setting(&mut cfg, "output_dir", "D:/c").unwrap();
+        assert_eq!(path_history(&cfg, "output_dir"), vec!["D:/b", "D:/a"]);
+
+        // Returning to an older path moves it out of the history; re-setting the same one is a no-op.
+        set_path_setting(&mut cfg, "output_dir", "D:/a/").unwrap();
+        assert_eq!(get_str(&cfg, "output_dir"), "D:/a/");
+        assert_eq!(path_history(&cfg, "output_dir"), vec!["D:/c", "D:/b"]);
+        set_path_setting(&mut cfg, "output_dir", "D:/a/").unwrap();
+        assert_eq!(path_history(&cfg, "output_dir"), vec!["D:/c", "D:/b"]);
+
+        for i in 0..20 {
+            set_path_setting(&mut cfg, "output_dir", &format!("E:/{i}")).unwrap();
+        }
+        let history = path_history(&cfg, "output_dir");
+        assert_eq!(history.len(), PATH_HISTORY_LIMIT);
+        assert_eq!(history[0], "E:/18");
+        assert_eq!(history[9], "E:/9");
+        assert!(path_history(&cfg, "temporary_path").is_empty());
+    }
+
+    #[test]
+    fn portable_settings_follow_the_allowlist() {
+        let mut cfg = default_config();
+        set_string(&mut cfg, "github_token", "secret".to_string()).unwrap();
+        set_string(&mut cfg, "output_dir", "D:/mt5".to_string()).unwrap();
+        let portable = portable_settings(&cfg);
+        assert_eq!(portable.len(), PORTABLE_SETTING_KEYS.len());
+        for key in [
+            "github_token",
+            "output_dir",
+            "output_dir_history",
+            "split_ratio",
+            "temporary_path",
+        ] {
+            assert!(!portable.contains_key(key), "{key} must stay local");
+        }
+
+        let incoming = json!({
+            "no_time_label": "Tentative",
+            "github_token": "leaked",
+            "sync_timeout_secs": "soon",
+            "event_alerts_enabled": true,
+        });
+        let (accepted, rejected) = validate_portable_settings(incoming.as_object().unwrap());
+        let mut accepted_keys: Vec<&String> = accepted.keys().collect();
+        accepted_keys.sort();
+        assert_eq!(accepted_keys, vec!["event_alerts_enabled", "no_time_label"]);
+        let mut rejected = rejected;
+        rejected.sort();
+        assert_eq!(rejected, vec!["github_token", "sync_timeout_secs"]);
+    }
+
+    #[test]
+    fn settings_diff_lists_only_changed_keys() {
+        let cfg = default_config();
+        let incoming = json!({
+            "no_time_label": "All Day",
+            "event_alert_minutes_before": 15,
+            "calendar_timezone_mode": "utc",
+        });
+        let diff = settings_diff(&cfg, incoming.as_object().unwrap());
+        let mut keys: Vec<&str> = diff.iter().filter_map(|d| d["key"].as_str()).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec!["calendar_timezone_mode", "event_alert_minutes_before"]
+        );
+        let minutes = diff
+            .iter()
+            .find(|d| d["key"] == "event_alert_minutes_before")
+            .unwrap();
+        assert_eq!(minutes["from"], json!(5));
+        assert_eq!(minutes["to"], json!(15));
+    }
+
+    #[test]
+    fn config_changes_redact_and_summarize() {
+        let old = json!({
+            "github_token": "ghp_old",
+            "output_dir": "D:/mt5",
+            "output_dir_last_sync_at": {"D:/mt5": "2026-01-01T00:00:00Z"},
+            "sync_atomic": false,
+            "debug": false,
+        });
+        let long_path = format!("E:/{}/MQL5/Files", "nested/".repeat(20));
+        let new = json!({
+            "github_token": "ghp_new",
+            "output_dir": long_path,
+            "output_dir_last_sync_at": {
+                "D:/mt5": "2026-01-01T00:00:00Z",
+                "E:/mt5": "2026-02-01T00:00:00Z",
+            },
+            "sync_atomic": true,
+            "debug": false,
+        });
+        let changes = config_changes(&old, &new);
+        let keys: Vec<&str> = changes.iter().map(|c| c["key"].as_str().unwrap()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "github_token",
+                "output_dir",
+                "output_dir_last_sync_at",
+                "sync_atomic"
+            ]
+        );
+        assert_eq!(changes[0]["from"], "[redacted]");
+        assert_eq!(changes[0]["to"], "[redacted]");
+        let shown = changes[1]["to"].as_str().unwrap();
+        assert_eq!(shown.chars().count(), AUDIT_VALUE_MAX_CHARS);
+        assert!(shown.starts_with('…') && shown.ends_with("/MQL5/Files"));
+        assert_eq!(changes[2]["from"], "{1 entries}");
+        assert_eq!(changes[2]["to"], "{2 entries}");
+        assert_eq!(changes[3]["to"], true);
+    }
+
+    #[test]
+    fn provenance_marks_customized_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let mut file = default_config();
+        set_string(&mut file, "github_token", "ghp_secret".to_string()).unwrap();
+        set_bool(&mut file, "sync_atomic", true).unwrap();
+        let file = file.as_object_mut().unwrap();
+        file.insert("timezone_offset".to_string(), json!(480));
+        file.remove("calendar_utc_offset_minutes");
+        file.insert("from_a_newer_build".to_string(), json!("x"));
+        fs::write(&path, serde_json::to_string(file).unwrap()).unwrap();
+
+        let defaults = default_config();
+        let provenance = config_provenance(&defaults, &read_config_file(&path).0);
+        let source = |key: &str| provenance[key]["source"].as_str().unwrap();
+        assert_eq!(provenance["github_token"]["value"], "[redacted]");
+        assert_eq!(source("github_token"), "file");
+        assert_eq!(provenance["sync_atomic"]["value"], true);
+        assert_eq!(source("sync_atomic"), "file");
+        // A legacy key reports under the name it migrates to.
+        assert_eq!(provenance["calendar_utc_offset_minutes"]["value"], 480);
+        assert_eq!(source("calendar_utc_offset_minutes"), "file");
+        assert!(!provenance.contains_key("timezone_offset"));
+        assert_eq!(source("from_a_newer_build"), "file");
+        // Saved defaults are not customizations.
+        assert_eq!(source("debug"), "default");
+        assert_eq!(provenance["debug"]["value"], false);
+        let customized = provenance
+            .values()
+            .filter(|e| e["source"] == "file")
+            .count();
+        assert_eq!(customized, 4);
+        assert_eq!(provenance.len(), defaults.as_object().unwrap().len() + 1);
+
+        let missing = config_provenance(&defaults, &read_config_file(&dir.path().join("none")).0);
+        assert!(missing.values().all(|e| e["source"] == "default"));
+    }
+
+    #[test]
+    fn audit_log_round_trips_across_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(AUDIT_LOG_FILE);
+        let old = json!({"currency": "USD"});
+        write_config_change(&path, &old, &old, "save_settings").unwrap();
+        assert!(!path.exists());
+        write_config_change(&path, &old, &json!({"currency": "EUR"}), "save_settings").unwrap();
+        // Pad past the size cap so the next entry rolls the file over to `audit.log.1`.
+        let line = fs::read_to_string(&path).unwrap();
+        fs::write(
+            &path,
+            line.repeat(AUDIT_LOG_MAX_BYTES as usize / line.len() + 1),
+        )
+        .unwrap();
+        write_config_change(&path, &old, &json!({"currency": "JPY"}), "set_output_dir").unwrap();
+        assert!(fs::metadata(&path).unwrap().len() < 1024);
+
+        let entries = read_audit_log(&path, 2);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["changes"][0]["to"], "EUR");
+        assert_eq!(entries[1]["source"], "set_output_dir");
+        assert_eq!(entries[1]["changes"][0]["to"], "JPY");
+    }
+
+    #[test]
+    fn legacy_keys_migrate_to_current_names() {
+        let mut cfg = json!({
+            "timezone_offset": 180,
+            "timezone_mode": "utc",
+            "calendar_timezone_mode": "system",
+            "no_time_label": "All Day",
+        });
+        let obj = cfg.as_object_mut().unwrap();
+        let migrated = apply_legacy_aliases(obj);
+        assert_eq!(migrated.len(), 2);
+        assert_eq!(obj.get("calendar_utc_offset_minutes"), Some(&json!(180)));
+        // An explicit current value is not overwritten by the legacy one.
+        assert_eq!(obj.get("calendar_timezone_mode"), Some(&json!("system")));
+        assert!(!obj.contains_key("timezone_offset"));
+        assert!(!obj.contains_key("timezone_mode"));
+
+        let merged = merge_objects(default_config(), cfg);
+        assert_eq!(
+            AppSettings::from_config(&merged).calendar_utc_offset_minutes,
+            180
+        );
+        assert!(apply_legacy_aliases(merged.clone().as_object_mut().unwrap()).is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::config;
+use crate::state::RuntimeState;
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+
+const CRASH_PREFIX: &str = "crash-";
+const MAX_CRASH_FILES: usize = 5;
+
+fn crash_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(CRASH_PREFIX) && n.ends_with(".log"))
+        })
+        .collect();
+    // Names embed a sortable timestamp, so name order is age order.
+    files.sort();
+    files
+}
+
+/// Deletes all but the newest `keep` crash files.
+pub fn prune_crash_files(dir: &Path, keep: usize) {
+    let files = crash_files(dir);
+    let excess = files.len().saturating_sub(keep);
+    for path in files.into_iter().take(excess) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Newest crash file, unless its file name is `seen` (already reported).
+pub fn unseen_crash_report(dir: &Path, seen: &str) -> Option<PathBuf> {
+    let newest = crash_files(dir).pop()?;
+    let name = newest.file_name()?.to_str()?;
+    if !seen.is_empty() && name == seen {
+        return None;
+    }
+    Some(newest)
+}
+
+/// Writes `crash-<timestamp>.log` with the panic message, the in-memory log buffer (newest
+/// first, as held in `RuntimeState`) and the backtrace. Never panics.
+pub fn write_crash_report(
+    dir: &Path,
+    message: &str,
+    logs: &[Value],
+    backtrace: &str,
+) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S%.3f");
+    let path = dir.join(format!("{CRASH_PREFIX}{stamp}.log"));
+    let mut file = std::fs::File::create(&path)?;
+    writeln!(file, "XAUUSD Calendar Agent {}", env!("APP_VERSION"))?;
+    writeln!(file, "Panic: {message}")?;
+    writeln!(file)?;
+    writeln!(file, "Recent logs:")?;
+    for entry in logs {
+        let field = |key: &str| entry.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        writeln!(
+            file,
+            "[{}] {} {}",
+            field("time"),
+            field("level"),
+            field("message")
+        )?;
+    }
+    writeln!(file)?;
+    writeln!(file, "Backtrace:")?;
+    writeln!(file, "{backtrace}")?;
+    prune_crash_files(dir, MAX_CRASH_FILES);
+    Ok(path)
+}
+
+/// Installs a panic hook that snapshots the log buffer to disk, then defers to the previous
+/// hook. The runtime lock is only tried: a panic while it is held skips the logs.
+pub fn install_panic_hook(app: tauri::AppHandle) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let logs = app
+            .try_state::<Mutex<RuntimeState>>()
+            .and_then(|state| state.try_lock().ok().map(|runtime| runtime.logs.clone()))
+            .unwrap_or_default();
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+        let _ = write_crash_report(&config::log_dir(), &info.to_string(), &logs, &backtrace);
+        previous(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn crash_report_contains_message_logs_and_backtrace() {
+        let dir = tempfile::tempdir().unwrap();
+        let logs =
+            vec![json!({"time": "01-01-2026 10:00", "level": "ERROR", "message": "Pull failed"})];
+        let path = write_crash_report(dir.path(), "index out of bounds", &logs, "frame 0").unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("Panic: index out of bounds"));
+        assert!(text.contains("[01-01-2026 10:00] ERROR Pull failed"));
+        assert!(text.contains("frame 0"));
+        assert_eq!(unseen_crash_report(dir.path(), ""), Some(path));
+    }
+
+    #[test]
+    fn prunes_to_newest_crash_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..8 {
+            let name = format!("crash-20260101-10000{i}.000.log");
+            std::fs::write(dir.path().join(name), "x").unwrap();
+        }
+        std::fs::write(dir.path().join("app.log"), "x").unwrap();
+        prune_crash_files(dir.path(), 5);
+        let left: Vec<String> = crash_files(dir.path())
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(left.len(), 5);
+        assert_eq!(left[0], "crash-20260101-100003.000.log");
+        assert!(dir.path().join("app.log").exists());
+    }
+
+    #[test]
+    fn startup_detection_skips_reports_already_seen() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(unseen_crash_report(dir.path(), ""), None);
+        std::fs::write(dir.path().join("crash-20260101-100000.000.log"), "x").unwrap();
+        std::fs::write(dir.path().join("crash-20260102-100000.000.log"), "x").unwrap();
+        let newest = dir.path().join("crash-20260102-100000.000.log");
+        assert_eq!(unseen_crash_report(dir.path(), ""), Some(newest.clone()));
+        assert_eq!(
+            unseen_crash_report(dir.path(), "crash-20260102-100000.000.log"),
+            None
+        );
+        assert_eq!(
+            unseen_crash_report(dir.path(), "crash-20260101-100000.000.log"),
+            Some(newest)
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use crate::calendar::currency_options;
+use crate::time_util::{parse_source_dt_to_utc, DEFAULT_SOURCE_DATE_FORMAT};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// User-authored rows in the appdata dir; pulls and output syncs never touch this file.
+pub const CUSTOM_EVENTS_FILE: &str = "custom_events.json";
+
+/// One stored custom event, using the calendar year-file field names so the calendar loader
+/// reads the file as-is. `Id` is extra and ignored there.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct CustomEvent {
+    #[serde(rename = "Id", default)]
+    pub id: String,
+    #[serde(rename = "Date")]
+    pub date: String,
+    #[serde(rename = "Time")]
+    pub time: String,
+    #[serde(rename = "Event")]
+    pub event: String,
+    #[serde(rename = "Cur.", default)]
+    pub currency: String,
+    #[serde(rename = "Imp.", default)]
+    pub importance: String,
+}
+
+pub fn read_custom_events(path: &Path) -> Result<Vec<CustomEvent>, String> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    if text.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+pub fn write_custom_events(path: &Path, events: &[CustomEvent]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(events).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension(format!("tmp-{}", std::process::id()));
+    fs::write(&tmp, text).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Trims and checks a new event. `--` (or empty) means no currency and is stored empty.
+pub fn validate_custom_event(event: CustomEvent) -> Result<CustomEvent, String> {
+    let date = event.date.trim().to_string();
+    let time = event.time.trim().to_string();
+    let name = event.event.trim().to_string();
+    if name.is_empty() {
+        return Err("Event name is required".to_string());
+    }
+    if chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_err() {
+        return Err(format!("Invalid date '{date}' (expected YYYY-MM-DD)"));
+    }
+    if time.contains(':') && chrono::NaiveTime::parse_from_str(&time, "%H:%M").is_err() {
+        return Err(format!("Invalid time '{time}' (expected HH:MM or All Day)"));
+    }
+    if parse_source_dt_to_utc(&date, &time, DEFAULT_SOURCE_DATE_FORMAT, 0).is_none() {
+        return Err(format!("Invalid date/time '{date} {time}'"));
+    }
+    let currency = event.currency.trim().to_uppercase();
+    let currency = if currency == "--" {
+        String::new()
+    } else {
+        currency
+    };
+    if !currency.is_empty() && (currency == "ALL" || !currency_options().contains(&currency)) {
+        return Err(format!("Unsupported currency '{currency}'"));
+    }
+    let importance = event.importance.trim().to_string();
+    let importance = match importance.to_lowercase().as_str() {
+        "" => String::new(),
+        "high" => "High".to_string(),
+        "medium" => "Medium".to_string(),
+        "low" => "Low".to_string(),
+        _ => return Err(format!("Unsupported importance '{importance}'")),
+    };
+    Ok(CustomEvent {
+        id: event.id.trim().to_string(),
+        date,
+        time: if time.is_empty() {
+            "All Day".to_string()
+        } else {
+            time
+        },
+        event: name,
+        currency,
+        importance,
+    })
+}
+
+fn next_id(events: &[CustomEvent]) -> String {
+    let max = events
+        .iter()
+        .filter_map(|e| e.id.strip_prefix("custom-"))
+        .filter_map(|n| n.parse::<u64>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("custom-{}", max + 1)
+}
+
+/// Validates and appends `event` with a fresh id, returning the stored entry.
+pub fn add_custom_event(path: &Path, event: CustomEvent) -> Result<CustomEvent, String> {
+    let mut event = validate_custom_event(event)?;
+    let mut events = read_custom_events(path)?;
+    event.id = next_id(&events);
+    events.push(event.clone());
+    write_custom_events(path, &events)?;
+    Ok(event)
+}
+
+/// Removes the event with `id`; returns whether anything was removed.
+pub fn remove_custom_event(path: &Path, id: &str) -> Result<bool, String> {
+    let mut events = read_custom_events(path)?;
+    let before = events.len();
+    events.retain(|e| e.id != id);
+    if events.len() == before {
+        return Ok(false);
+    }
+    write_custom_events(path, &events)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::{load_custom_events, EventSource, FileLimits};
+
+    fn draft(date: &str, time: &str, name: &str, currency: &str) -> CustomEvent {
+        CustomEvent {
+            date: date.to_string(),
+            time: time.to_string(),
+            event: name.to_string(),
+            currency: currency.to_string(),
+            ..CustomEvent::default()
+        }
+    }
+
+    #[test]
+    fn add_list_remove_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CUSTOM_EVENTS_FILE);
+        assert!(read_custom_events(&path).unwrap().is_empty());
+
+        let first =
+            add_custom_event(&path, draft("2026-03-06", "13:30", " Close longs ", "usd")).unwrap();
+        let second =
+            add_custom_event(&path, draft("2026-03-09", "", "Broker maintenance", "--")).unwrap();
+        assert_eq!(first.id, "custom-1");
+        assert_eq!(first.event, "Close longs");
+        assert_eq!(first.currency, "USD");
+        assert_eq!(second.id, "custom-2");
+        assert_eq!(second.time, "All Day");
+        assert_eq!(second.currency, "");
+        assert_eq!(
+            read_custom_events(&path).unwrap(),
+            vec![first.clone(), second.clone()]
+        );
+
+        // The stored file is readable by the calendar loader.
+        let loaded = load_custom_events(&path, &FileLimits::default(), &mut vec![]);
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded.iter().all(|e| e.source == EventSource::Custom));
+
+        assert!(remove_custom_event(&path, "custom-1").unwrap());
+        assert!(!remove_custom_event(&path, "custom-1").unwrap());
+        assert_eq!(read_custom_events(&path).unwrap(), vec![second]);
+        let third = add_custom_event(&path, draft("2026-03-10", "08:00", "Review", "")).unwrap();
+        assert_eq!(third.id, "custom-3");
+    }
+
+    #[test]
+    fn rejects_invalid_fields() {
+        let err = |e: CustomEvent| validate_custom_event(e).err().unwrap_or_default();
+        assert!(err(draft("2026-13-01", "10:00", "X", "USD")).contains("date"));
+        assert!(err(draft("2026-03-01", "25:00", "X", "USD")).contains("time"));
+        assert!(err(draft("2026-03-01", "10:00", "X", "XAU")).contains("currency"));
+        assert!(err(draft("2026-03-01", "10:00", "X", "ALL")).contains("currency"));
+        assert!(err(draft("2026-03-01", "10:00", "  ", "USD")).contains("name"));
+        let mut bad_imp = draft("2026-03-01", "10:00", "X", "USD");
+        bad_imp.importance = "Urgent".to_string();
+        assert!(err(bad_imp).contains("importance"));
+    }
+}
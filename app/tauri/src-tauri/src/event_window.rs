@@ -0,0 +1,144 @@
+use crate::calendar::{impact_rank, CalendarEvent};
+use crate::categories::matches_filter;
+use chrono::{DateTime, Utc};
+
+/// Selection for `get_events_window`: `[from, to)` in UTC plus optional filters.
+#[derive(Clone, Debug, Default)]
+pub struct WindowFilter {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Upper-case currency, or empty for all.
+    pub currency: String,
+    /// Minimum `impact_rank`; 0 keeps everything.
+    pub min_impact: u8,
+    /// Keep events carrying any of these categories; empty keeps everything.
+    pub categories: Vec<String>,
+}
+
+impl WindowFilter {
+    fn matches(&self, e: &CalendarEvent) -> bool {
+        (self.currency.is_empty() || e.currency.eq_ignore_ascii_case(&self.currency))
+            && impact_rank(&e.importance) >= self.min_impact
+            && matches_filter(e, &self.categories)
+    }
+}
+
+pub struct WindowPage<'a> {
+    pub items: Vec<&'a CalendarEvent>,
+    /// Index into the event slice to resume from, when more matches remain.
+    pub next: Option<usize>,
+}
+
+/// One page of at most `limit` matching events from `events` (sorted by `dt_utc`), starting at
+/// slice index `start` or the window start, whichever is later.
+pub fn events_window<'a>(
+    events: &'a [CalendarEvent],
+    filter: &WindowFilter,
+    start: usize,
+    limit: usize,
+) -> WindowPage<'a> {
+    let first = events
+        .partition_point(|e| e.dt_utc < filter.from)
+        .max(start);
+    let mut items = vec![];
+    let mut next = None;
+    for (idx, e) in events.iter().enumerate().skip(first) {
+        if e.dt_utc >= filter.to {
+            break;
+        }
+        if !filter.matches(e) {
+            continue;
+        }
+        if items.len() == limit {
+            next = Some(idx);
+            break;
+        }
+        items.push(e);
+    }
+    WindowPage { items, next }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(h: u32, cur: &str, importance: &str, categories: &[&str]) -> CalendarEvent {
+        CalendarEvent {
+            dt_utc: Utc.with_ymd_and_hms(2026, 3, 6, h, 0, 0).unwrap(),
+            time_label: format!("{h:02}:00"),
+            event: format!("{cur} {h}"),
+            currency: cur.to_string(),
+            importance: importance.to_string(),
+            actual: String::new(),
+            forecast: String::new(),
+            previous: String::new(),
+            time_confirmed: true,
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+            source: Default::default(),
+        }
+    }
+
+    fn window(from: u32, to: u32) -> WindowFilter {
+        WindowFilter {
+            from: Utc.with_ymd_and_hms(2026, 3, 6, from, 0, 0).unwrap(),
+            to: Utc.with_ymd_and_hms(2026, 3, 6, to, 0, 0).unwrap(),
+            ..WindowFilter::default()
+        }
+    }
+
+    fn names(page: &WindowPage) -> Vec<String> {
+        page.items.iter().map(|e| e.event.clone()).collect()
+    }
+
+    #[test]
+    fn window_includes_start_and_excludes_end() {
+        let events = vec![
+            event(8, "USD", "Low", &[]),
+            event(9, "USD", "High", &[]),
+            event(10, "EUR", "High", &[]),
+            event(11, "USD", "Medium", &[]),
+        ];
+        let page = events_window(&events, &window(9, 11), 0, 10);
+        assert_eq!(names(&page), vec!["USD 9", "EUR 10"]);
+        assert_eq!(page.next, None);
+    }
+
+    #[test]
+    fn filters_combine() {
+        let events = vec![
+            event(8, "USD", "Low", &["labor"]),
+            event(9, "USD", "High", &["labor"]),
+            event(10, "EUR", "High", &["labor"]),
+            event(11, "USD", "Medium", &["inflation"]),
+            event(12, "USD", "High", &["inflation"]),
+        ];
+        let filter = WindowFilter {
+            currency: "usd".to_string(),
+            min_impact: 2,
+            categories: vec!["labor".to_string()],
+            ..window(0, 23)
+        };
+        assert_eq!(
+            names(&events_window(&events, &filter, 0, 10)),
+            vec!["USD 9"]
+        );
+    }
+
+    #[test]
+    fn cursor_resumes_after_the_last_item() {
+        let events: Vec<CalendarEvent> = (0..7)
+            .map(|h| event(h, if h % 2 == 0 { "USD" } else { "EUR" }, "High", &[]))
+            .collect();
+        let filter = WindowFilter {
+            currency: "USD".to_string(),
+            ..window(0, 23)
+        };
+        let first = events_window(&events, &filter, 0, 2);
+        assert_eq!(names(&first), vec!["USD 0", "USD 2"]);
+        assert_eq!(first.next, Some(4));
+        let second = events_window(&events, &filter, first.next.unwrap(), 2);
+        assert_eq!(names(&second), vec!["USD 4", "USD 6"]);
+        assert_eq!(second.next, None);
+    }
+}
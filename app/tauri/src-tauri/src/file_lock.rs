@@ -0,0 +1,92 @@
+use fs4::fs_std::FileExt;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long readers wait for a writer before giving up on the lookup.
+pub const READ_WAIT: Duration = Duration::from_secs(2);
+/// How long writers wait for in-flight readers.
+pub const WRITE_WAIT: Duration = Duration::from_secs(10);
+
+/// Advisory lock on a sidecar `<file>.lock`; released on drop.
+///
+/// The sidecar (rather than the data file itself) is locked so writers can replace the data
+/// file without invalidating the lock.
+pub struct FileLock {
+    file: File,
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+fn lock_path(target: &Path) -> PathBuf {
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    target.with_file_name(name)
+}
+
+fn acquire(target: &Path, exclusive: bool, wait: Duration) -> Option<FileLock> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).ok()?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path(target))
+        .ok()?;
+    let deadline = Instant::now() + wait;
+    loop {
+        // Fully qualified: newer std has inherent `File` lock methods with other signatures.
+        let locked = if exclusive {
+            FileExt::try_lock_exclusive(&file)
+        } else {
+            FileExt::try_lock_shared(&file)
+        };
+        match locked {
+            Ok(true) => return Some(FileLock { file }),
+            Ok(false) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(25));
+            }
+            _ => return None,
+        }
+    }
+}
+
+pub fn lock_shared(target: &Path, wait: Duration) -> Option<FileLock> {
+    acquire(target, false, wait)
+}
+
+pub fn lock_exclusive(target: &Path, wait: Duration) -> Option<FileLock> {
+    acquire(target, true, wait)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readers_share_and_writers_wait() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("event_history_by_event.ndjson");
+        let short = Duration::from_millis(50);
+
+        let reader_a = lock_shared(&target, short).expect("first reader");
+        let reader_b = lock_shared(&target, short).expect("second reader");
+        assert!(lock_exclusive(&target, short).is_none());
+        drop(reader_a);
+        drop(reader_b);
+
+        let writer = lock_exclusive(&target, short).expect("writer");
+        assert!(lock_shared(&target, short).is_none());
+        drop(writer);
+        assert!(lock_shared(&target, short).is_some());
+        assert!(dir
+            .path()
+            .join("event_history_by_event.ndjson.lock")
+            .exists());
+    }
+}
@@ -1,5 +1,9 @@
+use crate::net_activity::{self, NetworkActivity};
+use std::io::Read;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -7,15 +11,121 @@ use std::os::windows::process::CommandExt;
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-fn run_git(args: &[&str], cwd: &Path) -> Result<String, String> {
+/// Host every remote git command talks to, as recorded in the network activity log.
+const GIT_HOST: &str = "github.com (git)";
+
+/// Executes a prepared git command. Tests substitute a fake to avoid spawning git.
+pub trait GitRunner {
+    fn output(&self, cmd: &mut Command) -> std::io::Result<Output>;
+}
+
+struct SystemGit;
+
+impl GitRunner for SystemGit {
+    fn output(&self, cmd: &mut Command) -> std::io::Result<Output> {
+        cmd.output()
+    }
+}
+
+/// Runs git like `SystemGit`, but kills the child as soon as `cancel` is set. Used by pulls so
+/// `cancel_pull` can stop a clone stuck on a slow network.
+struct CancellableGit<'a> {
+    cancel: &'a AtomicBool,
+}
+
+/// Reads a child's pipe to the end on its own thread, so a chatty git cannot fill the pipe and
+/// block while we poll it.
+fn drain(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = vec![];
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
+fn cancelled() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Interrupted, "cancelled")
+}
+
+impl GitRunner for CancellableGit<'_> {
+    fn output(&self, cmd: &mut Command) -> std::io::Result<Output> {
+        if self.cancel.load(Ordering::SeqCst) {
+            return Err(cancelled());
+        }
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if self.cancel.load(Ordering::SeqCst) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(cancelled());
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        };
+        Ok(Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+}
+
+fn git_command(args: &[&str]) -> Command {
     let mut cmd = Command::new("git");
-    cmd.args(args).current_dir(cwd);
+    cmd.args(args);
     #[cfg(target_os = "windows")]
     {
         cmd.creation_flags(CREATE_NO_WINDOW);
     }
-    let output = cmd
-        .output()
+    cmd
+}
+
+/// Runs `cmd`, recording it in `activity` when it reaches the remote.
+fn run_recorded(
+    runner: &dyn GitRunner,
+    activity: &NetworkActivity,
+    remote: bool,
+    cmd: &mut Command,
+) -> std::io::Result<Output> {
+    let result = runner.output(cmd);
+    if remote {
+        let status = match &result {
+            Ok(output) if output.status.success() => "ok".to_string(),
+            Ok(output) => match output.status.code() {
+                Some(code) => format!("exit {code}"),
+                None => "terminated".to_string(),
+            },
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => "cancelled".to_string(),
+            Err(_) => "spawn error".to_string(),
+        };
+        activity.record(GIT_HOST, &status);
+    }
+    result
+}
+
+fn run_git(args: &[&str], cwd: &Path, remote: bool) -> Result<String, String> {
+    run_git_with(&SystemGit, args, cwd, remote)
+}
+
+fn run_git_with(
+    runner: &dyn GitRunner,
+    args: &[&str],
+    cwd: &Path,
+    remote: bool,
+) -> Result<String, String> {
+    let mut cmd = git_command(args);
+    cmd.current_dir(cwd);
+    let output = run_recorded(runner, net_activity::global(), remote, &mut cmd)
         .map_err(|e| format!("failed to run git: {e}"))?;
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
@@ -24,20 +134,49 @@ fn run_git(args: &[&str], cwd: &Path) -> Result<String, String> {
     Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
 
-pub fn ls_remote_head_sha(repo_slug: &str, branch: &str) -> Result<String, String> {
+/// `owner/repo` from what users paste into `github_repo`: surrounding whitespace, a
+/// `https://github.com/` (or `github.com/`) prefix, a trailing `/` and `.git` are dropped.
+pub fn normalize_repo_slug(input: &str) -> Result<String, String> {
+    let mut slug = input.trim();
+    for prefix in ["https://", "http://", "www.", "github.com/"] {
+        slug = slug.strip_prefix(prefix).unwrap_or(slug);
+    }
+    let slug = slug.trim_end_matches('/');
+    let slug = slug.strip_suffix(".git").unwrap_or(slug);
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part != "."
+            && part != ".."
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    match slug.split_once('/') {
+        Some((owner, repo)) if valid_part(owner) && valid_part(repo) => Ok(slug.to_string()),
+        _ => Err(format!(
+            "Invalid GitHub repository: '{}' (expected owner/repo)",
+            input.trim()
+        )),
+    }
+}
+
+/// Head sha of `branch` on GitHub. Setting `cancel` kills the `git ls-remote` child.
+pub fn ls_remote_head_sha(
+    repo_slug: &str,
+    branch: &str,
+    cancel: &AtomicBool,
+) -> Result<String, String> {
     let url = format!("https://github.com/{repo_slug}.git");
     let refspec = format!("refs/heads/{branch}");
 
-    let mut cmd = Command::new("git");
-    cmd.args(["ls-remote", &url, &refspec]);
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    let output = cmd
-        .output()
-        .map_err(|e| format!("failed to run git: {e}"))?;
+    let mut cmd = git_command(&["ls-remote", &url, &refspec]);
+    let output = run_recorded(
+        &CancellableGit { cancel },
+        net_activity::global(),
+        true,
+        &mut cmd,
+    )
+    .map_err(|e| format!("failed to run git: {e}"))?;
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr).to_string();
         return Err(stderr.trim().to_string());
@@ -55,7 +194,14 @@ pub fn ls_remote_head_sha(repo_slug: &str, branch: &str) -> Result<String, Strin
     Ok(sha)
 }
 
-pub fn clone_sparse_data(repo_dir: &Path, repo_slug: &str, branch: &str) -> Result<String, String> {
+/// Shallow, sparse clone of `data/` into `repo_dir`; returns the checked-out sha. Setting
+/// `cancel` kills the running git child, leaving `repo_dir` for the caller to remove.
+pub fn clone_sparse_data(
+    repo_dir: &Path,
+    repo_slug: &str,
+    branch: &str,
+    cancel: &AtomicBool,
+) -> Result<String, String> {
     if repo_dir.exists() {
         return Err(format!("target exists: {}", repo_dir.display()));
     }
@@ -66,8 +212,7 @@ pub fn clone_sparse_data(repo_dir: &Path, repo_slug: &str, branch: &str) -> Resu
     let url = format!("https://github.com/{repo_slug}.git");
 
     // Sparse-checkout `data/` only.
-    let mut cmd = Command::new("git");
-    cmd.args([
+    let mut cmd = git_command(&[
         "clone",
         "--depth",
         "1",
@@ -76,32 +221,136 @@ pub fn clone_sparse_data(repo_dir: &Path, repo_slug: &str, branch: &str) -> Resu
         "--branch",
         branch,
         &url,
-    ])
-    .arg(repo_dir);
-    #[cfg(target_os = "windows")]
-    {
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-    let status = cmd.status().map_err(|e| format!("git clone failed: {e}"))?;
-    if !status.success() {
+    ]);
+    cmd.arg(repo_dir);
+    let runner = CancellableGit { cancel };
+    let output = run_recorded(&runner, net_activity::global(), true, &mut cmd)
+        .map_err(|e| format!("git clone failed: {e}"))?;
+    if !output.status.success() {
         // Fallback for older git versions: full clone.
-        let mut fallback = Command::new("git");
-        fallback
-            .args(["clone", "--depth", "1", "--branch", branch, &url])
-            .arg(repo_dir);
-        #[cfg(target_os = "windows")]
-        {
-            fallback.creation_flags(CREATE_NO_WINDOW);
-        }
-        let status = fallback
-            .status()
+        let mut fallback = git_command(&["clone", "--depth", "1", "--branch", branch, &url]);
+        fallback.arg(repo_dir);
+        let output = run_recorded(&runner, net_activity::global(), true, &mut fallback)
             .map_err(|e| format!("git clone failed: {e}"))?;
-        if !status.success() {
+        if !output.status.success() {
             return Err("git clone failed".to_string());
         }
     }
 
-    let _ = run_git(&["sparse-checkout", "set", "data"], repo_dir);
-    let sha = run_git(&["rev-parse", "HEAD"], repo_dir)?;
+    // With `--filter=blob:none` the checkout fetches the blobs it needs.
+    let _ = run_git_with(&runner, &["sparse-checkout", "set", "data"], repo_dir, true);
+    if cancel.load(Ordering::SeqCst) {
+        return Err("git clone failed: cancelled".to_string());
+    }
+    let sha = run_git(&["rev-parse", "HEAD"], repo_dir, false)?;
     Ok(sha)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::process::ExitStatus;
+
+    #[test]
+    fn repo_slugs_are_normalized() {
+        for input in [
+            "owner/repo",
+            " https://github.com/owner/repo.git ",
+            "http://www.github.com/owner/repo/",
+            "github.com/owner/repo",
+        ] {
+            assert_eq!(
+                normalize_repo_slug(input).as_deref(),
+                Ok("owner/repo"),
+                "{input}"
+            );
+        }
+        assert_eq!(
+            normalize_repo_slug("yiyousiow000814/XAUUSD-Calendar-Agent").as_deref(),
+            Ok("yiyousiow000814/XAUUSD-Calendar-Agent")
+        );
+        for input in [
+            "",
+            "owner",
+            "owner/",
+            "/repo",
+            "a/b/c",
+            "owner/re po",
+            "owner/..",
+        ] {
+            assert!(normalize_repo_slug(input).is_err(), "{input}");
+        }
+    }
+
+    #[cfg(unix)]
+    fn exit_status(code: i32) -> ExitStatus {
+        std::os::unix::process::ExitStatusExt::from_raw(code << 8)
+    }
+
+    #[cfg(windows)]
+    fn exit_status(code: i32) -> ExitStatus {
+        std::os::windows::process::ExitStatusExt::from_raw(code as u32)
+    }
+
+    /// Answers every command with `code` without spawning anything.
+    struct FakeGit {
+        code: i32,
+        calls: Cell<usize>,
+    }
+
+    impl GitRunner for FakeGit {
+        fn output(&self, _cmd: &mut Command) -> std::io::Result<Output> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(Output {
+                status: exit_status(self.code),
+                stdout: vec![],
+                stderr: vec![],
+            })
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cancelling_kills_the_running_child() {
+        let cancel = AtomicBool::new(false);
+        let runner = CancellableGit { cancel: &cancel };
+        let output = runner.output(Command::new("echo").arg("hi")).unwrap();
+        assert_eq!(output.stdout, b"hi\n");
+
+        let started = std::time::Instant::now();
+        let err = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(Duration::from_millis(200));
+                cancel.store(true, Ordering::SeqCst);
+            });
+            runner.output(Command::new("sleep").arg("30")).unwrap_err()
+        });
+        assert_eq!(err.kind(), std::io::ErrorKind::Interrupted);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(runner.output(Command::new("echo").arg("hi")).is_err());
+    }
+
+    #[test]
+    fn remote_git_commands_are_recorded() {
+        let activity = NetworkActivity::new();
+        let ok = FakeGit {
+            code: 0,
+            calls: Cell::new(0),
+        };
+        let failing = FakeGit {
+            code: 128,
+            calls: Cell::new(0),
+        };
+        let mut cmd = git_command(&["ls-remote"]);
+        run_recorded(&ok, &activity, true, &mut cmd).unwrap();
+        run_recorded(&ok, &activity, false, &mut cmd).unwrap();
+        assert_eq!(activity.snapshot()[GIT_HOST].last_status, "ok");
+        run_recorded(&failing, &activity, true, &mut cmd).unwrap();
+
+        let hosts = activity.snapshot();
+        assert_eq!(ok.calls.get() + failing.calls.get(), 3);
+        assert_eq!(hosts[GIT_HOST].requests, 2);
+        assert_eq!(hosts[GIT_HOST].last_status, "exit 128");
+    }
+}
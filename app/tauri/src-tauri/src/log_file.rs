@@ -0,0 +1,151 @@
+use crate::app_settings::AppSettings;
+use crate::config;
+use crate::time_util::now_display_time;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_uppercase().as_str() {
+            "DEBUG" => Some(Self::Debug),
+            "INFO" => Some(Self::Info),
+            "WARN" | "WARNING" => Some(Self::Warn),
+            "ERROR" => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Debug => "DEBUG",
+            Self::Info => "INFO",
+            Self::Warn => "WARN",
+            Self::Error => "ERROR",
+        }
+    }
+}
+
+/// In-memory log entries kept by default, and what the snapshot embeds.
+pub const DEFAULT_LOG_BUFFER_ENTRIES: usize = 200;
+
+/// `log_buffer_entries` from config, clamped to 100..=2000.
+pub fn log_buffer_entries(cfg: &serde_json::Value) -> usize {
+    AppSettings::from_config(cfg)
+        .log_buffer_entries
+        .clamp(100, 2000) as usize
+}
+
+/// Identifies this process in log entries so exported logs can be split by session.
+pub fn new_session_id() -> String {
+    format!(
+        "{:x}-{:04x}",
+        chrono::Utc::now().timestamp_millis(),
+        fastrand::u16(..)
+    )
+}
+
+/// Which log entries are kept, from `log_min_level` and `debug`. Cached on `RuntimeState`.
+#[derive(Clone, Copy, Debug)]
+pub struct LogFilter {
+    pub min_level: LogLevel,
+    pub debug: bool,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            min_level: LogLevel::Info,
+            debug: false,
+        }
+    }
+}
+
+impl LogFilter {
+    pub fn from_config(cfg: &serde_json::Value) -> Self {
+        let settings = AppSettings::from_config(cfg);
+        Self {
+            min_level: LogLevel::parse(&settings.log_min_level).unwrap_or(LogLevel::Info),
+            debug: settings.debug,
+        }
+    }
+
+    /// DEBUG entries additionally need `debug`; unknown levels count as INFO.
+    pub fn allows(&self, level: &str) -> bool {
+        let level = LogLevel::parse(level).unwrap_or(LogLevel::Info);
+        if level == LogLevel::Debug && !self.debug {
+            return false;
+        }
+        level >= self.min_level
+    }
+}
+
+pub fn app_log_path() -> PathBuf {
+    config::log_dir().join("app.log")
+}
+
+/// Appends `[time] LEVEL message` lines that pass `filter` to `app.log` (best effort).
+pub fn append_lines(lines: &[(String, String)], filter: &LogFilter) -> Result<(), String> {
+    let lines: Vec<&(String, String)> = lines
+        .iter()
+        .filter(|(level, _)| filter.allows(level))
+        .collect();
+    if lines.is_empty() {
+        return Ok(());
+    }
+    let path = app_log_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| e.to_string())?;
+    let time = now_display_time();
+    let mut text = String::new();
+    for (level, message) in lines {
+        text.push_str(&format!("[{time}] {level} {message}\n"));
+    }
+    file.write_all(text.as_bytes()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_drops_entries_below_threshold() {
+        let warn_only = LogFilter {
+            min_level: LogLevel::Warn,
+            debug: true,
+        };
+        assert!(!warn_only.allows("INFO"));
+        assert!(!warn_only.allows("DEBUG"));
+        assert!(warn_only.allows("WARN"));
+        assert!(warn_only.allows("error"));
+
+        let verbose = LogFilter {
+            min_level: LogLevel::Debug,
+            debug: false,
+        };
+        // DEBUG still needs `debug` on.
+        assert!(!verbose.allows("DEBUG"));
+        assert!(verbose.allows("INFO"));
+        assert!(LogFilter {
+            debug: true,
+            ..verbose
+        }
+        .allows("DEBUG"));
+        // Unknown levels behave like INFO.
+        assert!(LogFilter::default().allows("NOTICE"));
+    }
+}
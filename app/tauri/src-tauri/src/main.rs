@@ -1,25 +1,45 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod analytics;
+mod app_settings;
+mod backup;
+mod boot;
 mod calendar;
+mod categories;
 mod commands;
 mod config;
+mod crash;
+mod custom_events;
+mod event_window;
+mod file_lock;
 mod git_ops;
+mod log_file;
+mod modal;
+mod net_activity;
+mod notifications;
+mod platform;
+mod portable;
+mod runtime_store;
+mod single_flight;
 mod snapshot;
 mod startup;
 mod state;
+mod status_file;
 mod sync_util;
 mod time_util;
+mod tray_alert;
+mod version;
 
 use crate::commands::update::default_update_state;
 use crate::state::RuntimeState;
 use std::sync::Mutex;
-use tauri::menu::MenuBuilder;
+use tauri::menu::{MenuBuilder, SubmenuBuilder};
 use tauri::tray::TrayIconEvent;
 use tauri::tray::{MouseButton, MouseButtonState};
 use tauri::Manager;
 use tauri::WindowEvent;
 
-fn show_main_window(handle: &tauri::AppHandle) {
+pub(crate) fn show_main_window(handle: &tauri::AppHandle) {
     let Some(win) = handle.get_webview_window("main") else {
         return;
     };
@@ -28,15 +48,41 @@ fn show_main_window(handle: &tauri::AppHandle) {
     let _ = win.set_focus();
 }
 
+/// Asks the frontend to refresh now, unless `refresh_on_focus` is off.
+fn dispatch_wakeup(win: &tauri::WebviewWindow) {
+    if !app_settings::AppSettings::from_config(&config::load_config()).refresh_on_focus {
+        return;
+    }
+    let _ = win.eval("window.dispatchEvent(new Event('xauusd:wakeup'))");
+}
+
+/// `show_main_window` for tray actions, which also refresh like a focus does.
+fn show_main_window_from_tray(handle: &tauri::AppHandle) {
+    show_main_window(handle);
+    if let Some(win) = handle.get_webview_window("main") {
+        dispatch_wakeup(&win);
+    }
+}
+
 fn main() {
+    boot::mark_process_start();
+    time_util::set_date_format(time_util::DateFormat::from_config(&config::load_config()));
+    let durable = runtime_store::load(&runtime_store::runtime_state_path(), chrono::Utc::now());
     tauri::Builder::default()
         .manage(Mutex::new(RuntimeState {
             update_state: default_update_state(),
+            update_prompted_version: durable.update_prompted_version.clone(),
+            alerts: durable.alert_scheduler(),
+            notifications_muted_until: durable.notifications_muted_until(),
+            log_filter: log_file::LogFilter::from_config(&config::load_config()),
+            log_buffer_entries: log_file::log_buffer_entries(&config::load_config()),
+            session_id: log_file::new_session_id(),
             ..RuntimeState::default()
         }))
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
-            show_main_window(app);
+            commands::ui::handle_second_instance(app);
         }))
         .on_window_event(|window, event| {
             if window.label() != "main" {
@@ -48,20 +94,20 @@ fn main() {
                     // poke the WebView so it can immediately refresh and surface backend-driven
                     // alerts without requiring an extra click.
                     if let Some(webview) = window.get_webview_window(window.label()) {
-                        let _ = webview.eval("window.dispatchEvent(new Event('xauusd:wakeup'))");
+                        dispatch_wakeup(&webview);
                     }
                 }
                 WindowEvent::CloseRequested { api, .. } => {
-                    let cfg = config::load_config();
-                    let close_behavior = config::get_str(&cfg, "close_behavior");
+                    let close_behavior =
+                        commands::ui::effective_close_behavior(window.app_handle());
                     if close_behavior == "tray" {
                         api.prevent_close();
                         let _ = window.hide();
                     }
                 }
                 WindowEvent::Resized(_) => {
-                    let cfg = config::load_config();
-                    let close_behavior = config::get_str(&cfg, "close_behavior");
+                    let close_behavior =
+                        commands::ui::effective_close_behavior(window.app_handle());
                     if close_behavior != "tray" {
                         return;
                     }
@@ -74,56 +120,136 @@ fn main() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::snapshot_cmd::get_snapshot,
+            commands::snapshot_cmd::get_categories,
+            commands::snapshot_cmd::explain_event,
+            commands::snapshot_cmd::get_status_line,
+            commands::snapshot_cmd::get_countdowns,
+            commands::snapshot_cmd::get_last_released,
+            commands::snapshot_cmd::get_today_agenda,
+            commands::snapshot_cmd::get_events_window,
             commands::settings::get_settings,
             commands::settings::save_settings,
+            commands::settings::export_settings,
+            commands::settings::import_settings,
             commands::logs::add_log,
             commands::logs::clear_logs,
+            commands::logs::get_logs,
+            commands::logs::get_audit_log,
             commands::settings::set_currency,
+            commands::settings::set_min_forecast_change_pct,
             commands::update::get_update_state,
             commands::update::check_updates,
             commands::update::update_now,
+            commands::update::compare_version,
             commands::pull::pull_now,
+            commands::pull::schedule_pull,
+            commands::pull::cancel_scheduled_pull,
+            commands::pull::cancel_pull,
+            commands::pull::list_backups,
+            commands::pull::rollback_last_pull,
+            commands::reset::reset_working_data,
             commands::sync::sync_now,
+            commands::sync::cancel_sync,
+            commands::sync::confirm_sync_deletions,
+            commands::sync::cancel_sync_deletions,
             commands::ui::frontend_boot_complete,
             commands::ui::set_ui_state,
+            commands::ui::set_test_clock,
             commands::settings::get_temporary_path_task,
             commands::settings::probe_temporary_path,
+            commands::settings::validate_repo,
+            commands::settings::test_parse_date,
+            commands::settings::list_timezones,
+            commands::settings::normalize_repo_slug,
             commands::settings::temporary_path_use_as_is,
             commands::settings::temporary_path_reset,
             commands::settings::browse_temporary_path,
             commands::settings::set_temporary_path,
             commands::settings::browse_output_dir,
             commands::settings::set_output_dir,
+            commands::settings::get_path_history,
+            commands::settings::select_path_from_history,
             commands::open::open_log,
             commands::open::open_path,
             commands::open::open_url,
             commands::open::open_release_notes,
+            commands::open::open_event_source,
             commands::lifecycle::dismiss_modal,
-            commands::history::get_event_history
+            commands::lifecycle::get_modal_history,
+            commands::lifecycle::dismiss_timezone_hint,
+            commands::history::get_event_history,
+            commands::history::reload_history_index,
+            commands::history::repair_history_ndjson,
+            commands::history::list_events,
+            commands::history::parse_event_name,
+            commands::custom::list_custom_events,
+            commands::custom::add_custom_event,
+            commands::custom::remove_custom_event,
+            commands::diagnostics::get_diagnostics,
+            commands::diagnostics::get_network_activity,
+            commands::diagnostics::get_config_provenance,
+            commands::diagnostics::check_data_integrity,
+            commands::diagnostics::get_storage_usage,
+            commands::portable::convert_to_portable,
+            commands::portable::convert_to_installed,
+            commands::notify::fire_test_alert,
+            commands::notify::focus_event,
+            commands::notify::mute_notifications_for,
+            commands::notify::unmute_notifications,
+            commands::history::get_event_seasonality,
+            commands::history::compare_event_histories,
+            commands::export::export_ics,
+            commands::verify::verify_data,
+            commands::verify::cancel_verify_data,
+            commands::verify::get_verify_data_result,
+            commands::snapshot_cmd::get_data_fingerprint,
+            commands::benchmark::benchmark
         ])
         .setup(|app| {
+            crash::install_panic_hook(app.handle().clone());
+            commands::lifecycle::surface_previous_crash(app.handle());
+            commands::update::surface_update_outcome(app.handle());
             commands::ui::start_background_tasks(app.handle().clone());
 
             let handle = app.handle();
-            // Ensure startup setting is applied (Windows: HKCU Run entry).
             let cfg = config::load_config();
-            let run_on_startup = config::get_bool(&cfg, "run_on_startup", true);
-            let _ = startup::set_run_on_startup(run_on_startup);
+            commands::ui::apply_window_title(handle, &cfg);
 
             // If this launch is from OS autostart and launch mode is tray, hide the main window.
-            let autostart_launch_mode = config::get_str(&cfg, "autostart_launch_mode");
+            let launch_to_tray = app_settings::AppSettings::from_config(&cfg).launch_to_tray();
             let launched_by_autostart = std::env::args().any(|a| a == "--autostart");
 
             // Build tray menu and handlers (tray icon is created by `tauri.conf.json` trayIcon config).
+            let mut mute_menu = SubmenuBuilder::new(handle, "Mute notifications");
+            for (id, label) in commands::notify::TRAY_MUTE_ITEMS {
+                mute_menu = mute_menu.text(*id, *label);
+            }
+            let mute_menu = mute_menu
+                .separator()
+                .text(commands::notify::TRAY_UNMUTE_ID, "Unmute")
+                .build()?;
+            let mut sync_deletions_menu = SubmenuBuilder::new(handle, "Sync deletions");
+            for (id, label) in commands::sync::TRAY_SYNC_DELETIONS_ITEMS {
+                sync_deletions_menu = sync_deletions_menu.text(*id, *label);
+            }
+            let sync_deletions_menu = sync_deletions_menu.build()?;
             let menu = MenuBuilder::new(handle)
                 .text("tray:open", "Open")
+                .item(&mute_menu)
+                .item(&sync_deletions_menu)
                 .separator()
                 .text("tray:exit", "Exit")
                 .build()?;
 
-            if let Some(tray) = handle.tray_by_id("main") {
-                let _ = tray.set_menu(Some(menu));
-            }
+            let tray_supported = match handle.tray_by_id("main") {
+                Some(tray) => {
+                    let _ = tray.set_menu(Some(menu));
+                    platform::tray_host_available()
+                }
+                None => false,
+            };
+            commands::ui::set_tray_supported(handle, tray_supported);
+            commands::ui::set_install_dir_writable(handle, config::install_dir_writable());
 
             handle.on_menu_event(|app, event| {
                 let id = event.id().as_ref();
@@ -132,8 +258,11 @@ fn main() {
                     return;
                 }
                 if id == "tray:open" {
-                    show_main_window(app);
+                    show_main_window_from_tray(app);
+                    return;
                 }
+                commands::notify::handle_tray_mute(app, id);
+                commands::sync::handle_tray_sync_deletions(app, id);
             });
 
             handle.on_tray_icon_event(|app, event| {
@@ -154,16 +283,29 @@ fn main() {
                     }
                     _ => return,
                 };
-                show_main_window(app);
+                show_main_window_from_tray(app);
             });
 
-            if launched_by_autostart && autostart_launch_mode == "tray" {
+            if launched_by_autostart && launch_to_tray && tray_supported {
                 if let Some(win) = handle.get_webview_window("main") {
                     let _ = win.hide();
                 }
             }
+
+            // Registry writes and network probes wait for the first paint (or the fallback).
+            commands::lifecycle::schedule_deferred_startup(handle);
+            {
+                let state = handle.state::<Mutex<RuntimeState>>();
+                let mut runtime = state.lock().expect("runtime lock");
+                boot::mark(&mut runtime.boot.setup_done_ms);
+            }
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                commands::lifecycle::save_runtime_state(app);
+            }
+        });
 }
@@ -0,0 +1,149 @@
+use serde::Serialize;
+
+/// How the frontend colors a modal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModalTone {
+    #[default]
+    Info,
+    /// Something needs attention but nothing failed.
+    Warn,
+    Error,
+}
+
+/// A button under the modal message. Exactly one target is set: `command` is a backend command
+/// invoked without arguments, `url` opens in the browser, and `view` opens an app view
+/// ("settings").
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModalAction {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub view: Option<String>,
+}
+
+impl ModalAction {
+    fn labelled(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            command: None,
+            url: None,
+            view: None,
+        }
+    }
+
+    pub fn command(label: &str, command: &str) -> Self {
+        Self {
+            command: Some(command.to_string()),
+            ..Self::labelled(label)
+        }
+    }
+
+    pub fn url(label: &str, url: &str) -> Self {
+        Self {
+            url: Some(url.to_string()),
+            ..Self::labelled(label)
+        }
+    }
+
+    pub fn open_settings() -> Self {
+        Self {
+            view: Some("settings".to_string()),
+            ..Self::labelled("Open Settings")
+        }
+    }
+}
+
+/// The modal on `RuntimeState`, serialized as-is into the snapshot and `xauusd:modal` events.
+/// Set it with `RuntimeState::set_modal` so it lands in the history too.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Modal {
+    pub id: String,
+    pub title: String,
+    pub message: String,
+    pub tone: ModalTone,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<ModalAction>,
+    /// A file or folder the frontend offers to open.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_path: Option<String>,
+}
+
+impl Modal {
+    pub fn new(
+        id: impl Into<String>,
+        title: &str,
+        message: impl Into<String>,
+        tone: ModalTone,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            title: title.to_string(),
+            message: message.into(),
+            tone,
+            actions: vec![],
+            open_path: None,
+        }
+    }
+
+    pub fn with_action(mut self, action: ModalAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    pub fn with_open_path(mut self, path: impl Into<String>) -> Self {
+        self.open_path = Some(path.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_in_the_frontend_shape() {
+        let plain = Modal::new(
+            "data-layout",
+            "Calendar Data Empty",
+            "See log",
+            ModalTone::Error,
+        );
+        assert_eq!(
+            serde_json::to_value(&plain).unwrap(),
+            json!({
+                "id": "data-layout",
+                "title": "Calendar Data Empty",
+                "message": "See log",
+                "tone": "error"
+            })
+        );
+
+        let full = Modal::new("update-1.2.0", "Update Available", "1.2.0", ModalTone::Warn)
+            .with_action(ModalAction::open_settings())
+            .with_action(ModalAction::url("Release Notes", "https://example.com/r"))
+            .with_action(ModalAction::command("Keep files", "cancel_sync_deletions"))
+            .with_open_path("/tmp/data");
+        assert_eq!(
+            serde_json::to_value(&full).unwrap(),
+            json!({
+                "id": "update-1.2.0",
+                "title": "Update Available",
+                "message": "1.2.0",
+                "tone": "warn",
+                "actions": [
+                    {"label": "Open Settings", "view": "settings"},
+                    {"label": "Release Notes", "url": "https://example.com/r"},
+                    {"label": "Keep files", "command": "cancel_sync_deletions"}
+                ],
+                "openPath": "/tmp/data"
+            })
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// Outbound traffic to one host. Only the host name is kept: no URLs, paths or payloads.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostActivity {
+    pub requests: u64,
+    pub last_at: String,
+    pub last_status: String,
+}
+
+/// Per-host request counts. Kept apart from `RuntimeState` so the git layer, which has no app
+/// handle, can record too, and so recording never waits on the runtime lock.
+pub struct NetworkActivity {
+    hosts: Mutex<BTreeMap<String, HostActivity>>,
+}
+
+impl NetworkActivity {
+    pub const fn new() -> Self {
+        Self {
+            hosts: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn record(&self, host: &str, status: &str) {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = hosts.entry(host.to_string()).or_default();
+        entry.requests += 1;
+        entry.last_at = crate::time_util::now_iso_time();
+        entry.last_status = status.to_string();
+    }
+
+    pub fn record_http(&self, url: &str, result: &Result<ureq::Response, ureq::Error>) {
+        let status = match result {
+            Ok(resp) => resp.status().to_string(),
+            Err(ureq::Error::Status(code, _)) => code.to_string(),
+            Err(ureq::Error::Transport(_)) => "transport error".to_string(),
+        };
+        self.record(&host_of(url), &status);
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, HostActivity> {
+        self.hosts.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+static ACTIVITY: NetworkActivity = NetworkActivity::new();
+
+/// The process-wide recorder behind `get_network_activity`.
+pub fn global() -> &'static NetworkActivity {
+    &ACTIVITY
+}
+
+/// Host part of `url`, without scheme, credentials, port or path.
+pub fn host_of(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority.rsplit('@').next().unwrap_or("");
+    host.split(':').next().unwrap_or("").to_lowercase()
+}
+
+/// Sends `req`, recording the outcome against its host. All HTTP requests go through here.
+#[allow(clippy::result_large_err)]
+pub fn call(req: ureq::Request) -> Result<ureq::Response, ureq::Error> {
+    let url = req.url().to_string();
+    let result = req.call();
+    ACTIVITY.record_http(&url, &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_drops_everything_but_the_host() {
+        assert_eq!(
+            host_of("https://api.github.com/repos/a/b/releases/latest"),
+            "api.github.com"
+        );
+        assert_eq!(
+            host_of("https://user:pw@Example.com:8443/x?y"),
+            "example.com"
+        );
+        assert_eq!(host_of("github.com"), "github.com");
+    }
+
+    #[test]
+    fn http_results_count_per_host() {
+        let activity = NetworkActivity::new();
+        let ok = ureq::Response::new(200, "OK", "");
+        activity.record_http("https://api.github.com/user", &ok);
+        let denied = Err(ureq::Error::Status(
+            401,
+            ureq::Response::new(401, "Unauthorized", "").unwrap(),
+        ));
+        activity.record_http("https://api.github.com/repos/a/b", &denied);
+        activity.record_http("https://objects.githubusercontent.com/x", &ok);
+
+        let hosts = activity.snapshot();
+        assert_eq!(hosts["api.github.com"].requests, 2);
+        assert_eq!(hosts["api.github.com"].last_status, "401");
+        assert_eq!(hosts["objects.githubusercontent.com"].requests, 1);
+        assert!(!hosts["api.github.com"].last_at.is_empty());
+    }
+}
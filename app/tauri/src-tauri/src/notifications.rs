@@ -0,0 +1,304 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+// Snoozing is driven by toast actions, which only exist on Windows.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub const SNOOZE_MINUTES: i64 = 10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alert {
+    pub event_id: String,
+    pub title: String,
+    pub body: String,
+    pub event_at: DateTime<Utc>,
+}
+
+/// Presents an alert to the user. The OS toast layer implements this; tests use a recorder.
+pub trait Notifier {
+    fn show(&self, alert: &Alert) -> Result<(), String>;
+}
+
+/// Reports whether the OS asked not to be disturbed. The platform query implements this;
+/// tests use a fake.
+pub trait DndProbe {
+    fn active(&self) -> bool;
+}
+
+/// Whether a mute ending at `until` is still on at `now`.
+pub fn muted(until: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    until.is_some_and(|until| now < until)
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct FireOutcome {
+    pub shown: usize,
+    /// Due alerts held back because do-not-disturb was active; they stay queued.
+    pub deferred: usize,
+}
+
+#[derive(Clone, Debug)]
+struct PendingAlert {
+    alert: Alert,
+    fire_at: DateTime<Utc>,
+}
+
+/// Pending alert queue. Pure so the snooze rules can be tested without a toast layer.
+#[derive(Default)]
+pub struct AlertScheduler {
+    pending: Vec<PendingAlert>,
+    /// Event ids already queued once, with their event time for pruning.
+    seen: HashMap<String, DateTime<Utc>>,
+}
+
+impl AlertScheduler {
+    /// Queues an alert the first time an event is seen; later calls for the same id are ignored.
+    pub fn schedule(&mut self, alert: Alert, fire_at: DateTime<Utc>) -> bool {
+        if self.seen.contains_key(&alert.event_id) {
+            return false;
+        }
+        self.seen.insert(alert.event_id.clone(), alert.event_at);
+        self.pending.push(PendingAlert { alert, fire_at });
+        true
+    }
+
+    /// Re-queues an alert `minutes` later. Snoozing an alert that is still pending pushes it
+    /// further out instead of adding a second entry. Returns false when the new time would land
+    /// at or after the event itself.
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub fn snooze(&mut self, alert: Alert, now: DateTime<Utc>, minutes: i64) -> bool {
+        let existing = self
+            .pending
+            .iter()
+            .position(|p| p.alert.event_id == alert.event_id);
+        let base = existing
+            .map(|idx| self.pending[idx].fire_at.max(now))
+            .unwrap_or(now);
+        let fire_at = base + Duration::minutes(minutes);
+        if fire_at >= alert.event_at {
+            return false;
+        }
+        self.seen.insert(alert.event_id.clone(), alert.event_at);
+        match existing {
+            Some(idx) => self.pending[idx].fire_at = fire_at,
+            None => self.pending.push(PendingAlert { alert, fire_at }),
+        }
+        true
+    }
+
+    /// Removes and returns alerts that are due. Alerts whose event has already started are
+    /// dropped rather than shown late.
+    pub fn take_due(&mut self, now: DateTime<Utc>) -> Vec<Alert> {
+        let mut due = Vec::new();
+        self.pending.retain(|p| {
+            if p.alert.event_at <= now {
+                return false;
+            }
+            if p.fire_at <= now {
+                due.push(p.alert.clone());
+                return false;
+            }
+            true
+        });
+        let horizon = now - Duration::days(1);
+        self.seen.retain(|_, event_at| *event_at > horizon);
+        due
+    }
+
+    /// Alerts that `take_due` would return at `now`.
+    pub fn due_len(&self, now: DateTime<Utc>) -> usize {
+        self.pending
+            .iter()
+            .filter(|p| p.fire_at <= now && p.alert.event_at > now)
+            .count()
+    }
+
+    /// Event ids already queued, with their event time.
+    pub fn seen_entries(&self) -> impl Iterator<Item = (&String, &DateTime<Utc>)> {
+        self.seen.iter()
+    }
+
+    /// Queued alerts with their fire time.
+    pub fn pending_entries(&self) -> impl Iterator<Item = (&Alert, DateTime<Utc>)> {
+        self.pending.iter().map(|p| (&p.alert, p.fire_at))
+    }
+
+    /// Rebuilds a scheduler from persisted entries. Pending alerts count as seen.
+    pub fn restore(
+        seen: HashMap<String, DateTime<Utc>>,
+        pending: Vec<(Alert, DateTime<Utc>)>,
+    ) -> Self {
+        let mut scheduler = Self {
+            pending: vec![],
+            seen,
+        };
+        for (alert, fire_at) in pending {
+            scheduler
+                .seen
+                .insert(alert.event_id.clone(), alert.event_at);
+            scheduler.pending.push(PendingAlert { alert, fire_at });
+        }
+        scheduler
+    }
+
+    #[cfg(test)]
+    pub fn fire_at(&self, event_id: &str) -> Option<DateTime<Utc>> {
+        self.pending
+            .iter()
+            .find(|p| p.alert.event_id == event_id)
+            .map(|p| p.fire_at)
+    }
+
+    #[cfg(test)]
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+/// Shows every due alert through `notifier`; returns how many were shown.
+pub fn fire_due(
+    scheduler: &mut AlertScheduler,
+    now: DateTime<Utc>,
+    notifier: &dyn Notifier,
+) -> usize {
+    scheduler
+        .take_due(now)
+        .iter()
+        .filter(|alert| notifier.show(alert).is_ok())
+        .count()
+}
+
+/// `fire_due`, except that while `dnd` is active due alerts stay queued and are shown on the
+/// first tick after it ends (or dropped if their event has started by then).
+pub fn fire_due_unless_dnd(
+    scheduler: &mut AlertScheduler,
+    now: DateTime<Utc>,
+    notifier: &dyn Notifier,
+    dnd: &dyn DndProbe,
+) -> FireOutcome {
+    if dnd.active() {
+        return FireOutcome {
+            shown: 0,
+            deferred: scheduler.due_len(now),
+        };
+    }
+    FireOutcome {
+        shown: fire_due(scheduler, now, notifier),
+        deferred: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::cell::{Cell, RefCell};
+
+    #[derive(Default)]
+    struct Recorder {
+        shown: RefCell<Vec<String>>,
+    }
+
+    impl Notifier for Recorder {
+        fn show(&self, alert: &Alert) -> Result<(), String> {
+            self.shown.borrow_mut().push(alert.event_id.clone());
+            Ok(())
+        }
+    }
+
+    fn at(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 6, h, m, 0).unwrap()
+    }
+
+    fn alert(id: &str, event_at: DateTime<Utc>) -> Alert {
+        Alert {
+            event_id: id.to_string(),
+            title: "USD Non-Farm Payrolls".to_string(),
+            body: "High impact".to_string(),
+            event_at,
+        }
+    }
+
+    #[test]
+    fn snooze_requeues_ten_minutes_later() {
+        let mut scheduler = AlertScheduler::default();
+        let notifier = Recorder::default();
+        let nfp = alert("evt-1", at(13, 30));
+        assert!(scheduler.schedule(nfp.clone(), at(12, 30)));
+        assert_eq!(fire_due(&mut scheduler, at(12, 30), &notifier), 1);
+        assert!(!scheduler.schedule(nfp.clone(), at(12, 30)));
+
+        assert!(scheduler.snooze(nfp, at(12, 31), SNOOZE_MINUTES));
+        assert_eq!(fire_due(&mut scheduler, at(12, 40), &notifier), 0);
+        assert_eq!(fire_due(&mut scheduler, at(12, 41), &notifier), 1);
+        assert_eq!(*notifier.shown.borrow(), vec!["evt-1", "evt-1"]);
+    }
+
+    #[test]
+    fn snooze_never_lands_after_event_time() {
+        let mut scheduler = AlertScheduler::default();
+        let nfp = alert("evt-1", at(13, 30));
+        assert!(!scheduler.snooze(nfp.clone(), at(13, 20), SNOOZE_MINUTES));
+        assert!(!scheduler.snooze(nfp, at(13, 25), SNOOZE_MINUTES));
+        assert_eq!(scheduler.pending_len(), 0);
+    }
+
+    #[test]
+    fn resnooze_stacks_on_pending_entry() {
+        let mut scheduler = AlertScheduler::default();
+        let nfp = alert("evt-1", at(13, 30));
+        assert!(scheduler.snooze(nfp.clone(), at(12, 0), SNOOZE_MINUTES));
+        assert!(scheduler.snooze(nfp.clone(), at(12, 1), SNOOZE_MINUTES));
+        assert_eq!(scheduler.pending_len(), 1);
+        assert_eq!(scheduler.fire_at("evt-1"), Some(at(12, 20)));
+
+        assert!(scheduler.snooze(nfp, at(12, 2), SNOOZE_MINUTES));
+        assert_eq!(scheduler.fire_at("evt-1"), Some(at(12, 30)));
+        // Stacking past the event time is refused and leaves the pending entry alone.
+        let late = alert("evt-1", at(12, 35));
+        assert!(!scheduler.snooze(late, at(12, 3), SNOOZE_MINUTES));
+        assert_eq!(scheduler.fire_at("evt-1"), Some(at(12, 30)));
+    }
+
+    #[test]
+    fn due_alerts_for_started_events_are_dropped() {
+        let mut scheduler = AlertScheduler::default();
+        let notifier = Recorder::default();
+        scheduler.schedule(alert("evt-1", at(13, 30)), at(13, 25));
+        assert_eq!(fire_due(&mut scheduler, at(13, 31), &notifier), 0);
+        assert_eq!(scheduler.pending_len(), 0);
+    }
+
+    struct FakeDnd(Cell<bool>);
+
+    impl DndProbe for FakeDnd {
+        fn active(&self) -> bool {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn dnd_defers_alerts_until_it_ends() {
+        let mut scheduler = AlertScheduler::default();
+        let notifier = Recorder::default();
+        let dnd = FakeDnd(Cell::new(true));
+        scheduler.schedule(alert("evt-1", at(13, 30)), at(13, 25));
+        scheduler.schedule(alert("evt-2", at(13, 28)), at(13, 23));
+
+        let held = fire_due_unless_dnd(&mut scheduler, at(13, 25), &notifier, &dnd);
+        assert_eq!(
+            held,
+            FireOutcome {
+                shown: 0,
+                deferred: 2
+            }
+        );
+        assert!(notifier.shown.borrow().is_empty());
+
+        // evt-2 started while DND was on, so only evt-1 is still worth showing.
+        dnd.0.set(false);
+        let released = fire_due_unless_dnd(&mut scheduler, at(13, 29), &notifier, &dnd);
+        assert_eq!(released.shown, 1);
+        assert_eq!(*notifier.shown.borrow(), vec!["evt-1"]);
+        assert_eq!(scheduler.pending_len(), 0);
+    }
+}
@@ -0,0 +1,121 @@
+/// Opens a path or URL with the OS default handler.
+pub fn open_target(target: &str) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("cmd")
+            .args(["/c", "start", "", target])
+            .spawn()
+            .is_ok()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(target)
+            .spawn()
+            .is_ok()
+    }
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(target)
+            .spawn()
+            .is_ok()
+    }
+}
+
+/// Key used for per-platform settings such as `github_release_assets`.
+pub fn current_platform() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "windows"
+    } else if cfg!(target_os = "macos") {
+        "macos"
+    } else if cfg!(target_os = "linux") {
+        "linux"
+    } else {
+        "other"
+    }
+}
+
+/// Starts a downloaded release asset: runs the Windows installer, mounts the macOS disk image,
+/// or marks the Linux AppImage executable and runs it.
+pub fn launch_installer(path: &std::path::Path) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new(path)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(path)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| e.to_string())?;
+        std::process::Command::new(path)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = path;
+        Err("In-app update not supported here".to_string())
+    }
+}
+
+/// Whether a system tray host is running. Only Linux can lack one: without a
+/// StatusNotifierWatcher on the session bus the tray icon is silently never shown. When
+/// `dbus-send` itself is unavailable we assume a tray exists.
+pub fn tray_host_available() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        let output = std::process::Command::new("dbus-send")
+            .args([
+                "--session",
+                "--dest=org.freedesktop.DBus",
+                "--type=method_call",
+                "--print-reply",
+                "/org/freedesktop/DBus",
+                "org.freedesktop.DBus.NameHasOwner",
+                "string:org.kde.StatusNotifierWatcher",
+            ])
+            .output();
+        match output {
+            Ok(out) if out.status.success() => {
+                !String::from_utf8_lossy(&out.stdout).contains("boolean false")
+            }
+            _ => true,
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        true
+    }
+}
+
+/// Whether Windows reports the user as not wanting notifications right now: Focus Assist quiet
+/// time, presentation mode, or a full-screen/busy app (`SHQueryUserNotificationState`). Other
+/// platforms never report do-not-disturb.
+pub fn system_dnd_active() -> bool {
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::UI::Shell::{
+            SHQueryUserNotificationState, QUNS_ACCEPTS_NOTIFICATIONS, QUNS_NOT_PRESENT,
+        };
+        let mut state = QUNS_ACCEPTS_NOTIFICATIONS;
+        let hr = unsafe { SHQueryUserNotificationState(&mut state) };
+        hr >= 0 && state != QUNS_ACCEPTS_NOTIFICATIONS && state != QUNS_NOT_PRESENT
+    }
+    #[cfg(not(windows))]
+    {
+        false
+    }
+}
@@ -0,0 +1,245 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Folder next to the executable that holds all app data in portable mode.
+pub const PORTABLE_DIR_NAME: &str = "user-data";
+
+/// Written into a converted data dir once every file is in place, before it is switched to.
+pub const CONVERSION_MARKER: &str = ".conversion_complete";
+
+/// Left in a portable `user-data/` whose data moved back to AppData but which could not be
+/// removed yet (e.g. the running app still held its log open). Such a folder no longer
+/// counts as portable and is deleted on a later start.
+pub const RETIRED_MARKER: &str = ".retired";
+
+/// Whether `dir` is an active portable data folder.
+pub fn portable_dir_active(dir: &Path) -> bool {
+    dir.is_dir() && !dir.join(RETIRED_MARKER).exists()
+}
+
+/// Fails with a user-facing explanation unless files can be created in `dir`.
+pub fn check_writable(dir: &Path) -> Result<(), String> {
+    let probe = dir.join(format!(".write-probe-{}", std::process::id()));
+    fs::write(&probe, b"")
+        .and_then(|_| fs::remove_file(&probe))
+        .map_err(|e| {
+            format!(
+                "{} is not writable ({e}). Installs under Program Files cannot hold portable \
+                 data; move the app to a user-writable folder first.",
+                dir.display()
+            )
+        })
+}
+
+fn sibling(dir: &Path, suffix: &str) -> PathBuf {
+    let mut name = dir.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    dir.with_file_name(name)
+}
+
+/// Files under `src` to carry over, relative to it. Lock sidecars and conversion markers are
+/// local state and stay behind.
+fn data_files(src: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let mut files: Vec<(PathBuf, PathBuf)> = walkdir::WalkDir::new(src)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| entry.path().extension().is_none_or(|ext| ext != "lock"))
+        .filter_map(|entry| {
+            let rel = entry.path().strip_prefix(src).ok()?.to_path_buf();
+            let name = rel.file_name()?;
+            (name != CONVERSION_MARKER && name != RETIRED_MARKER)
+                .then(|| (rel, entry.path().to_path_buf()))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Copies every data file from `src` into `target`, which is only switched to once complete:
+/// the copy is assembled in a `.partial` sibling, marked with `CONVERSION_MARKER`, and renamed
+/// over `target`. A failed or interrupted run leaves `target` as it was. `progress` receives
+/// `(copied, total)` after each file. Returns the number of files copied.
+pub fn convert_data_dir(
+    src: &Path,
+    target: &Path,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<usize, String> {
+    if !src.is_dir() {
+        return Err(format!("Data folder not found: {}", src.display()));
+    }
+    let files = data_files(src);
+    let staging = sibling(target, ".partial");
+    let previous = sibling(target, ".previous");
+    let _ = fs::remove_dir_all(&staging);
+    let copied = (|| -> Result<usize, String> {
+        fs::create_dir_all(&staging).map_err(|e| e.to_string())?;
+        for (idx, (rel, from)) in files.iter().enumerate() {
+            let to = staging.join(rel);
+            if let Some(parent) = to.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::copy(from, &to).map_err(|e| format!("{}: {e}", from.display()))?;
+            progress(idx + 1, files.len());
+        }
+        let marker = format!(
+            "{{\"from\": {}, \"files\": {}}}\n",
+            serde_json::Value::String(src.to_string_lossy().to_string()),
+            files.len()
+        );
+        fs::write(staging.join(CONVERSION_MARKER), marker).map_err(|e| e.to_string())?;
+        Ok(files.len())
+    })();
+    let copied = match copied {
+        Ok(copied) => copied,
+        Err(err) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(err);
+        }
+    };
+
+    let _ = fs::remove_dir_all(&previous);
+    if target.exists() {
+        if let Err(err) = fs::rename(target, &previous) {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(format!("{} is in use: {err}", target.display()));
+        }
+    }
+    if let Err(err) = fs::rename(&staging, target) {
+        let _ = fs::rename(&previous, target);
+        let _ = fs::remove_dir_all(&staging);
+        return Err(err.to_string());
+    }
+    let _ = fs::remove_dir_all(&previous);
+    Ok(copied)
+}
+
+/// Removes a portable folder whose data now lives elsewhere. When files are still held open
+/// whatever is left is marked retired and the removal error comes back as `Ok(Some(..))`. Fails
+/// only when the folder stays in place unmarked, i.e. still counts as portable.
+pub fn retire_portable_dir(dir: &Path) -> Result<Option<String>, String> {
+    let err = match fs::remove_dir_all(dir) {
+        Ok(()) => return Ok(None),
+        Err(err) if !dir.exists() => return Ok(Some(err.to_string())),
+        Err(err) => err,
+    };
+    // The marker goes in last: a partial removal may have taken an earlier one with it.
+    fs::write(dir.join(RETIRED_MARKER), b"").map_err(|marker_err| {
+        format!(
+            "{} could not be removed ({err}) or marked retired ({marker_err})",
+            dir.display()
+        )
+    })?;
+    Ok(Some(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, text: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    /// An AppData-style data dir plus an empty "exe dir".
+    fn layout() -> (tempfile::TempDir, tempfile::TempDir) {
+        let appdata = tempfile::tempdir().unwrap();
+        write(&appdata.path().join("config.json"), "{}");
+        write(&appdata.path().join("logs/app.log"), "line\n");
+        write(
+            &appdata
+                .path()
+                .join("data/Economic_Calendar/2026/2026_calendar.json"),
+            "[]",
+        );
+        write(&appdata.path().join("data/x.ndjson.lock"), "");
+        (appdata, tempfile::tempdir().unwrap())
+    }
+
+    #[test]
+    fn converts_into_a_marked_portable_dir() {
+        let (appdata, exe_dir) = layout();
+        let target = exe_dir.path().join(PORTABLE_DIR_NAME);
+        let mut seen = vec![];
+        let copied = convert_data_dir(appdata.path(), &target, &mut |done, total| {
+            seen.push((done, total))
+        })
+        .unwrap();
+
+        assert_eq!(copied, 3);
+        assert_eq!(seen, vec![(1, 3), (2, 3), (3, 3)]);
+        assert!(portable_dir_active(&target));
+        assert!(target.join(CONVERSION_MARKER).exists());
+        assert_eq!(
+            fs::read_to_string(target.join("logs/app.log")).unwrap(),
+            "line\n"
+        );
+        assert!(!target.join("data/x.ndjson.lock").exists());
+        assert!(!exe_dir.path().join("user-data.partial").exists());
+        // The source is copied, not moved.
+        assert!(appdata.path().join("config.json").exists());
+    }
+
+    #[test]
+    fn interrupted_copy_never_switches_the_target() {
+        let (appdata, exe_dir) = layout();
+        let target = exe_dir.path().join(PORTABLE_DIR_NAME);
+        // A leftover from an earlier crashed conversion is discarded.
+        write(&exe_dir.path().join("user-data.partial/stale.json"), "{}");
+        let doomed = appdata.path().join("logs/app.log");
+        let err = convert_data_dir(appdata.path(), &target, &mut |_, _| {
+            let _ = fs::remove_file(&doomed);
+        })
+        .err()
+        .unwrap_or_default();
+
+        assert!(err.contains("app.log"), "{err}");
+        assert!(!target.exists());
+        assert!(!exe_dir.path().join("user-data.partial").exists());
+    }
+
+    #[test]
+    fn converting_back_replaces_existing_appdata() {
+        let (portable, appdata_parent) = layout();
+        let appdata = appdata_parent.path().join("XAUUSDCalendar");
+        write(&appdata.join("config.json"), "{\"old\": true}");
+        write(&appdata.join("stale.txt"), "");
+
+        convert_data_dir(portable.path(), &appdata, &mut |_, _| {}).unwrap();
+        assert_eq!(
+            fs::read_to_string(appdata.join("config.json")).unwrap(),
+            "{}"
+        );
+        assert!(!appdata.join("stale.txt").exists());
+        assert!(!appdata_parent
+            .path()
+            .join("XAUUSDCalendar.previous")
+            .exists());
+
+        assert_eq!(retire_portable_dir(portable.path()).unwrap(), None);
+        assert!(!portable.path().exists());
+        assert!(!portable_dir_active(portable.path()));
+    }
+
+    #[test]
+    fn retired_dir_is_not_portable() {
+        let exe_dir = tempfile::tempdir().unwrap();
+        let dir = exe_dir.path().join(PORTABLE_DIR_NAME);
+        assert!(!portable_dir_active(&dir));
+        write(&dir.join("config.json"), "{}");
+        assert!(portable_dir_active(&dir));
+        write(&dir.join(RETIRED_MARKER), "");
+        assert!(!portable_dir_active(&dir));
+    }
+
+    #[test]
+    fn unwritable_dir_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(check_writable(dir.path()).is_ok());
+        let err = check_writable(&dir.path().join("missing"))
+            .err()
+            .unwrap_or_default();
+        assert!(err.contains("Program Files"), "{err}");
+    }
+}
@@ -0,0 +1,263 @@
+use crate::config;
+use crate::notifications::{Alert, AlertScheduler};
+use crate::state::RuntimeState;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Bump when a field changes meaning; `load` migrates older files forward.
+pub const RUNTIME_STATE_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AnnouncedAlert {
+    pub event_id: String,
+    pub event_at_ms: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SnoozedAlert {
+    pub event_id: String,
+    pub title: String,
+    pub body: String,
+    pub event_at_ms: i64,
+    pub fire_at_ms: i64,
+}
+
+/// The subset of `RuntimeState` that should survive a restart. Unknown fields are ignored and
+/// missing ones default, so files written by other versions still load.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DurableState {
+    pub version: u32,
+    pub update_prompted_version: String,
+    pub announced_alerts: Vec<AnnouncedAlert>,
+    pub snoozed_alerts: Vec<SnoozedAlert>,
+    /// `RuntimeState::notifications_muted_until`, so a mute outlives a restart.
+    pub notifications_muted_until_ms: Option<i64>,
+}
+
+pub fn runtime_state_path() -> PathBuf {
+    config::appdata_dir().join("runtime_state.json")
+}
+
+fn from_ms(ms: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_millis_opt(ms).single()
+}
+
+impl DurableState {
+    pub fn capture(runtime: &RuntimeState) -> Self {
+        let mut announced_alerts: Vec<AnnouncedAlert> = runtime
+            .alerts
+            .seen_entries()
+            .map(|(event_id, event_at)| AnnouncedAlert {
+                event_id: event_id.clone(),
+                event_at_ms: event_at.timestamp_millis(),
+            })
+            .collect();
+        // Stable order keeps unchanged state byte-identical for the debounced writer.
+        announced_alerts.sort_by(|a, b| a.event_id.cmp(&b.event_id));
+        let snoozed_alerts = runtime
+            .alerts
+            .pending_entries()
+            .map(|(alert, fire_at)| SnoozedAlert {
+                event_id: alert.event_id.clone(),
+                title: alert.title.clone(),
+                body: alert.body.clone(),
+                event_at_ms: alert.event_at.timestamp_millis(),
+                fire_at_ms: fire_at.timestamp_millis(),
+            })
+            .collect();
+        Self {
+            version: RUNTIME_STATE_VERSION,
+            update_prompted_version: runtime.update_prompted_version.clone(),
+            announced_alerts,
+            snoozed_alerts,
+            notifications_muted_until_ms: runtime
+                .notifications_muted_until
+                .map(|at| at.timestamp_millis()),
+        }
+    }
+
+    pub fn notifications_muted_until(&self) -> Option<DateTime<Utc>> {
+        self.notifications_muted_until_ms.and_then(from_ms)
+    }
+
+    /// Drops alerts whose event has started (snoozes) or is over a day old (announced ids),
+    /// matching how `AlertScheduler` prunes at runtime.
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        let horizon = (now - Duration::days(1)).timestamp_millis();
+        let now_ms = now.timestamp_millis();
+        self.announced_alerts.retain(|a| a.event_at_ms > horizon);
+        self.snoozed_alerts.retain(|a| a.event_at_ms > now_ms);
+        self.notifications_muted_until_ms = self
+            .notifications_muted_until_ms
+            .filter(|until| *until > now_ms);
+    }
+
+    pub fn alert_scheduler(&self) -> AlertScheduler {
+        let seen: HashMap<String, DateTime<Utc>> = self
+            .announced_alerts
+            .iter()
+            .filter_map(|a| Some((a.event_id.clone(), from_ms(a.event_at_ms)?)))
+            .collect();
+        let pending = self
+            .snoozed_alerts
+            .iter()
+            .filter_map(|a| {
+                let alert = Alert {
+                    event_id: a.event_id.clone(),
+                    title: a.title.clone(),
+                    body: a.body.clone(),
+                    event_at: from_ms(a.event_at_ms)?,
+                };
+                Some((alert, from_ms(a.fire_at_ms)?))
+            })
+            .collect();
+        AlertScheduler::restore(seen, pending)
+    }
+}
+
+/// Reads and prunes the persisted state; a missing or unreadable file yields the defaults.
+pub fn load(path: &Path, now: DateTime<Utc>) -> DurableState {
+    let mut state: DurableState = fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    // Version 0 is a file without a version field; its fields already match version 1.
+    state.version = RUNTIME_STATE_VERSION;
+    state.prune(now);
+    state
+}
+
+fn to_text(state: &DurableState) -> Result<String, String> {
+    serde_json::to_string_pretty(state).map_err(|e| e.to_string())
+}
+
+/// Writes atomically (temp file + rename), like `config::save_config`.
+pub fn save(path: &Path, state: &DurableState) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let tmp = path.with_extension(format!("tmp-{}", std::process::id()));
+    fs::write(&tmp, to_text(state)?).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Saves only when the content differs from `last_saved`, which is updated on success.
+pub fn save_if_changed(
+    path: &Path,
+    state: &DurableState,
+    last_saved: &mut String,
+) -> Result<bool, String> {
+    let text = to_text(state)?;
+    if text == *last_saved {
+        return Ok(false);
+    }
+    save(path, state)?;
+    *last_saved = text;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 6, h, 0, 0).unwrap()
+    }
+
+    fn alert(id: &str, event_at: DateTime<Utc>) -> Alert {
+        Alert {
+            event_id: id.to_string(),
+            title: "USD CPI m/m".to_string(),
+            body: "High impact".to_string(),
+            event_at,
+        }
+    }
+
+    #[test]
+    fn round_trips_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("runtime_state.json");
+        let mut runtime = RuntimeState {
+            update_prompted_version: "0.4.0".to_string(),
+            ..RuntimeState::default()
+        };
+        runtime.alerts.schedule(alert("evt-a", at(13)), at(12));
+        runtime.alerts.take_due(at(12));
+        runtime.alerts.schedule(alert("evt-b", at(15)), at(14));
+
+        let captured = DurableState::capture(&runtime);
+        save(&path, &captured).unwrap();
+        let loaded = load(&path, at(12));
+        assert_eq!(loaded, captured);
+
+        let mut scheduler = loaded.alert_scheduler();
+        // evt-a was already announced; evt-b is still queued and must not be queued twice.
+        assert!(!scheduler.schedule(alert("evt-a", at(13)), at(12)));
+        assert!(!scheduler.schedule(alert("evt-b", at(15)), at(14)));
+        assert_eq!(scheduler.take_due(at(14)), vec![alert("evt-b", at(15))]);
+    }
+
+    #[test]
+    fn load_prunes_expired_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("runtime_state.json");
+        let state = DurableState {
+            version: RUNTIME_STATE_VERSION,
+            update_prompted_version: "0.4.0".to_string(),
+            announced_alerts: vec![
+                AnnouncedAlert {
+                    event_id: "old".to_string(),
+                    event_at_ms: (at(10) - Duration::days(2)).timestamp_millis(),
+                },
+                AnnouncedAlert {
+                    event_id: "recent".to_string(),
+                    event_at_ms: at(9).timestamp_millis(),
+                },
+            ],
+            snoozed_alerts: vec![SnoozedAlert {
+                event_id: "started".to_string(),
+                event_at_ms: at(9).timestamp_millis(),
+                fire_at_ms: at(8).timestamp_millis(),
+                ..SnoozedAlert::default()
+            }],
+            notifications_muted_until_ms: Some(at(10).timestamp_millis()),
+        };
+        save(&path, &state).unwrap();
+        let loaded = load(&path, at(10));
+        let ids: Vec<&str> = loaded
+            .announced_alerts
+            .iter()
+            .map(|a| a.event_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["recent"]);
+        assert!(loaded.snoozed_alerts.is_empty());
+        assert_eq!(loaded.update_prompted_version, "0.4.0");
+        // A mute ending exactly now has expired; one still running survives.
+        assert_eq!(loaded.notifications_muted_until(), None);
+        assert_eq!(load(&path, at(9)).notifications_muted_until(), Some(at(10)));
+    }
+
+    #[test]
+    fn unversioned_and_future_files_still_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("runtime_state.json");
+        fs::write(
+            &path,
+            r#"{"updatePromptedVersion":"0.3.1","someFutureField":{"x":1}}"#,
+        )
+        .unwrap();
+        let loaded = load(&path, at(10));
+        assert_eq!(loaded.version, RUNTIME_STATE_VERSION);
+        assert_eq!(loaded.update_prompted_version, "0.3.1");
+
+        let mut last = String::new();
+        assert!(save_if_changed(&path, &loaded, &mut last).unwrap());
+        assert!(!save_if_changed(&path, &loaded, &mut last).unwrap());
+    }
+}
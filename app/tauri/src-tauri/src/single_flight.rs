@@ -0,0 +1,199 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex};
+
+enum Slot<T, E> {
+    Pending,
+    Done(Result<T, E>),
+    /// The leading caller panicked; waiters fall back to loading themselves.
+    Abandoned,
+}
+
+struct Flight<T, E> {
+    slot: Mutex<Slot<T, E>>,
+    ready: Condvar,
+}
+
+struct Inner<T, E> {
+    in_flight: HashMap<String, Arc<Flight<T, E>>>,
+    /// Settled `Ok` results, most recently used first.
+    cache: VecDeque<(String, T)>,
+}
+
+/// Deduplicates concurrent loads per key and keeps a small LRU of successful results.
+///
+/// The first caller for a key runs the loader; callers arriving while it runs block until it
+/// settles and share its result. Errors are handed to waiters but not cached.
+pub struct SingleFlight<T, E> {
+    inner: Mutex<Inner<T, E>>,
+    capacity: usize,
+}
+
+/// Marks the flight abandoned if the loader unwinds, so waiters are not stranded.
+struct LeaderGuard<'a, T, E> {
+    owner: &'a SingleFlight<T, E>,
+    key: &'a str,
+    flight: &'a Flight<T, E>,
+}
+
+/// Cached results kept by `SingleFlight::default()`.
+const DEFAULT_CAPACITY: usize = 32;
+
+impl<T: Clone, E: Clone> Default for SingleFlight<T, E> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl<T, E> Drop for LeaderGuard<'_, T, E> {
+    fn drop(&mut self) {
+        let mut slot = self.flight.slot.lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(*slot, Slot::Pending) {
+            *slot = Slot::Abandoned;
+            self.flight.ready.notify_all();
+        }
+        drop(slot);
+        let mut inner = self.owner.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.in_flight.remove(self.key);
+    }
+}
+
+impl<T: Clone, E: Clone> SingleFlight<T, E> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                in_flight: HashMap::new(),
+                cache: VecDeque::new(),
+            }),
+            capacity,
+        }
+    }
+
+    pub fn run(&self, key: &str, load: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+        let (flight, leader) = {
+            let mut inner = self.inner.lock().expect("single flight lock");
+            if let Some(pos) = inner.cache.iter().position(|(k, _)| k == key) {
+                let entry = inner.cache.remove(pos).expect("cached entry");
+                let value = entry.1.clone();
+                inner.cache.push_front(entry);
+                return Ok(value);
+            }
+            match inner.in_flight.get(key) {
+                Some(flight) => (flight.clone(), false),
+                None => {
+                    let flight = Arc::new(Flight {
+                        slot: Mutex::new(Slot::Pending),
+                        ready: Condvar::new(),
+                    });
+                    inner.in_flight.insert(key.to_string(), flight.clone());
+                    (flight, true)
+                }
+            }
+        };
+
+        if !leader {
+            let mut slot = flight.slot.lock().expect("flight lock");
+            while matches!(*slot, Slot::Pending) {
+                slot = flight.ready.wait(slot).expect("flight lock");
+            }
+            return match &*slot {
+                Slot::Done(result) => result.clone(),
+                _ => {
+                    drop(slot);
+                    load()
+                }
+            };
+        }
+
+        let guard = LeaderGuard {
+            owner: self,
+            key,
+            flight: &flight,
+        };
+        let result = load();
+        if let Ok(value) = &result {
+            let mut inner = self.inner.lock().expect("single flight lock");
+            inner.cache.push_front((key.to_string(), value.clone()));
+            inner.cache.truncate(self.capacity);
+        }
+        *flight.slot.lock().expect("flight lock") = Slot::Done(result.clone());
+        flight.ready.notify_all();
+        drop(guard);
+        result
+    }
+
+    /// Drops cached results; loads already in flight still settle normally.
+    pub fn clear(&self) {
+        self.inner.lock().expect("single flight lock").cache.clear();
+    }
+
+    /// Drops the cached result for `key`, if any.
+    pub fn forget(&self, key: &str) {
+        self.inner
+            .lock()
+            .expect("single flight lock")
+            .cache
+            .retain(|(k, _)| k != key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn concurrent_callers_share_one_load() {
+        let flights: Arc<SingleFlight<String, String>> = Arc::new(SingleFlight::new(4));
+        let loads = Arc::new(AtomicUsize::new(0));
+        let callers: Vec<_> = (0..2)
+            .map(|_| {
+                let flights = flights.clone();
+                let loads = loads.clone();
+                std::thread::spawn(move || {
+                    flights.run("usd::nfp", || {
+                        loads.fetch_add(1, Ordering::SeqCst);
+                        // Slow fixture: keeps the first load in flight while the second arrives.
+                        std::thread::sleep(Duration::from_millis(200));
+                        Ok("points".to_string())
+                    })
+                })
+            })
+            .collect();
+        for caller in callers {
+            assert_eq!(caller.join().unwrap(), Ok("points".to_string()));
+        }
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        // Settled result is served from the cache afterwards.
+        let again = flights.run("usd::nfp", || Err("loader ran".to_string()));
+        assert_eq!(again, Ok("points".to_string()));
+    }
+
+    #[test]
+    fn errors_are_shared_but_not_cached() {
+        let flights: SingleFlight<String, String> = SingleFlight::new(4);
+        assert_eq!(
+            flights.run("a", || Err("missing".to_string())),
+            Err("missing".to_string())
+        );
+        assert_eq!(
+            flights.run("a", || Ok("found".to_string())),
+            Ok("found".to_string())
+        );
+    }
+
+    #[test]
+    fn cache_evicts_least_recently_used() {
+        let flights: SingleFlight<u32, ()> = SingleFlight::new(2);
+        flights.run("a", || Ok(1)).unwrap();
+        flights.run("b", || Ok(2)).unwrap();
+        flights.run("a", || Ok(0)).unwrap();
+        flights.run("c", || Ok(3)).unwrap();
+        assert_eq!(flights.run("a", || Ok(10)), Ok(1));
+        assert_eq!(flights.run("b", || Ok(20)), Ok(20));
+
+        flights.clear();
+        assert_eq!(flights.run("a", || Ok(100)), Ok(100));
+    }
+}
@@ -1,39 +1,502 @@
-use crate::calendar::CalendarEvent;
-use crate::time_util::{format_countdown, format_display_time};
+use crate::calendar::{parse_numeric, CalendarEvent, EventSource};
+use crate::categories::matches_filter;
+use crate::time_util::{
+    date_format, display_date, dst_note, format_countdown_from, format_display_time,
+    format_elapsed, now_utc, DateFormat,
+};
 use chrono::{DateTime, Duration, Utc};
 use serde_json::json;
 use sha1::{Digest, Sha1};
+use std::collections::HashMap;
 
+/// Date-only rows carry the `All Day` marker; `no_time_label` replaces it on display, and an
+/// empty label shows just the date.
 fn format_time_text(
     dt_utc: DateTime<Utc>,
     time_label: &str,
     source_date_label: Option<&str>,
     tz_mode: &str,
     utc_offset_minutes: i32,
+    no_time_label: &str,
+    date_format: DateFormat,
 ) -> String {
-    let time_text = format_display_time(dt_utc, tz_mode, utc_offset_minutes);
+    let time_text = format_display_time(dt_utc, tz_mode, utc_offset_minutes, date_format);
     let label = time_label.trim();
     if label.eq_ignore_ascii_case("all day") {
         let date_label = source_date_label
             .map(|s| s.to_string())
-            .unwrap_or_else(|| dt_utc.format("%d-%m-%Y").to_string());
-        return format!("{date_label} All Day");
+            .unwrap_or_else(|| dt_utc.format(date_format.date_pattern()).to_string());
+        let no_time_label = no_time_label.trim();
+        if no_time_label.is_empty() {
+            return date_label;
+        }
+        return format!("{date_label} {no_time_label}");
     }
     if !label.is_empty() && !label.contains(':') {
-        return format!("{} {}", dt_utc.format("%d-%m-%Y"), label);
+        return format!("{} {}", dt_utc.format(date_format.date_pattern()), label);
     }
     time_text
 }
 
-pub fn render_next_events(
+fn event_digest(e: &CalendarEvent) -> String {
+    let raw_id = format!(
+        "{}|{}|{}|{}|{}",
+        e.dt_utc.to_rfc3339(),
+        e.currency.to_uppercase(),
+        e.time_label.trim(),
+        e.importance.trim(),
+        e.event.trim()
+    );
+    format!("{:x}", Sha1::digest(raw_id.as_bytes()))
+}
+
+/// Stable row id (`evt-<sha1>`), suffixed with a sequence number when identical rows repeat.
+pub fn next_event_id(e: &CalendarEvent, seen: &mut HashMap<String, i32>) -> String {
+    let digest = event_digest(e);
+    let seq = seen.get(&digest).copied().unwrap_or(0) + 1;
+    seen.insert(digest.clone(), seq);
+    if seq == 1 {
+        format!("evt-{digest}")
+    } else {
+        format!("evt-{digest}-{seq}")
+    }
+}
+
+/// Row id (as rendered by `render_next_events`) to its index in the loaded events. Identical
+/// rows share a timestamp, so ids assigned in load order resolve to the same instant as the
+/// rendered ones. Built once per calendar load.
+pub type EventIndex = HashMap<String, usize>;
+
+pub fn build_event_index(events: &[CalendarEvent]) -> EventIndex {
+    let mut seen: HashMap<String, i32> = HashMap::new();
+    events
+        .iter()
+        .enumerate()
+        .map(|(idx, e)| (next_event_id(e, &mut seen), idx))
+        .collect()
+}
+
+/// `(state, countdown)` of a Next Events row at `now`, or `None` once it has left the window.
+pub fn countdown_state(
+    dt_utc: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<(&'static str, String)> {
+    if time_window(dt_utc, now) != TimeWindow::Next {
+        return None;
+    }
+    if dt_utc <= now {
+        Some(("current", "Current".to_string()))
+    } else {
+        Some(("upcoming", format_countdown_from(dt_utc, now)))
+    }
+}
+
+/// `{id: {countdown, state}}` for `ids`, or for every upcoming row when `ids` is `None`. Ids
+/// that are unknown are left out; known ones past the window report `state: "past"`.
+pub fn countdowns(
     events: &[CalendarEvent],
+    index: &EventIndex,
+    ids: Option<&[String]>,
+    now: DateTime<Utc>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let entry = |idx: usize| match countdown_state(events[idx].dt_utc, now) {
+        Some((state, countdown)) => json!({"countdown": countdown, "state": state}),
+        None => json!({"countdown": "", "state": "past"}),
+    };
+    match ids {
+        Some(ids) => ids
+            .iter()
+            .filter_map(|id| Some((id.clone(), entry(*index.get(id)?))))
+            .collect(),
+        None => index
+            .iter()
+            .filter(|(_, idx)| time_window(events[**idx].dt_utc, now) == TimeWindow::Next)
+            .map(|(id, idx)| (id.clone(), entry(*idx)))
+            .collect(),
+    }
+}
+
+/// The most recent released print of a metric, shown alongside its upcoming occurrences.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LastPrint {
+    pub dt_utc: DateTime<Utc>,
+    pub actual: String,
+    pub forecast: String,
+}
+
+/// `LastPrint` per loaded `(currency, event name)`, resolved through the normalized metric so
+/// rows differing only in their period suffix share one entry. Built once per calendar load.
+pub type LastPrints = HashMap<(String, String), LastPrint>;
+
+/// Default `get_status_line` template.
+pub const STATUS_LINE_FORMAT: &str = "{cur} {event} {countdown} [{impact}]";
+
+/// Fills `{cur}`, `{event}`, `{countdown}` and `{impact}` in `template` for `e`. The countdown
+/// is compact (`1h20m`) so the line stays short in status bars.
+pub fn render_status_line(template: &str, e: &CalendarEvent, now: DateTime<Utc>) -> String {
+    let impact = e.importance.trim();
+    template
+        .replace("{cur}", &e.currency.to_uppercase())
+        .replace("{event}", &e.event)
+        .replace(
+            "{countdown}",
+            &format_countdown_from(e.dt_utc, now).replace(' ', ""),
+        )
+        .replace("{impact}", if impact.is_empty() { "--" } else { impact })
+}
+
+/// Which list an event's time puts it in, before any other filter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeWindow {
+    /// Upcoming, or still "current" within the grace window.
+    Next,
+    History,
+    /// Older than the History cutoff.
+    Expired,
+}
+
+pub fn time_window(dt_utc: DateTime<Utc>, now: DateTime<Utc>) -> TimeWindow {
+    if dt_utc >= now - Duration::minutes(3) {
+        TimeWindow::Next
+    } else if dt_utc >= now - Duration::days(31) {
+        TimeWindow::History
+    } else {
+        TimeWindow::Expired
+    }
+}
+
+/// `selected` is the upper-cased currency selection; `ALL` keeps every event. Placeholder rows
+/// show under any selection.
+fn currency_selected(selected: &str, e: &CalendarEvent) -> bool {
+    selected == "ALL"
+        || e.source == EventSource::Placeholder
+        || e.currency.to_uppercase() == selected
+}
+
+/// `filter` holds lower-cased impact names from the UI; empty keeps every event.
+pub fn impact_selected(filter: &[String], e: &CalendarEvent) -> bool {
+    let impact = e.importance.to_lowercase();
+    filter.is_empty() || filter.iter().any(|f| impact.contains(f.as_str()))
+}
+
+/// `min_forecast_change_pct` from config; `None` (the default 0, or any non-positive value)
+/// turns the filter off.
+pub fn min_forecast_change_pct(cfg: &serde_json::Value) -> Option<f64> {
+    cfg.get("min_forecast_change_pct")
+        .and_then(|v| v.as_f64())
+        .filter(|pct| pct.is_finite() && *pct > 0.0)
+}
+
+/// `|forecast - previous| / |previous|` in percent; `None` when either value is not numeric or
+/// previous is zero.
+pub fn forecast_change_pct(e: &CalendarEvent) -> Option<f64> {
+    let forecast = parse_numeric(&e.forecast)?;
+    let previous = parse_numeric(&e.previous)?;
+    if previous == 0.0 {
+        return None;
+    }
+    Some((forecast - previous).abs() / previous.abs() * 100.0)
+}
+
+/// Whether `e` passes `min_forecast_change_pct`. Rows whose change cannot be computed are kept.
+pub fn forecast_change_selected(min_pct: Option<f64>, e: &CalendarEvent) -> bool {
+    match (min_pct, forecast_change_pct(e)) {
+        (Some(min), Some(change)) => change > min,
+        _ => true,
+    }
+}
+
+/// Rows `render_next_events` renders at most.
+pub const NEXT_EVENTS_LIMIT: usize = 240;
+
+/// Next Events order: rows still "current" first, newest first, then upcoming soonest first.
+fn next_events_order(
+    a: &CalendarEvent,
+    b: &CalendarEvent,
+    now: DateTime<Utc>,
+) -> std::cmp::Ordering {
+    let a_current = a.dt_utc <= now;
+    let b_current = b.dt_utc <= now;
+    match (a_current, b_current) {
+        (true, false) => std::cmp::Ordering::Less,
+        (false, true) => std::cmp::Ordering::Greater,
+        (true, true) => b.dt_utc.cmp(&a.dt_utc),
+        (false, false) => a.dt_utc.cmp(&b.dt_utc),
+    }
+}
+
+fn history_limit(selected: &str) -> usize {
+    if selected == "ALL" {
+        6000
+    } else {
+        300
+    }
+}
+
+/// Outcome of the list filters for one event, in the order `event_visibility` checks them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventVisibility {
+    Next,
+    History,
+    PastCutoff,
+    Currency,
+    Category,
+    /// Only the UI filters by impact; the caller decides this one.
+    Importance,
+    /// `min_forecast_change_pct`; only Next Events applies it.
+    ForecastChange,
+    NextLimit,
+    HistoryLimit,
+}
+
+impl EventVisibility {
+    pub fn is_shown(self) -> bool {
+        matches!(self, EventVisibility::Next | EventVisibility::History)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventVisibility::Next => "next",
+            EventVisibility::History => "history",
+            EventVisibility::PastCutoff => "pastCutoff",
+            EventVisibility::Currency => "currency",
+            EventVisibility::Category => "category",
+            EventVisibility::Importance => "importance",
+            EventVisibility::ForecastChange => "forecastChange",
+            EventVisibility::NextLimit => "nextLimit",
+            EventVisibility::HistoryLimit => "historyLimit",
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            EventVisibility::Next => "Shown in Next Events",
+            EventVisibility::History => "Shown in History",
+            EventVisibility::PastCutoff => "Older than the 31-day History window",
+            EventVisibility::Currency => "Filtered out by the selected currency",
+            EventVisibility::Category => "Filtered out by the category filter",
+            EventVisibility::Importance => "Filtered out by the impact filter",
+            EventVisibility::ForecastChange => "Filtered out by the minimum forecast change",
+            EventVisibility::NextLimit => "Beyond the Next Events row limit",
+            EventVisibility::HistoryLimit => "Beyond the History row limit",
+        }
+    }
+}
+
+/// One list filter applied to one event: whether it passed, and a sentence saying why.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VisibilityCheck {
+    /// The `EventVisibility` reported when this check fails.
+    pub reason: EventVisibility,
+    pub passed: bool,
+    pub detail: String,
+}
+
+impl VisibilityCheck {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({"check": self.reason.as_str(), "passed": self.passed, "detail": self.detail})
+    }
+}
+
+/// Every list filter applied to `events[idx]` (sorted by time, as loaded), in the order the
+/// renderers apply them: time window, currency, category, impact, forecast change, then the
+/// row limit of the list it lands in. All checks are evaluated, so one event can fail several.
+pub fn visibility_checks(
+    events: &[CalendarEvent],
+    idx: usize,
     currency: &str,
-    tz_mode: &str,
-    utc_offset_minutes: i32,
-    source_utc_offset_minutes: i32,
+    category_filter: &[String],
+    impact_filter: &[String],
+    min_forecast_change_pct: Option<f64>,
+    now: DateTime<Utc>,
+) -> Vec<VisibilityCheck> {
+    let e = &events[idx];
+    let selected = currency.trim().to_uppercase();
+    let window = time_window(e.dt_utc, now);
+    let check = |reason, passed, detail: String| VisibilityCheck {
+        reason,
+        passed,
+        detail,
+    };
+    let cur = if e.currency.is_empty() {
+        "--".to_string()
+    } else {
+        e.currency.to_uppercase()
+    };
+    let listed = |o: &CalendarEvent| {
+        time_window(o.dt_utc, now) == window
+            && currency_selected(&selected, o)
+            && matches_filter(o, category_filter)
+            && (window != TimeWindow::Next || forecast_change_selected(min_forecast_change_pct, o))
+    };
+    let forecast_change = match (window, min_forecast_change_pct) {
+        (_, None) => check(
+            EventVisibility::ForecastChange,
+            true,
+            "No minimum forecast change is set".to_string(),
+        ),
+        (TimeWindow::Next, Some(min)) => match forecast_change_pct(e) {
+            Some(change) => check(
+                EventVisibility::ForecastChange,
+                change > min,
+                format!("Forecast differs from previous by {change:.1}% (minimum {min}%)"),
+            ),
+            None => check(
+                EventVisibility::ForecastChange,
+                true,
+                "Forecast or previous is not numeric, so the minimum change does not apply"
+                    .to_string(),
+            ),
+        },
+        (_, Some(_)) => check(
+            EventVisibility::ForecastChange,
+            true,
+            "Only Next Events applies the minimum forecast change".to_string(),
+        ),
+    };
+    let limit = match window {
+        TimeWindow::Next => {
+            let ahead = events
+                .iter()
+                .enumerate()
+                .filter(|&(other, o)| {
+                    other != idx
+                        && listed(o)
+                        && next_events_order(o, e, now).then(other.cmp(&idx)).is_lt()
+                })
+                .count();
+            check(
+                EventVisibility::NextLimit,
+                ahead < NEXT_EVENTS_LIMIT,
+                format!(
+                    "{ahead} rows come before it in Next Events, which shows {NEXT_EVENTS_LIMIT}"
+                ),
+            )
+        }
+        // History renders newest first, so every qualifying row after this one comes before it.
+        TimeWindow::History | TimeWindow::Expired => {
+            let newer = events[idx + 1..].iter().filter(|o| listed(o)).count();
+            let max = history_limit(&selected);
+            check(
+                EventVisibility::HistoryLimit,
+                newer < max,
+                format!("{newer} newer rows come before it in History, which shows {max}"),
+            )
+        }
+    };
+    vec![
+        check(
+            EventVisibility::PastCutoff,
+            window != TimeWindow::Expired,
+            match window {
+                TimeWindow::Next => "Upcoming or just released, so it belongs in Next Events",
+                TimeWindow::History => "Released within the last 31 days, so it belongs in History",
+                TimeWindow::Expired => "Released more than 31 days ago, past the History window",
+            }
+            .to_string(),
+        ),
+        check(
+            EventVisibility::Currency,
+            currency_selected(&selected, e),
+            if currency_selected(&selected, e) {
+                format!("{cur} is included in the {selected} selection")
+            } else {
+                format!("{cur} is not the selected currency ({selected})")
+            },
+        ),
+        check(
+            EventVisibility::Category,
+            matches_filter(e, category_filter),
+            if category_filter.is_empty() {
+                "No category filter is set".to_string()
+            } else if matches_filter(e, category_filter) {
+                format!(
+                    "In the filtered categories ({})",
+                    category_filter.join(", ")
+                )
+            } else {
+                format!(
+                    "Not in the filtered categories ({})",
+                    category_filter.join(", ")
+                )
+            },
+        ),
+        check(
+            EventVisibility::Importance,
+            impact_selected(impact_filter, e),
+            if impact_filter.is_empty() {
+                "No impact filter is set".to_string()
+            } else if impact_selected(impact_filter, e) {
+                format!("Impact {} passes the impact filter", e.importance)
+            } else {
+                format!(
+                    "Impact {} is not in the impact filter ({})",
+                    e.importance,
+                    impact_filter.join(", ")
+                )
+            },
+        ),
+        forecast_change,
+        limit,
+    ]
+}
+
+/// Whether `events[idx]` (sorted by time, as loaded) makes it into Next Events or History, and
+/// if not, the first filter that drops it. Mirrors `render_next_events`/`render_past_events`
+/// applied to the category-filtered events, as `get_snapshot` does; impact is left to the
+/// caller since only the UI filters by it.
+pub fn event_visibility(
+    events: &[CalendarEvent],
+    idx: usize,
+    currency: &str,
+    category_filter: &[String],
+    min_forecast_change_pct: Option<f64>,
+    now: DateTime<Utc>,
+) -> EventVisibility {
+    let failed = visibility_checks(
+        events,
+        idx,
+        currency,
+        category_filter,
+        &[],
+        min_forecast_change_pct,
+        now,
+    )
+    .into_iter()
+    .find(|check| !check.passed);
+    match (failed, time_window(events[idx].dt_utc, now)) {
+        (Some(check), _) => check.reason,
+        (None, TimeWindow::Next) => EventVisibility::Next,
+        (None, _) => EventVisibility::History,
+    }
+}
+
+/// Which rows `render_next_events` keeps and how it formats them.
+pub struct NextEventsOptions<'a> {
+    pub currency: &'a str,
+    pub tz_mode: &'a str,
+    pub utc_offset_minutes: i32,
+    pub source_utc_offset_minutes: i32,
+    pub no_time_label: &'a str,
+    pub last_prints: &'a LastPrints,
+    pub min_forecast_change_pct: Option<f64>,
+}
+
+pub fn render_next_events(
+    events: &[CalendarEvent],
+    options: &NextEventsOptions,
 ) -> Vec<serde_json::Value> {
-    let now_utc = Utc::now();
-    let grace_window = Duration::minutes(3);
+    let NextEventsOptions {
+        currency,
+        tz_mode,
+        utc_offset_minutes,
+        source_utc_offset_minutes,
+        no_time_label,
+        last_prints,
+        min_forecast_change_pct,
+    } = *options;
+    let now_utc = now_utc();
+    let display_format = date_format();
     let selected = currency.trim().to_uppercase();
     if events.is_empty() {
         return vec![];
@@ -41,30 +504,17 @@ pub fn render_next_events(
 
     let mut visible: Vec<&CalendarEvent> = events
         .iter()
-        .filter(|e| e.dt_utc >= now_utc - grace_window)
+        .filter(|e| time_window(e.dt_utc, now_utc) == TimeWindow::Next)
         .collect();
 
-    visible.sort_by(|a, b| {
-        let a_current = a.dt_utc <= now_utc;
-        let b_current = b.dt_utc <= now_utc;
-        match (a_current, b_current) {
-            (true, false) => std::cmp::Ordering::Less,
-            (false, true) => std::cmp::Ordering::Greater,
-            _ => {
-                if a_current {
-                    b.dt_utc.cmp(&a.dt_utc)
-                } else {
-                    a.dt_utc.cmp(&b.dt_utc)
-                }
-            }
-        }
-    });
+    visible.sort_by(|a, b| next_events_order(a, b, now_utc));
 
-    let mut seen: std::collections::HashMap<String, i32> = std::collections::HashMap::new();
+    let mut seen: HashMap<String, i32> = HashMap::new();
     let mut rendered = vec![];
     for e in visible {
         let cur = e.currency.to_uppercase();
-        if selected != "ALL" && cur != selected {
+        if !currency_selected(&selected, e) || !forecast_change_selected(min_forecast_change_pct, e)
+        {
             continue;
         }
         let cur_display = if cur.is_empty() {
@@ -82,7 +532,7 @@ pub fn render_next_events(
         };
         let source_date_label = {
             let source = e.dt_utc + Duration::minutes(source_utc_offset_minutes as i64);
-            source.format("%d-%m-%Y").to_string()
+            source.format(display_format.date_pattern()).to_string()
         };
         let time_text = format_time_text(
             e.dt_utc,
@@ -90,141 +540,311 @@ pub fn render_next_events(
             Some(&source_date_label),
             tz_mode,
             utc_offset_minutes,
+            no_time_label,
+            display_format,
         );
-        let is_current = e.dt_utc <= now_utc && (now_utc - e.dt_utc) <= grace_window;
-        let raw_id = format!(
-            "{}|{}|{}|{}|{}",
-            e.dt_utc.to_rfc3339(),
-            cur,
-            e.time_label.trim(),
-            e.importance.trim(),
-            e.event.trim()
-        );
-        let digest = format!("{:x}", Sha1::digest(raw_id.as_bytes()));
-        let seq = seen.get(&digest).copied().unwrap_or(0) + 1;
-        seen.insert(digest.clone(), seq);
-        let id = if seq == 1 {
-            format!("evt-{digest}")
-        } else {
-            format!("evt-{digest}-{seq}")
-        };
+        let (state, countdown) =
+            countdown_state(e.dt_utc, now_utc).unwrap_or(("upcoming", String::new()));
+        let id = next_event_id(e, &mut seen);
+        let last = last_prints
+            .get(&(e.currency.clone(), e.event.clone()))
+            .filter(|last| last.dt_utc < e.dt_utc);
 
         rendered.push(json!({
             "id": id,
-            "state": if is_current { "current" } else { "upcoming" },
+            "state": state,
             "time": time_text,
             "cur": cur_display,
             "impact": impact_display,
             "event": e.event.clone(),
-            "countdown": if is_current { "Current".to_string() } else { format_countdown(e.dt_utc) },
+            "timeConfirmed": e.time_confirmed,
+            "categories": e.categories,
+            "source": e.source.as_str(),
+            "placeholder": e.source == EventSource::Placeholder,
+            "countdown": countdown,
+            "dstNote": dst_note(e.dt_utc, tz_mode, utc_offset_minutes),
+            "lastActual": last.map(|last| last.actual.clone()),
+            "lastForecast": last.map(|last| last.forecast.clone()),
+            "lastDate": last.map(|last| {
+                display_date(last.dt_utc, tz_mode, utc_offset_minutes)
+                    .format(display_format.date_pattern())
+                    .to_string()
+            }),
         }));
-        if rendered.len() >= 240 {
+        if rendered.len() >= NEXT_EVENTS_LIMIT {
             break;
         }
     }
     rendered
 }
 
+fn render_past_row(
+    e: &CalendarEvent,
+    tz_mode: &str,
+    utc_offset_minutes: i32,
+    source_utc_offset_minutes: i32,
+    no_time_label: &str,
+) -> serde_json::Value {
+    let display_format = date_format();
+    let cur = e.currency.to_uppercase();
+    let cur_display = if cur.is_empty() {
+        "--".to_string()
+    } else {
+        cur.clone()
+    };
+    let impact_display = {
+        let impact = e.importance.trim();
+        if impact.is_empty() {
+            "--".to_string()
+        } else {
+            impact.to_string()
+        }
+    };
+    let actual_display = {
+        let actual = e.actual.trim();
+        if actual.is_empty() {
+            "--".to_string()
+        } else {
+            actual.to_string()
+        }
+    };
+    let forecast_display = {
+        let forecast = e.forecast.trim();
+        if forecast.is_empty() {
+            "--".to_string()
+        } else {
+            forecast.to_string()
+        }
+    };
+    let previous_display = {
+        let previous = e.previous.trim();
+        if previous.is_empty() {
+            "--".to_string()
+        } else {
+            previous.to_string()
+        }
+    };
+    let source_date_label = {
+        let source = e.dt_utc + Duration::minutes(source_utc_offset_minutes as i64);
+        source.format(display_format.date_pattern()).to_string()
+    };
+    let time_text = format_time_text(
+        e.dt_utc,
+        &e.time_label,
+        Some(&source_date_label),
+        tz_mode,
+        utc_offset_minutes,
+        no_time_label,
+        display_format,
+    );
+
+    json!({
+        "time": time_text,
+        "cur": cur_display,
+        "impact": impact_display,
+        "event": e.event.clone(),
+        "actual": actual_display,
+        "forecast": forecast_display,
+        "previous": previous_display,
+        "timeConfirmed": e.time_confirmed,
+        "categories": e.categories,
+        "source": e.source.as_str(),
+        "dstNote": dst_note(e.dt_utc, tz_mode, utc_offset_minutes),
+    })
+}
+
+pub const DEFAULT_RECENT_HIGHLIGHT_HOURS: i64 = 6;
+
+/// `recent_highlight_hours`, clamped to a week; 0 turns the highlight off.
+pub fn recent_highlight_hours(cfg: &serde_json::Value) -> i64 {
+    crate::app_settings::AppSettings::from_config(cfg)
+        .recent_highlight_hours
+        .clamp(0, 168)
+}
+
+/// `event_name_max_len` in characters; 0 (the default) leaves names whole.
+pub fn event_name_max_len(cfg: &serde_json::Value) -> usize {
+    crate::app_settings::AppSettings::from_config(cfg)
+        .event_name_max_len
+        .clamp(0, 500) as usize
+}
+
+/// `text` cut to `max_chars` characters (not bytes) ending in an ellipsis, or `None` if it fits.
+fn truncate_chars(text: &str, max_chars: usize) -> Option<String> {
+    if max_chars == 0 || text.chars().count() <= max_chars {
+        return None;
+    }
+    let kept: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    Some(format!("{}…", kept.trim_end()))
+}
+
+/// Shortens each rendered row's `event` to `max_chars`, keeping the whole name in `eventFull`
+/// for the tooltip. Rows that fit are left as they are; 0 turns this off.
+pub fn limit_event_names(rows: &mut [serde_json::Value], max_chars: usize) {
+    for row in rows {
+        let Some(short) = row["event"]
+            .as_str()
+            .and_then(|name| truncate_chars(name, max_chars))
+        else {
+            continue;
+        };
+        row["eventFull"] = row["event"].take();
+        row["event"] = json!(short);
+    }
+}
+
+/// Released no more than `hours` before `now`, boundary included.
+fn is_recent(dt_utc: DateTime<Utc>, now: DateTime<Utc>, hours: i64) -> bool {
+    hours > 0 && dt_utc <= now && now - dt_utc <= Duration::hours(hours)
+}
+
+/// History rows, newest first. Rows released within `recent_hours` carry `recent: true`; the
+/// newest-first order already keeps them at the top.
 pub fn render_past_events(
     events: &[CalendarEvent],
     currency: &str,
     tz_mode: &str,
     utc_offset_minutes: i32,
     source_utc_offset_minutes: i32,
+    no_time_label: &str,
+    recent_hours: i64,
 ) -> Vec<serde_json::Value> {
-    let now_utc = Utc::now();
-    // Keep "current" items out of History until the same grace window used by Next Events passes.
-    let grace_window = Duration::minutes(3);
-    let cutoff = now_utc - Duration::days(31);
+    let now_utc = now_utc();
     let selected = currency.trim().to_uppercase();
     if events.is_empty() {
         return vec![];
     }
-    let max_items = if selected == "ALL" { 6000 } else { 300 };
+    let max_items = history_limit(&selected);
 
     let mut rendered = vec![];
     for e in events.iter().rev() {
-        if e.dt_utc >= now_utc || e.dt_utc < cutoff {
+        // Items still "current" in Next Events stay out of History until the grace window passes.
+        if time_window(e.dt_utc, now_utc) != TimeWindow::History {
             continue;
         }
-        // Exclude items still considered "Current" in Next Events.
-        if (now_utc - e.dt_utc) <= grace_window {
+        if !currency_selected(&selected, e) {
             continue;
         }
-        let cur = e.currency.to_uppercase();
-        if selected != "ALL" && cur != selected {
-            continue;
-        }
-        let cur_display = if cur.is_empty() {
-            "--".to_string()
-        } else {
-            cur.clone()
-        };
-        let impact_display = {
-            let impact = e.importance.trim();
-            if impact.is_empty() {
-                "--".to_string()
-            } else {
-                impact.to_string()
-            }
-        };
-        let actual_display = {
-            let actual = e.actual.trim();
-            if actual.is_empty() {
-                "--".to_string()
-            } else {
-                actual.to_string()
-            }
-        };
-        let forecast_display = {
-            let forecast = e.forecast.trim();
-            if forecast.is_empty() {
-                "--".to_string()
-            } else {
-                forecast.to_string()
-            }
-        };
-        let previous_display = {
-            let previous = e.previous.trim();
-            if previous.is_empty() {
-                "--".to_string()
-            } else {
-                previous.to_string()
-            }
-        };
-        let source_date_label = {
-            let source = e.dt_utc + Duration::minutes(source_utc_offset_minutes as i64);
-            source.format("%d-%m-%Y").to_string()
-        };
-        let time_text = format_time_text(
-            e.dt_utc,
-            &e.time_label,
-            Some(&source_date_label),
+        let mut row = render_past_row(
+            e,
             tz_mode,
             utc_offset_minutes,
+            source_utc_offset_minutes,
+            no_time_label,
         );
+        row["recent"] = json!(is_recent(e.dt_utc, now_utc, recent_hours));
+        rendered.push(row);
+        if rendered.len() >= max_items {
+            break;
+        }
+    }
+    rendered
+}
 
+/// Events still ahead (or within the "current" grace window) on today's date in the display
+/// timezone, soonest first, each with a countdown and `secondsUntil`.
+pub fn render_today_agenda(
+    events: &[CalendarEvent],
+    currency: &str,
+    tz_mode: &str,
+    utc_offset_minutes: i32,
+    source_utc_offset_minutes: i32,
+    no_time_label: &str,
+    now: DateTime<Utc>,
+) -> Vec<serde_json::Value> {
+    let display_format = date_format();
+    let selected = currency.trim().to_uppercase();
+    let today = display_date(now, tz_mode, utc_offset_minutes);
+    let start = events.partition_point(|e| time_window(e.dt_utc, now) != TimeWindow::Next);
+    let mut seen: HashMap<String, i32> = HashMap::new();
+    let mut rendered = vec![];
+    for e in &events[start..] {
+        if display_date(e.dt_utc, tz_mode, utc_offset_minutes) != today {
+            // Sorted input: once a later day starts there is nothing left for today.
+            if e.dt_utc > now {
+                break;
+            }
+            continue;
+        }
+        if !currency_selected(&selected, e) {
+            continue;
+        }
+        let cur = e.currency.to_uppercase();
+        let source_date_label = (e.dt_utc + Duration::minutes(source_utc_offset_minutes as i64))
+            .format(display_format.date_pattern())
+            .to_string();
+        let is_current = e.dt_utc <= now;
+        let impact = e.importance.trim();
         rendered.push(json!({
-            "time": time_text,
-            "cur": cur_display,
-            "impact": impact_display,
+            "id": next_event_id(e, &mut seen),
+            "state": if is_current { "current" } else { "upcoming" },
+            "time": format_time_text(
+                e.dt_utc,
+                &e.time_label,
+                Some(&source_date_label),
+                tz_mode,
+                utc_offset_minutes,
+                no_time_label,
+                display_format,
+            ),
+            "cur": if cur.is_empty() { "--".to_string() } else { cur },
+            "impact": if impact.is_empty() { "--" } else { impact },
             "event": e.event.clone(),
-            "actual": actual_display,
-            "forecast": forecast_display,
-            "previous": previous_display,
+            "countdown": if is_current { "Current".to_string() } else { format_countdown_from(e.dt_utc, now) },
+            "secondsUntil": (e.dt_utc - now).num_seconds().max(0),
+            "source": e.source.as_str(),
         }));
-        if rendered.len() >= max_items {
-            break;
-        }
     }
     rendered
 }
 
+/// Most recent event at or before `now` that has an actual, optionally for one currency.
+/// `events` must be sorted by `dt_utc`.
+pub fn last_released<'a>(
+    events: &'a [CalendarEvent],
+    currency: &str,
+    now: DateTime<Utc>,
+) -> Option<&'a CalendarEvent> {
+    let selected = currency.trim().to_uppercase();
+    let end = events.partition_point(|e| e.dt_utc <= now);
+    events[..end].iter().rev().find(|e| {
+        !e.actual.trim().is_empty()
+            && (selected.is_empty() || selected == "ALL" || e.currency.to_uppercase() == selected)
+    })
+}
+
+/// History-style row for the latest release, plus `releasedAgo` and the numeric surprise.
+pub fn render_last_released(
+    e: &CalendarEvent,
+    tz_mode: &str,
+    utc_offset_minutes: i32,
+    source_utc_offset_minutes: i32,
+    no_time_label: &str,
+) -> serde_json::Value {
+    let mut row = render_past_row(
+        e,
+        tz_mode,
+        utc_offset_minutes,
+        source_utc_offset_minutes,
+        no_time_label,
+    );
+    let actual = parse_numeric(&e.actual);
+    let forecast = parse_numeric(&e.forecast);
+    let previous = parse_numeric(&e.previous);
+    row["releasedAt"] = json!(e.dt_utc.to_rfc3339());
+    row["releasedAgo"] = json!(format_elapsed(e.dt_utc));
+    row["actualValue"] = json!(actual);
+    row["forecastValue"] = json!(forecast);
+    row["previousValue"] = json!(previous);
+    row["surprise"] = json!(actual.zip(forecast).map(|(a, f)| a - f));
+    row
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::calendar::CalendarEvent;
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
 
     fn make_event(dt_utc: DateTime<Utc>) -> CalendarEvent {
         CalendarEvent {
@@ -236,9 +856,56 @@ mod tests {
             actual: "1".to_string(),
             forecast: "1".to_string(),
             previous: "1".to_string(),
+            time_confirmed: true,
+            categories: vec![],
+            source: Default::default(),
+        }
+    }
+
+    /// UTC display, no offsets, no forecast filter.
+    fn test_next_options<'a>(
+        currency: &'a str,
+        last_prints: &'a LastPrints,
+    ) -> NextEventsOptions<'a> {
+        NextEventsOptions {
+            currency,
+            tz_mode: "utc",
+            utc_offset_minutes: 0,
+            source_utc_offset_minutes: 0,
+            no_time_label: "",
+            last_prints,
+            min_forecast_change_pct: None,
         }
     }
 
+    #[test]
+    fn recent_history_rows_are_tagged() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 6, 18, 0, 0).unwrap();
+        assert!(is_recent(now - Duration::hours(6), now, 6));
+        assert!(!is_recent(
+            now - Duration::hours(6) - Duration::seconds(1),
+            now,
+            6
+        ));
+        assert!(!is_recent(now - Duration::minutes(10), now, 0));
+        assert!(!is_recent(now + Duration::minutes(10), now, 6));
+
+        let now = Utc::now();
+        let events = vec![
+            make_event(now - Duration::hours(30)),
+            make_event(now - Duration::hours(7)),
+            make_event(now - Duration::hours(2)),
+        ];
+        let rendered = render_past_events(&events, "USD", "utc", 0, 0, "", 6);
+        let recent: Vec<bool> = rendered.iter().map(|r| r["recent"] == true).collect();
+        assert_eq!(recent, vec![true, false, false]);
+        assert_eq!(recent_highlight_hours(&json!({})), 6);
+        assert_eq!(
+            recent_highlight_hours(&json!({"recent_highlight_hours": 1000})),
+            168
+        );
+    }
+
     #[test]
     fn past_events_excludes_current_grace_window() {
         let now = Utc::now();
@@ -246,7 +913,7 @@ mod tests {
         let past = make_event(now - Duration::minutes(10));
 
         let events = vec![past.clone(), current_like.clone()];
-        let rendered = render_past_events(&events, "USD", "utc", 0, 0);
+        let rendered = render_past_events(&events, "USD", "utc", 0, 0, "All Day", 6);
 
         // Only the older item should appear.
         assert_eq!(rendered.len(), 1);
@@ -256,4 +923,375 @@ mod tests {
         );
         assert_eq!(rendered[0].get("cur").and_then(|v| v.as_str()), Some("USD"));
     }
+
+    #[test]
+    fn countdowns_match_the_full_render() {
+        let now = Utc::now();
+        // Half a minute past the mark so both reads land in the same countdown minute.
+        let slack = Duration::seconds(30);
+        let duplicate = make_event(now + Duration::hours(2) + Duration::minutes(30) + slack);
+        let events = vec![
+            make_event(now - Duration::minutes(10)),
+            make_event(now - Duration::minutes(1)),
+            duplicate.clone(),
+            duplicate,
+            make_event(now + Duration::days(3) + Duration::hours(5) + slack),
+        ];
+        let index = build_event_index(&events);
+        let last_prints = LastPrints::default();
+        let rendered = render_next_events(
+            &events,
+            &NextEventsOptions {
+                no_time_label: "All Day",
+                ..test_next_options("ALL", &last_prints)
+            },
+        );
+        let polled = countdowns(&events, &index, None, now);
+
+        assert_eq!(rendered.len(), 4);
+        assert_eq!(polled.len(), 4);
+        for row in &rendered {
+            let id = row["id"].as_str().unwrap();
+            assert_eq!(polled[id]["countdown"], row["countdown"], "{id}");
+            assert_eq!(polled[id]["state"], row["state"], "{id}");
+        }
+        assert_eq!(rendered[0]["state"], "current");
+
+        let past_id = index
+            .iter()
+            .find(|(_, idx)| **idx == 0)
+            .map(|(id, _)| id.clone())
+            .unwrap();
+        let asked = vec![past_id.clone(), "evt-unknown".to_string()];
+        let polled = countdowns(&events, &index, Some(&asked), now);
+        assert_eq!(polled.len(), 1);
+        assert_eq!(polled[&past_id]["state"], "past");
+    }
+
+    #[test]
+    fn visibility_reports_the_first_failing_filter() {
+        let now = Utc::now();
+        let mut eur = make_event(now + Duration::hours(1));
+        eur.currency = "EUR".to_string();
+        let mut tagged = make_event(now + Duration::hours(2));
+        tagged.categories = vec!["Inflation".to_string()];
+        let events = vec![
+            make_event(now - Duration::days(40)),
+            make_event(now - Duration::days(2)),
+            make_event(now - Duration::days(1)),
+            make_event(now - Duration::minutes(1)),
+            eur,
+            tagged,
+        ];
+        let filter = vec!["Inflation".to_string()];
+        let reasons: Vec<EventVisibility> = (0..events.len())
+            .map(|idx| event_visibility(&events, idx, "USD", &[], None, now))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                EventVisibility::PastCutoff,
+                EventVisibility::History,
+                EventVisibility::History,
+                EventVisibility::Next,
+                EventVisibility::Currency,
+                EventVisibility::Next,
+            ]
+        );
+        assert_eq!(
+            event_visibility(&events, 3, "USD", &filter, None, now),
+            EventVisibility::Category
+        );
+        assert_eq!(
+            event_visibility(&events, 5, "USD", &filter, None, now),
+            EventVisibility::Next
+        );
+        assert_eq!(
+            event_visibility(&events, 4, "ALL", &[], None, now),
+            EventVisibility::Next
+        );
+
+        // Only the newest 300 rows for a single currency reach History.
+        let many: Vec<CalendarEvent> = (0..301)
+            .map(|i| make_event(now - Duration::days(2) + Duration::minutes(i)))
+            .collect();
+        assert_eq!(
+            event_visibility(&many, 0, "USD", &[], None, now),
+            EventVisibility::HistoryLimit
+        );
+        assert_eq!(
+            event_visibility(&many, 1, "USD", &[], None, now),
+            EventVisibility::History
+        );
+        assert_eq!(
+            render_past_events(&many, "USD", "utc", 0, 0, "", 6).len(),
+            300
+        );
+    }
+
+    #[test]
+    fn visibility_checks_name_every_failing_filter() {
+        let now = Utc::now();
+        let failed =
+            |events: &[CalendarEvent], idx, cur, categories: &[String], impact: &[String]| {
+                visibility_checks(events, idx, cur, categories, impact, None, now)
+                    .into_iter()
+                    .filter(|check| !check.passed)
+                    .map(|check| check.reason)
+                    .collect::<Vec<_>>()
+            };
+        let mut old_eur = make_event(now - Duration::days(40));
+        old_eur.currency = "EUR".to_string();
+        old_eur.importance = "Low".to_string();
+        let events = vec![old_eur, make_event(now + Duration::hours(1))];
+        assert_eq!(
+            failed(
+                &events,
+                0,
+                "USD",
+                &["Inflation".to_string()],
+                &["high".to_string()]
+            ),
+            vec![
+                EventVisibility::PastCutoff,
+                EventVisibility::Currency,
+                EventVisibility::Category,
+                EventVisibility::Importance,
+            ]
+        );
+        assert!(failed(&events, 1, "USD", &[], &["high".to_string()]).is_empty());
+        let checks = visibility_checks(&events, 0, "USD", &[], &[], None, now);
+        assert_eq!(checks[1].detail, "EUR is not the selected currency (USD)");
+        assert_eq!(checks[2].detail, "No category filter is set");
+
+        // Next Events shows 240 rows: rows still "current" first, then the soonest upcoming.
+        let mut many: Vec<CalendarEvent> = (0..240)
+            .map(|i| make_event(now + Duration::minutes(i + 1)))
+            .collect();
+        many.insert(0, make_event(now - Duration::minutes(1)));
+        assert_eq!(failed(&many, 0, "USD", &[], &[]), vec![]);
+        assert_eq!(failed(&many, 239, "USD", &[], &[]), vec![]);
+        assert_eq!(
+            failed(&many, 240, "USD", &[], &[]),
+            vec![EventVisibility::NextLimit]
+        );
+        assert_eq!(
+            event_visibility(&many, 240, "USD", &[], None, now),
+            EventVisibility::NextLimit
+        );
+        let last_prints = LastPrints::default();
+        let rendered = render_next_events(&many, &test_next_options("USD", &last_prints));
+        assert_eq!(rendered.len(), NEXT_EVENTS_LIMIT);
+    }
+
+    #[test]
+    fn status_line_fills_placeholders() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 6, 12, 10, 0).unwrap();
+        let mut e = make_event(now + Duration::minutes(80));
+        e.event = "NFP".to_string();
+        assert_eq!(
+            render_status_line(STATUS_LINE_FORMAT, &e, now),
+            "USD NFP 1h20m [High]"
+        );
+        e.importance.clear();
+        assert_eq!(
+            render_status_line("{event}|{impact}|{unknown}", &e, now),
+            "NFP|--|{unknown}"
+        );
+    }
+
+    #[test]
+    fn date_only_rows_use_no_time_label() {
+        let dt = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let text = |label: &str| {
+            format_time_text(
+                dt,
+                "All Day",
+                Some("01-01-2026"),
+                "utc",
+                0,
+                label,
+                DateFormat::Dmy,
+            )
+        };
+        assert_eq!(text("All Day"), "01-01-2026 All Day");
+        assert_eq!(text("TBA"), "01-01-2026 TBA");
+        assert_eq!(text(""), "01-01-2026");
+        assert_eq!(
+            format_time_text(dt, "01:30", None, "utc", 0, "TBA", DateFormat::Dmy),
+            format_display_time(dt, "utc", 0, DateFormat::Dmy)
+        );
+    }
+
+    #[test]
+    fn time_text_follows_date_format() {
+        let dt = Utc.with_ymd_and_hms(2026, 3, 7, 13, 30, 0).unwrap();
+        let cases = [
+            (DateFormat::Dmy, "07-03-2026 13:30", "07-03-2026 Tentative"),
+            (DateFormat::Mdy, "03-07-2026 13:30", "03-07-2026 Tentative"),
+            (DateFormat::Iso, "2026-03-07 13:30", "2026-03-07 Tentative"),
+        ];
+        for (format, timed, labelled) in cases {
+            assert_eq!(
+                format_time_text(dt, "13:30", None, "utc", 0, "", format),
+                timed
+            );
+            assert_eq!(
+                format_time_text(dt, "Tentative", None, "utc", 0, "", format),
+                labelled
+            );
+            assert_eq!(
+                format_time_text(dt, "All Day", None, "utc", 0, "", format),
+                dt.format(format.date_pattern()).to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn last_released_picks_latest_with_actual() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 6, 14, 0, 0).unwrap();
+        let mut eur = make_event(now - Duration::hours(3));
+        eur.currency = "EUR".to_string();
+        let usd = make_event(now - Duration::hours(2));
+        let mut pending = make_event(now - Duration::minutes(30));
+        pending.actual = String::new();
+        let future = make_event(now + Duration::hours(1));
+        let events = vec![eur, usd, pending, future];
+
+        let latest = last_released(&events, "ALL", now).expect("latest release");
+        assert_eq!(latest.dt_utc, now - Duration::hours(2));
+        let eur_latest = last_released(&events, "eur", now).expect("eur release");
+        assert_eq!(eur_latest.currency, "EUR");
+        assert!(last_released(&events, "JPY", now).is_none());
+        assert!(last_released(&events, "ALL", now - Duration::hours(4)).is_none());
+    }
+
+    #[test]
+    fn today_agenda_keeps_remaining_events_for_the_display_day() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 6, 14, 0, 0).unwrap();
+        let at = |h: u32, m: u32| Utc.with_ymd_and_hms(2026, 3, 6, h, m, 0).unwrap();
+        let mut eur = make_event(at(15, 0));
+        eur.currency = "EUR".to_string();
+        let events = vec![
+            make_event(at(13, 30)),
+            make_event(at(13, 58)),
+            make_event(at(14, 30)),
+            eur,
+            make_event(Utc.with_ymd_and_hms(2026, 3, 7, 0, 30, 0).unwrap()),
+        ];
+
+        let rows = render_today_agenda(&events, "ALL", "utc", 0, 0, "", now);
+        let seconds: Vec<i64> = rows
+            .iter()
+            .map(|r| r["secondsUntil"].as_i64().unwrap())
+            .collect();
+        assert_eq!(seconds, vec![0, 1800, 3600]);
+        assert_eq!(rows[0]["countdown"], "Current");
+        assert_eq!(rows[1]["countdown"], "0h 30m");
+
+        let usd = render_today_agenda(&events, "USD", "utc", 0, 0, "", now);
+        assert_eq!(usd.len(), 2);
+
+        // At UTC+10 the display day has already rolled to 7 March: the 13:58 UTC row belongs
+        // to the previous day and the early-morning UTC row joins today.
+        let east = render_today_agenda(&events, "ALL", "system", 600, 0, "", now);
+        assert_eq!(east.len(), 3);
+    }
+
+    #[test]
+    fn long_event_names_are_cut_on_char_boundaries() {
+        let mut long = make_event(Utc::now() + Duration::hours(2));
+        long.event = "Índice de Preços ao Consumidor Amplo — 15 (IPCA-15) y/y".to_string();
+        let mut short = make_event(Utc::now() + Duration::hours(3));
+        short.event = "CPI m/m".to_string();
+        let mut past = long.clone();
+        past.dt_utc = Utc::now() - Duration::hours(2);
+        let events = vec![past, long.clone(), short];
+
+        let last_prints = LastPrints::default();
+        let mut next = render_next_events(&events, &test_next_options("USD", &last_prints));
+        let mut history = render_past_events(&events, "USD", "utc", 0, 0, "", 6);
+        limit_event_names(&mut next, 20);
+        limit_event_names(&mut history, 20);
+        for row in [&next[0], &history[0]] {
+            assert_eq!(row["event"], "Índice de Preços ao…");
+            assert_eq!(row["event"].as_str().unwrap().chars().count(), 20);
+            assert_eq!(row["eventFull"], long.event.as_str());
+        }
+        assert_eq!(next[1]["event"], "CPI m/m");
+        assert!(next[1].get("eventFull").is_none());
+        // Trailing whitespace before the cut is dropped, and 0 leaves names whole.
+        assert_eq!(truncate_chars("ab cd", 4).as_deref(), Some("ab…"));
+        assert_eq!(truncate_chars(&long.event, 0), None);
+        assert_eq!(event_name_max_len(&json!({"event_name_max_len": -5})), 0);
+    }
+
+    fn with_values(forecast: &str, previous: &str) -> CalendarEvent {
+        let mut e = make_event(Utc::now() + Duration::hours(3));
+        e.forecast = forecast.to_string();
+        e.previous = previous.to_string();
+        e
+    }
+
+    #[test]
+    fn forecast_change_is_relative_to_previous() {
+        let pct = |f: &str, p: &str| forecast_change_pct(&with_values(f, p));
+        assert_eq!(pct("110", "100"), Some(10.0));
+        assert_eq!(pct("90", "100"), Some(10.0));
+        // Negative previous values use their magnitude.
+        assert_eq!(pct("-0.3%", "-0.2%").map(|v| v.round()), Some(50.0));
+        assert_eq!(pct("1.5M", "1.2M").map(|v| v.round()), Some(25.0));
+        assert_eq!(pct("5", "0"), None);
+
+        let cfg = json!({"min_forecast_change_pct": 10});
+        let min = min_forecast_change_pct(&cfg);
+        assert_eq!(min, Some(10.0));
+        assert!(!forecast_change_selected(min, &with_values("110", "100")));
+        assert!(forecast_change_selected(min, &with_values("111", "100")));
+        assert_eq!(
+            min_forecast_change_pct(&json!({"min_forecast_change_pct": 0})),
+            None
+        );
+        assert!(forecast_change_selected(None, &with_values("100", "100")));
+    }
+
+    #[test]
+    fn forecast_change_filter_keeps_rows_it_cannot_evaluate() {
+        let mut events = vec![
+            with_values("200", "100"),
+            with_values("101", "100"),
+            with_values("", "100"),
+            with_values("1.2%", "--"),
+            with_values("n/a", "3"),
+        ];
+        for (i, e) in events.iter_mut().enumerate() {
+            e.event = format!("Event {i}");
+        }
+        let last_prints = LastPrints::default();
+        let render = |min| {
+            let options = NextEventsOptions {
+                min_forecast_change_pct: min,
+                ..test_next_options("USD", &last_prints)
+            };
+            render_next_events(&events, &options)
+                .iter()
+                .map(|row| row["event"].as_str().unwrap_or_default().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(render(None).len(), 5);
+        assert_eq!(
+            render(Some(5.0)),
+            vec!["Event 0", "Event 2", "Event 3", "Event 4"]
+        );
+
+        let now = Utc::now();
+        assert_eq!(
+            event_visibility(&events, 1, "USD", &[], Some(5.0), now),
+            EventVisibility::ForecastChange
+        );
+        assert_eq!(
+            event_visibility(&events, 3, "USD", &[], Some(5.0), now),
+            EventVisibility::Next
+        );
+    }
 }
@@ -1,5 +1,13 @@
+use crate::boot::{BootTimeline, RunOnce};
 use crate::calendar::CalendarEvent;
-use serde_json::Value;
+use crate::log_file::LogFilter;
+use crate::modal::Modal;
+use crate::notifications::AlertScheduler;
+use crate::single_flight::SingleFlight;
+use crate::snapshot::{EventIndex, LastPrints};
+use crate::tray_alert::TrayAlertState;
+use serde_json::{json, Value};
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
 #[derive(Default)]
@@ -7,14 +15,36 @@ pub struct CalendarCache {
     pub status: String,
     pub last_loaded_at_ms: i64,
     pub events: Arc<Vec<CalendarEvent>>,
+    pub last_prints: Arc<LastPrints>,
+    /// Row id to position in `events`, for id-based lookups.
+    pub event_index: Arc<EventIndex>,
+    pub warnings: Vec<String>,
+    /// SHA1 over the loaded events plus the history index `generated_at`.
+    pub fingerprint: String,
+    pub history_generated_at: String,
 }
 
 #[derive(Default)]
 pub struct RuntimeState {
     pub logs: Vec<Value>,
+    /// `log_min_level`/`debug` from config; refreshed when settings change.
+    pub log_filter: LogFilter,
+    /// `log_buffer_entries` from config; 0 (unset) keeps `DEFAULT_LOG_BUFFER_ENTRIES`.
+    pub log_buffer_entries: usize,
+    /// Stamped on every log entry; see `log_file::new_session_id`.
+    pub session_id: String,
     pub currency: String,
     pub pull_active: bool,
+    /// Set by `cancel_pull`; each pull gets a fresh flag.
+    pub pull_cancel: Arc<AtomicBool>,
+    /// The pull is mirroring into the working data and can no longer be cancelled.
+    pub pull_applying: bool,
     pub sync_active: bool,
+    pub sync_cancel: Arc<AtomicBool>,
+    /// A sync paused before a large delete pass; see `sync::approve_deletions`.
+    pub sync_deletion_prompt: Option<Arc<crate::sync_util::DeletionPrompt>>,
+    /// `reset_working_data` is running; the snapshot reports `calendarStatus: "resetting"`.
+    pub resetting: bool,
     pub boot_logged: bool,
     pub auto_pull_started: bool,
     pub auto_update_check_started: bool,
@@ -22,13 +52,93 @@ pub struct RuntimeState {
     pub github_token_last_seen: String,
     pub last_pull: String,
     pub last_pull_at: String,
+    /// Message from the most recent failed pull; cleared when a pull succeeds.
+    pub last_pull_error: String,
     pub last_sync: String,
     pub last_sync_at: String,
+    /// Message from the most recent failed sync; cleared when a sync succeeds.
+    pub last_sync_error: String,
+    /// When the hourly pull timer fires next; reported in `status.json`.
+    pub next_pull_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// One-time pull queued by `schedule_pull`; cleared when it fires or is cancelled.
+    pub scheduled_pull_at: Option<chrono::DateTime<chrono::Utc>>,
     pub update_state: Value,
     pub update_release_url: String,
     pub update_asset_url: String,
+    pub update_asset_name: String,
+    pub update_download_path: String,
+    /// `version.txt` asset of the latest release, when it ships one.
+    pub update_version_url: String,
+    /// Version the downloaded installer should leave behind; see `expected_install_version`.
+    pub update_expected_version: String,
+    pub update_prompted_version: String,
+    /// Latest release details with their ETag; see `update::CachedRelease`.
+    pub release_cache: Option<crate::commands::update::CachedRelease>,
+    /// Rate-limit headers of the latest GitHub releases response.
+    pub github_rate_limit: Option<crate::commands::update::RateLimit>,
+    /// `X-OAuth-Scopes` of the latest GitHub response; `None` when it carried none.
+    pub github_token_scopes: Option<Vec<String>>,
+    /// When `check_updates` last started; drives the catch-up check after a resume.
+    pub update_checked_at: Option<chrono::DateTime<chrono::Utc>>,
     pub output_dir: String,
     pub repo_path: String,
-    pub modal: Value,
+    pub modal: Option<Modal>,
+    /// Recent modals, newest first; see `RuntimeState::set_modal`.
+    pub modal_history: Vec<Value>,
     pub calendar: CalendarCache,
+    pub verify_active: bool,
+    pub verify_cancel: Arc<AtomicBool>,
+    pub verify_result: Value,
+    /// A `convert_to_portable`/`convert_to_installed` copy is running.
+    pub conversion_active: bool,
+    pub alerts: AlertScheduler,
+    /// Set by `mute_notifications_for`; alerts, update announcements and the tray alert icon
+    /// stay quiet until then.
+    pub notifications_muted_until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Row a notification click asked to show; the next snapshot reports it once.
+    pub pending_focus_event_id: Option<String>,
+    pub tray_supported: bool,
+    /// `config::install_dir_writable`, probed at startup.
+    pub install_dir_writable: bool,
+    pub tray_alert: TrayAlertState,
+    /// Debug override for `time_util::now_utc`, mirrored here for the snapshot.
+    pub test_clock: Option<chrono::DateTime<chrono::Utc>>,
+    pub boot: BootTimeline,
+    /// Startup work deferred until after the first paint; see `lifecycle::run_deferred_startup`.
+    pub deferred_startup: Arc<RunOnce>,
+    /// `get_event_history` responses keyed by normalized event id.
+    pub history_flights: Arc<SingleFlight<Value, Value>>,
+}
+
+/// Entries kept in `RuntimeState::modal_history`.
+const MODAL_HISTORY_LIMIT: usize = 20;
+
+impl RuntimeState {
+    /// Replaces the current modal and records it in `modal_history` (newest first, last 20) so it
+    /// can be reviewed after dismissal.
+    pub fn set_modal(&mut self, modal: Modal) {
+        self.modal_history.insert(
+            0,
+            json!({
+                "id": modal.id,
+                "title": modal.title,
+                "message": modal.message,
+                "tone": modal.tone,
+                "shownAt": crate::time_util::now_iso_time(),
+            }),
+        );
+        self.modal_history.truncate(MODAL_HISTORY_LIMIT);
+        self.modal = Some(modal);
+    }
+
+    pub fn modal_id(&self) -> Option<&str> {
+        self.modal.as_ref().map(|modal| modal.id.as_str())
+    }
+
+    /// Clears the modal if `id` is the one showing.
+    pub fn clear_modal(&mut self, id: &str) {
+        if self.modal_id() == Some(id) {
+            self.modal = None;
+        }
+    }
 }
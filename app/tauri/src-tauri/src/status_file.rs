@@ -0,0 +1,248 @@
+use crate::calendar::{CalendarEvent, EventSource};
+use crate::config;
+use crate::state::RuntimeState;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How often the background loop rewrites the file between pull/sync completions.
+pub const STATUS_FILE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Write failures are logged at most this often; a dead network share would otherwise log
+/// every five minutes.
+pub const STATUS_FAILURE_LOG_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Serializes writers so the pull/sync hooks and the loop never share a temp file.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+static LAST_FAILURE_LOG: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// `status_file_path` when set, else `status.json` in the appdata dir.
+pub fn status_file_path(cfg: &Value) -> PathBuf {
+    let custom = config::get_str(cfg, "status_file_path");
+    if custom.trim().is_empty() {
+        config::appdata_dir().join("status.json")
+    } else {
+        PathBuf::from(custom.trim())
+    }
+}
+
+/// The runtime fields `status.json` reports, cloned under the runtime lock. The events are an
+/// `Arc`, so counting them happens after the lock is released.
+pub struct StatusInputs {
+    pub last_pull_at: String,
+    pub last_pull_error: String,
+    pub last_sync_at: String,
+    pub last_sync_error: String,
+    pub pull_active: bool,
+    pub sync_active: bool,
+    pub next_pull_at: Option<DateTime<Utc>>,
+    pub events: Arc<Vec<CalendarEvent>>,
+}
+
+impl StatusInputs {
+    pub fn capture(runtime: &RuntimeState) -> Self {
+        Self {
+            last_pull_at: runtime.last_pull_at.clone(),
+            last_pull_error: runtime.last_pull_error.clone(),
+            last_sync_at: runtime.last_sync_at.clone(),
+            last_sync_error: runtime.last_sync_error.clone(),
+            pull_active: runtime.pull_active,
+            sync_active: runtime.sync_active,
+            next_pull_at: runtime.next_pull_at,
+            events: runtime.calendar.events.clone(),
+        }
+    }
+}
+
+/// Contents of `status.json`, read by external monitors. Fields are only ever added.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusFile {
+    pub app_version: String,
+    pub heartbeat_at: String,
+    pub last_pull_at: Option<String>,
+    /// `None` until a pull has finished this session or a previous one was recorded.
+    pub last_pull_ok: Option<bool>,
+    pub last_pull_error: Option<String>,
+    pub last_sync_at: Option<String>,
+    pub last_sync_ok: Option<bool>,
+    pub last_sync_error: Option<String>,
+    pub pull_active: bool,
+    pub sync_active: bool,
+    pub next_pull_at: Option<String>,
+    /// Date (UTC) of the latest calendar event loaded.
+    pub calendar_coverage_max_date: Option<String>,
+    pub event_count: usize,
+    pub upcoming_event_count: usize,
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    (!value.trim().is_empty()).then(|| value.to_string())
+}
+
+/// `Some(false)` after a failure, `Some(true)` once a run succeeded, `None` before either.
+fn run_ok(at: &str, error: &str) -> Option<bool> {
+    if !error.is_empty() {
+        Some(false)
+    } else {
+        non_empty(at).map(|_| true)
+    }
+}
+
+fn iso(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+impl StatusFile {
+    pub fn build(inputs: &StatusInputs, now: DateTime<Utc>) -> Self {
+        // Placeholder rows stand in for missing data and would hide an empty calendar.
+        let real = || {
+            inputs
+                .events
+                .iter()
+                .filter(|e| e.source != EventSource::Placeholder)
+        };
+        Self {
+            app_version: env!("APP_VERSION").to_string(),
+            heartbeat_at: iso(now),
+            last_pull_at: non_empty(&inputs.last_pull_at),
+            last_pull_ok: run_ok(&inputs.last_pull_at, &inputs.last_pull_error),
+            last_pull_error: non_empty(&inputs.last_pull_error),
+            last_sync_at: non_empty(&inputs.last_sync_at),
+            last_sync_ok: run_ok(&inputs.last_sync_at, &inputs.last_sync_error),
+            last_sync_error: non_empty(&inputs.last_sync_error),
+            pull_active: inputs.pull_active,
+            sync_active: inputs.sync_active,
+            next_pull_at: inputs.next_pull_at.map(iso),
+            calendar_coverage_max_date: real()
+                .map(|e| e.dt_utc)
+                .max()
+                .map(|at| at.format("%Y-%m-%d").to_string()),
+            event_count: real().count(),
+            upcoming_event_count: real().filter(|e| e.dt_utc >= now).count(),
+        }
+    }
+}
+
+/// Writes atomically (temp file + rename) so a monitor never reads a half-written file.
+pub fn write(path: &Path, status: &StatusFile) -> Result<(), String> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(status).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension(format!("tmp-{}", std::process::id()));
+    fs::write(&tmp, text).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        e.to_string()
+    })
+}
+
+fn failure_log_due(last: Option<Instant>, now: Instant) -> bool {
+    last.is_none_or(|at| now.duration_since(at) >= STATUS_FAILURE_LOG_INTERVAL)
+}
+
+/// Whether a write failure should be logged now; records the log when it should.
+pub fn take_failure_log_slot() -> bool {
+    let mut last = LAST_FAILURE_LOG.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    if !failure_log_due(*last, now) {
+        return false;
+    }
+    *last = Some(now);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(day: u32, source: EventSource) -> CalendarEvent {
+        CalendarEvent {
+            dt_utc: Utc.with_ymd_and_hms(2026, 3, day, 12, 30, 0).unwrap(),
+            time_label: "12:30".to_string(),
+            event: "CPI m/m".to_string(),
+            currency: "USD".to_string(),
+            importance: "high".to_string(),
+            actual: String::new(),
+            forecast: String::new(),
+            previous: String::new(),
+            time_confirmed: true,
+            categories: vec![],
+            source,
+        }
+    }
+
+    #[test]
+    fn file_matches_runtime_and_heartbeat_advances() {
+        let mut runtime = RuntimeState {
+            last_pull_at: "2026-03-05T08:00:00+00:00".to_string(),
+            last_sync_at: "2026-03-05T08:01:00+00:00".to_string(),
+            last_sync_error: "Output dir not configured".to_string(),
+            next_pull_at: Some(Utc.with_ymd_and_hms(2026, 3, 5, 9, 0, 0).unwrap()),
+            ..RuntimeState::default()
+        };
+        runtime.calendar.events = Arc::new(vec![
+            event(4, EventSource::Calendar),
+            event(6, EventSource::Calendar),
+            event(9, EventSource::Calendar),
+            event(20, EventSource::Placeholder),
+        ]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("status.json");
+        let inputs = StatusInputs::capture(&runtime);
+
+        let first_at = Utc.with_ymd_and_hms(2026, 3, 5, 8, 2, 0).unwrap();
+        write(&path, &StatusFile::build(&inputs, first_at)).unwrap();
+        let first: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(first["appVersion"], env!("APP_VERSION"));
+        assert_eq!(first["lastPullAt"], runtime.last_pull_at.as_str());
+        assert_eq!(first["lastPullOk"], true);
+        assert_eq!(first["lastPullError"], Value::Null);
+        assert_eq!(first["lastSyncAt"], runtime.last_sync_at.as_str());
+        assert_eq!(first["lastSyncOk"], false);
+        assert_eq!(first["lastSyncError"], "Output dir not configured");
+        assert_eq!(first["nextPullAt"], "2026-03-05T09:00:00+00:00");
+        assert_eq!(first["calendarCoverageMaxDate"], "2026-03-09");
+        assert_eq!(first["eventCount"], 3);
+        assert_eq!(first["upcomingEventCount"], 2);
+        assert_eq!(first["heartbeatAt"], "2026-03-05T08:02:00+00:00");
+
+        let second_at = first_at + chrono::Duration::minutes(5);
+        write(&path, &StatusFile::build(&inputs, second_at)).unwrap();
+        let second: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(second["heartbeatAt"].as_str() > first["heartbeatAt"].as_str());
+        assert_eq!(second["lastPullAt"], first["lastPullAt"]);
+        // Only the final file is left behind.
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn failures_log_at_most_hourly() {
+        let start = Instant::now();
+        assert!(failure_log_due(None, start));
+        assert!(!failure_log_due(
+            Some(start),
+            start + Duration::from_secs(59 * 60)
+        ));
+        assert!(failure_log_due(
+            Some(start),
+            start + STATUS_FAILURE_LOG_INTERVAL
+        ));
+    }
+
+    #[test]
+    fn never_run_reports_unknown_status() {
+        let status =
+            StatusFile::build(&StatusInputs::capture(&RuntimeState::default()), Utc::now());
+        assert_eq!(status.last_pull_ok, None);
+        assert_eq!(status.last_sync_ok, None);
+        assert_eq!(status.calendar_coverage_max_date, None);
+    }
+}
@@ -1,20 +1,86 @@
-use std::collections::HashMap;
+use crate::app_settings::AppSettings;
+use crate::categories::CATEGORY_OVERRIDE_FILE;
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha1::{Digest, Sha1};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
 
 #[derive(Default)]
 pub struct SyncResult {
     pub copied: i64,
     pub deleted: i64,
     pub skipped: i64,
+    /// Planned deletions left in place because they were not confirmed.
+    pub deletions_declined: i64,
+    /// Stopped early by the cancel token; the counts cover what was done before that.
+    pub cancelled: bool,
 }
 
-fn iter_files(root: &Path) -> HashMap<String, PathBuf> {
+impl SyncResult {
+    pub fn add(&mut self, other: SyncResult) {
+        self.copied += other.copied;
+        self.deleted += other.deleted;
+        self.skipped += other.skipped;
+        self.deletions_declined += other.deletions_declined;
+        self.cancelled |= other.cancelled;
+    }
+}
+
+/// Paths at least this long get the `\\?\` prefix on Windows: `CreateDirectory` already fails
+/// past 248 characters, a few short of `MAX_PATH` (260).
+#[cfg_attr(not(windows), allow(dead_code))]
+const LONG_PATH_THRESHOLD: usize = 248;
+
+/// Extended-length form of an absolute Windows path: `C:\x` becomes `\\?\C:\x` and
+/// `\\server\share` becomes `\\?\UNC\server\share`. Already-prefixed and relative paths are
+/// returned unchanged.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn long_path_form(raw: &str) -> String {
+    if raw.starts_with(r"\\?\") || raw.starts_with("//?/") {
+        return raw.to_string();
+    }
+    // Verbatim paths skip separator normalization, so only backslashes are allowed.
+    let path = raw.replace('/', "\\");
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        return format!(r"\\?\UNC\{unc}");
+    }
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\' {
+        return format!(r"\\?\{path}");
+    }
+    raw.to_string()
+}
+
+/// `path` in a form the fs calls accept past `MAX_PATH`: prefixed on Windows once it nears
+/// the limit, unchanged otherwise and on every other platform.
+pub fn to_long_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    if path.as_os_str().len() >= LONG_PATH_THRESHOLD {
+        if let Some(raw) = path.to_str() {
+            return PathBuf::from(long_path_form(raw));
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Files under `root` keyed by relative path, without lock sidecars.
+pub fn iter_files(root: &Path) -> HashMap<String, PathBuf> {
     let mut files = HashMap::new();
+    let root = &to_long_path(root);
     for entry in walkdir::WalkDir::new(root).into_iter().flatten() {
         if !entry.file_type().is_file() {
             continue;
         }
+        // Advisory lock sidecars (see `file_lock`) are local state, never mirrored or pruned.
+        if entry.path().extension().is_some_and(|ext| ext == "lock") {
+            continue;
+        }
         let rel = entry
             .path()
             .strip_prefix(root)
@@ -27,6 +93,7 @@ fn iter_files(root: &Path) -> HashMap<String, PathBuf> {
 }
 
 fn should_copy(src: &Path, dst: &Path) -> bool {
+    let dst = &to_long_path(dst);
     if !dst.exists() {
         return true;
     }
@@ -55,40 +122,404 @@ fn should_copy(src: &Path, dst: &Path) -> bool {
     src_mtime != dst_mtime
 }
 
-pub fn mirror_sync(src_dir: &Path, dst_dir: &Path) -> Result<SyncResult, String> {
+const MANAGED_MARKER: &str = ".xauusd_calendar_agent_managed_output";
+
+/// Written into the destination `data/` dir after each successful sync.
+pub const SYNC_MANIFEST_FILE: &str = ".sync_manifest.json";
+
+/// What a mirror would do, computed without touching the destination.
+#[derive(Default)]
+pub struct SyncPlan {
+    /// Relative paths to copy, with their source paths.
+    pub copy: Vec<(String, PathBuf)>,
+    /// Relative paths already identical at the destination.
+    pub unchanged: Vec<String>,
+    /// Destination files with no source counterpart.
+    pub delete: Vec<(String, PathBuf)>,
+    /// Destination files outside the include-set of a filtered mirror; never copied or deleted.
+    pub outside_scope: Vec<String>,
+    /// The mirror's destination and the files found there before it ran.
+    pub dst_dir: PathBuf,
+    pub destination_files: usize,
+}
+
+impl SyncPlan {
+    pub fn is_in_sync(&self) -> bool {
+        self.copy.is_empty() && self.delete.is_empty()
+    }
+}
+
+/// When a sync stops for confirmation before its delete pass: more than `max_files` planned
+/// deletions, or more than `max_pct` percent of the destination's files. 0 turns a limit off.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeleteThreshold {
+    pub max_files: usize,
+    pub max_pct: f64,
+}
+
+impl Default for DeleteThreshold {
+    fn default() -> Self {
+        Self {
+            max_files: 200,
+            max_pct: 30.0,
+        }
+    }
+}
+
+impl DeleteThreshold {
+    /// `sync_delete_confirm_threshold` and `sync_delete_confirm_pct`; negative values fall back
+    /// to the defaults.
+    pub fn from_config(cfg: &Value) -> Self {
+        let defaults = Self::default();
+        let settings = AppSettings::from_config(cfg);
+        let max_files = settings.sync_delete_confirm_threshold;
+        let max_pct = settings.sync_delete_confirm_pct;
+        Self {
+            max_files: usize::try_from(max_files).unwrap_or(defaults.max_files),
+            max_pct: if max_pct.is_finite() && max_pct >= 0.0 {
+                max_pct
+            } else {
+                defaults.max_pct
+            },
+        }
+    }
+
+    /// Asks before any deletion at all; used for the first sync after `sync_scope` changes.
+    pub fn every_deletion() -> Self {
+        Self {
+            max_files: 0,
+            max_pct: f64::MIN_POSITIVE,
+        }
+    }
+
+    pub fn exceeded(&self, planned: usize, destination_files: usize) -> bool {
+        let over_count = self.max_files > 0 && planned > self.max_files;
+        let over_share = self.max_pct > 0.0
+            && destination_files > 0
+            && planned as f64 * 100.0 / destination_files as f64 > self.max_pct;
+        over_count || over_share
+    }
+}
+
+/// How a pending deletion prompt ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeletionDecision {
+    Confirm,
+    Cancel,
+    /// Nobody answered in time; handled like `Cancel`.
+    TimedOut,
+}
+
+/// Planned deletions waiting for `confirm_sync_deletions`/`cancel_sync_deletions`. The sync
+/// worker blocks in `wait`; the first decision wins and later ones are refused.
+pub struct DeletionPrompt {
+    pub count: usize,
+    /// A few of the relative paths that would go.
+    pub examples: Vec<String>,
+    pub destination: PathBuf,
+    decision: Mutex<Option<DeletionDecision>>,
+    resolved: Condvar,
+}
+
+impl DeletionPrompt {
+    pub const EXAMPLES: usize = 5;
+
+    pub fn new(plan: &SyncPlan) -> Self {
+        Self {
+            count: plan.delete.len(),
+            examples: plan
+                .delete
+                .iter()
+                .take(Self::EXAMPLES)
+                .map(|(rel, _)| rel.clone())
+                .collect(),
+            destination: plan.dst_dir.clone(),
+            decision: Mutex::new(None),
+            resolved: Condvar::new(),
+        }
+    }
+
+    /// Records `decision` unless one was already made; returns whether it was recorded.
+    pub fn resolve(&self, decision: DeletionDecision) -> bool {
+        let mut current = self.decision.lock().unwrap_or_else(|e| e.into_inner());
+        if current.is_some() {
+            return false;
+        }
+        *current = Some(decision);
+        self.resolved.notify_all();
+        true
+    }
+
+    /// Blocks until a decision is made, or records `TimedOut` after `timeout`.
+    pub fn wait(&self, timeout: Duration) -> DeletionDecision {
+        let current = self.decision.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut current, _) = self
+            .resolved
+            .wait_timeout_while(current, timeout, |d| d.is_none())
+            .unwrap_or_else(|e| e.into_inner());
+        *current.get_or_insert(DeletionDecision::TimedOut)
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "count": self.count,
+            "examples": self.examples,
+            "destination": self.destination.to_string_lossy(),
+        })
+    }
+}
+
+pub fn plan_mirror(src_dir: &Path, dst_dir: &Path) -> Result<SyncPlan, String> {
+    plan_mirror_filtered(src_dir, dst_dir, None)
+}
+
+/// Whether `rel` is one of the `include` paths or lies under one. No include-set means all.
+fn in_scope(rel: &str, include: Option<&HashSet<String>>) -> bool {
+    include.is_none_or(|include| include.iter().any(|path| Path::new(rel).starts_with(path)))
+}
+
+/// The year dirs a "current" `sync_scope` mirrors: every calendar year from 31 days back to a
+/// year ahead of `now`.
+pub fn current_scope_years(now: DateTime<Utc>) -> HashSet<String> {
+    let first = (now - chrono::Duration::days(31)).year();
+    let last = (now + chrono::Duration::days(365)).year();
+    (first..=last).map(|year| year.to_string()).collect()
+}
+
+/// `plan_mirror` limited to `include` (relative paths, files or dirs): source files outside it
+/// are not copied, and destination files outside it are left alone rather than deleted.
+pub fn plan_mirror_filtered(
+    src_dir: &Path,
+    dst_dir: &Path,
+    include: Option<&HashSet<String>>,
+) -> Result<SyncPlan, String> {
     if !src_dir.exists() {
         return Err(format!("Source not found: {}", src_dir.display()));
     }
-    fs::create_dir_all(dst_dir).map_err(|e| e.to_string())?;
+    let mut src_files = iter_files(src_dir);
+    src_files.retain(|rel, _| in_scope(rel, include));
+    let dst_files = if dst_dir.exists() {
+        iter_files(dst_dir)
+    } else {
+        HashMap::new()
+    };
 
-    let src_files = iter_files(src_dir);
-    let dst_files = iter_files(dst_dir);
+    let mut plan = SyncPlan {
+        dst_dir: dst_dir.to_path_buf(),
+        destination_files: dst_files.len(),
+        ..SyncPlan::default()
+    };
+    for (rel, src_path) in src_files.iter() {
+        if should_copy(src_path, &dst_dir.join(rel)) {
+            plan.copy.push((rel.clone(), src_path.clone()));
+        } else {
+            plan.unchanged.push(rel.clone());
+        }
+    }
+    for (rel, dst_path) in dst_files.into_iter() {
+        let kept = [MANAGED_MARKER, CATEGORY_OVERRIDE_FILE, SYNC_MANIFEST_FILE];
+        if kept.contains(&rel.as_str()) || src_files.contains_key(&rel) {
+            continue;
+        }
+        if in_scope(&rel, include) {
+            plan.delete.push((rel, dst_path));
+        } else {
+            plan.outside_scope.push(rel);
+        }
+    }
+    plan.copy.sort();
+    plan.unchanged.sort();
+    plan.delete.sort();
+    plan.outside_scope.sort();
+    Ok(plan)
+}
 
-    let mut result = SyncResult::default();
+/// Sub-trees of the app data dir that `storage_usage` reports on their own.
+pub const STORAGE_CATEGORIES: [&str; 5] = [
+    "data/Economic_Calendar",
+    "data/event_history_index",
+    "logs",
+    "updates",
+    "backups",
+];
 
-    for (rel, src_path) in src_files.iter() {
-        let dst_path = dst_dir.join(rel);
+/// Bytes under `root` per `STORAGE_CATEGORIES` entry (missing ones count 0), then `other` for
+/// the rest, plus the total. Lock sidecars are skipped, as in every other walk.
+pub fn storage_usage(root: &Path) -> (Vec<(&'static str, u64)>, u64) {
+    let mut usage: Vec<(&'static str, u64)> = STORAGE_CATEGORIES
+        .iter()
+        .map(|category| (*category, 0))
+        .chain(std::iter::once(("other", 0)))
+        .collect();
+    let mut total = 0;
+    for (rel, path) in iter_files(root) {
+        let size = path.metadata().map(|meta| meta.len()).unwrap_or(0);
+        let rel = rel.replace('\\', "/");
+        let slot = STORAGE_CATEGORIES
+            .iter()
+            .position(|category| {
+                rel.strip_prefix(category)
+                    .is_some_and(|rest| rest.starts_with('/'))
+            })
+            .unwrap_or(STORAGE_CATEGORIES.len());
+        usage[slot].1 += size;
+        total += size;
+    }
+    (usage, total)
+}
+
+/// Headroom left on the destination volume beyond the bytes a sync plans to copy.
+pub const FREE_SPACE_MARGIN_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Total size of the source files a plan would copy.
+pub fn planned_copy_bytes(plan: &SyncPlan) -> u64 {
+    plan.copy
+        .iter()
+        .filter_map(|(_, src)| src.metadata().ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Space available to this user on the volume holding `path`. A destination that does not
+/// exist yet is measured at its nearest existing ancestor.
+pub fn available_space(path: &Path) -> Result<u64, String> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| format!("No existing directory above {}", path.display()))?;
+    fs4::available_space(existing).map_err(|e| e.to_string())
+}
+
+/// Plans every `(src, dst)` mirror and fails, before anything is written, when the copies plus
+/// `FREE_SPACE_MARGIN_BYTES` would not fit on the volume holding `volume`.
+pub fn ensure_free_space(
+    pairs: &[(&Path, &Path)],
+    volume: &Path,
+    available: impl Fn(&Path) -> Result<u64, String>,
+) -> Result<(), String> {
+    let mut required = 0;
+    for (src, dst) in pairs {
+        required += planned_copy_bytes(&plan_mirror(src, dst)?);
+    }
+    if required == 0 {
+        return Ok(());
+    }
+    let available = available(volume)?;
+    if required + FREE_SPACE_MARGIN_BYTES > available {
+        return Err(format!(
+            "Not enough free space on destination: {required} bytes to copy (+{FREE_SPACE_MARGIN_BYTES} margin), {available} available"
+        ));
+    }
+    Ok(())
+}
+
+/// Fails when `available` bytes are under the `floor_mb` floor configured by
+/// `min_free_space_mb`. `label` names the location in the message.
+pub fn check_free_space_floor(label: &str, available: u64, floor_mb: u64) -> Result<(), String> {
+    let floor = floor_mb.saturating_mul(1024 * 1024);
+    if available < floor {
+        return Err(format!(
+            "Not enough free space for the {label}: {} MB available, at least {floor_mb} MB required",
+            available / (1024 * 1024)
+        ));
+    }
+    Ok(())
+}
+
+/// Measures the volume holding `path` and applies `check_free_space_floor`.
+pub fn ensure_min_free_space(label: &str, path: &Path, floor_mb: u64) -> Result<(), String> {
+    check_free_space_floor(label, available_space(path)?, floor_mb)
+}
+
+/// Error returned by an atomic `mirror_sync_filtered` when `cancel` is raised while staging.
+pub const SYNC_CANCELLED: &str = "Sync cancelled";
+
+/// Mirrors `src_dir` into `dst_dir`. `cancel` is checked before each file operation; once it is
+/// raised the partial result comes back with `cancelled` set. Every file is copied whole, so the
+/// destination is incomplete but never holds a torn file.
+pub fn mirror_sync(
+    src_dir: &Path,
+    dst_dir: &Path,
+    cancel: &AtomicBool,
+) -> Result<SyncResult, String> {
+    mirror_sync_guarded(src_dir, dst_dir, cancel, &|_| true)
+}
+
+/// `mirror_sync` that asks `approve_deletions` before its delete pass, after the copies. When
+/// it declines, the files stay and are counted in `deletions_declined`.
+pub fn mirror_sync_guarded(
+    src_dir: &Path,
+    dst_dir: &Path,
+    cancel: &AtomicBool,
+    approve_deletions: &dyn Fn(&SyncPlan) -> bool,
+) -> Result<SyncResult, String> {
+    mirror_in_place(
+        plan_mirror(src_dir, dst_dir)?,
+        dst_dir,
+        cancel,
+        approve_deletions,
+    )
+}
+
+/// Mirrors only the `include` part of `src_dir` (see `plan_mirror_filtered`), in place or,
+/// with `atomic`, swapped in whole (see `mirror_atomic`). `None` mirrors everything.
+pub fn mirror_sync_filtered(
+    src_dir: &Path,
+    dst_dir: &Path,
+    include: Option<&HashSet<String>>,
+    atomic: bool,
+    cancel: &AtomicBool,
+    approve_deletions: &dyn Fn(&SyncPlan) -> bool,
+) -> Result<SyncResult, String> {
+    let plan = plan_mirror_filtered(src_dir, dst_dir, include)?;
+    if atomic {
+        mirror_atomic(plan, src_dir, dst_dir, include, cancel, approve_deletions)
+    } else {
+        mirror_in_place(plan, dst_dir, cancel, approve_deletions)
+    }
+}
+
+fn mirror_in_place(
+    plan: SyncPlan,
+    dst_dir: &Path,
+    cancel: &AtomicBool,
+    approve_deletions: &dyn Fn(&SyncPlan) -> bool,
+) -> Result<SyncResult, String> {
+    fs::create_dir_all(to_long_path(dst_dir)).map_err(|e| e.to_string())?;
+
+    let mut result = SyncResult {
+        skipped: plan.unchanged.len() as i64,
+        ..SyncResult::default()
+    };
+
+    for (rel, src_path) in plan.copy.iter() {
+        if cancel.load(Ordering::SeqCst) {
+            result.cancelled = true;
+            return Ok(result);
+        }
+        let dst_path = to_long_path(&dst_dir.join(rel));
         if let Some(parent) = dst_path.parent() {
             let _ = fs::create_dir_all(parent);
         }
-        if should_copy(src_path, &dst_path) {
-            fs::copy(src_path, &dst_path).map_err(|e| e.to_string())?;
-            result.copied += 1;
-        } else {
-            result.skipped += 1;
-        }
+        fs::copy(to_long_path(src_path), &dst_path).map_err(|e| e.to_string())?;
+        result.copied += 1;
     }
 
-    for (rel, dst_path) in dst_files.iter() {
-        if rel != ".xauusd_calendar_agent_managed_output"
-            && !src_files.contains_key(rel)
-            && fs::remove_file(dst_path).is_ok()
-        {
+    if !plan.delete.is_empty() && !approve_deletions(&plan) {
+        result.deletions_declined = plan.delete.len() as i64;
+        return Ok(result);
+    }
+    for (_, dst_path) in plan.delete.iter() {
+        if cancel.load(Ordering::SeqCst) {
+            result.cancelled = true;
+            return Ok(result);
+        }
+        if fs::remove_file(to_long_path(dst_path)).is_ok() {
             result.deleted += 1;
         }
     }
 
-    for entry in walkdir::WalkDir::new(dst_dir)
+    for entry in walkdir::WalkDir::new(to_long_path(dst_dir))
         .contents_first(true)
         .into_iter()
         .flatten()
@@ -101,3 +532,647 @@ pub fn mirror_sync(src_dir: &Path, dst_dir: &Path) -> Result<SyncResult, String>
 
     Ok(result)
 }
+
+fn sibling_with_suffix(dir: &Path, suffix: &str) -> PathBuf {
+    let mut name = dir.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    dir.with_file_name(name)
+}
+
+/// Builds the full new tree in `staging`: unchanged and out-of-scope files are hard-linked (or
+/// copied) from the current destination, changed ones copied from the source.
+fn build_staging(
+    plan: &SyncPlan,
+    dst_dir: &Path,
+    staging: &Path,
+    cancel: &AtomicBool,
+) -> Result<(), String> {
+    fs::create_dir_all(to_long_path(staging)).map_err(|e| e.to_string())?;
+    let kept = plan
+        .unchanged
+        .iter()
+        .chain(&plan.outside_scope)
+        .map(String::as_str)
+        .chain(std::iter::once(MANAGED_MARKER))
+        .filter_map(|rel| {
+            Some((rel, to_long_path(&dst_dir.join(rel)))).filter(|(_, p)| p.is_file())
+        });
+    let staged = kept.map(|(rel, from)| (rel.to_string(), from, true)).chain(
+        plan.copy
+            .iter()
+            .map(|(rel, src)| (rel.clone(), src.clone(), false)),
+    );
+    for (rel, from, reuse) in staged {
+        if cancel.load(Ordering::SeqCst) {
+            return Err(SYNC_CANCELLED.to_string());
+        }
+        let to = to_long_path(&staging.join(&rel));
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        if reuse && fs::hard_link(&from, &to).is_ok() {
+            continue;
+        }
+        fs::copy(to_long_path(&from), &to).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Like `mirror_sync`, but readers of `dst_dir` never see a mix of old and new files: the new
+/// tree is assembled in a `.staging` sibling and swapped in with two renames. When the swap
+/// is not possible (e.g. the destination is held open), it falls back to the in-place mirror.
+/// `approve_deletions` is asked before staging; declined deletions are staged like unchanged
+/// files, so the swapped-in tree still holds them.
+fn mirror_atomic(
+    mut plan: SyncPlan,
+    src_dir: &Path,
+    dst_dir: &Path,
+    include: Option<&HashSet<String>>,
+    cancel: &AtomicBool,
+    approve_deletions: &dyn Fn(&SyncPlan) -> bool,
+) -> Result<SyncResult, String> {
+    let mut deletions_declined = 0;
+    if !plan.delete.is_empty() && !approve_deletions(&plan) {
+        deletions_declined = plan.delete.len() as i64;
+        let declined = std::mem::take(&mut plan.delete);
+        plan.unchanged
+            .extend(declined.into_iter().map(|(rel, _)| rel));
+    }
+    let result = SyncResult {
+        copied: plan.copy.len() as i64,
+        deleted: plan.delete.len() as i64,
+        skipped: plan.unchanged.len() as i64 - deletions_declined,
+        deletions_declined,
+        cancelled: false,
+    };
+    if plan.is_in_sync() && dst_dir.exists() {
+        return Ok(result);
+    }
+
+    // The in-place fallback must not undo a declined delete pass.
+    let keep_decision = |_: &SyncPlan| deletions_declined == 0;
+    let staging = sibling_with_suffix(dst_dir, ".staging");
+    let previous = sibling_with_suffix(dst_dir, ".previous");
+    let _ = fs::remove_dir_all(&staging);
+    let _ = fs::remove_dir_all(&previous);
+    if let Err(err) = build_staging(&plan, dst_dir, &staging, cancel) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(err);
+    }
+
+    let fallback = || {
+        let plan = plan_mirror_filtered(src_dir, dst_dir, include)?;
+        mirror_in_place(plan, dst_dir, cancel, &keep_decision)
+    };
+    if dst_dir.exists() && fs::rename(dst_dir, &previous).is_err() {
+        let _ = fs::remove_dir_all(&staging);
+        return fallback();
+    }
+    if fs::rename(&staging, dst_dir).is_err() {
+        let _ = fs::rename(&previous, dst_dir);
+        let _ = fs::remove_dir_all(&staging);
+        return fallback();
+    }
+    let _ = fs::remove_dir_all(&previous);
+    Ok(result)
+}
+
+/// One file listed in the sync manifest. `modified` (seconds since the epoch) lets the next
+/// sync carry the hash forward when the file was not touched.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub size: u64,
+    pub sha1: String,
+    #[serde(default)]
+    pub modified: u64,
+}
+
+/// `data/.sync_manifest.json`: every mirrored file with its size and SHA-1. It is written last,
+/// so readers can treat its presence and `completed_at` as a consistency barrier.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncManifest {
+    pub completed_at: String,
+    pub file_count: usize,
+    pub files: Vec<ManifestEntry>,
+}
+
+fn modified_secs(meta: &fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn sha1_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut hasher = Sha1::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Rewrites the manifest for everything under `data_dir`. Entries whose size and mtime match
+/// the previous manifest keep their hash; only new or changed files are read. Returns the
+/// manifest and how many files were hashed.
+pub fn write_sync_manifest(data_dir: &Path) -> Result<(SyncManifest, usize), String> {
+    let manifest_path = to_long_path(&data_dir.join(SYNC_MANIFEST_FILE));
+    let previous: HashMap<String, ManifestEntry> = fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|raw| serde_json::from_str::<SyncManifest>(&raw).ok())
+        .map(|manifest| {
+            manifest
+                .files
+                .into_iter()
+                .map(|entry| (entry.path.clone(), entry))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut files: Vec<(String, PathBuf)> = iter_files(data_dir)
+        .into_iter()
+        .map(|(rel, path)| (rel.replace('\\', "/"), path))
+        .filter(|(rel, _)| rel != SYNC_MANIFEST_FILE)
+        .collect();
+    files.sort();
+
+    let mut hashed = 0;
+    let mut entries = Vec::with_capacity(files.len());
+    for (rel, path) in files {
+        let meta = path
+            .metadata()
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+        let (size, modified) = (meta.len(), modified_secs(&meta));
+        let sha1 = match previous.get(&rel) {
+            Some(prior) if prior.size == size && prior.modified == modified => prior.sha1.clone(),
+            _ => {
+                hashed += 1;
+                sha1_file(&path)?
+            }
+        };
+        entries.push(ManifestEntry {
+            path: rel,
+            size,
+            sha1,
+            modified,
+        });
+    }
+
+    let manifest = SyncManifest {
+        completed_at: crate::time_util::now_iso_time(),
+        file_count: entries.len(),
+        files: entries,
+    };
+    let raw = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    let tmp = manifest_path.with_extension("json.tmp");
+    fs::write(&tmp, raw).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, &manifest_path).map_err(|e| e.to_string())?;
+    Ok((manifest, hashed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, len: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![b'x'; len]).unwrap();
+    }
+
+    #[test]
+    fn planned_bytes_count_only_files_to_copy() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        write(&src.path().join("2026/2026_calendar.json"), 300);
+        write(&src.path().join("2025/2025_calendar.json"), 200);
+        let plan = plan_mirror(src.path(), dst.path()).unwrap();
+        assert_eq!(planned_copy_bytes(&plan), 500);
+
+        mirror_sync(src.path(), dst.path(), &AtomicBool::new(false)).unwrap();
+        write(&src.path().join("2026/2026_calendar.json"), 350);
+        let plan = plan_mirror(src.path(), dst.path()).unwrap();
+        assert_eq!(planned_copy_bytes(&plan), 350);
+    }
+
+    #[test]
+    fn storage_usage_splits_by_category() {
+        let root = tempfile::tempdir().unwrap();
+        write(
+            &root
+                .path()
+                .join("data/Economic_Calendar/2026/2026_calendar.json"),
+            300,
+        );
+        write(
+            &root.path().join("data/event_history_index/history.ndjson"),
+            200,
+        );
+        write(
+            &root
+                .path()
+                .join("data/event_history_index/history.ndjson.lock"),
+            9,
+        );
+        write(&root.path().join("logs/app.log"), 50);
+        write(&root.path().join("logs_old/app.log"), 7);
+        write(&root.path().join("config.json"), 10);
+
+        let (usage, total) = storage_usage(root.path());
+        assert_eq!(
+            usage,
+            vec![
+                ("data/Economic_Calendar", 300),
+                ("data/event_history_index", 200),
+                ("logs", 50),
+                ("updates", 0),
+                ("backups", 0),
+                ("other", 17),
+            ]
+        );
+        assert_eq!(total, 567);
+    }
+
+    #[test]
+    fn available_space_walks_up_to_existing_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("not").join("yet");
+        assert!(available_space(&missing).unwrap() > 0);
+    }
+
+    #[test]
+    fn insufficient_space_aborts_without_touching_destination() {
+        let src = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        write(&src.path().join("2026/2026_calendar.json"), 1024);
+        let dst = out.path().join("data").join("Economic_Calendar");
+        write(&dst.join("stale.json"), 10);
+
+        let tight = |_: &Path| Ok(FREE_SPACE_MARGIN_BYTES + 1000);
+        let err = ensure_free_space(&[(src.path(), &dst)], out.path(), tight).unwrap_err();
+        assert!(err.contains("1024 bytes to copy"), "{err}");
+        assert!(err.contains(&format!("{} available", FREE_SPACE_MARGIN_BYTES + 1000)));
+        assert!(dst.join("stale.json").exists());
+        assert!(!dst.join("2026").exists());
+
+        let roomy = |_: &Path| Ok(FREE_SPACE_MARGIN_BYTES + 1024);
+        assert!(ensure_free_space(&[(src.path(), &dst)], out.path(), roomy).is_ok());
+    }
+
+    #[test]
+    fn atomic_sync_swaps_in_the_new_tree() {
+        let src = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let dst = out.path().join("Economic_Calendar");
+        write(&src.path().join("2026/2026_calendar.json"), 10);
+        write(&dst.join("2026/2026_calendar.json"), 5);
+        write(&dst.join("2019/2019_calendar.json"), 5);
+        write(&dst.join(MANAGED_MARKER), 1);
+
+        let result = mirror_sync_filtered(
+            src.path(),
+            &dst,
+            None,
+            true,
+            &AtomicBool::new(false),
+            &|_| true,
+        )
+        .unwrap();
+        assert_eq!((result.copied, result.deleted), (1, 1));
+        assert_eq!(
+            fs::metadata(dst.join("2026/2026_calendar.json"))
+                .unwrap()
+                .len(),
+            10
+        );
+        assert!(!dst.join("2019/2019_calendar.json").exists());
+        assert!(dst.join(MANAGED_MARKER).exists());
+        assert!(!out.path().join("Economic_Calendar.staging").exists());
+        assert!(!out.path().join("Economic_Calendar.previous").exists());
+    }
+
+    #[test]
+    fn cancelled_atomic_sync_leaves_destination_untouched() {
+        let src = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let dst = out.path().join("Economic_Calendar");
+        write(&src.path().join("2026/2026_calendar.json"), 10);
+        write(&dst.join("2025/2025_calendar.json"), 5);
+
+        let result = mirror_sync_filtered(
+            src.path(),
+            &dst,
+            None,
+            true,
+            &AtomicBool::new(true),
+            &|_| true,
+        );
+        assert_eq!(result.err().as_deref(), Some(SYNC_CANCELLED));
+        assert!(dst.join("2025/2025_calendar.json").exists());
+        assert!(!dst.join("2026").exists());
+        assert!(!out.path().join("Economic_Calendar.staging").exists());
+    }
+
+    #[test]
+    fn manifest_matches_the_tree_and_rehashes_only_changes() {
+        let src = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let data = out.path().join("data");
+        write(&src.path().join("2026/2026_calendar.json"), 10);
+        write(&src.path().join("2025/2025_calendar.json"), 20);
+        let cal = data.join("Economic_Calendar");
+        mirror_sync(src.path(), &cal, &AtomicBool::new(false)).unwrap();
+
+        let (manifest, hashed) = write_sync_manifest(&data).unwrap();
+        assert_eq!(hashed, 2);
+        assert_eq!(manifest.file_count, 2);
+        let entry = &manifest.files[1];
+        assert_eq!(entry.path, "Economic_Calendar/2026/2026_calendar.json");
+        assert_eq!(entry.size, 10);
+        assert_eq!(entry.sha1, format!("{:x}", Sha1::digest([b'x'; 10])));
+        let on_disk: SyncManifest =
+            serde_json::from_str(&fs::read_to_string(data.join(SYNC_MANIFEST_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(on_disk.files, manifest.files);
+        assert!(!on_disk.completed_at.is_empty());
+
+        write(&src.path().join("2026/2026_calendar.json"), 15);
+        fs::remove_file(src.path().join("2025/2025_calendar.json")).unwrap();
+        write(&src.path().join("2027/2027_calendar.json"), 5);
+        let result = mirror_sync(src.path(), &cal, &AtomicBool::new(false)).unwrap();
+        assert_eq!(result.deleted, 1);
+        let (manifest, hashed) = write_sync_manifest(&data).unwrap();
+        assert_eq!(hashed, 2);
+        let paths: Vec<&str> = manifest.files.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "Economic_Calendar/2026/2026_calendar.json",
+                "Economic_Calendar/2027/2027_calendar.json",
+            ]
+        );
+        assert_eq!(manifest.files[0].size, 15);
+
+        // Nothing changed: every entry is carried forward without reading the file.
+        let (again, hashed) = write_sync_manifest(&data).unwrap();
+        assert_eq!(hashed, 0);
+        assert_eq!(again.files, manifest.files);
+
+        // The manifest survives a mirror of the whole data dir.
+        let whole = tempfile::tempdir().unwrap();
+        write(
+            &whole
+                .path()
+                .join("Economic_Calendar/2026/2026_calendar.json"),
+            15,
+        );
+        mirror_sync(whole.path(), &data, &AtomicBool::new(false)).unwrap();
+        assert!(data.join(SYNC_MANIFEST_FILE).exists());
+    }
+
+    #[test]
+    fn long_path_form_prefixes_drive_and_unc_paths() {
+        assert_eq!(
+            long_path_form(r"C:\Users\me\MT5\MQL5\Files"),
+            r"\\?\C:\Users\me\MT5\MQL5\Files"
+        );
+        assert_eq!(long_path_form("d:/MT5/Files"), r"\\?\d:\MT5\Files");
+        assert_eq!(
+            long_path_form(r"\\nas\share\MT5\Files"),
+            r"\\?\UNC\nas\share\MT5\Files"
+        );
+        assert_eq!(
+            long_path_form(r"\\?\C:\already\long"),
+            r"\\?\C:\already\long"
+        );
+        assert_eq!(long_path_form(r"\\?\UNC\nas\share"), r"\\?\UNC\nas\share");
+        assert_eq!(long_path_form(r"relative\dir"), r"relative\dir");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn to_long_path_passes_through_off_windows() {
+        let long = Path::new("/tmp").join("x".repeat(300));
+        assert_eq!(to_long_path(&long), long);
+    }
+
+    #[test]
+    fn mirrors_trees_deeper_than_max_path() {
+        let src = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let nested: PathBuf = (0..8).map(|i| format!("{i}_{}", "d".repeat(40))).collect();
+        let file = to_long_path(&src.path().join(&nested).join("2026_calendar.json"));
+        // Hosts whose filesystem refuses the tree cannot run this check.
+        if fs::create_dir_all(file.parent().unwrap()).is_err() || fs::write(&file, b"[]").is_err() {
+            return;
+        }
+        let dst = out.path().join("Economic_Calendar");
+        assert!(dst.join(&nested).as_os_str().len() > 260);
+
+        let result = mirror_sync(src.path(), &dst, &AtomicBool::new(false)).unwrap();
+        assert_eq!(result.copied, 1);
+        let copied = to_long_path(&dst.join(&nested).join("2026_calendar.json"));
+        assert_eq!(fs::read(copied).unwrap(), b"[]");
+        assert!(plan_mirror(src.path(), &dst).unwrap().is_in_sync());
+    }
+
+    #[test]
+    fn cancelled_sync_returns_partial_result_promptly() {
+        let src = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let dst = out.path().join("Economic_Calendar");
+        let total = 4000;
+        for i in 0..total {
+            write(&src.path().join(format!("{i:05}.json")), 4096);
+        }
+
+        // Raise the token as soon as the first file (in plan order) lands.
+        let cancel = std::sync::Arc::new(AtomicBool::new(false));
+        let first = dst.join("00000.json");
+        let watcher = {
+            let cancel = cancel.clone();
+            std::thread::spawn(move || {
+                while !first.exists() {
+                    std::thread::yield_now();
+                }
+                cancel.store(true, Ordering::SeqCst);
+                std::time::Instant::now()
+            })
+        };
+        let result = mirror_sync(src.path(), &dst, &cancel).unwrap();
+        let returned = std::time::Instant::now();
+        let cancelled_at = watcher.join().unwrap();
+
+        assert!(result.cancelled);
+        assert!(
+            result.copied >= 1 && result.copied < total,
+            "{}",
+            result.copied
+        );
+        assert!(returned.duration_since(cancelled_at) < std::time::Duration::from_secs(1));
+        // Whatever was copied is complete; the rest is picked up by the next sync.
+        let copied: Vec<_> = fs::read_dir(&dst).unwrap().flatten().collect();
+        assert_eq!(copied.len() as i64, result.copied);
+        assert!(copied
+            .iter()
+            .all(|entry| entry.metadata().unwrap().len() == 4096));
+        let plan = plan_mirror(src.path(), &dst).unwrap();
+        assert!(plan.copy.len() as i64 >= total - result.copied);
+    }
+
+    #[test]
+    fn free_space_floor_threshold() {
+        let mb = 1024 * 1024;
+        assert!(check_free_space_floor("pull", 200 * mb, 200).is_ok());
+        assert!(check_free_space_floor("pull", 0, 0).is_ok());
+        let err = check_free_space_floor("update download", 200 * mb - 1, 200)
+            .err()
+            .unwrap_or_default();
+        assert_eq!(
+            err,
+            "Not enough free space for the update download: 199 MB available, at least 200 MB required"
+        );
+    }
+
+    #[test]
+    fn delete_threshold_counts_files_and_share() {
+        let t = DeleteThreshold::default();
+        assert!(!t.exceeded(200, 10_000));
+        assert!(t.exceeded(201, 10_000));
+        assert!(!t.exceeded(30, 100));
+        assert!(t.exceeded(31, 100));
+        assert!(!t.exceeded(0, 0));
+
+        let off = DeleteThreshold::from_config(&json!({
+            "sync_delete_confirm_threshold": 0,
+            "sync_delete_confirm_pct": 0
+        }));
+        assert!(!off.exceeded(4_000, 4_000));
+        assert_eq!(
+            DeleteThreshold::from_config(&json!({"sync_delete_confirm_threshold": -5})),
+            DeleteThreshold::default()
+        );
+    }
+
+    #[test]
+    fn deletion_prompt_takes_the_first_answer() {
+        let plan = SyncPlan {
+            delete: (0..8)
+                .map(|i| (format!("{i}.json"), PathBuf::from(format!("/out/{i}.json"))))
+                .collect(),
+            dst_dir: PathBuf::from("/out"),
+            destination_files: 10,
+            ..SyncPlan::default()
+        };
+        for answer in [DeletionDecision::Confirm, DeletionDecision::Cancel] {
+            let prompt = std::sync::Arc::new(DeletionPrompt::new(&plan));
+            assert_eq!(prompt.count, 8);
+            assert_eq!(prompt.examples.len(), DeletionPrompt::EXAMPLES);
+            let answering = prompt.clone();
+            let handle = std::thread::spawn(move || answering.resolve(answer));
+            assert_eq!(prompt.wait(Duration::from_secs(10)), answer);
+            assert!(handle.join().unwrap());
+            assert!(!prompt.resolve(DeletionDecision::Confirm));
+        }
+
+        let unanswered = DeletionPrompt::new(&plan);
+        assert_eq!(
+            unanswered.wait(Duration::from_millis(10)),
+            DeletionDecision::TimedOut
+        );
+        // A late answer cannot turn the timeout into a confirmation.
+        assert!(!unanswered.resolve(DeletionDecision::Confirm));
+    }
+
+    #[test]
+    fn declined_deletions_keep_files_and_copies() {
+        for atomic in [false, true] {
+            let src = tempfile::tempdir().unwrap();
+            let out = tempfile::tempdir().unwrap();
+            let dst = out.path().join("Economic_Calendar");
+            write(&src.path().join("2026/2026_calendar.json"), 10);
+            write(&dst.join("2019/2019_calendar.json"), 5);
+
+            let asked = AtomicBool::new(false);
+            let decline = |plan: &SyncPlan| {
+                asked.store(true, Ordering::SeqCst);
+                assert_eq!(plan.delete.len(), 1);
+                false
+            };
+            let result = mirror_sync_filtered(
+                src.path(),
+                &dst,
+                None,
+                atomic,
+                &AtomicBool::new(false),
+                &decline,
+            )
+            .unwrap();
+            assert!(asked.load(Ordering::SeqCst));
+            assert_eq!(
+                (result.copied, result.deleted, result.deletions_declined),
+                (1, 0, 1)
+            );
+            assert!(dst.join("2026/2026_calendar.json").exists());
+            assert!(dst.join("2019/2019_calendar.json").exists());
+        }
+    }
+
+    #[test]
+    fn current_scope_spans_the_year_boundary() {
+        let years = |y, m, d| {
+            let now = chrono::TimeZone::with_ymd_and_hms(&Utc, y, m, d, 12, 0, 0).unwrap();
+            let mut years: Vec<String> = current_scope_years(now).into_iter().collect();
+            years.sort();
+            years
+        };
+        // Within 31 days of New Year the previous year is still covered.
+        assert_eq!(years(2027, 1, 20), vec!["2026", "2027", "2028"]);
+        assert_eq!(years(2027, 2, 15), vec!["2027", "2028"]);
+        assert_eq!(years(2026, 12, 31), vec!["2026", "2027"]);
+    }
+
+    #[test]
+    fn filtered_sync_leaves_files_outside_the_scope() {
+        for atomic in [false, true] {
+            let src = tempfile::tempdir().unwrap();
+            let out = tempfile::tempdir().unwrap();
+            let dst = out.path().join("Economic_Calendar");
+            for year in [2019, 2026, 2027] {
+                write(&src.path().join(format!("{year}/{year}_calendar.json")), 10);
+            }
+            // A full mirror from before the switch, plus a stale file inside the scope.
+            write(&dst.join("2015/2015_calendar.json"), 5);
+            write(&dst.join("2019/2019_calendar.json"), 5);
+            write(&dst.join("2026/2026_old.json"), 5);
+
+            let include: HashSet<String> = ["2026", "2027"].map(String::from).into();
+            let asked = AtomicBool::new(false);
+            let approve = |plan: &SyncPlan| {
+                asked.store(true, Ordering::SeqCst);
+                assert_eq!(plan.delete.len(), 1);
+                true
+            };
+            let result = mirror_sync_filtered(
+                src.path(),
+                &dst,
+                Some(&include),
+                atomic,
+                &AtomicBool::new(false),
+                &approve,
+            )
+            .unwrap();
+            assert!(asked.load(Ordering::SeqCst));
+            assert_eq!((result.copied, result.deleted), (2, 1), "atomic={atomic}");
+            assert!(dst.join("2027/2027_calendar.json").exists());
+            assert!(!dst.join("2026/2026_old.json").exists());
+            // Out-of-scope files are neither refreshed nor removed.
+            assert!(dst.join("2015/2015_calendar.json").exists());
+            assert_eq!(
+                fs::metadata(dst.join("2019/2019_calendar.json"))
+                    .unwrap()
+                    .len(),
+                5
+            );
+        }
+    }
+}
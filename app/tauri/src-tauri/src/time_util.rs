@@ -1,7 +1,91 @@
-use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use chrono::{DateTime, Duration, FixedOffset, Local, Offset, TimeZone, Utc};
+use std::sync::RwLock;
+
+/// Fixed instant set through the debug-only `set_test_clock` command.
+static TEST_CLOCK: RwLock<Option<DateTime<Utc>>> = RwLock::new(None);
+
+/// Current time for rendering and countdowns: `Utc::now()` unless a test clock is pinned.
+pub fn now_utc() -> DateTime<Utc> {
+    TEST_CLOCK
+        .read()
+        .ok()
+        .and_then(|clock| *clock)
+        .unwrap_or_else(Utc::now)
+}
+
+pub fn set_test_clock(at: Option<DateTime<Utc>>) {
+    if let Ok(mut clock) = TEST_CLOCK.write() {
+        *clock = at;
+    }
+}
+
+/// Date order in display strings (`date_format`). Stored timestamps (config, history
+/// `generated_at`, ISO fields) keep their own fixed formats.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DateFormat {
+    #[default]
+    Dmy,
+    Mdy,
+    Iso,
+}
+
+impl DateFormat {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "dmy" => Some(Self::Dmy),
+            "mdy" => Some(Self::Mdy),
+            "iso" => Some(Self::Iso),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dmy => "dmy",
+            Self::Mdy => "mdy",
+            Self::Iso => "iso",
+        }
+    }
+
+    pub fn from_config(cfg: &serde_json::Value) -> Self {
+        Self::parse(&crate::config::get_str(cfg, "date_format")).unwrap_or_default()
+    }
+
+    /// The one mapping from setting to `strftime` pattern; every display date goes through it.
+    pub fn date_pattern(self) -> &'static str {
+        match self {
+            Self::Dmy => "%d-%m-%Y",
+            Self::Mdy => "%m-%d-%Y",
+            Self::Iso => "%Y-%m-%d",
+        }
+    }
+
+    pub fn date_time_pattern(self) -> &'static str {
+        match self {
+            Self::Dmy => "%d-%m-%Y %H:%M",
+            Self::Mdy => "%m-%d-%Y %H:%M",
+            Self::Iso => "%Y-%m-%d %H:%M",
+        }
+    }
+}
+
+/// Active `date_format`, refreshed from config at startup and whenever settings change.
+static DATE_FORMAT: RwLock<DateFormat> = RwLock::new(DateFormat::Dmy);
+
+pub fn set_date_format(format: DateFormat) {
+    if let Ok(mut current) = DATE_FORMAT.write() {
+        *current = format;
+    }
+}
+
+pub fn date_format() -> DateFormat {
+    DATE_FORMAT.read().map(|f| *f).unwrap_or_default()
+}
 
 pub fn now_display_time() -> String {
-    Local::now().format("%d-%m-%Y %H:%M").to_string()
+    Local::now()
+        .format(date_format().date_time_pattern())
+        .to_string()
 }
 
 pub fn now_iso_time() -> String {
@@ -16,30 +100,35 @@ pub fn display_time_from_iso(iso: &str) -> Option<String> {
     let dt = DateTime::parse_from_rfc3339(iso).ok()?;
     Some(
         dt.with_timezone(&Local)
-            .format("%d-%m-%Y %H:%M")
+            .format(date_format().date_time_pattern())
             .to_string(),
     )
 }
 
-pub fn format_display_time(dt: DateTime<Utc>, mode: &str, utc_offset_minutes: i32) -> String {
+/// Renders a UTC instant for display. Converting from UTC is never ambiguous, so DST
+/// transitions only shift the local wall-clock reading. A non-zero `utc_offset_minutes`
+/// pins a fixed offset that ignores DST entirely.
+pub fn format_display_time(
+    dt: DateTime<Utc>,
+    mode: &str,
+    utc_offset_minutes: i32,
+    format: DateFormat,
+) -> String {
+    let pattern = format.date_time_pattern();
     if mode == "utc" {
-        return dt.format("%d-%m-%Y %H:%M").to_string();
+        return dt.format(pattern).to_string();
     }
     if utc_offset_minutes != 0 {
         let offset = FixedOffset::east_opt(utc_offset_minutes * 60)
             .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
-        return dt
-            .with_timezone(&offset)
-            .format("%d-%m-%Y %H:%M")
-            .to_string();
+        return dt.with_timezone(&offset).format(pattern).to_string();
     }
-    dt.with_timezone(&Local)
-        .format("%d-%m-%Y %H:%M")
-        .to_string()
+    dt.with_timezone(&Local).format(pattern).to_string()
 }
 
-pub fn format_countdown(target_utc: DateTime<Utc>) -> String {
-    let delta = target_utc - Utc::now();
+/// Time left until `target_utc` as seen at `now`: `2d 5h`, `3h 20m`, or `Now` once reached.
+pub fn format_countdown_from(target_utc: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let delta = target_utc - now;
     if delta.num_seconds() <= 0 {
         return "Now".to_string();
     }
@@ -54,15 +143,109 @@ pub fn format_countdown(target_utc: DateTime<Utc>) -> String {
     format!("{hours}h {mins}m")
 }
 
+/// Calendar date of `dt` in the display timezone chosen by `mode`/`utc_offset_minutes`
+/// (see `format_display_time`).
+pub fn display_date(dt: DateTime<Utc>, mode: &str, utc_offset_minutes: i32) -> chrono::NaiveDate {
+    if mode == "utc" {
+        return dt.date_naive();
+    }
+    if utc_offset_minutes != 0 {
+        let offset = FixedOffset::east_opt(utc_offset_minutes * 60)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+        return dt.with_timezone(&offset).date_naive();
+    }
+    dt.with_timezone(&Local).date_naive()
+}
+
+/// Start of the display day after `now`'s, with the day boundary `display_date` uses. Mutes
+/// "for the rest of the day" end here.
+pub fn end_of_display_day(
+    now: DateTime<Utc>,
+    mode: &str,
+    utc_offset_minutes: i32,
+) -> DateTime<Utc> {
+    let Some(midnight) = display_date(now, mode, utc_offset_minutes)
+        .succ_opt()
+        .map(|day| day.and_time(chrono::NaiveTime::MIN))
+    else {
+        return now + Duration::days(1);
+    };
+    if mode == "utc" {
+        return midnight.and_utc();
+    }
+    if utc_offset_minutes != 0 {
+        return (midnight - Duration::minutes(utc_offset_minutes as i64)).and_utc();
+    }
+    // A DST jump can skip local midnight; the day then starts an hour later.
+    [midnight, midnight + Duration::hours(1)]
+        .iter()
+        .find_map(|at| Local.from_local_datetime(at).earliest())
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or(now + Duration::days(1))
+}
+
+/// Elapsed time since `past_utc` in the countdown's units, e.g. `2h 5m ago`.
+pub fn format_elapsed(past_utc: DateTime<Utc>) -> String {
+    let minutes = (now_utc() - past_utc).num_minutes();
+    if minutes < 1 {
+        return "Just now".to_string();
+    }
+    let hours = minutes / 60;
+    let mins = minutes % 60;
+    let days = hours / 24;
+    let hours = hours % 24;
+    if days > 0 {
+        return format!("{days}d {hours}h ago");
+    }
+    format!("{hours}h {mins}m ago")
+}
+
+/// `base` moved by up to `spread` (a fraction, e.g. 0.1 for ±10%) in either direction.
+/// `unit` is a uniform sample in `[0, 1)`; 0.5 returns `base` unchanged.
+pub fn jittered(base: std::time::Duration, spread: f64, unit: f64) -> std::time::Duration {
+    let factor = 1.0 + spread * (unit.clamp(0.0, 1.0) * 2.0 - 1.0);
+    base.mul_f64(factor.max(0.0))
+}
+
+/// `jittered` with a fresh random sample, for background schedules.
+pub fn random_jitter(base: std::time::Duration, spread: f64) -> std::time::Duration {
+    jittered(base, spread, fastrand::f64())
+}
+
+/// strftime layout of calendar row dates unless `source_date_format` overrides it.
+pub const DEFAULT_SOURCE_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Whether `format` is a usable strftime date layout.
+pub fn valid_date_format(format: &str) -> bool {
+    !format.trim().is_empty()
+        && chrono::format::StrftimeItems::new(format)
+            .all(|item| !matches!(item, chrono::format::Item::Error))
+}
+
+/// `source_date_format` from config; blank or invalid values fall back to the default.
+pub fn source_date_format(cfg: &serde_json::Value) -> String {
+    let format = crate::config::get_str(cfg, "source_date_format");
+    if valid_date_format(&format) {
+        format
+    } else {
+        DEFAULT_SOURCE_DATE_FORMAT.to_string()
+    }
+}
+
+/// A calendar row's time of day; the hour may have one digit ("9:30").
+pub fn parse_hhmm(time: &str) -> Option<chrono::NaiveTime> {
+    chrono::NaiveTime::parse_from_str(time, "%H:%M").ok()
+}
+
 pub fn parse_source_dt_to_utc(
-    date_iso: &str,
+    date_raw: &str,
     time_hhmm: &str,
+    date_format: &str,
     source_utc_offset_minutes: i32,
 ) -> Option<DateTime<Utc>> {
-    let date = chrono::NaiveDate::parse_from_str(date_iso, "%Y-%m-%d").ok()?;
+    let date = chrono::NaiveDate::parse_from_str(date_raw.trim(), date_format).ok()?;
     let time = if time_hhmm.contains(':') {
-        chrono::NaiveTime::parse_from_str(time_hhmm, "%H:%M")
-            .unwrap_or_else(|_| chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap())
+        parse_hhmm(time_hhmm).unwrap_or_else(|| chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap())
     } else {
         chrono::NaiveTime::from_hms_opt(0, 0, 0)?
     };
@@ -71,3 +254,261 @@ pub fn parse_source_dt_to_utc(
     let source = offset.from_local_datetime(&naive).single()?;
     Some(source.with_timezone(&Utc))
 }
+
+/// Minutes the zone's UTC offset moved during the 24 hours before `dt_utc`
+/// (positive when clocks went forward).
+pub fn offset_change_minutes<Tz: TimeZone>(dt_utc: DateTime<Utc>, tz: &Tz) -> i32 {
+    let offset_at = |t: DateTime<Utc>| t.with_timezone(tz).offset().fix().local_minus_utc();
+    (offset_at(dt_utc) - offset_at(dt_utc - Duration::hours(24))) / 60
+}
+
+pub fn dst_note_for_zone<Tz: TimeZone>(dt_utc: DateTime<Utc>, tz: &Tz) -> Option<String> {
+    let change = offset_change_minutes(dt_utc, tz);
+    if change == 0 {
+        return None;
+    }
+    let direction = if change > 0 { "forward" } else { "back" };
+    let amount = change.abs();
+    let amount = if amount % 60 == 0 {
+        format!("{}h", amount / 60)
+    } else {
+        format!("{amount}m")
+    };
+    Some(format!("Clocks go {direction} {amount} this day"))
+}
+
+/// DST hint for rendered rows. Only the system-timezone display can observe a transition.
+pub fn dst_note(dt_utc: DateTime<Utc>, mode: &str, utc_offset_minutes: i32) -> Option<String> {
+    if mode == "utc" || utc_offset_minutes != 0 {
+        return None;
+    }
+    dst_note_for_zone(dt_utc, &Local)
+}
+
+pub fn local_utc_offset_minutes() -> i32 {
+    Local::now().offset().fix().local_minus_utc() / 60
+}
+
+/// Differences up to this many minutes are not reported as a mismatch.
+pub const TIMEZONE_MISMATCH_TOLERANCE_MINUTES: i32 = 60;
+
+/// Compares a pinned `utc_offset_minutes` against the system's current offset. Returns
+/// `(configured, detected)` when they differ by more than the tolerance; UTC mode and the
+/// plain system mode (offset 0) never mismatch.
+pub fn timezone_mismatch(
+    mode: &str,
+    utc_offset_minutes: i32,
+    local_offset_minutes: i32,
+) -> Option<(i32, i32)> {
+    if mode == "utc" || utc_offset_minutes == 0 {
+        return None;
+    }
+    if (utc_offset_minutes - local_offset_minutes).abs() <= TIMEZONE_MISMATCH_TOLERANCE_MINUTES {
+        return None;
+    }
+    Some((utc_offset_minutes, local_offset_minutes))
+}
+
+/// `UTC`, `UTC+8`, `UTC-5`, `UTC+5:30`.
+pub fn format_utc_offset(offset_minutes: i32) -> String {
+    if offset_minutes == 0 {
+        return "UTC".to_string();
+    }
+    let sign = if offset_minutes < 0 { '-' } else { '+' };
+    let (hours, minutes) = (offset_minutes.abs() / 60, offset_minutes.abs() % 60);
+    if minutes == 0 {
+        format!("UTC{sign}{hours}")
+    } else {
+        format!("UTC{sign}{hours}:{minutes:02}")
+    }
+}
+
+/// Every IANA zone known to `chrono-tz` with its UTC offset in minutes at `at`, sorted by name.
+pub fn timezone_offsets(at: DateTime<Utc>) -> Vec<(&'static str, i32)> {
+    let mut zones: Vec<(&'static str, i32)> = chrono_tz::TZ_VARIANTS
+        .iter()
+        .map(|tz| {
+            let offset = at.with_timezone(tz).offset().fix().local_minus_utc() / 60;
+            (tz.name(), offset)
+        })
+        .collect();
+    zones.sort_by_key(|(name, _)| *name);
+    zones
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{America::New_York, Australia::Sydney, Europe::London};
+
+    #[test]
+    fn source_dates_follow_the_configured_format() {
+        let expected = Utc.with_ymd_and_hms(2026, 3, 6, 13, 30, 0).unwrap();
+        assert_eq!(
+            parse_source_dt_to_utc("2026-03-06", "13:30", DEFAULT_SOURCE_DATE_FORMAT, 0),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_source_dt_to_utc("06/03/2026", "13:30", DEFAULT_SOURCE_DATE_FORMAT, 0),
+            None
+        );
+        assert_eq!(
+            parse_source_dt_to_utc("06/03/2026", "13:30", "%d/%m/%Y", 0),
+            Some(expected)
+        );
+
+        assert!(valid_date_format("%d/%m/%Y"));
+        assert!(!valid_date_format("%Q-%m"));
+        assert!(!valid_date_format(" "));
+        let cfg = serde_json::json!({"source_date_format": "%Q"});
+        assert_eq!(source_date_format(&cfg), DEFAULT_SOURCE_DATE_FORMAT);
+        let cfg = serde_json::json!({"source_date_format": "%d.%m.%Y"});
+        assert_eq!(source_date_format(&cfg), "%d.%m.%Y");
+    }
+
+    #[test]
+    fn zones_carry_their_current_offset() {
+        assert_eq!(format_utc_offset(0), "UTC");
+        assert_eq!(format_utc_offset(-300), "UTC-5");
+        assert_eq!(format_utc_offset(330), "UTC+5:30");
+        assert_eq!(format_utc_offset(-570), "UTC-9:30");
+
+        let winter = timezone_offsets(utc("2026-01-15T12:00:00Z"));
+        let summer = timezone_offsets(utc("2026-07-15T12:00:00Z"));
+        let offset = |zones: &[(&str, i32)], name: &str| {
+            zones.iter().find(|(n, _)| *n == name).map(|(_, o)| *o)
+        };
+        assert_eq!(offset(&winter, "America/New_York"), Some(-300));
+        assert_eq!(offset(&summer, "America/New_York"), Some(-240));
+        assert_eq!(offset(&winter, "Asia/Kolkata"), Some(330));
+        assert_eq!(offset(&winter, "Not/AZone"), None);
+        assert!(winter.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn spring_forward_in_northern_hemisphere() {
+        // London: 30-03-2025 01:00 UTC clocks go from GMT to BST.
+        let before = utc("2025-03-30T00:30:00Z");
+        let after = utc("2025-03-30T01:30:00Z");
+        assert_eq!(
+            before.with_timezone(&London).format("%H:%M").to_string(),
+            "00:30"
+        );
+        assert_eq!(
+            after.with_timezone(&London).format("%H:%M").to_string(),
+            "02:30"
+        );
+        assert_eq!(offset_change_minutes(before, &London), 0);
+        assert_eq!(offset_change_minutes(after, &London), 60);
+        assert_eq!(
+            dst_note_for_zone(after, &London).as_deref(),
+            Some("Clocks go forward 1h this day")
+        );
+        // One day later the comparison window no longer spans the change.
+        assert_eq!(
+            dst_note_for_zone(after + Duration::hours(24), &London),
+            None
+        );
+    }
+
+    #[test]
+    fn fall_back_keeps_distinct_instants_distinct() {
+        // New York: 02-11-2025 06:00 UTC clocks go from EDT back to EST; 01:30 local repeats.
+        let first = utc("2025-11-02T05:30:00Z");
+        let second = utc("2025-11-02T06:30:00Z");
+        let fmt = |t: DateTime<Utc>| t.with_timezone(&New_York).format("%H:%M %z").to_string();
+        assert_eq!(fmt(first), "01:30 -0400");
+        assert_eq!(fmt(second), "01:30 -0500");
+        assert_eq!(offset_change_minutes(second, &New_York), -60);
+        assert_eq!(
+            dst_note_for_zone(second, &New_York).as_deref(),
+            Some("Clocks go back 1h this day")
+        );
+    }
+
+    #[test]
+    fn southern_hemisphere_transitions() {
+        // Sydney: 06-04-2025 16:00 UTC AEDT -> AEST; 04-10-2025 16:00 UTC AEST -> AEDT.
+        assert_eq!(
+            offset_change_minutes(utc("2025-04-06T00:00:00Z"), &Sydney),
+            -60
+        );
+        assert_eq!(
+            offset_change_minutes(utc("2025-10-05T00:00:00Z"), &Sydney),
+            60
+        );
+        assert_eq!(
+            offset_change_minutes(utc("2025-07-01T00:00:00Z"), &Sydney),
+            0
+        );
+    }
+
+    #[test]
+    fn fixed_offset_mode_has_no_dst_shift() {
+        let before = utc("2025-03-30T00:30:00Z");
+        let after = utc("2025-03-30T01:30:00Z");
+        assert_eq!(
+            format_display_time(before, "system", 60, DateFormat::Dmy),
+            "30-03-2025 01:30"
+        );
+        assert_eq!(
+            format_display_time(after, "system", 60, DateFormat::Dmy),
+            "30-03-2025 02:30"
+        );
+        assert_eq!(dst_note(after, "system", 60), None);
+        assert_eq!(dst_note(after, "utc", 0), None);
+    }
+
+    #[test]
+    fn timezone_mismatch_only_for_pinned_offsets_beyond_tolerance() {
+        // Pinned to UTC+8, now travelling in London summer time (UTC+1).
+        assert_eq!(timezone_mismatch("system", 480, 60), Some((480, 60)));
+        assert_eq!(timezone_mismatch("system", -300, 540), Some((-300, 540)));
+        // Within an hour (e.g. a DST shift) is tolerated.
+        assert_eq!(timezone_mismatch("system", 60, 0), None);
+        assert_eq!(timezone_mismatch("system", 480, 420), None);
+        // UTC display and plain system time follow no pinned offset.
+        assert_eq!(timezone_mismatch("utc", 480, 60), None);
+        assert_eq!(timezone_mismatch("system", 0, 600), None);
+    }
+
+    #[test]
+    fn display_time_follows_date_format() {
+        let dt = utc("2026-03-07T13:30:00Z");
+        let cases = [
+            (DateFormat::Dmy, "07-03-2026 13:30", "07-03-2026 15:30"),
+            (DateFormat::Mdy, "03-07-2026 13:30", "03-07-2026 15:30"),
+            (DateFormat::Iso, "2026-03-07 13:30", "2026-03-07 15:30"),
+        ];
+        for (format, in_utc, pinned) in cases {
+            assert_eq!(format_display_time(dt, "utc", 0, format), in_utc);
+            assert_eq!(format_display_time(dt, "system", 120, format), pinned);
+            assert_eq!(DateFormat::parse(format.as_str()), Some(format));
+        }
+        assert_eq!(DateFormat::parse("ymd"), None);
+        assert_eq!(DateFormat::default(), DateFormat::Dmy);
+    }
+
+    #[test]
+    fn jitter_stays_within_spread() {
+        let hour = std::time::Duration::from_secs(3600);
+        assert_eq!(jittered(hour, 0.1, 0.5), hour);
+        assert_eq!(
+            jittered(hour, 0.1, 0.0),
+            std::time::Duration::from_secs(3240)
+        );
+        assert_eq!(
+            jittered(hour, 0.1, 1.0),
+            std::time::Duration::from_secs(3960)
+        );
+        for _ in 0..100 {
+            let d = random_jitter(hour, 0.1);
+            assert!(d >= std::time::Duration::from_secs(3240));
+            assert!(d <= std::time::Duration::from_secs(3960));
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::calendar::CalendarEvent;
+use chrono::{DateTime, Duration, Utc};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrayAlertChange {
+    Raise,
+    Clear,
+}
+
+/// Edge-triggered tray alert: reports a change only when imminence flips, so the icon swap and
+/// the attention flash happen once per crossing rather than on every tick.
+#[derive(Default)]
+pub struct TrayAlertState {
+    raised: bool,
+}
+
+impl TrayAlertState {
+    pub fn update(&mut self, imminent: bool) -> Option<TrayAlertChange> {
+        if imminent == self.raised {
+            return None;
+        }
+        self.raised = imminent;
+        Some(if imminent {
+            TrayAlertChange::Raise
+        } else {
+            TrayAlertChange::Clear
+        })
+    }
+}
+
+/// True when a high-impact event for `currency` (`ALL` matches any) starts within `window`.
+/// `events` must be sorted by time, as the calendar loader leaves them.
+pub fn high_impact_within(
+    events: &[CalendarEvent],
+    currency: &str,
+    now: DateTime<Utc>,
+    window: Duration,
+) -> bool {
+    let selected = currency.trim().to_uppercase();
+    let start = events.partition_point(|e| e.dt_utc < now);
+    events[start..]
+        .iter()
+        .take_while(|e| e.dt_utc <= now + window)
+        .filter(|e| e.time_label.contains(':'))
+        .filter(|e| selected.is_empty() || selected == "ALL" || e.currency == selected)
+        .any(|e| e.importance.trim().eq_ignore_ascii_case("high"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 6, h, m, 0).unwrap()
+    }
+
+    fn event(dt_utc: DateTime<Utc>, currency: &str, importance: &str) -> CalendarEvent {
+        CalendarEvent {
+            dt_utc,
+            time_label: dt_utc.format("%H:%M").to_string(),
+            event: "Non-Farm Payrolls".to_string(),
+            currency: currency.to_string(),
+            importance: importance.to_string(),
+            actual: String::new(),
+            forecast: String::new(),
+            previous: String::new(),
+            time_confirmed: true,
+            categories: vec![],
+            source: Default::default(),
+        }
+    }
+
+    #[test]
+    fn raises_once_when_entering_window_and_clears_after_start() {
+        let events = vec![
+            event(at(12, 0), "USD", "Low"),
+            event(at(13, 30), "USD", "High"),
+        ];
+        let window = Duration::minutes(30);
+        let mut state = TrayAlertState::default();
+        let mut changes = vec![];
+        for minute in [0, 30, 59, 60, 61, 89, 90, 91] {
+            let now = at(12, 0) + Duration::minutes(minute);
+            let imminent = high_impact_within(&events, "USD", now, window);
+            if let Some(change) = state.update(imminent) {
+                changes.push((minute, change));
+            }
+        }
+        assert_eq!(
+            changes,
+            vec![(60, TrayAlertChange::Raise), (91, TrayAlertChange::Clear)]
+        );
+    }
+
+    #[test]
+    fn only_high_impact_for_selected_currency_counts() {
+        let events = vec![
+            event(at(13, 0), "EUR", "High"),
+            event(at(13, 10), "USD", "Medium"),
+        ];
+        let window = Duration::minutes(30);
+        assert!(!high_impact_within(&events, "USD", at(12, 45), window));
+        assert!(high_impact_within(&events, "EUR", at(12, 45), window));
+        assert!(high_impact_within(&events, "ALL", at(12, 45), window));
+        assert!(!high_impact_within(&events, "EUR", at(12, 29), window));
+    }
+}
@@ -0,0 +1,143 @@
+use std::cmp::Ordering;
+
+pub fn normalize_version_tag(tag: &str) -> String {
+    let trimmed = tag.trim();
+    if let Some(rest) = trimmed.strip_prefix('v') {
+        rest.trim().to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+pub fn parse_version_numbers(v: &str) -> Option<Vec<u32>> {
+    let v = v.trim();
+    if v.is_empty() {
+        return None;
+    }
+    let core = v.split('-').next().unwrap_or(v);
+    let mut nums = vec![];
+    for part in core.split('.') {
+        let part = part.trim();
+        if part.is_empty() {
+            return None;
+        }
+        nums.push(part.parse::<u32>().ok()?);
+    }
+    Some(nums)
+}
+
+/// The `-prerelease` suffix of a version (`rc1` in `1.2.0-rc1`), if any.
+fn prerelease(v: &str) -> Option<&str> {
+    v.trim().split_once('-').map(|(_, pre)| pre)
+}
+
+/// Orders two pre-release suffixes the way semver does: dot-separated identifiers compared in
+/// turn, numeric ones numerically and below alphanumeric ones, a shorter prefix first.
+fn cmp_prerelease(a: &str, b: &str) -> Ordering {
+    let mut a_ids = a.split('.');
+    let mut b_ids = b.split('.');
+    loop {
+        let order = match (a_ids.next(), b_ids.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => x.cmp(y),
+            },
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+}
+
+/// Compares dotted numeric versions; missing components count as zero. With equal numbers a
+/// pre-release (`1.2.0-rc1`) sorts below its release, and two pre-releases by their suffix.
+pub fn cmp_versions(a: &str, b: &str) -> Ordering {
+    let a_nums = parse_version_numbers(a).unwrap_or_default();
+    let b_nums = parse_version_numbers(b).unwrap_or_default();
+    let max_len = a_nums.len().max(b_nums.len());
+    for i in 0..max_len {
+        let ai = *a_nums.get(i).unwrap_or(&0);
+        let bi = *b_nums.get(i).unwrap_or(&0);
+        match ai.cmp(&bi) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+    match (prerelease(a), prerelease(b)) {
+        (None, None) => Ordering::Equal,
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(a_pre), Some(b_pre)) => cmp_prerelease(a_pre, b_pre),
+    }
+}
+
+/// Where `tag` sits relative to `current`: `"older"`, `"same"` or `"newer"`.
+pub fn version_relation(tag: &str, current: &str) -> &'static str {
+    match cmp_versions(tag, current) {
+        Ordering::Less => "older",
+        Ordering::Equal => "same",
+        Ordering::Greater => "newer",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_version_tag_strips_leading_v_and_whitespace() {
+        assert_eq!(normalize_version_tag("v0.3.0"), "0.3.0");
+        assert_eq!(normalize_version_tag("  v 1.2 "), "1.2");
+        assert_eq!(normalize_version_tag("1.2.3"), "1.2.3");
+        assert_eq!(normalize_version_tag("V1.0"), "V1.0");
+        assert_eq!(normalize_version_tag(""), "");
+    }
+
+    #[test]
+    fn cmp_versions_orders_numeric_components() {
+        assert_eq!(cmp_versions("0.3.1", "0.3.0"), Ordering::Greater);
+        assert_eq!(cmp_versions("0.10.0", "0.9.9"), Ordering::Greater);
+        assert_eq!(cmp_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(cmp_versions("1.2.0", "1.2.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn cmp_versions_orders_prereleases_below_their_release() {
+        assert_eq!(cmp_versions("1.2.0-beta.1", "1.2.0"), Ordering::Less);
+        assert_eq!(cmp_versions("1.2.0", "1.2.0-rc1"), Ordering::Greater);
+        assert_eq!(cmp_versions("1.2.0-rc1", "1.2.0-beta"), Ordering::Greater);
+        assert_eq!(cmp_versions("1.2.0-rc1", "1.2-rc1"), Ordering::Equal);
+        assert_eq!(
+            cmp_versions("1.2.0-beta.2", "1.2.0-beta.10"),
+            Ordering::Less
+        );
+        assert_eq!(cmp_versions("1.2.0-beta", "1.2.0-beta.1"), Ordering::Less);
+        assert_eq!(cmp_versions("1.2.0-1", "1.2.0-alpha"), Ordering::Less);
+        assert_eq!(cmp_versions("1.3.0-alpha", "1.2.9"), Ordering::Greater);
+        assert_eq!(cmp_versions("1.2.0", "1.2.1-rc1"), Ordering::Less);
+    }
+
+    #[test]
+    fn cmp_versions_treats_unparseable_as_zero() {
+        assert_eq!(cmp_versions("", "0.0.0"), Ordering::Equal);
+        assert_eq!(cmp_versions("latest", "0.1.0"), Ordering::Less);
+        assert_eq!(cmp_versions("1..2", "0.0.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn version_relation_handles_prerelease_and_uneven_components() {
+        assert_eq!(version_relation("0.3.0-beta.2", "0.3.0"), "older");
+        assert_eq!(version_relation("0.3.0-beta.2", "0.3.0-beta.2"), "same");
+        assert_eq!(version_relation("0.3.1-rc1", "0.3.0"), "newer");
+        assert_eq!(version_relation("0.3", "0.3.0.0"), "same");
+        assert_eq!(version_relation("0.2.9.9", "0.3"), "older");
+        assert_eq!(version_relation("1", "0.99.99"), "newer");
+        assert_eq!(parse_version_numbers("1.2.0-beta.1"), Some(vec![1, 2, 0]));
+        assert_eq!(parse_version_numbers("1..2"), None);
+    }
+}
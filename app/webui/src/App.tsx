@@ -1,7 +1,15 @@
 ﻿import { useEffect, useMemo, useRef, useState, type MouseEvent as ReactMouseEvent } from "react";
 import { useCallback, useLayoutEffect } from "react";
 import { backend, isWebview, tauriListen } from "./api";
-import type { EventHistoryResponse, FilterOption, Settings, Snapshot, ToastType } from "./types";
+import type {
+  EventHistoryResponse,
+  FilterOption,
+  Settings,
+  Snapshot,
+  ToastType,
+  UiModal,
+  UiModalAction
+} from "./types";
 import { ActivityDrawer } from "./components/ActivityDrawer";
 import { ActivityLog } from "./components/ActivityLog";
 import { AlertModal } from "./components/AlertModal";
@@ -82,8 +90,16 @@ type AlertContext = {
   id: string;
   title: string;
   message: string;
-  tone: "info" | "error";
+  tone: UiModal["tone"];
+  actions: UiModalAction[];
+  openPath?: string;
 };
+
+// Matches `DELETION_MODAL_ID` in commands/sync.rs.
+const SYNC_DELETIONS_MODAL_ID = "sync-deletions";
+
+const alertTone = (tone: unknown): UiModal["tone"] =>
+  tone === "error" || tone === "warn" ? tone : "info";
 type UiStatePayload = { visible: boolean; focused: boolean; lastInputAt: number };
 
 export default function App() {
@@ -385,14 +401,16 @@ export default function App() {
                   id: modal.id,
                   title: modal.title || "Notice",
                   message: modal.message || "",
-                  tone: modal.tone || "info"
+                  tone: alertTone(modal.tone),
+                  actions: modal.actions || [],
+                  openPath: modal.openPath
                 });
               } else if (modal && modal.id && modal.id === activeAlertIdRef.current) {
                 setAlertContext((prev) => {
                   if (!prev || prev.id !== modal.id) return prev;
                   const nextTitle = modal.title || prev.title;
                   const nextMessage = modal.message || prev.message;
-                  const nextTone = (modal.tone as "info" | "error" | undefined) || prev.tone;
+                  const nextTone = modal.tone ? alertTone(modal.tone) : prev.tone;
                   if (nextTitle === prev.title && nextMessage === prev.message && nextTone === prev.tone) {
                     return prev;
                   }
@@ -827,12 +845,7 @@ export default function App() {
 
     const handler = (event: Event) => {
       const detail = (event as CustomEvent).detail as
-        | {
-            id?: string;
-            title?: string;
-            message?: string;
-            tone?: "info" | "error";
-          }
+        | Partial<UiModal>
         | undefined;
 
       const id = (detail?.id || "").trim();
@@ -844,7 +857,9 @@ export default function App() {
         id,
         title: detail?.title || "Notice",
         message: detail?.message || "",
-        tone: detail?.tone === "error" ? "error" : "info"
+        tone: alertTone(detail?.tone),
+        actions: detail?.actions || [],
+        openPath: detail?.openPath
       });
     };
 
@@ -861,12 +876,7 @@ export default function App() {
     let cancelled = false;
 
     const start = async () => {
-      const un = await tauriListen<{
-        id?: string;
-        title?: string;
-        message?: string;
-        tone?: "info" | "error";
-      }>("xauusd:modal", (detail) => {
+      const un = await tauriListen<Partial<UiModal>>("xauusd:modal", (detail) => {
         const id = (detail?.id || "").trim();
         if (!id) return;
         if (id === dismissedAlertIdRef.current) return;
@@ -875,7 +885,9 @@ export default function App() {
             id,
             title: detail?.title || "Notice",
             message: detail?.message || "",
-            tone: detail?.tone === "error" ? "error" : "info"
+            tone: alertTone(detail?.tone),
+            actions: detail?.actions || [],
+            openPath: detail?.openPath
           });
           return;
         }
@@ -883,7 +895,7 @@ export default function App() {
           if (!prev || prev.id !== id) return prev;
           const nextTitle = detail?.title || prev.title;
           const nextMessage = detail?.message || prev.message;
-          const nextTone = detail?.tone === "error" ? "error" : "info";
+          const nextTone = alertTone(detail?.tone);
           if (nextTitle === prev.title && nextMessage === prev.message && nextTone === prev.tone) {
             return prev;
           }
@@ -1813,6 +1825,39 @@ export default function App() {
     }
   };
 
+  // Closing the sync deletion prompt without an answer keeps the files, as its timeout would.
+  const handleAlertClose = () => {
+    if (alertContext?.id === SYNC_DELETIONS_MODAL_ID) {
+      void backend.cancelSyncDeletions().catch(() => {});
+    }
+    closeAlertModal();
+  };
+
+  const handleAlertAction = async (action: UiModalAction) => {
+    closeAlertModal();
+    try {
+      if (action.command) {
+        const result = await backend.runModalCommand(action.command);
+        if (!result.ok) {
+          pushToast("error", result.message || `${action.label} failed`);
+        }
+        return;
+      }
+      if (action.url) {
+        const result = await backend.openUrl(action.url);
+        if (!result.ok) {
+          pushToast("error", result.message || "Failed to open link");
+        }
+        return;
+      }
+      if (action.view === "settings") {
+        openSettings();
+      }
+    } catch (err) {
+      pushToast("error", err instanceof Error ? err.message : `${action.label} failed`);
+    }
+  };
+
   const handleOpenPath = async (path: string) => {
     const result = await backend.openPath(path);
     if (!result.ok) {
@@ -2184,7 +2229,8 @@ export default function App() {
   }, [alertOpen, alertEntering]);
 
   useEffect(() => {
-    if (!alertOpen) return;
+    // A modal with actions waits for an answer instead of closing itself.
+    if (!alertOpen || (alertContext?.actions.length ?? 0) > 0) return;
     const alertContextId = alertContext?.id || "";
 
     const reset = () => {
@@ -2269,7 +2315,7 @@ export default function App() {
       window.removeEventListener("pointermove", onPointerMove);
       window.removeEventListener("mousemove", onPointerMove);
     };
-  }, [alertOpen, alertCountdownArmed, closeAlertModal, alertContext?.id]);
+  }, [alertOpen, alertCountdownArmed, closeAlertModal, alertContext?.id, alertContext?.actions.length]);
 
   useEffect(() => {
     if (!settingsOpen) pendingPathsScrollRef.current = false;
@@ -2655,7 +2701,12 @@ export default function App() {
         patchSnapshot?: (next: Partial<Snapshot>) => void;
         refreshUpdateState?: () => Promise<void>;
         setActivityHover?: (active: boolean) => void;
-        showAlertModal?: (payload: { title?: string; message?: string; tone?: "info" | "error" }) => void;
+        showAlertModal?: (payload: {
+          title?: string;
+          message?: string;
+          tone?: UiModal["tone"];
+          actions?: UiModalAction[];
+        }) => void;
         hideAlertModal?: () => void;
         showTemporaryPathWarning?: (payload: {
           mode?: TemporaryPathWarningMode;
@@ -2758,7 +2809,8 @@ export default function App() {
           id: `ui-check-alert-${Date.now()}`,
           title: payload?.title ?? "Notice",
           message: payload?.message ?? "Token detected and verified.",
-          tone: payload?.tone ?? "info"
+          tone: alertTone(payload?.tone),
+          actions: payload?.actions ?? []
         });
       },
       hideAlertModal: () => {
@@ -3336,6 +3388,7 @@ export default function App() {
               impactTone={impactTone}
               impactFilter={impactFilter}
               onOpenHistory={(item) => openEventHistory({ event: item.event, cur: item.cur })}
+              dateFormat={snapshot.dateFormat}
             />
           </div>
         </div>
@@ -3595,8 +3648,12 @@ export default function App() {
         title={alertContext?.title || "Notice"}
         message={alertContext?.message || ""}
         tone={alertContext?.tone || "info"}
+        actions={alertContext?.actions || []}
+        openPath={alertContext?.openPath}
         secondsRemaining={alertCountdown}
-        onClose={closeAlertModal}
+        onOpenPath={(path) => void handleOpenPath(path)}
+        onAction={handleAlertAction}
+        onClose={handleAlertClose}
       />
 
       <ActivityDrawer
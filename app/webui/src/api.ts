@@ -61,6 +61,7 @@ type BackendApi = {
   set_temporary_path: (path: string) => ApiResult<{ ok: boolean }>;
   pull_now: () => ApiResult<{ ok: boolean }>;
   sync_now: () => ApiResult<{ ok: boolean }>;
+  cancel_sync_deletions?: () => ApiResult<{ ok: boolean; message?: string }>;
   browse_output_dir: () => ApiResult<{ ok: boolean; path?: string }>;
   set_output_dir: (path: string) => ApiResult<{ ok: boolean }>;
   set_currency: (value: string) => ApiResult<{ ok: boolean }>;
@@ -873,6 +874,13 @@ export const backend = {
     }
     return api.sync_now();
   },
+  cancelSyncDeletions: async () => {
+    const api = await withApi();
+    if (!api || !hasMethod(api, "cancel_sync_deletions")) {
+      return { ok: false, message: "No sync is waiting for confirmation" };
+    }
+    return api.cancel_sync_deletions();
+  },
   browseOutputDir: async () => {
     const api = await withApi();
     if (!api || !hasMethod(api, "browse_output_dir")) {
@@ -917,6 +925,16 @@ export const backend = {
     }
     return api.clear_logs();
   },
+  // Modal action buttons name a backend command that takes no arguments.
+  runModalCommand: async (command: string): Promise<{ ok: boolean; message?: string }> => {
+    if (!isTauri()) {
+      if (isWebview() && !isUiCheckRuntime()) {
+        throw new Error("Desktop backend unavailable");
+      }
+      return { ok: true };
+    }
+    return tauriInvoke<{ ok: boolean; message?: string }>(command);
+  },
   dismissModal: async (id: string) => {
     const api = await withApi();
     if (!api || !hasMethod(api, "dismiss_modal")) {
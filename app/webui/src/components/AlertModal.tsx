@@ -1,15 +1,18 @@
+import type { UiModal, UiModalAction } from "../types";
 import "./AlertModal.css";
 
-type AlertModalTone = "info" | "error";
-
 type AlertModalProps = {
   isOpen: boolean;
   isClosing: boolean;
   isEntering: boolean;
   title: string;
   message: string;
-  tone: AlertModalTone;
+  tone: UiModal["tone"];
+  actions: UiModalAction[];
+  openPath?: string;
   secondsRemaining: number;
+  onAction: (action: UiModalAction) => void;
+  onOpenPath: (path: string) => void;
   onClose: () => void;
 };
 
@@ -20,7 +23,11 @@ export function AlertModal({
   title,
   message,
   tone,
+  actions,
+  openPath,
   secondsRemaining,
+  onAction,
+  onOpenPath,
   onClose
 }: AlertModalProps) {
   if (!isOpen) return null;
@@ -65,16 +72,44 @@ export function AlertModal({
           {paragraphs}
         </div>
         <div className="alert-footer" data-qa="qa:modal-footer:alert">
-          <button
-            type="button"
-            className="alert-close-btn"
-            onClick={onClose}
-            data-qa="qa:alert:close"
-            aria-label={`Closing in ${Math.max(0, secondsRemaining)} seconds`}
-          >
-            <span className="close-label">Closing</span>
-            <span className="close-count">{Math.max(0, secondsRemaining)}</span>
-          </button>
+          {actions.map((action, index) => (
+            <button
+              key={`${index}-${action.label}`}
+              type="button"
+              className="alert-action-btn"
+              onClick={() => onAction(action)}
+              data-qa={`qa:alert:action:${index}`}
+            >
+              {action.label}
+            </button>
+          ))}
+          {openPath ? (
+            <button
+              type="button"
+              className="alert-action-btn"
+              onClick={() => onOpenPath(openPath)}
+              data-qa="qa:alert:open-path"
+              title={openPath}
+            >
+              Open
+            </button>
+          ) : null}
+          {actions.length > 0 ? (
+            <button type="button" className="alert-close-btn" onClick={onClose} data-qa="qa:alert:close">
+              <span className="close-label">Close</span>
+            </button>
+          ) : (
+            <button
+              type="button"
+              className="alert-close-btn"
+              onClick={onClose}
+              data-qa="qa:alert:close"
+              aria-label={`Closing in ${Math.max(0, secondsRemaining)} seconds`}
+            >
+              <span className="close-label">Closing</span>
+              <span className="close-count">{Math.max(0, secondsRemaining)}</span>
+            </button>
+          )}
         </div>
       </div>
     </div>
@@ -1,5 +1,5 @@
 import { useEffect, useMemo, useRef, useState } from "react";
-import type { PastEventItem } from "../types";
+import type { DateFormat, PastEventItem } from "../types";
 import { normalizeAcronyms } from "../utils/normalizeAcronyms";
 import "./HistoryPanel.css";
 
@@ -24,6 +24,7 @@ type HistoryPanelProps = {
   impactTone: (impact: string) => string;
   impactFilter: string[];
   onOpenHistory: (item: PastEventItem) => void;
+  dateFormat?: DateFormat;
 };
 
 type HistoryTrend = "up" | "flat" | "down" | "tba" | "na";
@@ -157,10 +158,16 @@ function TrendIcon({ trend }: { trend: Exclude<HistoryTrend, "tba"> }) {
   );
 }
 
-const parseEventDate = (value: string) => {
+const parseEventDate = (value: string, dateFormat: DateFormat) => {
   const [datePart, timePart] = value.split(" ");
   if (!datePart) return null;
-  const [day, month, year] = datePart.split("-").map((part) => Number(part));
+  const parts = datePart.split("-").map((part) => Number(part));
+  const [day, month, year] =
+    dateFormat === "iso"
+      ? [parts[2], parts[1], parts[0]]
+      : dateFormat === "mdy"
+        ? [parts[1], parts[0], parts[2]]
+        : parts;
   const [hour, minute] = timePart ? timePart.split(":").map((part) => Number(part)) : [0, 0];
   if (!day || !month || !year) return null;
   return new Date(year, month - 1, day, hour || 0, minute || 0);
@@ -198,7 +205,8 @@ export function HistoryPanel({
   downloading = false,
   impactTone,
   impactFilter,
-  onOpenHistory
+  onOpenHistory,
+  dateFormat = "dmy"
 }: HistoryPanelProps) {
   const rangeStorageKey = "xauusd:history:range";
   const scrollStorageKey = "xauusd:scroll:history";
@@ -267,7 +275,7 @@ export function HistoryPanel({
     const parsed = events
       .map((entry) => ({
         entry,
-        date: parseEventDate(entry.time)
+        date: parseEventDate(entry.time, dateFormat)
       }))
       .filter((item) => {
         if (!item.date || item.date.getTime() < cutoff) return false;
@@ -315,7 +323,7 @@ export function HistoryPanel({
       groups.forEach((group) => rows.push({ type: "group", key: `group-${group.key}`, group }));
     }
     return { groups, rows };
-  }, [events, range, impactFilter]);
+  }, [events, range, impactFilter, dateFormat]);
   const groups = view.groups;
   const rows = view.rows;
 
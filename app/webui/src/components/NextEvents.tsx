@@ -392,6 +392,14 @@ export function NextEvents({
                     </div>
                     <div className="event-meta">
                       <span className="event-cur mono">{item.cur}</span>
+                      {item.lastActual ? (
+                        <span
+                          className="event-last mono"
+                          title={`Forecast ${item.lastForecast || "--"} · ${item.lastDate ?? ""}`}
+                        >
+                          Last {item.lastActual}
+                        </span>
+                      ) : null}
                     </div>
                   </div>
                   <span
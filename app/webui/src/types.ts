@@ -2,17 +2,29 @@ export type LogEntry = {
   time: string;
   message: string;
   level: string;
+  sessionId?: string;
 };
 
+export type DateFormat = "dmy" | "mdy" | "iso";
+
 export type FilterOption = "ALL" | "INFO" | "WARN" | "ERROR";
 
 export type ToastType = "success" | "error" | "info";
 
+export type UiModalAction = {
+  label: string;
+  command?: string;
+  url?: string;
+  view?: "settings";
+};
+
 export type UiModal = {
   id: string;
   title: string;
   message: string;
-  tone: "info" | "error";
+  tone: "info" | "warn" | "error";
+  actions?: UiModalAction[];
+  openPath?: string;
 };
 
 export type EventItem = {
@@ -22,7 +34,16 @@ export type EventItem = {
   cur: string;
   impact: string;
   event: string;
+  eventFull?: string;
   countdown: string;
+  dstNote?: string | null;
+  timeConfirmed?: boolean;
+  categories?: string[];
+  source?: "calendar" | "custom" | "placeholder";
+  placeholder?: boolean;
+  lastActual?: string | null;
+  lastForecast?: string | null;
+  lastDate?: string | null;
 };
 
 export type PastEventItem = {
@@ -30,9 +51,24 @@ export type PastEventItem = {
   cur: string;
   impact: string;
   event: string;
+  eventFull?: string;
   actual: string;
   forecast: string;
   previous: string;
+  dstNote?: string | null;
+  timeConfirmed?: boolean;
+  categories?: string[];
+  source?: "calendar" | "custom";
+  recent?: boolean;
+};
+
+export type LastReleasedItem = PastEventItem & {
+  releasedAt: string;
+  releasedAgo: string;
+  actualValue: number | null;
+  forecastValue: number | null;
+  previousValue: number | null;
+  surprise: number | null;
 };
 
 export type EventHistoryPoint = {
@@ -57,6 +93,7 @@ export type EventHistoryResponse = {
   cur?: string;
   points?: EventHistoryPoint[];
   cached?: boolean;
+  merged?: boolean;
   message?: string;
 };
 
@@ -64,31 +101,86 @@ export type Snapshot = {
   lastPull: string;
   lastSync: string;
   lastPullAt?: string;
+  lastPullError?: string | null;
   lastSyncAt?: string;
   outputDir: string;
   repoPath: string;
   currency: string;
   currencyOptions: string[];
+  categoryFilter?: string[];
   events: EventItem[];
   pastEvents: PastEventItem[];
   logs: LogEntry[];
   version: string;
   pullActive?: boolean;
   syncActive?: boolean;
-  calendarStatus?: "loading" | "downloading" | "loaded" | "empty" | "error";
+  syncCancellable?: boolean;
+  pullCancellable?: boolean;
+  syncAwaitingConfirmation?: boolean;
+  syncPendingDeletions?: {
+    count: number;
+    examples: string[];
+    destination: string;
+  } | null;
+  focusEventId?: string | null;
+  focusEventLocation?: "upcoming" | "past" | "gone" | null;
+  scheduledPullAt?: string | null;
+  notificationsMutedUntil?: string | null;
+  calendarStatus?: "loading" | "downloading" | "loaded" | "empty" | "placeholder" | "resetting" | "error";
   restartInSeconds?: number;
   modal?: UiModal | null;
+  testClock?: string | null;
+  instanceLabel?: string | null;
+  dateFormat?: DateFormat;
+  timezoneMismatch?: { configured: number; detected: number } | null;
 };
 
 export type Settings = {
   autoSyncAfterPull: boolean;
   autoUpdateEnabled: boolean;
+  updateNotifyMode?: "modal" | "log" | "silent";
+  noTimeLabel?: string;
+  recentHighlightHours?: number;
+  eventNameMaxLen?: number;
+  minForecastChangePct?: number;
+  statusFileEnabled?: boolean;
+  statusFilePath?: string;
+  instanceLabel?: string;
+  dateFormat?: DateFormat;
+  sourceDateFormat?: string;
+  githubRepo?: string;
+  eventAlertsEnabled?: boolean;
+  eventAlertMinutesBefore?: number;
+  eventAlertMinImpact?: "High" | "Medium" | "Low";
+  syncTimeoutSecs?: number;
+  syncIntervalMinutes?: number;
+  syncAtomic?: boolean;
+  syncScope?: "full" | "current";
+  githubRateLimit?: {
+    limit: number | null;
+    remaining: number | null;
+    resetAt: number | null;
+    recordedAt: string;
+  } | null;
+  githubTokenScopes?: string[] | null;
+  backupKeepCount?: number;
+  minFreeSpaceMb?: number;
+  trayAlertMinutes?: number;
+  respectSystemDnd?: boolean;
   runOnStartup: boolean;
   autostartLaunchMode: "tray" | "show";
   closeBehavior: "exit" | "tray";
+  secondInstanceAction?: "focus" | "ignore" | "notify";
   traySupported: boolean;
+  portableMode?: boolean;
+  portableUnavailableReason?: string | null;
   debug: boolean;
+  logMinLevel?: "DEBUG" | "INFO" | "WARN" | "ERROR";
+  logBufferEntries?: number;
+  maxCalendarFileMb?: number;
+  maxEventsPerFile?: number;
   autoSave: boolean;
+  refreshOnFocus?: boolean;
   splitRatio: number;
   enableSystemTheme: boolean;
   theme: "system" | "dark" | "light";
@@ -96,6 +188,19 @@ export type Settings = {
   calendarUtcOffsetMinutes: number;
   enableTemporaryPath: boolean;
   temporaryPath: string;
+  historySlimMode?: boolean;
   repoPath: string;
   logPath: string;
 };
+
+export type TimezoneOption = {
+  name: string;
+  offsetMinutes: number;
+  offsetLabel: string;
+  label: string;
+};
+
+export type CountdownEntry = {
+  countdown: string;
+  state: "current" | "upcoming" | "past";
+};